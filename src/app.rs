@@ -1,32 +1,60 @@
 use relm4::{
     abstractions::Toaster,
-    actions::{RelmAction, RelmActionGroup},
+    actions::{AccelsPlus, RelmAction, RelmActionGroup},
     adw,
+    adw::prelude::{AdwDialogExt, AlertDialogExt},
     factory::FactoryVecDeque,
     gtk, main_application, Component, ComponentController, ComponentParts, ComponentSender,
     Controller,
 };
 
 use gtk::prelude::{
-    ApplicationExt, ApplicationWindowExt, ButtonExt, Cast, EditableExt, GtkWindowExt, ListModelExt,
-    OrientableExt, SettingsExt, WidgetExt,
+    ApplicationExt, ApplicationWindowExt, BoxExt, ButtonExt, Cast, EditableExt, GtkWindowExt,
+    ListBoxRowExt, ListModelExt, OrientableExt, PopoverExt, SettingsExt, StaticType,
+    ToggleButtonExt, WidgetExt,
 };
 use gtk::{gio, glib};
 
-use gettextrs::gettext;
+use gettextrs::{gettext, ngettext};
 
-use crate::article::{Article, ArticleInit, ArticleOutput, ArticleRenderer, ArticleRendererInput};
+use crate::article::listen_bar::{ListenBar, ListenBarInput, ListenBarOutput};
+use crate::article::outline::{
+    OutlineEntry, OutlinePopover, OutlinePopoverInput, OutlinePopoverOutput,
+};
+use crate::article::reader_style::ReaderStyle;
+use crate::article::reading_time;
+use crate::article::speech;
+use crate::article::style_popover::{
+    ReaderStylePopover, ReaderStylePopoverInput, ReaderStylePopoverOutput,
+};
+use crate::article::{
+    Article, ArticleInit, ArticleInput, ArticleOutput, ArticleRenderer, ArticleRendererInput,
+    ArticleRendererOutput,
+};
+use crate::backup::{self, Library, RestoreReport};
 use crate::config::{APP_ID, PROFILE};
+use crate::export::ExportArticle;
 use crate::modals::about::AboutDialog;
-use crate::modals::add_bookmark::{AddBookmarkDialog, AddBookmarkOutput};
+use crate::modals::add_bookmark::{self, AddBookmarkDialog, AddBookmarkOutput};
+use crate::modals::backup::{self as backup_dialog, BackupDialog, BackupInit, BackupOutput};
+use crate::modals::export::{ExportDialog, ExportInit, ExportOutput};
+use crate::modals::import::{ImportDialog, ImportInit, ImportOutput};
 use crate::modals::login::{LoginDialog, LoginOutput};
-use crate::network::instapaper;
-use crate::persistence::articles::{self, PersistedArticle};
+use crate::modals::preferences::PreferencesDialog;
+use crate::network::batch::{self, BatchOperation, BatchReport, BatchTarget};
+use crate::network::content;
+use crate::network::instapaper::{self, InstapaperFolder};
+use crate::network::translation;
+use crate::persistence::article_cache;
+use crate::persistence::articles::PersistedArticle;
+use crate::persistence::import_queue;
+use crate::persistence::offline_cache;
+use crate::persistence::store::ArticleStore;
 use crate::persistence::token::{self, TokenPair};
-use article_scraper::{FtrConfigEntry, FullTextParser, Readability};
-use reqwest::Client;
+use crate::persistence::translation_key;
+use crate::persistence::translations;
+use crate::preferences::{ContentSource, Preferences};
 use std::collections::HashSet;
-use url::Url;
 
 pub(super) struct App {
     loading: bool,
@@ -40,13 +68,47 @@ pub(super) struct App {
     toaster: Toaster,
     login_dialog: Option<Controller<LoginDialog>>,
     add_bookmark_dialog: Option<Controller<AddBookmarkDialog>>,
+    export_dialog: Option<Controller<ExportDialog>>,
+    import_dialog: Option<Controller<ImportDialog>>,
+    backup_dialog: Option<Controller<BackupDialog>>,
     article_renderer: Controller<ArticleRenderer>,
+    /// Link under the pointer in the reader, shown in the status bar
+    hovered_link: Option<String>,
+    find_mode: bool,
+    find_query: String,
+    /// Current match and match count of the find bar
+    find_result: (usize, usize),
+    reader_style_popover: Controller<ReaderStylePopover>,
+    outline_popover: Controller<OutlinePopover>,
+    /// Whether the open article has headings for the table of contents
+    has_outline: bool,
+    listen_bar: Controller<ListenBar>,
+    /// Article to start reading aloud once it's loaded, when listening
+    /// carries on from the previous one
+    continue_listening: Option<String>,
+    /// Articles opened since the app started
+    read_items: HashSet<String>,
+    /// Waiting for the translation server
+    translating: bool,
+    /// Whether the open article shows its translation
+    showing_translation: bool,
     search_mode: bool,
     search_query: String,
     all_articles: Vec<Article>,
+    store: Option<ArticleStore>,
     selected_tag: Option<String>,
     available_tags: Vec<String>,
     tag_model: gtk::StringList,
+    settings: gio::Settings,
+    preferences: Preferences,
+    sync_source: Option<glib::SourceId>,
+    selection_mode: bool,
+    selected_items: HashSet<String>,
+    folders: Vec<InstapaperFolder>,
+    folder_list_box: gtk::ListBox,
+    batch_progress: Option<(usize, usize)>,
+    search_entry: gtk::SearchEntry,
+    find_entry: gtk::SearchEntry,
 }
 
 #[derive(Debug)]
@@ -59,24 +121,77 @@ pub(super) enum AppMsg {
     ArticleSelected(String, String, String, String, f64),
     RefreshArticles,
     ArchiveArticle,
+    StarArticle,
     CopyArticleUrl,
     OpenArticle,
+    SelectNextArticle,
+    SelectPreviousArticle,
+    ScrollArticle(gtk::ScrollType),
+    PreferencesChanged,
+    ReaderStyleChanged(ReaderStyle),
     ShowAddBookmarkDialog,
     AddBookmarkCompleted(String, Vec<String>),
     AddBookmarkCancelled,
+    ShowExportDialog,
+    ExportCompleted(String),
+    MarkdownFolderChosen(std::path::PathBuf),
+    ExportCancelled,
+    ShowImportDialog,
+    ImportCompleted(String),
+    ImportDiscarded,
+    ImportCancelled,
+    ShowBackupDialog,
+    BackupCompleted(String),
+    LibraryRestored(Box<Library>, RestoreReport),
+    BackupCancelled,
+    LinkHovered(Option<String>),
+    StartFind,
+    StopFind,
+    FindInArticle(String),
+    FindNext,
+    FindPrevious,
+    FindResult(usize, usize),
+    OutlineChanged(Vec<OutlineEntry>),
+    CurrentHeadingChanged(Option<usize>),
+    ScrollToHeading(usize),
+    Listen,
+    ListenTextReady(Vec<String>),
+    SpeakingParagraph(Option<usize>),
+    ListenFinished,
+    ListenError(String),
+    ToggleTranslation,
+    TranslationTextReady(Vec<String>),
+    Print,
+    PrintFailed(String),
     ToggleSearchMode,
+    StartSearch,
     UpdateSearchQuery(String),
     ClearSearch,
     SetTagFilter(Option<String>),
+    ToggleSelectionMode,
+    SetArticleSelected(String, bool),
+    SelectAll,
+    RunBatch(BatchOperation),
+    DeleteSelected,
+    MoveSelectedToFolder(usize),
+    TagSelected(String),
 }
 
 #[derive(Debug)]
 pub(super) enum CommandMsg {
     RefreshedArticles(Vec<Article>),
-    ScrapedArticle(String),
+    ScrapedArticle(String, String, usize),
+    ScrapeFailed(String, String),
+    WordsCounted(Vec<(String, usize)>),
     ArticleArchived(String),
+    ArticleStarred,
     OpenUrl(String),
     BookmarkAdded,
+    RefreshedFolders(Vec<InstapaperFolder>),
+    BatchProgress(usize, usize),
+    BatchFinished(BatchOperation, BatchReport),
+    Translated(String, Vec<String>),
+    TranslationFailed(String),
     Error(String),
 }
 
@@ -85,6 +200,49 @@ relm4::new_stateless_action!(PreferencesAction, WindowActionGroup, "preferences"
 relm4::new_stateless_action!(pub(super) ShortcutsAction, WindowActionGroup, "show-help-overlay");
 relm4::new_stateless_action!(AboutAction, WindowActionGroup, "about");
 relm4::new_stateless_action!(LogoutAction, WindowActionGroup, "logout");
+relm4::new_stateless_action!(ImportAction, WindowActionGroup, "import");
+relm4::new_stateless_action!(ExportAction, WindowActionGroup, "export");
+relm4::new_stateless_action!(BackupAction, WindowActionGroup, "backup");
+relm4::new_stateless_action!(SearchAction, WindowActionGroup, "search");
+relm4::new_stateless_action!(FindAction, WindowActionGroup, "find");
+relm4::new_stateless_action!(FindNextAction, WindowActionGroup, "find-next");
+relm4::new_stateless_action!(FindPreviousAction, WindowActionGroup, "find-previous");
+relm4::new_stateless_action!(RefreshAction, WindowActionGroup, "refresh");
+relm4::new_stateless_action!(ArchiveAction, WindowActionGroup, "archive");
+relm4::new_stateless_action!(StarAction, WindowActionGroup, "star");
+relm4::new_stateless_action!(OpenArticleAction, WindowActionGroup, "open-article");
+relm4::new_stateless_action!(CopyUrlAction, WindowActionGroup, "copy-url");
+relm4::new_stateless_action!(PrintAction, WindowActionGroup, "print");
+relm4::new_stateless_action!(NextArticleAction, WindowActionGroup, "next-article");
+relm4::new_stateless_action!(PreviousArticleAction, WindowActionGroup, "previous-article");
+relm4::new_stateless_action!(PageDownAction, WindowActionGroup, "page-down");
+relm4::new_stateless_action!(PageUpAction, WindowActionGroup, "page-up");
+relm4::new_stateless_action!(ZoomInAction, WindowActionGroup, "zoom-in");
+relm4::new_stateless_action!(ZoomOutAction, WindowActionGroup, "zoom-out");
+relm4::new_stateless_action!(ZoomResetAction, WindowActionGroup, "zoom-reset");
+
+/// Adds stateless actions to `$group`, each sending its input through
+/// `$sender` when activated.
+macro_rules! send_actions {
+    ($group:expr, $sender:expr, { $($action:ty => $input:expr),* $(,)? }) => {
+        $({
+            let sender = $sender.clone();
+            $group.add_action(RelmAction::<$action>::new_stateless(move |_| sender.emit($input)));
+        })*
+    };
+}
+
+/// Single-key shortcuts are handled by a window-level controller in the bubble
+/// phase, so they never steal keystrokes from focused text entries.
+const KEY_SHORTCUTS: &[(&str, &str)] = &[
+    ("j", "win.next-article"),
+    ("k", "win.previous-article"),
+    ("e", "win.archive"),
+    ("s", "win.star"),
+    ("o", "win.open-article"),
+    ("space", "win.page-down"),
+    ("<Shift>space", "win.page-up"),
+];
 
 #[relm4::component(pub)]
 impl Component for App {
@@ -96,6 +254,11 @@ impl Component for App {
 
     menu! {
         primary_menu: {
+            section! {
+                &gettext("_Import…") => ImportAction,
+                &gettext("_Export…") => ExportAction,
+                &gettext("_Back Up and Restore…") => BackupAction,
+            },
             section! {
                 &gettext("_Preferences") => PreferencesAction,
                 &gettext("_Keyboard") => ShortcutsAction,
@@ -138,7 +301,8 @@ impl Component for App {
                         add_top_bar = if model.search_mode {
                             &adw::HeaderBar {
                                 #[wrap(Some)]
-                                set_title_widget = &gtk::SearchEntry {
+                                #[local_ref]
+                                set_title_widget = search_entry -> gtk::SearchEntry {
                                     set_placeholder_text: Some(&gettext("Search articles...")),
                                     connect_search_changed[sender] => move |entry| {
                                         sender.input(AppMsg::UpdateSearchQuery(entry.text().to_string()));
@@ -184,6 +348,17 @@ impl Component for App {
                                 },
 
                                 pack_end = &gtk::Box {
+                                    gtk::ToggleButton {
+                                        #[watch]
+                                        set_visible: model.tokens.is_some(),
+                                        set_icon_name: "selection-mode-symbolic",
+                                        set_tooltip_text: Some(&gettext("Select articles")),
+                                        #[watch]
+                                        #[block_signal(selection_toggled_handler)]
+                                        set_active: model.selection_mode,
+                                        connect_toggled => AppMsg::ToggleSelectionMode @selection_toggled_handler,
+                                    },
+
                                     gtk::Button {
                                         #[watch]
                                         set_visible: model.tokens.is_some(),
@@ -236,7 +411,112 @@ impl Component for App {
                                     }
                                 }
                             }
-                        }
+                        },
+
+                        add_bottom_bar = &gtk::ActionBar {
+                            #[watch]
+                            set_revealed: model.selection_mode,
+
+                            pack_start = &gtk::Button {
+                                set_icon_name: "edit-select-all-symbolic",
+                                set_tooltip_text: Some(&gettext("Select all")),
+                                #[watch]
+                                set_sensitive: model.batch_progress.is_none(),
+                                connect_clicked => AppMsg::SelectAll,
+                            },
+
+                            #[wrap(Some)]
+                            set_center_widget = &gtk::Box {
+                                set_orientation: gtk::Orientation::Vertical,
+                                set_valign: gtk::Align::Center,
+                                set_spacing: 4,
+
+                                gtk::Label {
+                                    #[watch]
+                                    set_label: &ngettext("{} selected", "{} selected", model.selected_items.len() as u32)
+                                        .replace("{}", &model.selected_items.len().to_string()),
+                                },
+
+                                gtk::ProgressBar {
+                                    #[watch]
+                                    set_visible: model.batch_progress.is_some(),
+                                    #[watch]
+                                    set_fraction: model
+                                        .batch_progress
+                                        .map(|(done, total)| done as f64 / total.max(1) as f64)
+                                        .unwrap_or(0.0),
+                                },
+                            },
+
+                            pack_end = &gtk::Box {
+                                set_spacing: 6,
+                                #[watch]
+                                set_sensitive: !model.selected_items.is_empty() && model.batch_progress.is_none(),
+
+                                gtk::Button {
+                                    set_icon_name: "shoe-box-symbolic",
+                                    set_tooltip_text: Some(&gettext("Archive selected")),
+                                    connect_clicked => AppMsg::RunBatch(BatchOperation::Archive),
+                                },
+
+                                gtk::Button {
+                                    set_icon_name: "starred-symbolic",
+                                    set_tooltip_text: Some(&gettext("Star selected")),
+                                    connect_clicked => AppMsg::RunBatch(BatchOperation::Star),
+                                },
+
+                                gtk::MenuButton {
+                                    set_icon_name: "folder-symbolic",
+                                    set_tooltip_text: Some(&gettext("Move to folder")),
+
+                                    #[wrap(Some)]
+                                    set_popover = &gtk::Popover {
+                                        #[local_ref]
+                                        folder_list_box -> gtk::ListBox {
+                                            set_selection_mode: gtk::SelectionMode::None,
+                                            connect_row_activated[sender] => move |list_box, row| {
+                                                if let Some(popover) = list_box
+                                                    .ancestor(gtk::Popover::static_type())
+                                                    .and_then(|w| w.downcast::<gtk::Popover>().ok())
+                                                {
+                                                    popover.popdown();
+                                                }
+                                                sender.input(AppMsg::MoveSelectedToFolder(row.index() as usize));
+                                            },
+                                        },
+                                    },
+                                },
+
+                                gtk::MenuButton {
+                                    set_icon_name: "tag-symbolic",
+                                    set_tooltip_text: Some(&gettext("Tag selected")),
+
+                                    #[wrap(Some)]
+                                    set_popover = &gtk::Popover {
+                                        gtk::Entry {
+                                            set_placeholder_text: Some(&gettext("Comma-separated tags")),
+                                            connect_activate[sender] => move |entry| {
+                                                sender.input(AppMsg::TagSelected(entry.text().to_string()));
+                                                entry.set_text("");
+                                                if let Some(popover) = entry
+                                                    .ancestor(gtk::Popover::static_type())
+                                                    .and_then(|w| w.downcast::<gtk::Popover>().ok())
+                                                {
+                                                    popover.popdown();
+                                                }
+                                            },
+                                        },
+                                    },
+                                },
+
+                                gtk::Button {
+                                    set_icon_name: "user-trash-symbolic",
+                                    set_tooltip_text: Some(&gettext("Delete selected")),
+                                    add_css_class: "destructive-action",
+                                    connect_clicked => AppMsg::DeleteSelected,
+                                },
+                            },
+                        },
                     },
                 },
 
@@ -255,6 +535,10 @@ impl Component for App {
                                         set_icon_name: "shoe-box-symbolic",
                                         connect_clicked => AppMsg::ArchiveArticle
                                     },
+                                    gtk::Button {
+                                        set_icon_name: "starred-symbolic",
+                                        connect_clicked => AppMsg::StarArticle
+                                    },
                                     gtk::Button {
                                         set_icon_name: "edit-copy-symbolic",
                                         connect_clicked => AppMsg::CopyArticleUrl
@@ -263,12 +547,107 @@ impl Component for App {
                                         set_icon_name: "compass-symbolic",
                                         connect_clicked => AppMsg::OpenArticle
                                     },
+                                    gtk::Button {
+                                        set_icon_name: "audio-speakers-symbolic",
+                                        set_tooltip_text: Some(&gettext("Listen")),
+                                        connect_clicked => AppMsg::Listen
+                                    },
+                                    gtk::Button {
+                                        set_icon_name: "preferences-desktop-locale-symbolic",
+                                        #[watch]
+                                        set_tooltip_text: Some(&if model.showing_translation {
+                                            gettext("Show Original")
+                                        } else {
+                                            gettext("Translate")
+                                        }),
+                                        #[watch]
+                                        set_sensitive: !model.translating,
+                                        connect_clicked => AppMsg::ToggleTranslation
+                                    },
+                                    gtk::Button {
+                                        set_icon_name: "document-print-symbolic",
+                                        set_tooltip_text: Some(&gettext("Print or Save as PDF")),
+                                        set_action_name: Some("win.print"),
+                                    },
                                 },
 
                                 #[wrap(Some)]
                                 set_title_widget = &adw::WindowTitle {
                                     set_title: "Cauldron",
-                                }
+                                },
+
+                                pack_end = &gtk::MenuButton {
+                                    set_icon_name: "preferences-desktop-font-symbolic",
+                                    set_tooltip_text: Some(&gettext("Reader Style")),
+                                    set_popover: Some(reader_style_popover),
+                                },
+
+                                pack_end = &gtk::MenuButton {
+                                    set_icon_name: "view-list-bullet-symbolic",
+                                    set_tooltip_text: Some(&gettext("Table of Contents")),
+                                    #[watch]
+                                    set_visible: model.article_html.is_some() && model.has_outline,
+                                    set_popover: Some(outline_popover),
+                                },
+                            },
+
+                            #[local_ref]
+                            add_bottom_bar = listen_bar -> gtk::ActionBar {},
+
+                            add_top_bar = &gtk::SearchBar {
+                                set_show_close_button: true,
+                                #[watch]
+                                set_search_mode: model.find_mode,
+                                connect_search_mode_enabled_notify[sender] => move |bar| {
+                                    if !bar.is_search_mode() {
+                                        sender.input(AppMsg::StopFind);
+                                    }
+                                },
+
+                                #[wrap(Some)]
+                                set_child = &adw::Clamp {
+                                    set_maximum_size: 500,
+
+                                    gtk::Box {
+                                        set_spacing: 6,
+
+                                        #[local_ref]
+                                        find_entry -> gtk::SearchEntry {
+                                            set_hexpand: true,
+                                            set_placeholder_text: Some(&gettext("Find in article...")),
+                                            connect_search_changed[sender] => move |entry| {
+                                                sender.input(AppMsg::FindInArticle(entry.text().to_string()));
+                                            },
+                                            connect_activate => AppMsg::FindNext,
+                                            connect_next_match => AppMsg::FindNext,
+                                            connect_previous_match => AppMsg::FindPrevious,
+                                            connect_stop_search => AppMsg::StopFind,
+                                        },
+
+                                        gtk::Label {
+                                            add_css_class: "dim-label",
+                                            add_css_class: "numeric",
+                                            #[watch]
+                                            set_label: &model.find_status(),
+                                        },
+
+                                        gtk::Button {
+                                            set_icon_name: "go-up-symbolic",
+                                            set_tooltip_text: Some(&gettext("Previous Match")),
+                                            #[watch]
+                                            set_sensitive: model.find_result.1 > 0,
+                                            connect_clicked => AppMsg::FindPrevious,
+                                        },
+
+                                        gtk::Button {
+                                            set_icon_name: "go-down-symbolic",
+                                            set_tooltip_text: Some(&gettext("Next Match")),
+                                            #[watch]
+                                            set_sensitive: model.find_result.1 > 0,
+                                            connect_clicked => AppMsg::FindNext,
+                                        },
+                                    },
+                                },
                             },
 
                             #[wrap(Some)]
@@ -281,10 +660,26 @@ impl Component for App {
                                     set_hexpand: true,
                                     set_text: &gettext("Select an article"),
                                 },
-                                #[local_ref]
-                                article_renderer_widget -> gtk::ScrolledWindow {
+                                gtk::Overlay {
                                     #[watch]
                                     set_visible: model.article_html.is_some(),
+                                    set_hexpand: true,
+
+                                    #[wrap(Some)]
+                                    #[local_ref]
+                                    set_child = article_renderer_widget -> gtk::ScrolledWindow {},
+
+                                    add_overlay = &gtk::Label {
+                                        add_css_class: "link-status-bar",
+                                        set_halign: gtk::Align::Start,
+                                        set_valign: gtk::Align::End,
+                                        set_ellipsize: gtk::pango::EllipsizeMode::Middle,
+                                        set_can_target: false,
+                                        #[watch]
+                                        set_visible: model.hovered_link.is_some(),
+                                        #[watch]
+                                        set_label: model.hovered_link.as_deref().unwrap_or_default(),
+                                    },
                                 },
                             }
                         },
@@ -311,9 +706,24 @@ impl Component for App {
                 ArticleOutput::ArticleSelected(title, uri, item_id, description, time, _tags) => {
                     AppMsg::ArticleSelected(title, uri, item_id, description, time)
                 }
+                ArticleOutput::SelectionChanged(item_id, selected) => {
+                    AppMsg::SetArticleSelected(item_id, selected)
+                }
             });
 
-        let cached_articles = articles::read_articles().unwrap_or_default();
+        let settings = gio::Settings::new(APP_ID);
+        translation_key::migrate_from_settings(&settings);
+        let preferences = Preferences::load(&settings);
+        let words_per_minute = preferences.words_per_minute;
+
+        let (store, store_error) = match ArticleStore::open() {
+            Ok(store) => (Some(store), None),
+            Err(e) => (None, Some(e.to_string())),
+        };
+        let cached_articles = store
+            .as_ref()
+            .and_then(|store| store.articles().ok())
+            .unwrap_or_default();
 
         let all_articles: Vec<Article> = cached_articles
             .iter()
@@ -324,6 +734,10 @@ impl Component for App {
                 description: article.description.clone(),
                 time: article.time,
                 tags: article.tags.clone(),
+                word_count: article.word_count,
+                words_per_minute,
+                selection_mode: false,
+                selected: false,
             })
             .collect();
 
@@ -335,10 +749,72 @@ impl Component for App {
                 description: article.description.clone(),
                 time: article.time,
                 tags: article.tags.clone(),
+                word_count: article.word_count,
+                words_per_minute,
+                selection_mode: false,
+                selected: false,
             });
         });
 
-        let article_renderer = ArticleRenderer::builder().launch(()).detach();
+        let article_renderer =
+            ArticleRenderer::builder()
+                .launch(())
+                .forward(sender.input_sender(), |output| match output {
+                    ArticleRendererOutput::SaveLink(url) => {
+                        AppMsg::AddBookmarkCompleted(url, Vec::new())
+                    }
+                    ArticleRendererOutput::LinkHovered(url) => AppMsg::LinkHovered(url),
+                    ArticleRendererOutput::FindResult { current, total } => {
+                        AppMsg::FindResult(current, total)
+                    }
+                    ArticleRendererOutput::Outline(entries) => AppMsg::OutlineChanged(entries),
+                    ArticleRendererOutput::CurrentHeading(index) => {
+                        AppMsg::CurrentHeadingChanged(index)
+                    }
+                    ArticleRendererOutput::ListenText(paragraphs) => {
+                        AppMsg::ListenTextReady(paragraphs)
+                    }
+                    ArticleRendererOutput::TranslationText(paragraphs) => {
+                        AppMsg::TranslationTextReady(paragraphs)
+                    }
+                    ArticleRendererOutput::PrintFailed(error) => AppMsg::PrintFailed(error),
+                });
+        article_renderer.emit(ArticleRendererInput::SetWordsPerMinute(words_per_minute));
+        article_renderer.emit(ArticleRendererInput::SetStyle(
+            preferences.reader_style.clone(),
+        ));
+        article_renderer.emit(ArticleRendererInput::SetImageLoading(
+            preferences.image_loading,
+        ));
+        article_renderer.emit(ArticleRendererInput::SetImageCacheSize(
+            preferences.offline_cache_size,
+        ));
+
+        let reader_style_popover = ReaderStylePopover::builder()
+            .launch(preferences.reader_style.clone())
+            .forward(sender.input_sender(), |output| match output {
+                ReaderStylePopoverOutput::StyleChanged(style) => AppMsg::ReaderStyleChanged(style),
+            });
+
+        let listen_bar = ListenBar::builder()
+            .launch(speech::Backend::detect())
+            .forward(sender.input_sender(), |output| match output {
+                ListenBarOutput::Speaking(index) => AppMsg::SpeakingParagraph(index),
+                ListenBarOutput::Finished => AppMsg::ListenFinished,
+                ListenBarOutput::Error(error) => AppMsg::ListenError(error),
+            });
+
+        let outline_popover =
+            OutlinePopover::builder()
+                .launch(())
+                .forward(sender.input_sender(), |output| match output {
+                    OutlinePopoverOutput::HeadingSelected(index) => AppMsg::ScrollToHeading(index),
+                });
+
+        settings.connect_changed(None, {
+            let sender = sender.clone();
+            move |_, _| sender.input(AppMsg::PreferencesChanged)
+        });
 
         let mut available_tags: Vec<String> = all_articles
             .iter()
@@ -353,7 +829,7 @@ impl Component for App {
         tag_items.extend(available_tags.iter().map(|s| s.as_str()));
         let tag_model = gtk::StringList::new(&tag_items);
 
-        let model = Self {
+        let mut model = Self {
             tokens,
             username,
             articles,
@@ -365,21 +841,72 @@ impl Component for App {
             toaster: Toaster::default(),
             login_dialog: None,
             add_bookmark_dialog: None,
+            export_dialog: None,
+            import_dialog: None,
+            backup_dialog: None,
             article_renderer,
+            hovered_link: None,
+            find_mode: false,
+            find_query: String::new(),
+            find_result: (0, 0),
+            reader_style_popover,
+            outline_popover,
+            has_outline: false,
+            listen_bar,
+            continue_listening: None,
+            read_items: HashSet::new(),
+            translating: false,
+            showing_translation: false,
             search_mode: false,
             search_query: String::new(),
             all_articles,
+            store,
             selected_tag: None,
             available_tags,
             tag_model,
+            settings,
+            preferences,
+            sync_source: None,
+            selection_mode: false,
+            selected_items: HashSet::new(),
+            folders: Vec::new(),
+            folder_list_box: gtk::ListBox::default(),
+            batch_progress: None,
+            search_entry: gtk::SearchEntry::default(),
+            find_entry: gtk::SearchEntry::default(),
         };
 
+        model.schedule_sync(&sender);
+
+        if let Some(error) = store_error {
+            // Bookmarks still load from Instapaper, they just aren't kept
+            let title = format!("{}: {}", gettext("Failed to open the library"), error);
+            let toast = adw::Toast::builder().title(&title).timeout(0).build();
+            model.toaster.add_toast(toast);
+        }
+
         let toast_overlay = model.toaster.overlay_widget();
 
         let articles_list_box = model.articles.widget();
 
         let article_renderer_widget = model.article_renderer.widget();
 
+        let folder_list_box = &model.folder_list_box;
+        let search_entry = &model.search_entry;
+        let find_entry = &model.find_entry;
+        let reader_style_popover = model.reader_style_popover.widget();
+        let outline_popover = model.outline_popover.widget();
+        let listen_bar = model.listen_bar.widget();
+        folder_list_box.set_placeholder(Some(
+            &gtk::Label::builder()
+                .label(gettext("No folders"))
+                .margin_top(12)
+                .margin_bottom(12)
+                .margin_start(12)
+                .margin_end(12)
+                .build(),
+        ));
+
         let widgets = view_output!();
 
         let mut actions = RelmActionGroup::<WindowActionGroup>::new();
@@ -391,36 +918,103 @@ impl Component for App {
             })
         };
 
-        let about_action = {
-            RelmAction::<AboutAction>::new_stateless(move |_| {
-                AboutDialog::builder().launch(()).detach();
+        let preferences_action = {
+            let settings = model.settings.clone();
+            RelmAction::<PreferencesAction>::new_stateless(move |_| {
+                PreferencesDialog::builder()
+                    .launch(settings.clone())
+                    .detach();
             })
         };
 
-        let logout_action = {
-            let sender_clone = sender.clone();
-            RelmAction::<LogoutAction>::new_stateless(move |_| {
-                sender_clone.input(AppMsg::Logout);
+        let about_action = {
+            RelmAction::<AboutAction>::new_stateless(move |_| {
+                AboutDialog::builder().launch(()).detach();
             })
         };
 
+        actions.add_action(preferences_action);
         actions.add_action(shortcuts_action);
         actions.add_action(about_action);
-        actions.add_action(logout_action);
+        send_actions!(actions, sender.input_sender(), {
+            LogoutAction => AppMsg::Logout,
+            ImportAction => AppMsg::ShowImportDialog,
+            ExportAction => AppMsg::ShowExportDialog,
+            BackupAction => AppMsg::ShowBackupDialog,
+            SearchAction => AppMsg::StartSearch,
+            FindAction => AppMsg::StartFind,
+            FindNextAction => AppMsg::FindNext,
+            FindPreviousAction => AppMsg::FindPrevious,
+            RefreshAction => AppMsg::RefreshArticles,
+            ArchiveAction => AppMsg::ArchiveArticle,
+            StarAction => AppMsg::StarArticle,
+            OpenArticleAction => AppMsg::OpenArticle,
+            CopyUrlAction => AppMsg::CopyArticleUrl,
+            PrintAction => AppMsg::Print,
+            NextArticleAction => AppMsg::SelectNextArticle,
+            PreviousArticleAction => AppMsg::SelectPreviousArticle,
+            PageDownAction => AppMsg::ScrollArticle(gtk::ScrollType::PageDown),
+            PageUpAction => AppMsg::ScrollArticle(gtk::ScrollType::PageUp),
+        });
+        send_actions!(actions, model.reader_style_popover.sender(), {
+            ZoomInAction => ReaderStylePopoverInput::ZoomIn,
+            ZoomOutAction => ReaderStylePopoverInput::ZoomOut,
+            ZoomResetAction => ReaderStylePopoverInput::ZoomReset,
+        });
         actions.register_for_widget(&widgets.main_window);
 
+        let app = main_application();
+        app.set_accelerators_for_action::<SearchAction>(&["<Control>k"]);
+        app.set_accelerators_for_action::<FindAction>(&["<Control>f"]);
+        app.set_accelerators_for_action::<FindNextAction>(&["<Control>g"]);
+        app.set_accelerators_for_action::<FindPreviousAction>(&["<Control><Shift>g"]);
+        app.set_accelerators_for_action::<RefreshAction>(&["<Control>r", "F5"]);
+        app.set_accelerators_for_action::<CopyUrlAction>(&["<Control>l"]);
+        app.set_accelerators_for_action::<PrintAction>(&["<Control>p"]);
+        app.set_accelerators_for_action::<ZoomInAction>(&[
+            "<Control>plus",
+            "<Control>equal",
+            "<Control>KP_Add",
+        ]);
+        app.set_accelerators_for_action::<ZoomOutAction>(&[
+            "<Control>minus",
+            "<Control>KP_Subtract",
+        ]);
+        app.set_accelerators_for_action::<ZoomResetAction>(&["<Control>0", "<Control>KP_0"]);
+
+        let key_shortcuts = gtk::ShortcutController::new();
+        for (trigger, action) in KEY_SHORTCUTS {
+            key_shortcuts.add_shortcut(gtk::Shortcut::new(
+                gtk::ShortcutTrigger::parse_string(trigger),
+                Some(gtk::NamedAction::new(action)),
+            ));
+        }
+        widgets.main_window.add_controller(key_shortcuts);
+
         widgets.load_window_size();
 
         ComponentParts { model, widgets }
     }
 
-    fn update(&mut self, message: Self::Input, sender: ComponentSender<Self>, _: &Self::Root) {
+    fn update(&mut self, message: Self::Input, sender: ComponentSender<Self>, root: &Self::Root) {
         match message {
             AppMsg::Quit => main_application().quit(),
             AppMsg::ArticleSelected(title, uri, item_id, description, time) => {
+                if self.continue_listening.as_deref() != Some(item_id.as_str()) {
+                    self.continue_listening = None;
+                    self.listen_bar.emit(ListenBarInput::Stop);
+                }
+                self.read_items.insert(item_id.clone());
                 self.article_title = Some(title.clone());
                 self.article_uri = Some(uri.clone());
-                self.article_item_id = Some(item_id);
+                self.article_item_id = Some(item_id.clone());
+                self.translating = false;
+
+                let word_count = self
+                    .all_articles
+                    .iter()
+                    .find(|a| a.item_id == item_id)
+                    .and_then(|a| a.word_count);
 
                 self.article_renderer
                     .emit(ArticleRendererInput::SetTitle(title));
@@ -430,12 +1024,26 @@ impl Component for App {
                         url: uri.clone(),
                         description: description.clone(),
                         time,
+                        word_count,
                     });
 
+                let instapaper_tokens = match self.preferences.content_source {
+                    ContentSource::Instapaper => self.tokens.clone(),
+                    ContentSource::Original => None,
+                };
+
+                let cache_size = self.preferences.offline_cache_size;
+
                 sender.oneshot_command(async move {
-                    let article = get_html(Some(uri)).await;
-                    let html = Readability::extract(&article, None).await;
-                    CommandMsg::ScrapedArticle(html.unwrap())
+                    match content::fetch(&item_id, &uri, instapaper_tokens.as_ref(), cache_size)
+                        .await
+                    {
+                        Ok(article) => {
+                            let word_count = reading_time::count_html_words(&article.html);
+                            CommandMsg::ScrapedArticle(item_id, article.html, word_count)
+                        }
+                        Err(e) => CommandMsg::ScrapeFailed(item_id, e.to_string()),
+                    }
                 });
             }
             AppMsg::StartLogin => {
@@ -464,7 +1072,16 @@ impl Component for App {
             AppMsg::Logout => {
                 println!("porco dio");
                 let _ = token::clear_tokens();
-                let _ = articles::clear_articles();
+                if let Some(store) = &mut self.store {
+                    let _ = store.clear();
+                }
+                let _ = import_queue::clear();
+                if let Err(e) = offline_cache::clear() {
+                    eprintln!("Failed to clear the offline cache: {}", e);
+                }
+                if let Err(e) = translations::clear() {
+                    eprintln!("Failed to clear the translations: {}", e);
+                }
                 self.tokens = None;
                 self.username = String::new();
                 self.articles.guard().clear();
@@ -476,6 +1093,10 @@ impl Component for App {
                 self.search_mode = false;
                 self.selected_tag = None;
                 self.available_tags.clear();
+                self.selection_mode = false;
+                self.selected_items.clear();
+                self.folders.clear();
+                self.rebuild_folder_list();
                 self.tag_model
                     .splice(0, self.tag_model.n_items(), &[&gettext("All")]);
             }
@@ -485,7 +1106,8 @@ impl Component for App {
 
                     sender.oneshot_command(async move {
                         let client = instapaper::client();
-                        let entries = instapaper::get_bookmarks(&client, &tokens).await;
+                        let entries =
+                            instapaper::get_bookmarks(&client, &tokens, instapaper::BASE_URL).await;
 
                         match entries {
                             Ok(bookmarks) => {
@@ -500,6 +1122,19 @@ impl Component for App {
                             )),
                         }
                     });
+
+                    let tokens = self.tokens.clone().unwrap();
+                    sender.oneshot_command(async move {
+                        let client = instapaper::client();
+                        match instapaper::get_folders(&client, &tokens, instapaper::BASE_URL).await
+                        {
+                            Ok(folders) => CommandMsg::RefreshedFolders(folders),
+                            Err(e) => {
+                                eprintln!("Failed to refresh folders: {}", e);
+                                CommandMsg::RefreshedFolders(Vec::new())
+                            }
+                        }
+                    });
                 }
             }
             AppMsg::ArchiveArticle => {
@@ -508,8 +1143,19 @@ impl Component for App {
                 {
                     sender.oneshot_command(async move {
                         let client = instapaper::client();
-                        let bookmark_id: i64 = item_id.parse().unwrap_or(0);
-                        match instapaper::archive_bookmark(&client, &tokens, bookmark_id).await {
+                        let result = match instapaper::bookmark_id(&item_id) {
+                            Ok(bookmark_id) => {
+                                instapaper::archive_bookmark(
+                                    &client,
+                                    &tokens,
+                                    bookmark_id,
+                                    instapaper::BASE_URL,
+                                )
+                                .await
+                            }
+                            Err(e) => Err(e),
+                        };
+                        match result {
                             Ok(_) => CommandMsg::ArticleArchived(item_id),
                             Err(e) => CommandMsg::Error(format!(
                                 "{}: {}",
@@ -520,6 +1166,35 @@ impl Component for App {
                     });
                 }
             }
+            AppMsg::StarArticle => {
+                if let (Some(tokens), Some(item_id)) =
+                    (self.tokens.clone(), self.article_item_id.clone())
+                {
+                    sender.oneshot_command(async move {
+                        let client = instapaper::client();
+                        let result = match instapaper::bookmark_id(&item_id) {
+                            Ok(bookmark_id) => {
+                                instapaper::star_bookmark(
+                                    &client,
+                                    &tokens,
+                                    bookmark_id,
+                                    instapaper::BASE_URL,
+                                )
+                                .await
+                            }
+                            Err(e) => Err(e),
+                        };
+                        match result {
+                            Ok(_) => CommandMsg::ArticleStarred,
+                            Err(e) => CommandMsg::Error(format!(
+                                "{}: {}",
+                                gettext("Failed to star article"),
+                                e
+                            )),
+                        }
+                    });
+                }
+            }
             AppMsg::CopyArticleUrl => match self.article_uri.clone() {
                 Some(uri) => {
                     let _ = crate::persistence::clipboard::copy(&uri);
@@ -536,6 +1211,84 @@ impl Component for App {
                     sender.oneshot_command(async move { CommandMsg::OpenUrl(uri.to_owned()) });
                 }
             }
+            AppMsg::SelectNextArticle | AppMsg::SelectPreviousArticle => {
+                let forward = matches!(message, AppMsg::SelectNextArticle);
+                let visible = self.filter_articles();
+                let item_ids: Vec<&str> = visible.iter().map(|a| a.item_id.as_str()).collect();
+
+                if let Some(index) =
+                    adjacent_article_index(&item_ids, self.article_item_id.as_deref(), forward)
+                {
+                    self.open_article(&visible[index], index, &sender);
+                }
+            }
+            AppMsg::ScrollArticle(scroll) => {
+                if self.article_html.is_some() {
+                    self.article_renderer
+                        .widget()
+                        .emit_scroll_child(scroll, false);
+                }
+            }
+            AppMsg::PreferencesChanged => {
+                let preferences = Preferences::load(&self.settings);
+                if preferences == self.preferences {
+                    return;
+                }
+                let previous = std::mem::replace(&mut self.preferences, preferences);
+
+                if previous.reader_style != self.preferences.reader_style {
+                    self.article_renderer.emit(ArticleRendererInput::SetStyle(
+                        self.preferences.reader_style.clone(),
+                    ));
+                    self.reader_style_popover
+                        .emit(ReaderStylePopoverInput::SetStyle(
+                            self.preferences.reader_style.clone(),
+                        ));
+                }
+
+                if previous.image_loading != self.preferences.image_loading {
+                    self.article_renderer
+                        .emit(ArticleRendererInput::SetImageLoading(
+                            self.preferences.image_loading,
+                        ));
+                }
+
+                if previous.offline_cache_size != self.preferences.offline_cache_size {
+                    self.article_renderer
+                        .emit(ArticleRendererInput::SetImageCacheSize(
+                            self.preferences.offline_cache_size,
+                        ));
+                }
+
+                if previous.words_per_minute != self.preferences.words_per_minute {
+                    let words_per_minute = self.preferences.words_per_minute;
+                    for article in self.all_articles.iter_mut() {
+                        article.words_per_minute = words_per_minute;
+                    }
+                    self.articles
+                        .broadcast(ArticleInput::SetWordsPerMinute(words_per_minute));
+                    self.article_renderer
+                        .emit(ArticleRendererInput::SetWordsPerMinute(words_per_minute));
+                }
+
+                if previous.sync_interval != self.preferences.sync_interval {
+                    self.schedule_sync(&sender);
+                }
+            }
+            AppMsg::ReaderStyleChanged(style) => {
+                // Persisting the style triggers PreferencesChanged, which applies it
+                let _ = self
+                    .settings
+                    .set_string("reader-font-family", &style.font_family);
+                let _ = self.settings.set_uint("reader-font-size", style.font_size);
+                let _ = self
+                    .settings
+                    .set_uint("reader-line-width", style.line_width);
+                let _ = self
+                    .settings
+                    .set_double("reader-line-height", style.line_height);
+                let _ = self.settings.set_string("reader-theme", style.theme.key());
+            }
             AppMsg::ShowAddBookmarkDialog => {
                 if let Some(tokens) = self.tokens.clone() {
                     let add_bookmark_dialog = AddBookmarkDialog::builder().launch(tokens).forward(
@@ -554,7 +1307,16 @@ impl Component for App {
                 if let Some(tokens) = self.tokens.clone() {
                     sender.oneshot_command(async move {
                         let client = instapaper::client();
-                        match instapaper::add_bookmark(&client, &tokens, &url, &tags).await {
+                        match instapaper::add_bookmark(
+                            &client,
+                            &tokens,
+                            &url,
+                            "",
+                            &tags,
+                            instapaper::BASE_URL,
+                        )
+                        .await
+                        {
                             Ok(_) => CommandMsg::BookmarkAdded,
                             Err(e) => CommandMsg::Error(format!(
                                 "{}: {}",
@@ -569,6 +1331,296 @@ impl Component for App {
             AppMsg::AddBookmarkCancelled => {
                 self.add_bookmark_dialog = None;
             }
+            AppMsg::ShowExportDialog => {
+                let visible: HashSet<String> = self
+                    .filter_articles()
+                    .into_iter()
+                    .map(|a| a.item_id)
+                    .collect();
+                let init = ExportInit {
+                    visible: self
+                        .all_articles
+                        .iter()
+                        .filter(|a| visible.contains(&a.item_id))
+                        .map(ExportArticle::from)
+                        .collect(),
+                    all: self.all_articles.iter().map(ExportArticle::from).collect(),
+                    tags: self.available_tags.clone(),
+                    folders: self.folders.clone(),
+                    tokens: self.tokens.clone(),
+                    content_source: self.preferences.content_source,
+                    cache_size: self.preferences.offline_cache_size,
+                    markdown_folder: Some(self.settings.string("markdown-export-folder"))
+                        .filter(|folder| !folder.is_empty())
+                        .map(|folder| folder.as_str().into()),
+                };
+
+                let export_dialog =
+                    ExportDialog::builder()
+                        .launch(init)
+                        .forward(sender.input_sender(), |output| match output {
+                            ExportOutput::Exported(summary) => AppMsg::ExportCompleted(summary),
+                            ExportOutput::MarkdownFolderChosen(folder) => {
+                                AppMsg::MarkdownFolderChosen(folder)
+                            }
+                            ExportOutput::Cancelled => AppMsg::ExportCancelled,
+                        });
+                self.export_dialog = Some(export_dialog);
+            }
+            AppMsg::ExportCompleted(summary) => {
+                let toast = adw::Toast::builder().title(&summary).timeout(5).build();
+                self.toaster.add_toast(toast);
+                self.export_dialog = None;
+            }
+            AppMsg::MarkdownFolderChosen(folder) => {
+                let _ = self
+                    .settings
+                    .set_string("markdown-export-folder", &folder.to_string_lossy());
+            }
+            AppMsg::ExportCancelled => {
+                self.export_dialog = None;
+            }
+            AppMsg::ShowImportDialog => {
+                if let Some(tokens) = self.tokens.clone() {
+                    let init = ImportInit {
+                        tokens,
+                        existing: self.all_articles.iter().map(|a| a.uri.clone()).collect(),
+                        pending: import_queue::read(),
+                    };
+
+                    let import_dialog = ImportDialog::builder().launch(init).forward(
+                        sender.input_sender(),
+                        |output| match output {
+                            ImportOutput::Imported(summary) => AppMsg::ImportCompleted(summary),
+                            ImportOutput::Discarded => AppMsg::ImportDiscarded,
+                            ImportOutput::Cancelled => AppMsg::ImportCancelled,
+                        },
+                    );
+                    self.import_dialog = Some(import_dialog);
+                }
+            }
+            AppMsg::ImportCompleted(summary) => {
+                let toast = adw::Toast::builder().title(&summary).timeout(5).build();
+                self.toaster.add_toast(toast);
+                self.import_dialog = None;
+                sender.input(AppMsg::RefreshArticles);
+            }
+            AppMsg::ImportDiscarded => {
+                let _ = import_queue::clear();
+            }
+            AppMsg::ImportCancelled => {
+                self.import_dialog = None;
+            }
+            AppMsg::ShowBackupDialog => {
+                let init = BackupInit {
+                    articles: self.persisted_articles(),
+                    settings: backup::settings_snapshot(&self.settings),
+                    tokens: self.tokens.clone(),
+                };
+
+                let backup_dialog =
+                    BackupDialog::builder()
+                        .launch(init)
+                        .forward(sender.input_sender(), |output| match output {
+                            BackupOutput::BackedUp(summary) => AppMsg::BackupCompleted(summary),
+                            BackupOutput::Restored(library, report) => {
+                                AppMsg::LibraryRestored(library, report)
+                            }
+                            BackupOutput::Cancelled => AppMsg::BackupCancelled,
+                        });
+                self.backup_dialog = Some(backup_dialog);
+            }
+            AppMsg::BackupCompleted(summary) => {
+                let toast = adw::Toast::builder().title(&summary).timeout(5).build();
+                self.toaster.add_toast(toast);
+                self.backup_dialog = None;
+            }
+            AppMsg::LibraryRestored(library, mut report) => {
+                let existing: Vec<String> = self
+                    .all_articles
+                    .iter()
+                    .map(|a| a.item_id.clone())
+                    .collect();
+                let restored = backup::missing_bookmarks(&library, &existing);
+                report.bookmarks = restored.len();
+
+                if !restored.is_empty() {
+                    let words_per_minute = self.preferences.words_per_minute;
+                    self.all_articles
+                        .extend(restored.into_iter().map(|article| Article {
+                            title: article.title,
+                            uri: article.uri,
+                            item_id: article.item_id,
+                            description: article.description,
+                            time: article.time,
+                            tags: article.tags,
+                            word_count: article.word_count,
+                            words_per_minute,
+                            selection_mode: false,
+                            selected: false,
+                        }));
+                    self.refresh_available_tags();
+                    self.rebuild_article_list();
+                    self.save_articles_cache();
+                }
+
+                // Applying them goes through PreferencesChanged like any other change
+                report.settings = backup::restore_settings(&self.settings, &library.settings);
+
+                let toast = adw::Toast::builder()
+                    .title(&backup_dialog::restore_summary(&report))
+                    .timeout(5)
+                    .build();
+                self.toaster.add_toast(toast);
+                self.backup_dialog = None;
+
+                // Picks up what the restore added on Instapaper
+                if self.tokens.is_some() {
+                    sender.input(AppMsg::RefreshArticles);
+                }
+            }
+            AppMsg::BackupCancelled => {
+                self.backup_dialog = None;
+            }
+            AppMsg::LinkHovered(url) => {
+                self.hovered_link = url;
+            }
+            AppMsg::StartFind => {
+                // Without an article Ctrl+F searches the list instead
+                if self.article_item_id.is_none() {
+                    sender.input(AppMsg::StartSearch);
+                    return;
+                }
+                self.find_mode = true;
+
+                let find_entry = self.find_entry.clone();
+                glib::idle_add_local_once(move || {
+                    find_entry.grab_focus();
+                    find_entry.select_region(0, -1);
+                });
+            }
+            AppMsg::StopFind => {
+                if !self.find_mode {
+                    return;
+                }
+                self.find_mode = false;
+                self.find_query.clear();
+                self.find_result = (0, 0);
+                self.find_entry.set_text("");
+                self.article_renderer.emit(ArticleRendererInput::ClearFind);
+            }
+            AppMsg::FindInArticle(query) => {
+                // Clearing the entry on close lands here after the bar is gone
+                if !self.find_mode {
+                    return;
+                }
+                self.find_query = query.clone();
+                self.article_renderer
+                    .emit(ArticleRendererInput::Find(query));
+            }
+            AppMsg::FindNext => {
+                if self.find_mode {
+                    self.article_renderer.emit(ArticleRendererInput::FindNext);
+                }
+            }
+            AppMsg::FindPrevious => {
+                if self.find_mode {
+                    self.article_renderer
+                        .emit(ArticleRendererInput::FindPrevious);
+                }
+            }
+            AppMsg::FindResult(current, total) => {
+                self.find_result = (current, total);
+            }
+            AppMsg::OutlineChanged(entries) => {
+                self.has_outline = !entries.is_empty();
+                self.outline_popover
+                    .emit(OutlinePopoverInput::SetEntries(entries));
+            }
+            AppMsg::CurrentHeadingChanged(index) => {
+                self.outline_popover
+                    .emit(OutlinePopoverInput::SetCurrent(index));
+            }
+            AppMsg::ScrollToHeading(index) => {
+                self.article_renderer
+                    .emit(ArticleRendererInput::ScrollToHeading(index));
+            }
+            AppMsg::Listen => {
+                if self.article_html.is_some() {
+                    self.article_renderer
+                        .emit(ArticleRendererInput::StartListening);
+                }
+            }
+            AppMsg::ListenTextReady(paragraphs) => {
+                self.listen_bar.emit(ListenBarInput::Start(paragraphs));
+            }
+            AppMsg::SpeakingParagraph(index) => {
+                self.article_renderer
+                    .emit(ArticleRendererInput::HighlightParagraph(index));
+            }
+            AppMsg::ListenFinished => {
+                let visible = self.filter_articles();
+                let item_ids: Vec<&str> = visible.iter().map(|a| a.item_id.as_str()).collect();
+                if let Some(index) =
+                    next_unread_index(&item_ids, self.article_item_id.as_deref(), &self.read_items)
+                {
+                    self.continue_listening = Some(visible[index].item_id.clone());
+                    self.open_article(&visible[index], index, &sender);
+                }
+            }
+            AppMsg::ListenError(error) => {
+                let toast = adw::Toast::builder().title(&error).timeout(5).build();
+                self.toaster.add_toast(toast);
+            }
+            AppMsg::ToggleTranslation => {
+                if self.showing_translation {
+                    self.showing_translation = false;
+                    self.article_renderer
+                        .emit(ArticleRendererInput::ShowOriginal);
+                } else if self.article_html.is_some() {
+                    self.article_renderer
+                        .emit(ArticleRendererInput::StartTranslation);
+                }
+            }
+            AppMsg::TranslationTextReady(paragraphs) => {
+                let Some(item_id) = self.article_item_id.clone() else {
+                    return;
+                };
+                if paragraphs.is_empty() {
+                    let toast = adw::Toast::builder()
+                        .title(&gettext("This article has no text to translate"))
+                        .timeout(5)
+                        .build();
+                    self.toaster.add_toast(toast);
+                    return;
+                }
+
+                self.translating = true;
+                let mut settings = self.preferences.translation.clone();
+                sender.oneshot_command(async move {
+                    match translation_key::read().await {
+                        Ok(api_key) => settings.api_key = api_key,
+                        Err(e) => eprintln!("Failed to read the translation API key: {}", e),
+                    }
+                    match translation::translate_article(&item_id, &settings, &paragraphs).await {
+                        Ok(translated) => CommandMsg::Translated(item_id, translated),
+                        Err(e) => CommandMsg::TranslationFailed(format!(
+                            "{}: {}",
+                            gettext("Failed to translate the article"),
+                            e
+                        )),
+                    }
+                });
+            }
+            AppMsg::Print => {
+                if self.article_html.is_some() {
+                    self.article_renderer.emit(ArticleRendererInput::Print);
+                }
+            }
+            AppMsg::PrintFailed(error) => {
+                let toast = adw::Toast::builder().title(&error).timeout(5).build();
+                self.toaster.add_toast(toast);
+            }
             AppMsg::ToggleSearchMode => {
                 self.search_mode = !self.search_mode;
                 if !self.search_mode {
@@ -576,6 +1628,18 @@ impl Component for App {
                     self.rebuild_article_list();
                 }
             }
+            AppMsg::StartSearch => {
+                if self.tokens.is_none() {
+                    return;
+                }
+                self.search_mode = true;
+
+                // The entry only becomes visible once the view has been updated
+                let search_entry = self.search_entry.clone();
+                glib::idle_add_local_once(move || {
+                    search_entry.grab_focus();
+                });
+            }
             AppMsg::UpdateSearchQuery(query) => {
                 self.search_query = query;
                 self.rebuild_article_list();
@@ -589,6 +1653,125 @@ impl Component for App {
                 self.selected_tag = tag;
                 self.rebuild_article_list();
             }
+            AppMsg::ToggleSelectionMode => {
+                self.selection_mode = !self.selection_mode;
+                self.selected_items.clear();
+                self.articles
+                    .broadcast(ArticleInput::SetSelectionMode(self.selection_mode));
+            }
+            AppMsg::SetArticleSelected(item_id, selected) => {
+                if selected {
+                    self.selected_items.insert(item_id);
+                } else {
+                    self.selected_items.remove(&item_id);
+                }
+            }
+            AppMsg::SelectAll => {
+                let visible: HashSet<String> = self
+                    .filter_articles()
+                    .into_iter()
+                    .map(|a| a.item_id)
+                    .collect();
+
+                if !visible.is_empty() && visible.is_subset(&self.selected_items) {
+                    self.selected_items.retain(|id| !visible.contains(id));
+                    self.articles.broadcast(ArticleInput::SetSelected(false));
+                } else {
+                    self.selected_items.extend(visible);
+                    self.articles.broadcast(ArticleInput::SetSelected(true));
+                }
+            }
+            AppMsg::RunBatch(operation) => {
+                let Some(tokens) = self.tokens.clone() else {
+                    return;
+                };
+
+                let targets: Vec<BatchTarget> = self
+                    .all_articles
+                    .iter()
+                    .filter(|a| self.selected_items.contains(&a.item_id))
+                    .map(|a| BatchTarget {
+                        item_id: a.item_id.clone(),
+                        uri: a.uri.clone(),
+                        title: a.title.clone(),
+                        tags: a.tags.clone(),
+                    })
+                    .collect();
+
+                if targets.is_empty() || self.batch_progress.is_some() {
+                    return;
+                }
+
+                self.batch_progress = Some((0, targets.len()));
+
+                sender.command(move |out, shutdown| {
+                    shutdown
+                        .register(async move {
+                            let client = instapaper::client();
+                            let report = batch::run(
+                                targets,
+                                |target| {
+                                    let client = client.clone();
+                                    let tokens = tokens.clone();
+                                    let operation = operation.clone();
+                                    async move {
+                                        batch::apply(&client, &tokens, &operation, &target).await
+                                    }
+                                },
+                                |done, total| {
+                                    let _ = out.send(CommandMsg::BatchProgress(done, total));
+                                },
+                            )
+                            .await;
+                            let _ = out.send(CommandMsg::BatchFinished(operation, report));
+                        })
+                        .drop_on_shutdown()
+                });
+            }
+            AppMsg::DeleteSelected => {
+                let count = self.selected_items.len();
+                if count == 0 {
+                    return;
+                }
+
+                let dialog = adw::AlertDialog::new(
+                    Some(
+                        &ngettext("Delete {} article?", "Delete {} articles?", count as u32)
+                            .replace("{}", &count.to_string()),
+                    ),
+                    Some(&gettext(
+                        "Deleted articles are removed from your Instapaper account permanently.",
+                    )),
+                );
+                dialog.add_responses(&[
+                    ("cancel", &gettext("_Cancel")),
+                    ("delete", &gettext("_Delete")),
+                ]);
+                dialog.set_response_appearance("delete", adw::ResponseAppearance::Destructive);
+                dialog.set_default_response(Some("cancel"));
+                dialog.set_close_response("cancel");
+
+                let sender = sender.clone();
+                dialog.connect_response(None, move |_, response| {
+                    if response == "delete" {
+                        sender.input(AppMsg::RunBatch(BatchOperation::Delete));
+                    }
+                });
+                dialog.present(Some(root));
+            }
+            AppMsg::MoveSelectedToFolder(index) => {
+                if let Some(folder) = self.folders.get(index) {
+                    sender.input(AppMsg::RunBatch(BatchOperation::MoveToFolder(
+                        folder.folder_id,
+                    )));
+                }
+            }
+            AppMsg::TagSelected(input) => {
+                let tags = add_bookmark::parse_tags(&input);
+                if !tags.is_empty() {
+                    sender.input(AppMsg::RunBatch(BatchOperation::AddTags(tags)));
+                }
+            }
         }
     }
 
@@ -599,9 +1782,18 @@ impl Component for App {
         _: &Self::Root,
     ) {
         match message {
-            CommandMsg::RefreshedArticles(entries) => {
+            CommandMsg::RefreshedArticles(mut entries) => {
                 self.loading = false;
 
+                for entry in entries.iter_mut() {
+                    entry.words_per_minute = self.preferences.words_per_minute;
+                    entry.word_count = self
+                        .all_articles
+                        .iter()
+                        .find(|a| a.item_id == entry.item_id)
+                        .and_then(|a| a.word_count);
+                }
+
                 self.all_articles = entries.clone();
 
                 for a in &entries {
@@ -615,47 +1807,57 @@ impl Component for App {
                     entries.iter().filter(|a| !a.tags.is_empty()).count()
                 );
 
-                let mut tags: Vec<String> = entries
-                    .iter()
-                    .flat_map(|a| a.tags.iter().cloned())
-                    .collect::<HashSet<String>>()
-                    .into_iter()
-                    .collect();
-                tags.sort();
-                self.available_tags = tags;
+                self.refresh_available_tags();
                 println!("Available tags: {:?}", self.available_tags);
 
-                let all_label = gettext("All");
-                let mut tag_items: Vec<&str> = vec![&all_label];
-                tag_items.extend(self.available_tags.iter().map(|s| s.as_str()));
-                self.tag_model
-                    .splice(0, self.tag_model.n_items(), &tag_items);
-
                 self.rebuild_article_list();
-
-                let persisted: Vec<PersistedArticle> = entries
-                    .iter()
-                    .map(|a| PersistedArticle {
-                        title: a.title.clone(),
-                        uri: a.uri.clone(),
-                        item_id: a.item_id.clone(),
-                        description: a.description.clone(),
-                        time: a.time,
-                        tags: a.tags.clone(),
-                    })
-                    .collect();
-
-                if let Err(e) = articles::save_articles(&persisted) {
-                    eprintln!("Failed to save articles cache: {}", e);
+                self.save_articles_cache();
+                self.count_cached_words(&sender);
+            }
+            CommandMsg::WordsCounted(counts) => {
+                for (item_id, word_count) in counts {
+                    self.set_word_count(&item_id, word_count);
                 }
             }
-            CommandMsg::ScrapedArticle(html) => {
-                self.article_html = Some(html.clone());
-                self.article_renderer
-                    .emit(ArticleRendererInput::SetContent(html));
+            CommandMsg::ScrapedArticle(item_id, html, word_count) => {
+                self.set_word_count(&item_id, word_count);
+
+                if self.article_item_id.as_deref() == Some(item_id.as_str()) {
+                    // Nothing to read when the article couldn't be loaded
+                    let has_content = !html.is_empty();
+                    self.article_html = Some(html.clone());
+                    self.showing_translation = false;
+                    self.article_renderer
+                        .emit(ArticleRendererInput::SetContent(html));
+                    self.article_renderer
+                        .emit(ArticleRendererInput::SetWordCount(word_count));
+
+                    if self.continue_listening.as_deref() == Some(item_id.as_str()) {
+                        self.continue_listening = None;
+                        if has_content {
+                            self.article_renderer
+                                .emit(ArticleRendererInput::StartListening);
+                        }
+                    }
+                }
+            }
+            CommandMsg::ScrapeFailed(item_id, error) => {
+                if self.continue_listening.as_deref() == Some(item_id.as_str()) {
+                    self.continue_listening = None;
+                }
+                if self.article_item_id.as_deref() == Some(item_id.as_str()) {
+                    self.article_html = None;
+                    self.article_renderer
+                        .emit(ArticleRendererInput::SetContent(String::new()));
+
+                    let title = format!("{}: {}", gettext("Failed to load the article"), error);
+                    let toast = adw::Toast::builder().title(&title).timeout(5).build();
+                    self.toaster.add_toast(toast);
+                }
             }
             CommandMsg::ArticleArchived(item_id) => {
                 self.all_articles.retain(|a| a.item_id != item_id);
+                self.save_articles_cache();
 
                 self.article_html = None;
                 self.article_title = None;
@@ -663,6 +1865,13 @@ impl Component for App {
                 self.article_item_id = None;
                 sender.input(AppMsg::RefreshArticles);
             }
+            CommandMsg::ArticleStarred => {
+                let toast = adw::Toast::builder()
+                    .title(&gettext("Article starred"))
+                    .timeout(3)
+                    .build();
+                self.toaster.add_toast(toast);
+            }
             CommandMsg::OpenUrl(url) => {
                 open::that(url).expect("Could not open the browser");
             }
@@ -674,6 +1883,35 @@ impl Component for App {
                 self.toaster.add_toast(toast);
                 sender.input(AppMsg::RefreshArticles);
             }
+            CommandMsg::RefreshedFolders(folders) => {
+                self.folders = folders;
+                self.rebuild_folder_list();
+            }
+            CommandMsg::BatchProgress(done, total) => {
+                self.batch_progress = Some((done, total));
+            }
+            CommandMsg::BatchFinished(operation, report) => {
+                self.batch_progress = None;
+                self.finish_batch(&operation, &report);
+            }
+            CommandMsg::Translated(item_id, paragraphs) => {
+                // Another article may have been opened in the meantime
+                if self.article_item_id.as_deref() != Some(item_id.as_str()) {
+                    return;
+                }
+                self.translating = false;
+                self.showing_translation = true;
+                self.article_renderer
+                    .emit(ArticleRendererInput::ShowTranslation {
+                        paragraphs,
+                        layout: self.preferences.translation.layout,
+                    });
+            }
+            CommandMsg::TranslationFailed(error) => {
+                self.translating = false;
+                let toast = adw::Toast::builder().title(&error).timeout(5).build();
+                self.toaster.add_toast(toast);
+            }
             CommandMsg::Error(error) => {
                 self.loading = false;
                 let toast = adw::Toast::builder().title(&error).timeout(5).build();
@@ -683,25 +1921,39 @@ impl Component for App {
     }
 
     fn shutdown(&mut self, widgets: &mut Self::Widgets, _output: relm4::Sender<Self::Output>) {
-        let current_articles: Vec<PersistedArticle> = self
-            .articles
-            .iter()
-            .map(|a| PersistedArticle {
-                title: a.title.clone(),
-                uri: a.uri.clone(),
-                item_id: a.item_id.clone(),
-                description: a.description.clone(),
-                time: a.time,
-                tags: a.tags.clone(),
-            })
-            .collect();
-        let _ = articles::save_articles(&current_articles);
-
         widgets.save_window_size().unwrap();
     }
 }
 
 impl App {
+    /// The "n of m" next to the find entry.
+    fn find_status(&self) -> String {
+        match self.find_result {
+            _ if self.find_query.trim().is_empty() => String::new(),
+            (_, 0) => gettext("No matches"),
+            (current, total) => gettext("{} of {}")
+                .replacen("{}", &current.to_string(), 1)
+                .replacen("{}", &total.to_string(), 1),
+        }
+    }
+
+    /// Selects the row of an article in the list and opens it.
+    fn open_article(&self, article: &ArticleInit, index: usize, sender: &ComponentSender<Self>) {
+        let list_box = self.articles.widget();
+        if let Some(row) = list_box.row_at_index(index as i32) {
+            list_box.select_row(Some(&row));
+            row.grab_focus();
+        }
+
+        sender.input(AppMsg::ArticleSelected(
+            article.title.clone(),
+            article.uri.clone(),
+            article.item_id.clone(),
+            article.description.clone(),
+            article.time,
+        ));
+    }
+
     fn filter_articles(&self) -> Vec<ArticleInit> {
         self.all_articles
             .iter()
@@ -726,6 +1978,10 @@ impl App {
                 description: a.description.clone(),
                 time: a.time,
                 tags: a.tags.clone(),
+                word_count: a.word_count,
+                words_per_minute: self.preferences.words_per_minute,
+                selection_mode: self.selection_mode,
+                selected: self.selected_items.contains(&a.item_id),
             })
             .collect()
     }
@@ -737,6 +1993,218 @@ impl App {
             self.articles.guard().push_back(article);
         }
     }
+
+    fn set_word_count(&mut self, item_id: &str, word_count: usize) {
+        let Some(article) = self.all_articles.iter_mut().find(|a| a.item_id == item_id) else {
+            return;
+        };
+        if article.word_count == Some(word_count) {
+            return;
+        }
+        article.word_count = Some(word_count);
+
+        if let Some(index) = self.articles.iter().position(|a| a.item_id == item_id) {
+            self.articles
+                .send(index, ArticleInput::SetWordCount(word_count));
+        }
+
+        if let Some(store) = &self.store {
+            if let Err(e) = store.set_word_count(item_id, word_count) {
+                eprintln!("Failed to save articles cache: {}", e);
+            }
+        }
+    }
+
+    /// Counts the words of the articles whose body is in the offline cache
+    /// without a count yet, as after restoring a backup. Other articles get
+    /// theirs when opened.
+    fn count_cached_words(&self, sender: &ComponentSender<Self>) {
+        let missing: Vec<String> = self
+            .all_articles
+            .iter()
+            .filter(|a| a.word_count.is_none())
+            .map(|a| a.item_id.clone())
+            .collect();
+        if missing.is_empty() {
+            return;
+        }
+
+        sender.oneshot_command(async move {
+            let counts = relm4::spawn_blocking(move || {
+                missing
+                    .into_iter()
+                    .filter_map(|item_id| {
+                        let article = article_cache::read(&item_id)?;
+                        Some((item_id, reading_time::count_html_words(&article.html)))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .await
+            .unwrap_or_default();
+            CommandMsg::WordsCounted(counts)
+        });
+    }
+
+    /// (Re)starts the periodic refresh according to the sync interval preference.
+    fn schedule_sync(&mut self, sender: &ComponentSender<Self>) {
+        if let Some(source) = self.sync_source.take() {
+            source.remove();
+        }
+
+        let interval = self.preferences.sync_interval;
+        if interval == 0 {
+            return;
+        }
+
+        let sender = sender.clone();
+        self.sync_source = Some(glib::timeout_add_seconds_local(interval * 60, move || {
+            sender.input(AppMsg::RefreshArticles);
+            glib::ControlFlow::Continue
+        }));
+    }
+
+    fn refresh_available_tags(&mut self) {
+        let mut tags: Vec<String> = self
+            .all_articles
+            .iter()
+            .flat_map(|a| a.tags.iter().cloned())
+            .collect::<HashSet<String>>()
+            .into_iter()
+            .collect();
+        tags.sort();
+        self.available_tags = tags;
+
+        let all_label = gettext("All");
+        let mut tag_items: Vec<&str> = vec![&all_label];
+        tag_items.extend(self.available_tags.iter().map(|s| s.as_str()));
+        self.tag_model
+            .splice(0, self.tag_model.n_items(), &tag_items);
+    }
+
+    fn rebuild_folder_list(&self) {
+        while let Some(child) = self.folder_list_box.first_child() {
+            self.folder_list_box.remove(&child);
+        }
+
+        for folder in &self.folders {
+            let label = gtk::Label::builder()
+                .label(&folder.title)
+                .xalign(0.0)
+                .margin_top(6)
+                .margin_bottom(6)
+                .margin_start(6)
+                .margin_end(6)
+                .build();
+            self.folder_list_box.append(&label);
+        }
+    }
+
+    fn finish_batch(&mut self, operation: &BatchOperation, report: &BatchReport) {
+        let succeeded: HashSet<&String> = report.succeeded.iter().collect();
+
+        match operation {
+            BatchOperation::Archive | BatchOperation::Delete | BatchOperation::MoveToFolder(_) => {
+                self.all_articles
+                    .retain(|a| !succeeded.contains(&a.item_id));
+
+                if self
+                    .article_item_id
+                    .as_ref()
+                    .is_some_and(|id| succeeded.contains(id))
+                {
+                    self.article_html = None;
+                    self.article_title = None;
+                    self.article_uri = None;
+                    self.article_item_id = None;
+                }
+            }
+            BatchOperation::AddTags(tags) => {
+                for article in self.all_articles.iter_mut() {
+                    if succeeded.contains(&article.item_id) {
+                        article.tags = batch::merge_tags(&article.tags, tags);
+                    }
+                }
+                self.refresh_available_tags();
+            }
+            BatchOperation::Star => {}
+        }
+
+        // Keep the failed articles selected so the action can be retried
+        self.selected_items.retain(|id| !succeeded.contains(id));
+        if self.selected_items.is_empty() {
+            self.selection_mode = false;
+        }
+
+        self.rebuild_article_list();
+        self.save_articles_cache();
+
+        let title = if report.failed.is_empty() {
+            Self::batch_success_message(operation, report.succeeded.len())
+        } else {
+            for (title, reason) in &report.failed {
+                eprintln!("Batch operation failed for '{}': {}", title, reason);
+            }
+
+            let failed = report.failed.len();
+            let total = failed + report.succeeded.len();
+            format!(
+                "{}: {}",
+                ngettext(
+                    "{failed} of {total} article could not be updated",
+                    "{failed} of {total} articles could not be updated",
+                    total as u32
+                )
+                .replace("{failed}", &failed.to_string())
+                .replace("{total}", &total.to_string()),
+                report
+                    .failed
+                    .iter()
+                    .map(|(title, reason)| format!("{} ({})", title, reason))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        };
+
+        let toast = adw::Toast::builder().title(&title).timeout(5).build();
+        self.toaster.add_toast(toast);
+    }
+
+    fn batch_success_message(operation: &BatchOperation, count: usize) -> String {
+        let n = count as u32;
+        let message = match operation {
+            BatchOperation::Archive => ngettext("Archived {} article", "Archived {} articles", n),
+            BatchOperation::Delete => ngettext("Deleted {} article", "Deleted {} articles", n),
+            BatchOperation::Star => ngettext("Starred {} article", "Starred {} articles", n),
+            BatchOperation::MoveToFolder(_) => ngettext("Moved {} article", "Moved {} articles", n),
+            BatchOperation::AddTags(_) => ngettext("Tagged {} article", "Tagged {} articles", n),
+        };
+        message.replace("{}", &count.to_string())
+    }
+
+    fn persisted_articles(&self) -> Vec<PersistedArticle> {
+        self.all_articles
+            .iter()
+            .map(|a| PersistedArticle {
+                title: a.title.clone(),
+                uri: a.uri.clone(),
+                item_id: a.item_id.clone(),
+                description: a.description.clone(),
+                time: a.time,
+                tags: a.tags.clone(),
+                word_count: a.word_count,
+            })
+            .collect()
+    }
+
+    fn save_articles_cache(&mut self) {
+        let articles = self.persisted_articles();
+        let Some(store) = &mut self.store else {
+            return;
+        };
+        if let Err(e) = store.replace_articles(&articles) {
+            eprintln!("Failed to save articles cache: {}", e);
+        }
+    }
 }
 
 impl AppWidgets {
@@ -767,24 +2235,34 @@ impl AppWidgets {
     }
 }
 
-async fn get_html(source_url: Option<String>) -> String {
-    let source_url = source_url.map(|url| Url::parse(&url).expect("invalid source url"));
-
-    if let Some(source_url) = source_url {
-        match FullTextParser::download(
-            &source_url,
-            &Client::new(),
-            None,
-            &FtrConfigEntry::default(),
-        )
-        .await
-        {
-            Ok(html) => html,
-            Err(_err) => "".to_owned(),
-        }
-    } else {
-        unreachable!()
-    }
+/// Returns the position of the article next to (or before) the current one,
+/// starting from the top of the list when nothing is selected yet.
+fn adjacent_article_index(
+    item_ids: &[&str],
+    current: Option<&str>,
+    forward: bool,
+) -> Option<usize> {
+    let index = match current.and_then(|id| item_ids.iter().position(|i| *i == id)) {
+        Some(index) if forward => index + 1,
+        Some(index) => index.checked_sub(1)?,
+        None => 0,
+    };
+
+    (index < item_ids.len()).then_some(index)
+}
+
+/// Returns the position of the first article after the current one that
+/// hasn't been opened yet.
+fn next_unread_index(
+    item_ids: &[&str],
+    current: Option<&str>,
+    read: &HashSet<String>,
+) -> Option<usize> {
+    let start = current
+        .and_then(|id| item_ids.iter().position(|i| *i == id))
+        .map_or(0, |index| index + 1);
+
+    (start..item_ids.len()).find(|&index| !read.contains(item_ids[index]))
 }
 
 #[cfg(test)]
@@ -799,6 +2277,10 @@ mod tests {
             description: format!("About {}", title),
             time: 0.0,
             tags,
+            word_count: None,
+            words_per_minute: 200,
+            selection_mode: false,
+            selected: false,
         }
     }
 
@@ -826,6 +2308,10 @@ mod tests {
                 description: a.description.clone(),
                 time: a.time,
                 tags: a.tags.clone(),
+                word_count: a.word_count,
+                words_per_minute: a.words_per_minute,
+                selection_mode: false,
+                selected: false,
             })
             .collect()
     }
@@ -935,4 +2421,38 @@ mod tests {
 
         assert_eq!(tags, vec!["programming", "python", "rust"]);
     }
+
+    #[test]
+    fn test_adjacent_article_index() {
+        let ids = ["1", "2", "3"];
+
+        // Nothing selected yet: both directions start from the top
+        assert_eq!(adjacent_article_index(&ids, None, true), Some(0));
+        assert_eq!(adjacent_article_index(&ids, None, false), Some(0));
+
+        assert_eq!(adjacent_article_index(&ids, Some("1"), true), Some(1));
+        assert_eq!(adjacent_article_index(&ids, Some("2"), false), Some(0));
+
+        // Stop at both ends of the list
+        assert_eq!(adjacent_article_index(&ids, Some("3"), true), None);
+        assert_eq!(adjacent_article_index(&ids, Some("1"), false), None);
+
+        // The current article was filtered out of the list
+        assert_eq!(adjacent_article_index(&ids, Some("9"), true), Some(0));
+        assert_eq!(adjacent_article_index(&[], None, true), None);
+    }
+
+    #[test]
+    fn test_next_unread_index() {
+        let ids = ["1", "2", "3", "4"];
+        let read: HashSet<String> = ["1", "2", "3"].iter().map(|id| id.to_string()).collect();
+
+        assert_eq!(next_unread_index(&ids, Some("1"), &read), Some(3));
+        assert_eq!(next_unread_index(&ids, Some("4"), &read), None);
+        assert_eq!(next_unread_index(&ids, None, &HashSet::new()), Some(0));
+
+        // Articles before the current one are never picked
+        let read: HashSet<String> = ["3", "4"].iter().map(|id| id.to_string()).collect();
+        assert_eq!(next_unread_index(&ids, Some("2"), &read), None);
+    }
 }
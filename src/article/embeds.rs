@@ -0,0 +1,404 @@
+use scraper::ElementRef;
+use url::Url;
+
+use super::images::resolve_url;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Provider {
+    YouTube,
+    Vimeo,
+    Twitter,
+    Mastodon,
+    /// Any other site, by host name
+    Other(String),
+}
+
+impl Provider {
+    fn from_url(url: &Url) -> Self {
+        let host = url.host_str().unwrap_or_default();
+        let host = host.strip_prefix("www.").unwrap_or(host);
+
+        match host {
+            "youtube.com" | "m.youtube.com" | "youtube-nocookie.com" | "youtu.be" => {
+                Provider::YouTube
+            }
+            "vimeo.com" | "player.vimeo.com" => Provider::Vimeo,
+            "twitter.com" | "platform.twitter.com" | "x.com" => Provider::Twitter,
+            _ => Provider::Other(host.to_string()),
+        }
+    }
+
+    pub fn display_name(&self) -> &str {
+        match self {
+            Provider::YouTube => "YouTube",
+            Provider::Vimeo => "Vimeo",
+            Provider::Twitter => "Twitter",
+            Provider::Mastodon => "Mastodon",
+            Provider::Other(host) => host,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EmbedKind {
+    /// A `video` source that can be played in the app
+    Video,
+    /// An `audio` source that can be played in the app
+    Audio,
+    /// A player, post or page only a browser can show
+    Page,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Embed {
+    pub kind: EmbedKind,
+    pub provider: Provider,
+    /// The media source for `Video` and `Audio`, the page to open otherwise
+    pub url: String,
+    pub title: Option<String>,
+    pub thumbnail: Option<String>,
+}
+
+/// Whether `element` is media or a social post the renderer shows as a card.
+pub fn is_embed(element: ElementRef) -> bool {
+    match element.value().name() {
+        "audio" | "iframe" | "video" => true,
+        "blockquote" => is_social_post(element),
+        _ => false,
+    }
+}
+
+/// Describes an `iframe`, `video`, `audio` or social post embed, with URLs
+/// resolved against `base`. Returns `None` for invisible frames and elements
+/// without a usable source.
+pub fn resolve(element: ElementRef, base: Option<&Url>) -> Option<Embed> {
+    match element.value().name() {
+        "iframe" => resolve_frame(element, base),
+        "video" => resolve_media(element, base, EmbedKind::Video),
+        "audio" => resolve_media(element, base, EmbedKind::Audio),
+        "blockquote" if is_social_post(element) => resolve_post(element, base),
+        _ => None,
+    }
+}
+
+fn is_social_post(element: ElementRef) -> bool {
+    element
+        .value()
+        .classes()
+        .any(|class| matches!(class, "twitter-tweet" | "mastodon-embed"))
+}
+
+fn resolve_frame(element: ElementRef, base: Option<&Url>) -> Option<Embed> {
+    // Tracking pixels and hidden helper frames
+    let is_hidden = ["width", "height"].iter().any(|attribute| {
+        element
+            .value()
+            .attr(attribute)
+            .and_then(|value| value.trim().trim_end_matches("px").parse::<u32>().ok())
+            .is_some_and(|size| size <= 1)
+    });
+    if is_hidden {
+        return None;
+    }
+
+    let src = element
+        .value()
+        .attr("src")
+        .filter(|src| !src.trim().is_empty() && src.trim() != "about:blank")
+        .or_else(|| element.value().attr("data-src"))?;
+    let src = Url::parse(&resolve_url(src, base)?).ok()?;
+    let title = attribute_text(element, "title");
+
+    let mut provider = Provider::from_url(&src);
+    let mut url = src.to_string();
+    let mut thumbnail = None;
+
+    match provider {
+        Provider::YouTube => {
+            if let Some(id) = path_after(&src, "embed") {
+                url = format!("https://www.youtube.com/watch?v={}", id);
+                thumbnail = Some(format!("https://i.ytimg.com/vi/{}/hqdefault.jpg", id));
+            }
+        }
+        Provider::Vimeo => {
+            if let Some(id) = path_after(&src, "video") {
+                url = format!("https://vimeo.com/{}", id);
+            }
+        }
+        Provider::Twitter => {
+            if let Some((_, id)) = src.query_pairs().find(|(key, _)| key == "id") {
+                url = format!("https://twitter.com/i/status/{}", id);
+            }
+        }
+        Provider::Mastodon | Provider::Other(_) => {
+            // Mastodon serves embeds from the post URL plus /embed
+            let is_mastodon = element.value().classes().any(|c| c == "mastodon-embed")
+                || src
+                    .path_segments()
+                    .is_some_and(|segments| is_mastodon_embed_path(segments.collect()));
+            if is_mastodon {
+                provider = Provider::Mastodon;
+                url = url
+                    .trim_end_matches('/')
+                    .trim_end_matches("/embed")
+                    .to_string();
+            }
+        }
+    }
+
+    Some(Embed {
+        kind: EmbedKind::Page,
+        provider,
+        url,
+        title,
+        thumbnail,
+    })
+}
+
+fn resolve_media(element: ElementRef, base: Option<&Url>, kind: EmbedKind) -> Option<Embed> {
+    let src = element.value().attr("src").or_else(|| {
+        element
+            .children()
+            .filter_map(ElementRef::wrap)
+            .filter(|child| child.value().name() == "source")
+            .find_map(|source| source.value().attr("src"))
+    })?;
+    let url = resolve_url(src, base)?;
+
+    Some(Embed {
+        kind,
+        provider: Provider::from_url(&Url::parse(&url).ok()?),
+        url,
+        title: attribute_text(element, "title").or_else(|| attribute_text(element, "aria-label")),
+        thumbnail: element
+            .value()
+            .attr("poster")
+            .and_then(|poster| resolve_url(poster, base)),
+    })
+}
+
+/// Social posts are pasted as a quote of the post text ending with a link to
+/// it, the script that would turn them into a frame being stripped.
+fn resolve_post(element: ElementRef, base: Option<&Url>) -> Option<Embed> {
+    let is_mastodon = element.value().classes().any(|c| c == "mastodon-embed");
+
+    let links: Vec<&str> = element
+        .descendants()
+        .filter_map(ElementRef::wrap)
+        .filter(|e| e.value().name() == "a")
+        .filter_map(|a| a.value().attr("href"))
+        .collect();
+
+    let href = if is_mastodon {
+        element
+            .value()
+            .attr("data-embed-url")
+            .map(|url| url.trim_end_matches("/embed"))
+            .or_else(|| links.last().copied())
+    } else {
+        links
+            .iter()
+            .rev()
+            .find(|href| href.contains("/status/"))
+            .copied()
+    }?;
+    let url = resolve_url(href, base)?;
+
+    let title = element
+        .descendants()
+        .filter_map(ElementRef::wrap)
+        .find(|e| e.value().name() == "p")
+        .map(|p| p.text().collect::<Vec<_>>().join(" "))
+        .map(|text| text.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|text| !text.is_empty());
+
+    Some(Embed {
+        kind: EmbedKind::Page,
+        provider: if is_mastodon {
+            Provider::Mastodon
+        } else {
+            Provider::Twitter
+        },
+        url,
+        title,
+        thumbnail: None,
+    })
+}
+
+/// The path segment following `prefix`, like the video ID in `/embed/<id>`.
+fn path_after<'a>(url: &'a Url, prefix: &str) -> Option<&'a str> {
+    let mut segments = url.path_segments()?;
+    segments.find(|segment| *segment == prefix)?;
+    segments.next().filter(|id| !id.is_empty())
+}
+
+/// Matches `/@user/<id>/embed` and `/users/<user>/statuses/<id>/embed`.
+fn is_mastodon_embed_path(segments: Vec<&str>) -> bool {
+    match segments.as_slice() {
+        [user, id, "embed"] => user.starts_with('@') && id.chars().all(|c| c.is_ascii_digit()),
+        ["users", _, "statuses", _, "embed"] => true,
+        _ => false,
+    }
+}
+
+fn attribute_text(element: ElementRef, attribute: &str) -> Option<String> {
+    element
+        .value()
+        .attr(attribute)
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scraper::{Html, Selector};
+
+    fn resolve_first(html: &str, selector: &str) -> Option<Embed> {
+        let document = Html::parse_fragment(html);
+        let selector = Selector::parse(selector).unwrap();
+        let element = document.select(&selector).next().unwrap();
+        let base = Url::parse("https://example.com/blog/post.html").unwrap();
+
+        resolve(element, Some(&base))
+    }
+
+    #[test]
+    fn test_youtube_frame() {
+        let embed = resolve_first(
+            r#"<iframe src="https://www.youtube-nocookie.com/embed/dQw4w9WgXcQ?rel=0" title="Never Gonna Give You Up"></iframe>"#,
+            "iframe",
+        )
+        .unwrap();
+
+        assert_eq!(
+            embed,
+            Embed {
+                kind: EmbedKind::Page,
+                provider: Provider::YouTube,
+                url: "https://www.youtube.com/watch?v=dQw4w9WgXcQ".to_string(),
+                title: Some("Never Gonna Give You Up".to_string()),
+                thumbnail: Some("https://i.ytimg.com/vi/dQw4w9WgXcQ/hqdefault.jpg".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_vimeo_and_other_frames() {
+        let embed = resolve_first(
+            r#"<iframe src="//player.vimeo.com/video/76979871?h=8272103f6e"></iframe>"#,
+            "iframe",
+        )
+        .unwrap();
+        assert_eq!(embed.provider, Provider::Vimeo);
+        assert_eq!(embed.url, "https://vimeo.com/76979871");
+        assert_eq!(embed.title, None);
+
+        let embed = resolve_first(
+            r#"<iframe src="https://www.openstreetmap.org/export/embed.html"></iframe>"#,
+            "iframe",
+        )
+        .unwrap();
+        assert_eq!(
+            embed.provider,
+            Provider::Other("openstreetmap.org".to_string())
+        );
+        assert_eq!(embed.provider.display_name(), "openstreetmap.org");
+    }
+
+    #[test]
+    fn test_hidden_frames_are_ignored() {
+        assert_eq!(
+            resolve_first(
+                r#"<iframe src="https://tracker.example.com/" width="1" height="1"></iframe>"#,
+                "iframe"
+            ),
+            None
+        );
+        assert_eq!(
+            resolve_first(r#"<iframe src="about:blank"></iframe>"#, "iframe"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_media_elements() {
+        let embed = resolve_first(
+            r#"<video poster="poster.jpg" controls>
+                <source src="clip.webm" type="video/webm">
+                <source src="clip.mp4" type="video/mp4">
+            </video>"#,
+            "video",
+        )
+        .unwrap();
+        assert_eq!(embed.kind, EmbedKind::Video);
+        assert_eq!(embed.url, "https://example.com/blog/clip.webm");
+        assert_eq!(
+            embed.thumbnail.as_deref(),
+            Some("https://example.com/blog/poster.jpg")
+        );
+
+        let embed = resolve_first(
+            r#"<audio src="/episode.mp3" title="Episode 1"></audio>"#,
+            "audio",
+        )
+        .unwrap();
+        assert_eq!(embed.kind, EmbedKind::Audio);
+        assert_eq!(embed.url, "https://example.com/episode.mp3");
+        assert_eq!(embed.title.as_deref(), Some("Episode 1"));
+
+        assert_eq!(resolve_first("<video></video>", "video"), None);
+    }
+
+    #[test]
+    fn test_tweet() {
+        let html = r#"
+            <blockquote class="twitter-tweet">
+                <p lang="en">Just setting up my
+                    twttr</p>&mdash; jack (@jack)
+                <a href="https://twitter.com/jack/status/20?ref_src=twsrc">March 21, 2006</a>
+            </blockquote>"#;
+
+        let document = Html::parse_fragment(html);
+        let blockquote = document
+            .select(&Selector::parse("blockquote").unwrap())
+            .next()
+            .unwrap();
+        assert!(is_embed(blockquote));
+
+        let embed = resolve_first(html, "blockquote").unwrap();
+        assert_eq!(embed.provider, Provider::Twitter);
+        assert_eq!(
+            embed.url,
+            "https://twitter.com/jack/status/20?ref_src=twsrc"
+        );
+        assert_eq!(embed.title.as_deref(), Some("Just setting up my twttr"));
+    }
+
+    #[test]
+    fn test_mastodon() {
+        let embed = resolve_first(
+            r#"<iframe src="https://mastodon.social/@Gargron/1/embed" class="mastodon-embed"></iframe>"#,
+            "iframe",
+        )
+        .unwrap();
+        assert_eq!(embed.provider, Provider::Mastodon);
+        assert_eq!(embed.url, "https://mastodon.social/@Gargron/1");
+
+        let embed = resolve_first(
+            r#"<blockquote class="mastodon-embed" data-embed-url="https://fosstodon.org/@user/42/embed">
+                <a href="https://fosstodon.org/@user/42">View on Mastodon</a>
+            </blockquote>"#,
+            "blockquote",
+        )
+        .unwrap();
+        assert_eq!(embed.provider, Provider::Mastodon);
+        assert_eq!(embed.url, "https://fosstodon.org/@user/42");
+
+        // Plain quotes are left to the renderer
+        assert_eq!(
+            resolve_first("<blockquote>Hi</blockquote>", "blockquote"),
+            None
+        );
+    }
+}
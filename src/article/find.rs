@@ -0,0 +1,81 @@
+use std::ops::Range;
+
+/// Byte ranges of every occurrence of `query` in `text`, ignoring case.
+/// Matches don't overlap, and a blank query matches nothing.
+pub fn find_matches(text: &str, query: &str) -> Vec<Range<usize>> {
+    if query.trim().is_empty() {
+        return Vec::new();
+    }
+
+    let query = query.to_lowercase();
+    // Lowercasing can change the length of a character, so remember where
+    // each folded byte came from in the original text
+    let mut folded = String::with_capacity(text.len());
+    let mut origins = Vec::with_capacity(text.len());
+    for (index, c) in text.char_indices() {
+        for lower in c.to_lowercase() {
+            folded.push(lower);
+            origins.resize(folded.len(), (index, index + c.len_utf8()));
+        }
+    }
+
+    let mut matches = Vec::new();
+    let mut from = 0;
+    while let Some(offset) = folded[from..].find(&query) {
+        let start = from + offset;
+        let end = start + query.len();
+        let range = origins[start].0..origins[end - 1].1;
+
+        // Two matches can meet inside a character that folds to several
+        if matches
+            .last()
+            .is_none_or(|last: &Range<usize>| last.end <= range.start)
+        {
+            matches.push(range);
+        }
+        from = end;
+    }
+
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_matches_ignores_case() {
+        let text = "Rust is fast. RUST is safe, trust me.";
+        let matches = find_matches(text, "rust");
+
+        assert_eq!(matches, [0..4, 14..18, 29..33]);
+        assert_eq!(&text[matches[1].clone()], "RUST");
+    }
+
+    #[test]
+    fn test_find_matches_multibyte() {
+        let text = "Straße und STRASSE, İstanbul";
+
+        let matches = find_matches(text, "straße");
+        assert_eq!(matches.len(), 1);
+        assert_eq!(&text[matches[0].clone()], "Straße");
+
+        // The dotted capital I folds to two characters
+        let matches = find_matches(text, "stan");
+        assert_eq!(matches.len(), 1);
+        assert_eq!(&text[matches[0].clone()], "stan");
+        assert_eq!(find_matches(text, "i̇stanbul").len(), 1);
+    }
+
+    #[test]
+    fn test_find_matches_blank_query() {
+        assert!(find_matches("Some text", "").is_empty());
+        assert!(find_matches("Some text", "  ").is_empty());
+        assert!(find_matches("", "text").is_empty());
+    }
+
+    #[test]
+    fn test_find_matches_do_not_overlap() {
+        assert_eq!(find_matches("aaaa", "aa"), [0..2, 2..4]);
+    }
+}
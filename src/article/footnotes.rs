@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+
+use scraper::{ElementRef, Html, Node, Selector};
+use url::Url;
+
+/// Elements a footnote body can be, when the id sits on an inner anchor.
+const NOTE_CONTAINERS: &[&str] = &["aside", "dd", "div", "li", "p", "section"];
+
+/// The id of the footnote a link refers to, for `<a href="#fn1">` inside a
+/// `sup` or marked up as a note reference by Markdown and DPUB-ARIA.
+pub fn reference_target(link: ElementRef) -> Option<String> {
+    let value = link.value();
+    let id = value.attr("href")?.strip_prefix('#')?;
+
+    let is_reference = value.attr("role") == Some("doc-noteref")
+        || value
+            .classes()
+            .any(|class| matches!(class, "footnote-ref" | "footnote-reference"))
+        || value.attr("id").is_some_and(|id| id.starts_with("fnref"))
+        || link
+            .parent()
+            .and_then(ElementRef::wrap)
+            .is_some_and(|parent| parent.value().name() == "sup");
+
+    (is_reference && !id.is_empty()).then(|| decode(id))
+}
+
+/// Whether a link inside a footnote leads back to where it was referenced.
+pub fn is_backlink(link: ElementRef) -> bool {
+    let value = link.value();
+
+    value.attr("role") == Some("doc-backlink")
+        || value
+            .classes()
+            .any(|class| matches!(class, "footnote-backref" | "reversefootnote"))
+        || value
+            .attr("href")
+            .is_some_and(|href| href.starts_with("#fnref"))
+}
+
+/// The element id an in-page link points to. Links to `page` itself with a
+/// fragment count too, as extracted articles often get absolute links.
+pub fn fragment_target(href: &str, page: Option<&Url>) -> Option<String> {
+    if let Some(id) = href.strip_prefix('#') {
+        return (!id.is_empty()).then(|| decode(id));
+    }
+
+    let mut url = Url::parse(href).ok()?;
+    let id = url.fragment().filter(|id| !id.is_empty()).map(decode)?;
+    url.set_fragment(None);
+
+    let mut page = page?.clone();
+    page.set_fragment(None);
+
+    (url == page).then_some(id)
+}
+
+/// Collects the text of every footnote referenced in the document, by id,
+/// leaving out the links back to the text.
+pub fn collect(document: &Html) -> HashMap<String, String> {
+    let link_selector = Selector::parse("a[href]").unwrap();
+    let mut notes = HashMap::new();
+
+    for link in document.select(&link_selector) {
+        let Some(id) = reference_target(link) else {
+            continue;
+        };
+        if notes.contains_key(&id) {
+            continue;
+        }
+
+        let Some(note) = find_by_id(document, &id) else {
+            continue;
+        };
+        let text = note_text(note);
+        if !text.is_empty() {
+            notes.insert(id, text);
+        }
+    }
+
+    notes
+}
+
+fn find_by_id<'a>(document: &'a Html, id: &str) -> Option<ElementRef<'a>> {
+    let element = document
+        .root_element()
+        .descendants()
+        .filter_map(ElementRef::wrap)
+        .find(|e| e.value().attr("id") == Some(id))?;
+
+    if NOTE_CONTAINERS.contains(&element.value().name()) {
+        return Some(element);
+    }
+
+    // Some generators put the id on an empty anchor at the start of the note
+    element
+        .ancestors()
+        .filter_map(ElementRef::wrap)
+        .find(|e| NOTE_CONTAINERS.contains(&e.value().name()))
+}
+
+fn note_text(note: ElementRef) -> String {
+    let mut text = String::new();
+    push_text(note, &mut text);
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn push_text(element: ElementRef, text: &mut String) {
+    for child in element.children() {
+        match child.value() {
+            Node::Text(t) => text.push_str(t),
+            Node::Element(e) => {
+                let Some(child) = ElementRef::wrap(child) else {
+                    continue;
+                };
+                if e.name() == "a" && is_backlink(child) {
+                    continue;
+                }
+                if matches!(e.name(), "p" | "div" | "br") {
+                    text.push(' ');
+                }
+                push_text(child, text);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn decode(id: &str) -> String {
+    urlencoding::decode(id)
+        .map(|id| id.into_owned())
+        .unwrap_or_else(|_| id.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ARTICLE: &str = r##"
+        <p>Rust is fast<sup id="fnref:1"><a href="#fn:1" class="footnote-ref">1</a></sup>
+        and safe<a href="#note-2" role="doc-noteref">[2]</a>. See <a href="#intro">the intro</a>.</p>
+        <section class="footnotes">
+            <ol>
+                <li id="fn:1"><p>Benchmarks   vary.
+                    <a href="#fnref:1" class="footnote-backref">↩</a></p></li>
+                <li><a id="note-2"></a>Mostly. <a href="#ref-2" role="doc-backlink">↩︎</a></li>
+            </ol>
+        </section>"##;
+
+    fn first_link<'a>(document: &'a Html, selector: &str) -> ElementRef<'a> {
+        document
+            .select(&Selector::parse(selector).unwrap())
+            .next()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_reference_target() {
+        let document = Html::parse_fragment(ARTICLE);
+
+        assert_eq!(
+            reference_target(first_link(&document, r##"a[href="#fn:1"]"##)),
+            Some("fn:1".to_string())
+        );
+        assert_eq!(
+            reference_target(first_link(&document, r##"a[href="#note-2"]"##)),
+            Some("note-2".to_string())
+        );
+        // An ordinary in-page link
+        assert_eq!(
+            reference_target(first_link(&document, r##"a[href="#intro"]"##)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_backlinks() {
+        let document = Html::parse_fragment(ARTICLE);
+
+        assert!(is_backlink(first_link(&document, ".footnote-backref")));
+        assert!(is_backlink(first_link(
+            &document,
+            r#"[role="doc-backlink"]"#
+        )));
+        assert!(!is_backlink(first_link(&document, ".footnote-ref")));
+    }
+
+    #[test]
+    fn test_collect() {
+        let document = Html::parse_document(ARTICLE);
+        let notes = collect(&document);
+
+        assert_eq!(notes.len(), 2);
+        assert_eq!(notes["fn:1"], "Benchmarks vary.");
+        assert_eq!(notes["note-2"], "Mostly.");
+    }
+
+    #[test]
+    fn test_fragment_target() {
+        let page = Url::parse("https://example.com/post.html").unwrap();
+
+        assert_eq!(
+            fragment_target("#fn%3A1", Some(&page)),
+            Some("fn:1".to_string())
+        );
+        assert_eq!(
+            fragment_target("https://example.com/post.html#fn1", Some(&page)),
+            Some("fn1".to_string())
+        );
+        assert_eq!(
+            fragment_target("https://example.com/other.html#fn1", Some(&page)),
+            None
+        );
+        assert_eq!(fragment_target("#", Some(&page)), None);
+        assert_eq!(
+            fragment_target("https://example.com/post.html", Some(&page)),
+            None
+        );
+    }
+}
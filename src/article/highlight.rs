@@ -0,0 +1,603 @@
+use scraper::ElementRef;
+
+/// A small lexer-based highlighter. It only knows about keywords, literals,
+/// strings, numbers and comments, which is plenty for reading code snippets.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Language {
+    C,
+    Css,
+    Go,
+    Java,
+    JavaScript,
+    Json,
+    Python,
+    Ruby,
+    Rust,
+    Shell,
+    Sql,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TokenKind {
+    Keyword,
+    Literal,
+    String,
+    Number,
+    Comment,
+    Function,
+}
+
+impl TokenKind {
+    /// Name of the text tag used to style the token.
+    pub fn tag_name(self) -> &'static str {
+        match self {
+            TokenKind::Keyword => "code-keyword",
+            TokenKind::Literal => "code-literal",
+            TokenKind::String => "code-string",
+            TokenKind::Number => "code-number",
+            TokenKind::Comment => "code-comment",
+            TokenKind::Function => "code-function",
+        }
+    }
+}
+
+/// A highlighted range, in characters so it maps directly onto text buffer
+/// offsets.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub start: usize,
+    pub end: usize,
+}
+
+struct Syntax {
+    /// Space separated, to keep the tables below compact
+    keywords: &'static str,
+    literals: &'static str,
+    line_comments: &'static [&'static str],
+    block_comment: Option<(&'static str, &'static str)>,
+    /// String delimiters, longest first so triple quotes win
+    strings: &'static [&'static str],
+    case_insensitive: bool,
+}
+
+const C_LIKE_STRINGS: &[&str] = &["\"", "'"];
+
+impl Language {
+    pub fn from_name(name: &str) -> Option<Self> {
+        let language = match name.trim().to_ascii_lowercase().as_str() {
+            "c" | "h" | "cpp" | "c++" | "cxx" | "hpp" | "cc" | "objc" | "csharp" | "cs" => {
+                Language::C
+            }
+            "css" | "scss" | "less" => Language::Css,
+            "go" | "golang" => Language::Go,
+            "java" | "kotlin" | "kt" => Language::Java,
+            "js" | "javascript" | "jsx" | "ts" | "typescript" | "tsx" | "mjs" => {
+                Language::JavaScript
+            }
+            "json" | "jsonc" => Language::Json,
+            "py" | "python" | "python3" => Language::Python,
+            "rb" | "ruby" => Language::Ruby,
+            "rs" | "rust" => Language::Rust,
+            "sh" | "bash" | "shell" | "zsh" | "console" | "shell-session" => Language::Shell,
+            "sql" | "mysql" | "postgresql" | "postgres" | "sqlite" => Language::Sql,
+            _ => return None,
+        };
+
+        Some(language)
+    }
+
+    pub fn display_name(self) -> &'static str {
+        match self {
+            Language::C => "C",
+            Language::Css => "CSS",
+            Language::Go => "Go",
+            Language::Java => "Java",
+            Language::JavaScript => "JavaScript",
+            Language::Json => "JSON",
+            Language::Python => "Python",
+            Language::Ruby => "Ruby",
+            Language::Rust => "Rust",
+            Language::Shell => "Shell",
+            Language::Sql => "SQL",
+        }
+    }
+
+    fn syntax(self) -> Syntax {
+        match self {
+            Language::C => Syntax {
+                keywords:
+                    "auto break case char class const continue default delete do double else enum \
+                    extern float for goto if include inline int long namespace new private \
+                    protected public return short signed sizeof static struct switch template \
+                    typedef union unsigned using virtual void volatile while",
+                literals: "true false NULL nullptr this",
+                line_comments: &["//"],
+                block_comment: Some(("/*", "*/")),
+                strings: C_LIKE_STRINGS,
+                case_insensitive: false,
+            },
+            Language::Css => Syntax {
+                keywords: "important media import supports keyframes",
+                literals: "",
+                line_comments: &[],
+                block_comment: Some(("/*", "*/")),
+                strings: C_LIKE_STRINGS,
+                case_insensitive: false,
+            },
+            Language::Go => Syntax {
+                keywords:
+                    "break case chan const continue default defer else fallthrough for func go \
+                    goto if import interface map package range return select struct switch type \
+                    var",
+                literals: "true false nil iota",
+                line_comments: &["//"],
+                block_comment: Some(("/*", "*/")),
+                strings: &["\"", "`", "'"],
+                case_insensitive: false,
+            },
+            Language::Java => Syntax {
+                keywords:
+                    "abstract boolean break case catch class continue default do double else enum \
+                    extends final finally float for fun if implements import instanceof int \
+                    interface long new package private protected public return static super \
+                    switch synchronized throw throws try val var void while",
+                literals: "true false null this",
+                line_comments: &["//"],
+                block_comment: Some(("/*", "*/")),
+                strings: C_LIKE_STRINGS,
+                case_insensitive: false,
+            },
+            Language::JavaScript => Syntax {
+                keywords:
+                    "async await break case catch class const continue default delete do else \
+                    export extends finally for from function if import in instanceof interface \
+                    let new of return static switch throw try type typeof var void while yield",
+                literals: "true false null undefined this NaN",
+                line_comments: &["//"],
+                block_comment: Some(("/*", "*/")),
+                strings: &["\"", "'", "`"],
+                case_insensitive: false,
+            },
+            Language::Json => Syntax {
+                keywords: "",
+                literals: "true false null",
+                line_comments: &[],
+                block_comment: None,
+                strings: &["\""],
+                case_insensitive: false,
+            },
+            Language::Python => Syntax {
+                keywords:
+                    "and as assert async await break class continue def del elif else except \
+                    finally for from global if import in is lambda nonlocal not or pass raise \
+                    return try while with yield",
+                literals: "True False None self",
+                line_comments: &["#"],
+                block_comment: None,
+                strings: &["\"\"\"", "'''", "\"", "'"],
+                case_insensitive: false,
+            },
+            Language::Ruby => Syntax {
+                keywords:
+                    "alias and begin break case class def do else elsif end ensure for if in \
+                    module next not or redo require rescue retry return then unless until when \
+                    while yield",
+                literals: "true false nil self",
+                line_comments: &["#"],
+                block_comment: None,
+                strings: C_LIKE_STRINGS,
+                case_insensitive: false,
+            },
+            Language::Rust => Syntax {
+                keywords:
+                    "as async await break const continue crate dyn else enum extern fn for if \
+                    impl in let loop match mod move mut pub ref return static struct super trait \
+                    type unsafe use where while",
+                literals: "true false self Self None Some Ok Err",
+                line_comments: &["//"],
+                block_comment: Some(("/*", "*/")),
+                // Single quotes are mostly lifetimes, char literals are rare
+                strings: &["\""],
+                case_insensitive: false,
+            },
+            Language::Shell => Syntax {
+                keywords:
+                    "case do done elif else esac export fi for function if in local return then \
+                    until while",
+                literals: "true false",
+                line_comments: &["#"],
+                block_comment: None,
+                strings: C_LIKE_STRINGS,
+                case_insensitive: false,
+            },
+            Language::Sql => Syntax {
+                keywords:
+                    "add all alter and as asc by create delete desc distinct drop exists from \
+                    group having in index insert into join key left like limit not on or order \
+                    primary right select set table union update values where",
+                literals: "null true false",
+                line_comments: &["--"],
+                block_comment: Some(("/*", "*/")),
+                strings: C_LIKE_STRINGS,
+                case_insensitive: true,
+            },
+        }
+    }
+}
+
+/// Finds the language of a `pre` block from the `language-xxx` class or the
+/// `lang` attributes used by most highlighters, on the block itself or on its
+/// `code` child, falling back to guessing from the code.
+pub fn detect_language(pre: ElementRef, code: &str) -> Option<Language> {
+    let code_child = pre
+        .children()
+        .filter_map(ElementRef::wrap)
+        .find(|e| e.value().name() == "code");
+
+    let declared = [Some(pre), code_child]
+        .into_iter()
+        .flatten()
+        .flat_map(|element| {
+            let classes = element.value().classes().filter_map(|class| {
+                class
+                    .strip_prefix("language-")
+                    .or_else(|| class.strip_prefix("lang-"))
+                    .or_else(|| class.strip_prefix("highlight-"))
+            });
+            let attributes = ["lang", "data-lang", "data-language"]
+                .into_iter()
+                .filter_map(move |attribute| element.value().attr(attribute));
+
+            classes.chain(attributes).collect::<Vec<_>>()
+        })
+        .find_map(Language::from_name);
+
+    declared.or_else(|| guess_language(code))
+}
+
+/// Guesses the language from telltale constructs. Returns `None` rather than
+/// a wrong guess when nothing stands out.
+pub fn guess_language(code: &str) -> Option<Language> {
+    let trimmed = code.trim_start();
+    let contains_line = |prefix: &str| {
+        code.lines()
+            .any(|line| line.trim_start().starts_with(prefix))
+    };
+
+    if trimmed.starts_with("#!") {
+        return if trimmed.starts_with("#!/usr/bin/env python") {
+            Some(Language::Python)
+        } else {
+            Some(Language::Shell)
+        };
+    }
+    if trimmed.starts_with('{') || trimmed.starts_with('[') {
+        let end = code.trim_end();
+        if (end.ends_with('}') || end.ends_with(']')) && code.contains("\":") {
+            return Some(Language::Json);
+        }
+    }
+    if code.contains("fn ") && (code.contains("let ") || code.contains("::") || code.contains("->"))
+    {
+        return Some(Language::Rust);
+    }
+    if contains_line("package ") && code.contains("func ") {
+        return Some(Language::Go);
+    }
+    if contains_line("#include") {
+        return Some(Language::C);
+    }
+    if code.contains("public class ") || code.contains("System.out.") {
+        return Some(Language::Java);
+    }
+    if contains_line("def ") && code.contains("):")
+        || contains_line("from ") && code.contains(" import ")
+    {
+        return Some(Language::Python);
+    }
+    if code.contains("function ")
+        || code.contains("=> {")
+        || code.contains("console.")
+        || contains_line("const ") && code.contains(" = ")
+    {
+        return Some(Language::JavaScript);
+    }
+    if contains_line("$ ") || contains_line("sudo ") {
+        return Some(Language::Shell);
+    }
+
+    let upper = code.to_ascii_uppercase();
+    if (upper.contains("SELECT ") && upper.contains(" FROM "))
+        || upper.contains("INSERT INTO ")
+        || upper.contains("CREATE TABLE ")
+    {
+        return Some(Language::Sql);
+    }
+
+    None
+}
+
+/// Splits `code` into highlighted tokens. Text that isn't part of any token
+/// keeps the default style.
+pub fn highlight(code: &str, language: Language) -> Vec<Token> {
+    let syntax = language.syntax();
+    let chars: Vec<char> = code.chars().collect();
+    let starts_with = |at: usize, pattern: &str| {
+        (at..)
+            .zip(pattern.chars())
+            .all(|(offset, c)| chars.get(offset) == Some(&c))
+    };
+
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if let Some(prefix) = syntax.line_comments.iter().find(|p| starts_with(i, p)) {
+            let start = i;
+            i += prefix.chars().count();
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            tokens.push(Token {
+                kind: TokenKind::Comment,
+                start,
+                end: i,
+            });
+            continue;
+        }
+
+        if let Some((open, close)) = syntax
+            .block_comment
+            .filter(|(open, _)| starts_with(i, open))
+        {
+            let start = i;
+            i += open.chars().count();
+            while i < chars.len() && !starts_with(i, close) {
+                i += 1;
+            }
+            i = (i + close.chars().count()).min(chars.len());
+            tokens.push(Token {
+                kind: TokenKind::Comment,
+                start,
+                end: i,
+            });
+            continue;
+        }
+
+        if let Some(delimiter) = syntax.strings.iter().find(|d| starts_with(i, d)) {
+            let start = i;
+            let length = delimiter.chars().count();
+            // Only triple quotes and backticks span lines
+            let multiline = length > 1 || *delimiter == "`";
+            i += length;
+            while i < chars.len() && !starts_with(i, delimiter) {
+                if chars[i] == '\n' && !multiline {
+                    break;
+                }
+                if chars[i] == '\\' {
+                    i += 1;
+                }
+                i += 1;
+            }
+            if i < chars.len() && starts_with(i, delimiter) {
+                i += length;
+            }
+            i = i.min(chars.len());
+            tokens.push(Token {
+                kind: TokenKind::String,
+                start,
+                end: i,
+            });
+            continue;
+        }
+
+        let after_word = i > 0 && is_word_char(chars[i - 1]);
+
+        if c.is_ascii_digit() && !after_word {
+            let start = i;
+            while i < chars.len()
+                && (is_word_char(chars[i])
+                    || chars[i] == '.' && chars.get(i + 1).is_some_and(|c| c.is_ascii_digit()))
+            {
+                i += 1;
+            }
+            tokens.push(Token {
+                kind: TokenKind::Number,
+                start,
+                end: i,
+            });
+            continue;
+        }
+
+        if (c.is_alphabetic() || c == '_') && !after_word {
+            let start = i;
+            while i < chars.len() && is_word_char(chars[i]) {
+                i += 1;
+            }
+            let word: String = chars[start..i].iter().collect();
+            let matches = |list: &str| {
+                list.split_whitespace().any(|candidate| {
+                    if syntax.case_insensitive {
+                        candidate.eq_ignore_ascii_case(&word)
+                    } else {
+                        candidate == word
+                    }
+                })
+            };
+
+            let kind = if matches(syntax.keywords) {
+                Some(TokenKind::Keyword)
+            } else if matches(syntax.literals) {
+                Some(TokenKind::Literal)
+            } else if chars.get(i) == Some(&'(') {
+                Some(TokenKind::Function)
+            } else {
+                None
+            };
+
+            if let Some(kind) = kind {
+                tokens.push(Token {
+                    kind,
+                    start,
+                    end: i,
+                });
+            }
+            continue;
+        }
+
+        i += 1;
+    }
+
+    tokens
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scraper::{Html, Selector};
+
+    fn spans(code: &str, language: Language) -> Vec<(TokenKind, String)> {
+        let chars: Vec<char> = code.chars().collect();
+        highlight(code, language)
+            .into_iter()
+            .map(|token| (token.kind, chars[token.start..token.end].iter().collect()))
+            .collect()
+    }
+
+    fn detect(html: &str) -> Option<Language> {
+        let document = Html::parse_fragment(html);
+        let pre = document
+            .select(&Selector::parse("pre").unwrap())
+            .next()
+            .unwrap();
+        let code = pre.text().collect::<String>();
+
+        detect_language(pre, &code)
+    }
+
+    #[test]
+    fn test_detect_language_from_class() {
+        assert_eq!(
+            detect(r#"<pre><code class="hljs language-python">x = 1</code></pre>"#),
+            Some(Language::Python)
+        );
+        assert_eq!(
+            detect(r#"<pre class="lang-ts">x</pre>"#),
+            Some(Language::JavaScript)
+        );
+        assert_eq!(
+            detect(r#"<pre data-lang="Rust">x</pre>"#),
+            Some(Language::Rust)
+        );
+    }
+
+    #[test]
+    fn test_detect_language_falls_back_to_guessing() {
+        assert_eq!(
+            detect(r#"<pre><code class="language-unknown">fn main() -> () {}</code></pre>"#),
+            Some(Language::Rust)
+        );
+        assert_eq!(detect("<pre>Just some text</pre>"), None);
+    }
+
+    #[test]
+    fn test_guess_language() {
+        assert_eq!(
+            guess_language("#!/bin/bash\necho hi"),
+            Some(Language::Shell)
+        );
+        assert_eq!(
+            guess_language("{\n  \"name\": \"cauldron\"\n}"),
+            Some(Language::Json)
+        );
+        assert_eq!(
+            guess_language("def greet(name):\n    print(name)"),
+            Some(Language::Python)
+        );
+        assert_eq!(
+            guess_language("package main\n\nfunc main() {}"),
+            Some(Language::Go)
+        );
+        assert_eq!(
+            guess_language("const answer = 42;\nconsole.log(answer);"),
+            Some(Language::JavaScript)
+        );
+        assert_eq!(
+            guess_language("select id from users where name = 'a'"),
+            Some(Language::Sql)
+        );
+    }
+
+    #[test]
+    fn test_highlight_rust() {
+        let tokens = spans(
+            "// Entry point\nfn main() { let x = \"hi\"; println!(\"{}\", 42); }",
+            Language::Rust,
+        );
+
+        assert_eq!(
+            tokens,
+            vec![
+                (TokenKind::Comment, "// Entry point".to_string()),
+                (TokenKind::Keyword, "fn".to_string()),
+                (TokenKind::Function, "main".to_string()),
+                (TokenKind::Keyword, "let".to_string()),
+                (TokenKind::String, "\"hi\"".to_string()),
+                (TokenKind::String, "\"{}\"".to_string()),
+                (TokenKind::Number, "42".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_highlight_strings_with_escapes_and_triple_quotes() {
+        let tokens = spans(
+            r#"s = "a \" b" + """multi
+line""""#,
+            Language::Python,
+        );
+
+        assert_eq!(
+            tokens,
+            vec![
+                (TokenKind::String, r#""a \" b""#.to_string()),
+                (TokenKind::String, "\"\"\"multi\nline\"\"\"".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_highlight_ignores_words_containing_keywords() {
+        let tokens = spans("format x2 if_done", Language::Python);
+        assert!(tokens.is_empty());
+    }
+
+    #[test]
+    fn test_highlight_sql_is_case_insensitive() {
+        let tokens = spans("SELECT * from t -- all", Language::Sql);
+
+        assert_eq!(
+            tokens,
+            vec![
+                (TokenKind::Keyword, "SELECT".to_string()),
+                (TokenKind::Keyword, "from".to_string()),
+                (TokenKind::Comment, "-- all".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_highlight_offsets_are_in_characters() {
+        let tokens = highlight("// é\nreturn", Language::JavaScript);
+
+        assert_eq!(tokens[1].start, 5);
+        assert_eq!(tokens[1].end, 11);
+    }
+}
@@ -0,0 +1,336 @@
+use scraper::ElementRef;
+use url::Url;
+
+/// Attributes used by lazy-loading scripts to hold the real image URL.
+const LAZY_SRC_ATTRIBUTES: &[&str] = &["data-src", "data-lazy-src", "data-original", "data-url"];
+const LAZY_SRCSET_ATTRIBUTES: &[&str] = &["data-srcset", "data-lazy-srcset"];
+
+/// Image formats gdk-pixbuf can't be relied upon to decode.
+const UNSUPPORTED_TYPES: &[&str] = &["image/avif", "image/jxl"];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImageSource {
+    pub url: String,
+    pub alt: Option<String>,
+    /// Intrinsic size from the `width` and `height` attributes
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+}
+
+impl ImageSource {
+    /// Size to reserve while the image downloads, scaled down to fit
+    /// `max_width` the way the loaded picture will be.
+    pub fn placeholder_size(&self, max_width: u32) -> Option<(i32, i32)> {
+        let (width, height) = (self.width?, self.height?);
+        if width == 0 || height == 0 {
+            return None;
+        }
+
+        let display_width = width.min(max_width);
+        let display_height = height as f64 * display_width as f64 / width as f64;
+
+        Some((display_width as i32, display_height.round() as i32))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Descriptor {
+    Width(u32),
+    Density(f64),
+}
+
+#[derive(Debug, PartialEq)]
+struct Candidate<'a> {
+    url: &'a str,
+    descriptor: Descriptor,
+}
+
+/// Picks the URL to download for an `img` or `picture` element.
+///
+/// `target_width` is the width the image will be displayed at in CSS pixels
+/// and `scale_factor` the display scale, used to choose among `srcset`
+/// candidates. Relative URLs are resolved against `base`.
+pub fn resolve(
+    element: ElementRef,
+    base: Option<&Url>,
+    target_width: u32,
+    scale_factor: i32,
+) -> Option<ImageSource> {
+    let img = if element.value().name() == "img" {
+        Some(element)
+    } else {
+        element
+            .children()
+            .filter_map(ElementRef::wrap)
+            .find(|e| e.value().name() == "img")
+    };
+
+    let mut url = None;
+
+    if element.value().name() == "picture" {
+        url = element
+            .children()
+            .filter_map(ElementRef::wrap)
+            .filter(|e| e.value().name() == "source")
+            .filter(|source| {
+                source
+                    .value()
+                    .attr("type")
+                    .is_none_or(|t| !UNSUPPORTED_TYPES.contains(&t.trim()))
+            })
+            .find_map(|source| srcset_url(source, target_width, scale_factor));
+    }
+
+    if url.is_none() {
+        url = img.and_then(|img| img_url(img, target_width, scale_factor));
+    }
+
+    let url = resolve_url(url?, base)?;
+    let alt = img
+        .and_then(|img| img.value().attr("alt"))
+        .map(|alt| alt.trim().to_string())
+        .filter(|alt| !alt.is_empty());
+
+    let dimension = |attribute| {
+        img.and_then(|img| img.value().attr(attribute))
+            .and_then(|value| value.trim().trim_end_matches("px").parse().ok())
+    };
+
+    Some(ImageSource {
+        url,
+        alt,
+        width: dimension("width"),
+        height: dimension("height"),
+    })
+}
+
+/// Resolves a possibly relative URL against the article URL.
+pub fn resolve_url(url: &str, base: Option<&Url>) -> Option<String> {
+    let url = url.trim();
+    if url.is_empty() {
+        return None;
+    }
+
+    let resolved = match base {
+        Some(base) => base.join(url).ok()?,
+        None => Url::parse(url).ok()?,
+    };
+
+    matches!(resolved.scheme(), "http" | "https").then(|| resolved.to_string())
+}
+
+fn img_url<'a>(img: ElementRef<'a>, target_width: u32, scale_factor: i32) -> Option<&'a str> {
+    if let Some(url) = srcset_url(img, target_width, scale_factor) {
+        return Some(url);
+    }
+
+    let src = img.value().attr("src").filter(|src| !is_placeholder(src));
+    let lazy_src = LAZY_SRC_ATTRIBUTES
+        .iter()
+        .find_map(|attribute| img.value().attr(attribute))
+        .filter(|src| !is_placeholder(src));
+
+    // Lazy-loading scripts keep a placeholder in `src` until the image scrolls
+    // into view, so the data attribute always holds the better URL
+    lazy_src.or(src)
+}
+
+fn srcset_url<'a>(
+    element: ElementRef<'a>,
+    target_width: u32,
+    scale_factor: i32,
+) -> Option<&'a str> {
+    std::iter::once("srcset")
+        .chain(LAZY_SRCSET_ATTRIBUTES.iter().copied())
+        .filter_map(|attribute| element.value().attr(attribute))
+        .find_map(|srcset| best_candidate(&parse_srcset(srcset), target_width, scale_factor))
+}
+
+fn parse_srcset(srcset: &str) -> Vec<Candidate<'_>> {
+    srcset
+        .split(',')
+        .filter_map(|candidate| {
+            let mut parts = candidate.split_whitespace();
+            let url = parts.next()?;
+            if is_placeholder(url) {
+                return None;
+            }
+
+            let descriptor = match parts.next() {
+                Some(d) if d.ends_with('w') => Descriptor::Width(d[..d.len() - 1].parse().ok()?),
+                Some(d) if d.ends_with('x') => Descriptor::Density(d[..d.len() - 1].parse().ok()?),
+                Some(_) => return None,
+                None => Descriptor::Density(1.0),
+            };
+
+            Some(Candidate { url, descriptor })
+        })
+        .collect()
+}
+
+/// Chooses the smallest candidate that is still sharp at the displayed size,
+/// or the largest one available when none is big enough.
+fn best_candidate<'a>(
+    candidates: &[Candidate<'a>],
+    target_width: u32,
+    scale_factor: i32,
+) -> Option<&'a str> {
+    let scale = scale_factor.max(1) as f64;
+    let needed_width = target_width as f64 * scale;
+
+    // Pixels each candidate has, relative to what the screen needs
+    let size = |candidate: &Candidate| match candidate.descriptor {
+        Descriptor::Width(width) => width as f64 / needed_width.max(1.0),
+        Descriptor::Density(density) => density / scale,
+    };
+
+    let mut sorted: Vec<&Candidate<'a>> = candidates.iter().collect();
+    sorted.sort_by(|a, b| size(a).total_cmp(&size(b)));
+
+    sorted
+        .iter()
+        .find(|candidate| size(candidate) >= 1.0)
+        .or(sorted.last())
+        .map(|candidate| candidate.url)
+}
+
+fn is_placeholder(url: &str) -> bool {
+    let url = url.trim();
+    url.is_empty() || url.starts_with("data:") || url == "#"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scraper::{Html, Selector};
+
+    fn resolve_first(html: &str, selector: &str, width: u32, scale: i32) -> Option<ImageSource> {
+        let document = Html::parse_fragment(html);
+        let selector = Selector::parse(selector).unwrap();
+        let element = document.select(&selector).next().unwrap();
+        let base = Url::parse("https://example.com/blog/post.html").unwrap();
+
+        resolve(element, Some(&base), width, scale)
+    }
+
+    #[test]
+    fn test_resolve_relative_src() {
+        let source = resolve_first(r#"<img src="images/a.png" alt=" A cat ">"#, "img", 800, 1);
+
+        assert_eq!(
+            source,
+            Some(ImageSource {
+                url: "https://example.com/blog/images/a.png".to_string(),
+                alt: Some("A cat".to_string()),
+                width: None,
+                height: None,
+            })
+        );
+
+        let source = resolve_first(r#"<img src="//cdn.example.com/b.png">"#, "img", 800, 1);
+        assert_eq!(source.unwrap().url, "https://cdn.example.com/b.png");
+    }
+
+    #[test]
+    fn test_resolve_lazy_loaded_image() {
+        let source = resolve_first(
+            r#"<img src="data:image/gif;base64,R0lGOD" data-src="/real.jpg" alt="">"#,
+            "img",
+            800,
+            1,
+        )
+        .unwrap();
+
+        assert_eq!(source.url, "https://example.com/real.jpg");
+        assert_eq!(source.alt, None);
+    }
+
+    #[test]
+    fn test_srcset_width_descriptors() {
+        let html =
+            r#"<img src="small.jpg" srcset="small.jpg 400w, medium.jpg 800w, large.jpg 1600w">"#;
+
+        let source = resolve_first(html, "img", 700, 1).unwrap();
+        assert_eq!(source.url, "https://example.com/blog/medium.jpg");
+
+        // HiDPI screens need twice the pixels
+        let source = resolve_first(html, "img", 700, 2).unwrap();
+        assert_eq!(source.url, "https://example.com/blog/large.jpg");
+
+        // Nothing is big enough, take the largest
+        let source = resolve_first(html, "img", 2000, 1).unwrap();
+        assert_eq!(source.url, "https://example.com/blog/large.jpg");
+
+        // No larger than the scaled width needs
+        let html = r#"<img srcset="medium.jpg 800w, large.jpg 1600w, huge.jpg 3200w">"#;
+        let source = resolve_first(html, "img", 700, 2).unwrap();
+        assert_eq!(source.url, "https://example.com/blog/large.jpg");
+    }
+
+    #[test]
+    fn test_srcset_density_descriptors() {
+        let html = r#"<img src="a.jpg" srcset="a.jpg, a@2x.jpg 2x">"#;
+
+        assert_eq!(
+            resolve_first(html, "img", 800, 1).unwrap().url,
+            "https://example.com/blog/a.jpg"
+        );
+        assert_eq!(
+            resolve_first(html, "img", 800, 2).unwrap().url,
+            "https://example.com/blog/a@2x.jpg"
+        );
+    }
+
+    #[test]
+    fn test_picture_prefers_supported_sources() {
+        let html = r#"
+            <picture>
+                <source type="image/avif" srcset="photo.avif">
+                <source type="image/webp" srcset="photo.webp">
+                <img src="photo.jpg" alt="Photo">
+            </picture>"#;
+
+        let source = resolve_first(html, "picture", 800, 1).unwrap();
+        assert_eq!(source.url, "https://example.com/blog/photo.webp");
+        assert_eq!(source.alt.as_deref(), Some("Photo"));
+    }
+
+    #[test]
+    fn test_picture_falls_back_to_img() {
+        let html = r#"<picture><source type="image/avif" srcset="photo.avif"><img src="photo.jpg"></picture>"#;
+
+        let source = resolve_first(html, "picture", 800, 1).unwrap();
+        assert_eq!(source.url, "https://example.com/blog/photo.jpg");
+    }
+
+    #[test]
+    fn test_placeholder_size() {
+        let source = resolve_first(
+            r#"<img src="a.png" width="1600" height="900px">"#,
+            "img",
+            800,
+            1,
+        )
+        .unwrap();
+
+        assert_eq!(source.width, Some(1600));
+        assert_eq!(source.height, Some(900));
+        // Scaled down to the text column
+        assert_eq!(source.placeholder_size(800), Some((800, 450)));
+        // Small images keep their size
+        assert_eq!(source.placeholder_size(2000), Some((1600, 900)));
+
+        let source = resolve_first(r#"<img src="a.png" width="100%">"#, "img", 800, 1).unwrap();
+        assert_eq!(source.placeholder_size(800), None);
+    }
+
+    #[test]
+    fn test_resolve_url_rejects_unsupported_schemes() {
+        assert_eq!(resolve_url("javascript:alert(1)", None), None);
+        assert_eq!(resolve_url("relative.png", None), None);
+        assert_eq!(
+            resolve_url("https://example.com/a.png", None),
+            Some("https://example.com/a.png".to_string())
+        );
+    }
+}
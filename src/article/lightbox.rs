@@ -0,0 +1,655 @@
+use std::cell::Cell;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use gettextrs::gettext;
+use gtk::prelude::*;
+use gtk::{gdk, gio, glib};
+use relm4::{
+    abstractions::Toaster, adw, adw::prelude::AdwDialogExt, gtk, Component, ComponentParts,
+    ComponentSender,
+};
+use url::Url;
+
+use crate::network;
+use crate::persistence::clipboard;
+
+/// Factor applied by each zoom in or zoom out step.
+const ZOOM_STEP: f64 = 1.25;
+const MIN_ZOOM: f64 = 0.1;
+const MAX_ZOOM: f64 = 8.0;
+
+#[derive(Clone, Debug)]
+pub struct LightboxImage {
+    pub url: String,
+    pub alt: Option<String>,
+    /// Set once the article view has downloaded the image
+    pub texture: Option<gdk::Texture>,
+}
+
+#[derive(Debug)]
+pub struct LightboxInit {
+    pub images: Vec<LightboxImage>,
+    pub index: usize,
+    /// Image cache budget, in megabytes
+    pub cache_size: u32,
+}
+
+pub struct ImageLightbox {
+    images: Vec<LightboxImage>,
+    index: usize,
+    /// Scale relative to the actual image size, `None` to fit the window
+    zoom: Option<f64>,
+    cache_size: u32,
+    loading: bool,
+    picture: gtk::Picture,
+    scrolled_window: gtk::ScrolledWindow,
+    toaster: Toaster,
+}
+
+#[derive(Debug)]
+pub enum ImageLightboxInput {
+    Next,
+    Previous,
+    ZoomIn,
+    ZoomOut,
+    ToggleFit,
+    Copy,
+    Save,
+    SaveTo(PathBuf),
+}
+
+#[derive(Debug)]
+pub enum ImageLightboxCommandOutput {
+    Loaded(usize, Result<Vec<u8>, String>),
+    Saved(Result<(), String>),
+}
+
+#[relm4::component(pub)]
+impl Component for ImageLightbox {
+    type Init = LightboxInit;
+    type Input = ImageLightboxInput;
+    type Output = ();
+    type CommandOutput = ImageLightboxCommandOutput;
+
+    view! {
+        adw::Dialog {
+            set_title: &gettext("Image"),
+            add_css_class: "image-lightbox",
+
+            #[wrap(Some)]
+            set_child = &adw::ToolbarView {
+                add_top_bar = &adw::HeaderBar {
+                    #[wrap(Some)]
+                    set_title_widget = &adw::WindowTitle {
+                        #[watch]
+                        set_title: &model.title(),
+                        #[watch]
+                        set_subtitle: &format!("{} / {}", model.index + 1, model.images.len()),
+                    },
+
+                    pack_start = &gtk::Button {
+                        set_icon_name: "go-previous-symbolic",
+                        set_tooltip_text: Some(&gettext("Previous Image")),
+                        #[watch]
+                        set_sensitive: model.index > 0,
+                        connect_clicked => ImageLightboxInput::Previous,
+                    },
+
+                    pack_start = &gtk::Button {
+                        set_icon_name: "go-next-symbolic",
+                        set_tooltip_text: Some(&gettext("Next Image")),
+                        #[watch]
+                        set_sensitive: model.index + 1 < model.images.len(),
+                        connect_clicked => ImageLightboxInput::Next,
+                    },
+
+                    pack_end = &gtk::Button {
+                        set_icon_name: "document-save-as-symbolic",
+                        set_tooltip_text: Some(&gettext("Save Image As…")),
+                        connect_clicked => ImageLightboxInput::Save,
+                    },
+
+                    pack_end = &gtk::Button {
+                        set_icon_name: "edit-copy-symbolic",
+                        set_tooltip_text: Some(&gettext("Copy Image")),
+                        #[watch]
+                        set_sensitive: model.texture().is_some(),
+                        connect_clicked => ImageLightboxInput::Copy,
+                    },
+
+                    pack_end = &gtk::Box {
+                        add_css_class: "linked",
+
+                        gtk::Button {
+                            set_icon_name: "zoom-out-symbolic",
+                            set_tooltip_text: Some(&gettext("Zoom Out")),
+                            connect_clicked => ImageLightboxInput::ZoomOut,
+                        },
+
+                        gtk::Button {
+                            #[watch]
+                            set_icon_name: if model.zoom.is_some() {
+                                "zoom-fit-best-symbolic"
+                            } else {
+                                "zoom-original-symbolic"
+                            },
+                            #[watch]
+                            set_tooltip_text: Some(&if model.zoom.is_some() {
+                                gettext("Fit to Window")
+                            } else {
+                                gettext("Actual Size")
+                            }),
+                            connect_clicked => ImageLightboxInput::ToggleFit,
+                        },
+
+                        gtk::Button {
+                            set_icon_name: "zoom-in-symbolic",
+                            set_tooltip_text: Some(&gettext("Zoom In")),
+                            connect_clicked => ImageLightboxInput::ZoomIn,
+                        },
+                    },
+                },
+
+                #[wrap(Some)]
+                set_content = &gtk::Overlay {
+                    #[wrap(Some)]
+                    #[local_ref]
+                    set_child = toast_overlay -> adw::ToastOverlay {
+                        #[wrap(Some)]
+                        #[local_ref]
+                        set_child = scrolled_window -> gtk::ScrolledWindow {
+                            set_hexpand: true,
+                            set_vexpand: true,
+
+                            #[wrap(Some)]
+                            #[local_ref]
+                            set_child = picture -> gtk::Picture {
+                                set_content_fit: gtk::ContentFit::Contain,
+                                set_halign: gtk::Align::Center,
+                                set_valign: gtk::Align::Center,
+                            },
+                        },
+                    },
+
+                    add_overlay = &adw::Spinner {
+                        set_halign: gtk::Align::Center,
+                        set_valign: gtk::Align::Center,
+                        set_width_request: 48,
+                        set_height_request: 48,
+                        #[watch]
+                        set_visible: model.loading,
+                    },
+                },
+            },
+        }
+    }
+
+    fn init(
+        init: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let index = init.index.min(init.images.len().saturating_sub(1));
+
+        let mut model = Self {
+            images: init.images,
+            index,
+            zoom: None,
+            cache_size: init.cache_size,
+            loading: false,
+            picture: gtk::Picture::new(),
+            scrolled_window: gtk::ScrolledWindow::new(),
+            toaster: Toaster::default(),
+        };
+        model.show_current(&sender);
+
+        let picture = &model.picture;
+        let scrolled_window = &model.scrolled_window;
+        let toast_overlay = model.toaster.overlay_widget();
+        let widgets = view_output!();
+
+        Self::add_shortcuts(&root, &sender);
+        Self::add_gestures(scrolled_window, &sender);
+
+        // Only present the dialog if we're not in a test environment
+        if !cfg!(test) {
+            let window = &relm4::main_application().windows()[0];
+            root.set_content_width(window.width());
+            root.set_content_height(window.height());
+            root.present(Some(window));
+        }
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, message: Self::Input, sender: ComponentSender<Self>, root: &Self::Root) {
+        match message {
+            ImageLightboxInput::Next => {
+                if self.index + 1 < self.images.len() {
+                    self.index += 1;
+                    self.show_current(&sender);
+                }
+            }
+            ImageLightboxInput::Previous => {
+                if self.index > 0 {
+                    self.index -= 1;
+                    self.show_current(&sender);
+                }
+            }
+            ImageLightboxInput::ZoomIn => {
+                let zoom = self.zoom.unwrap_or_else(|| self.fit_zoom());
+                self.set_zoom(Some(zoomed_in(zoom)));
+            }
+            ImageLightboxInput::ZoomOut => {
+                let zoom = self.zoom.unwrap_or_else(|| self.fit_zoom());
+                self.set_zoom(Some(zoomed_out(zoom)));
+            }
+            ImageLightboxInput::ToggleFit => {
+                let zoom = match self.zoom {
+                    Some(_) => None,
+                    None => Some(1.0),
+                };
+                self.set_zoom(zoom);
+            }
+            ImageLightboxInput::Copy => {
+                if let Some(texture) = self.texture() {
+                    clipboard::copy_texture(texture);
+                    self.show_toast(&gettext("Image copied to clipboard"));
+                }
+            }
+            ImageLightboxInput::Save => {
+                let Some(image) = self.images.get(self.index) else {
+                    return;
+                };
+
+                let dialog = gtk::FileDialog::builder()
+                    .title(gettext("Save Image"))
+                    .initial_name(suggested_file_name(&image.url))
+                    .modal(true)
+                    .build();
+
+                let window = root.root().and_downcast::<gtk::Window>();
+                let sender = sender.clone();
+                dialog.save(window.as_ref(), gio::Cancellable::NONE, move |result| {
+                    if let Some(path) = result.ok().and_then(|file| file.path()) {
+                        sender.input(ImageLightboxInput::SaveTo(path));
+                    }
+                });
+            }
+            ImageLightboxInput::SaveTo(path) => {
+                let Some(image) = self.images.get(self.index) else {
+                    return;
+                };
+
+                // The original bytes, usually straight from the image cache
+                let url = image.url.clone();
+                let cache_size = self.cache_size;
+                sender.oneshot_command(async move {
+                    let result = match network::images::fetch(&url, cache_size).await {
+                        Ok(bytes) => std::fs::write(&path, bytes).map_err(|e| e.to_string()),
+                        Err(e) => Err(e.to_string()),
+                    };
+                    ImageLightboxCommandOutput::Saved(result)
+                });
+            }
+        }
+    }
+
+    fn update_cmd(
+        &mut self,
+        message: Self::CommandOutput,
+        _sender: ComponentSender<Self>,
+        _root: &Self::Root,
+    ) {
+        match message {
+            ImageLightboxCommandOutput::Loaded(index, result) => {
+                let texture = result.and_then(|bytes| {
+                    gdk::Texture::from_bytes(&glib::Bytes::from_owned(bytes))
+                        .map_err(|e| e.to_string())
+                });
+
+                match texture {
+                    Ok(texture) => {
+                        if let Some(image) = self.images.get_mut(index) {
+                            image.texture = Some(texture);
+                        }
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to load image for the viewer: {}", e);
+                    }
+                }
+
+                // The user may have moved on while it was loading
+                if index == self.index {
+                    self.loading = false;
+                    self.update_picture();
+                }
+            }
+            ImageLightboxCommandOutput::Saved(Ok(())) => {
+                self.show_toast(&gettext("Image saved"));
+            }
+            ImageLightboxCommandOutput::Saved(Err(e)) => {
+                eprintln!("Failed to save image: {}", e);
+                self.show_toast(&gettext("Failed to save image"));
+            }
+        }
+    }
+}
+
+impl ImageLightbox {
+    fn title(&self) -> String {
+        self.images
+            .get(self.index)
+            .and_then(|image| image.alt.clone())
+            .unwrap_or_else(|| gettext("Image"))
+    }
+
+    fn texture(&self) -> Option<&gdk::Texture> {
+        self.images.get(self.index)?.texture.as_ref()
+    }
+
+    /// Shows the current image fitted to the window, downloading it first
+    /// when the article view hadn't loaded it.
+    fn show_current(&mut self, sender: &ComponentSender<Self>) {
+        self.zoom = None;
+        self.loading = false;
+
+        if let Some(image) = self.images.get(self.index) {
+            if image.texture.is_none() {
+                self.loading = true;
+
+                let index = self.index;
+                let url = image.url.clone();
+                let cache_size = self.cache_size;
+                sender.oneshot_command(async move {
+                    let result = network::images::fetch(&url, cache_size)
+                        .await
+                        .map_err(|e| e.to_string());
+                    ImageLightboxCommandOutput::Loaded(index, result)
+                });
+            }
+        }
+
+        self.update_picture();
+    }
+
+    fn set_zoom(&mut self, zoom: Option<f64>) {
+        self.zoom = zoom;
+        self.update_picture();
+    }
+
+    /// The scale the image is displayed at when fitted to the window.
+    fn fit_zoom(&self) -> f64 {
+        let Some(texture) = self.texture() else {
+            return 1.0;
+        };
+
+        fit_scale(
+            texture.width(),
+            texture.height(),
+            self.scrolled_window.width(),
+            self.scrolled_window.height(),
+        )
+    }
+
+    fn update_picture(&self) {
+        let texture = self.texture();
+        self.picture.set_paintable(texture);
+        self.picture
+            .set_alternative_text(self.images.get(self.index).and_then(|i| i.alt.as_deref()));
+
+        match (self.zoom, texture) {
+            (Some(zoom), Some(texture)) => {
+                self.picture.set_can_shrink(false);
+                self.picture.set_size_request(
+                    (texture.width() as f64 * zoom).round() as i32,
+                    (texture.height() as f64 * zoom).round() as i32,
+                );
+                self.scrolled_window
+                    .set_policy(gtk::PolicyType::Automatic, gtk::PolicyType::Automatic);
+            }
+            _ => {
+                self.picture.set_can_shrink(true);
+                self.picture.set_size_request(-1, -1);
+                self.scrolled_window
+                    .set_policy(gtk::PolicyType::Never, gtk::PolicyType::Never);
+            }
+        }
+    }
+
+    fn show_toast(&self, title: &str) {
+        let toast = adw::Toast::builder().title(title).timeout(3).build();
+        self.toaster.add_toast(toast);
+    }
+
+    fn add_shortcuts(root: &adw::Dialog, sender: &ComponentSender<Self>) {
+        let controller = gtk::ShortcutController::new();
+        controller.set_scope(gtk::ShortcutScope::Managed);
+
+        let shortcuts: [(&str, fn() -> ImageLightboxInput); 7] = [
+            ("Right", || ImageLightboxInput::Next),
+            ("Left", || ImageLightboxInput::Previous),
+            ("plus|equal|KP_Add", || ImageLightboxInput::ZoomIn),
+            ("minus|KP_Subtract", || ImageLightboxInput::ZoomOut),
+            ("0|KP_0", || ImageLightboxInput::ToggleFit),
+            ("<Control>c", || ImageLightboxInput::Copy),
+            ("<Control>s", || ImageLightboxInput::Save),
+        ];
+
+        for (accelerator, input) in shortcuts {
+            let sender = sender.clone();
+            let action = gtk::CallbackAction::new(move |_, _| {
+                sender.input(input());
+                glib::Propagation::Stop
+            });
+            controller.add_shortcut(gtk::Shortcut::new(
+                gtk::ShortcutTrigger::parse_string(accelerator),
+                Some(action),
+            ));
+        }
+
+        root.add_controller(controller);
+    }
+
+    /// Ctrl+scroll zooms, dragging pans a zoomed image and double clicking
+    /// toggles between fitting the window and the actual size.
+    fn add_gestures(scrolled_window: &gtk::ScrolledWindow, sender: &ComponentSender<Self>) {
+        let scroll = gtk::EventControllerScroll::new(gtk::EventControllerScrollFlags::VERTICAL);
+        let scroll_sender = sender.clone();
+        scroll.connect_scroll(move |controller, _, dy| {
+            if !controller
+                .current_event_state()
+                .contains(gdk::ModifierType::CONTROL_MASK)
+            {
+                return glib::Propagation::Proceed;
+            }
+
+            if dy < 0.0 {
+                scroll_sender.input(ImageLightboxInput::ZoomIn);
+            } else if dy > 0.0 {
+                scroll_sender.input(ImageLightboxInput::ZoomOut);
+            }
+            glib::Propagation::Stop
+        });
+        scroll.set_propagation_phase(gtk::PropagationPhase::Capture);
+        scrolled_window.add_controller(scroll);
+
+        let drag = gtk::GestureDrag::new();
+        let drag_start = Rc::new(Cell::new((0.0, 0.0)));
+        let window = scrolled_window.clone();
+        let start = drag_start.clone();
+        drag.connect_drag_begin(move |_, _, _| {
+            start.set((window.hadjustment().value(), window.vadjustment().value()));
+        });
+        let window = scrolled_window.clone();
+        drag.connect_drag_update(move |_, offset_x, offset_y| {
+            let (x, y) = drag_start.get();
+            window.hadjustment().set_value(x - offset_x);
+            window.vadjustment().set_value(y - offset_y);
+        });
+        scrolled_window.add_controller(drag);
+
+        let click = gtk::GestureClick::new();
+        let click_sender = sender.clone();
+        click.connect_pressed(move |_, n_press, _, _| {
+            if n_press == 2 {
+                click_sender.input(ImageLightboxInput::ToggleFit);
+            }
+        });
+        scrolled_window.add_controller(click);
+    }
+}
+
+/// File name offered when saving the image at `url`.
+pub fn suggested_file_name(url: &str) -> String {
+    Url::parse(url)
+        .ok()
+        .and_then(|url| {
+            let name = url.path_segments()?.next_back()?.to_string();
+            urlencoding::decode(&name)
+                .ok()
+                .map(|name| name.into_owned())
+        })
+        .map(|name| name.replace(['/', '\\'], "_"))
+        .filter(|name| !name.trim().is_empty())
+        .unwrap_or_else(|| String::from("image"))
+}
+
+/// Scale that fits an image inside the viewport without enlarging it.
+fn fit_scale(width: i32, height: i32, viewport_width: i32, viewport_height: i32) -> f64 {
+    if width <= 0 || height <= 0 || viewport_width <= 0 || viewport_height <= 0 {
+        return 1.0;
+    }
+
+    let horizontal = viewport_width as f64 / width as f64;
+    let vertical = viewport_height as f64 / height as f64;
+
+    horizontal.min(vertical).min(1.0)
+}
+
+fn zoomed_in(zoom: f64) -> f64 {
+    (zoom * ZOOM_STEP).clamp(MIN_ZOOM, MAX_ZOOM)
+}
+
+fn zoomed_out(zoom: f64) -> f64 {
+    (zoom / ZOOM_STEP).clamp(MIN_ZOOM, MAX_ZOOM)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::ComponentTester;
+
+    fn test_image(url: &str, alt: Option<&str>) -> LightboxImage {
+        let texture = gdk::MemoryTexture::new(
+            2,
+            1,
+            gdk::MemoryFormat::R8g8b8a8,
+            &glib::Bytes::from_static(&[0; 8]),
+            8,
+        );
+
+        LightboxImage {
+            url: url.to_string(),
+            alt: alt.map(str::to_string),
+            texture: Some(texture.upcast()),
+        }
+    }
+
+    fn launch(index: usize) -> ComponentTester<ImageLightbox> {
+        let tester = ComponentTester::<ImageLightbox>::launch(LightboxInit {
+            images: vec![
+                test_image("https://example.com/a.png", Some("First")),
+                test_image("https://example.com/b.png", None),
+                test_image("https://example.com/c.png", Some("Third")),
+            ],
+            index,
+            cache_size: 0,
+        });
+        tester.process_events();
+        tester
+    }
+
+    #[test]
+    fn test_suggested_file_name() {
+        assert_eq!(
+            suggested_file_name("https://example.com/images/cat%20photo.jpg?w=800"),
+            "cat photo.jpg"
+        );
+        assert_eq!(suggested_file_name("https://example.com/"), "image");
+        assert_eq!(suggested_file_name("not a url"), "image");
+    }
+
+    #[test]
+    fn test_fit_scale() {
+        // Large images shrink to the viewport
+        assert_eq!(fit_scale(2000, 1000, 1000, 1000), 0.5);
+        assert_eq!(fit_scale(1000, 2000, 1000, 1000), 0.5);
+        // Small images are never enlarged
+        assert_eq!(fit_scale(100, 100, 1000, 1000), 1.0);
+        // Nothing allocated yet
+        assert_eq!(fit_scale(100, 100, 0, 0), 1.0);
+    }
+
+    #[test]
+    fn test_zoom_steps() {
+        assert_eq!(zoomed_in(1.0), 1.25);
+        assert_eq!(zoomed_out(1.25), 1.0);
+        assert_eq!(zoomed_in(MAX_ZOOM), MAX_ZOOM);
+        assert_eq!(zoomed_out(MIN_ZOOM), MIN_ZOOM);
+    }
+
+    #[gtk::test]
+    fn test_navigation() {
+        let tester = launch(1);
+
+        assert_eq!(tester.model().index, 1);
+        assert_eq!(tester.model().title(), "Image");
+
+        tester.send_input(ImageLightboxInput::Next);
+        tester.process_events();
+        assert_eq!(tester.model().index, 2);
+        assert_eq!(tester.model().title(), "Third");
+
+        // Already on the last image
+        tester.send_input(ImageLightboxInput::Next);
+        tester.process_events();
+        assert_eq!(tester.model().index, 2);
+
+        tester.send_input(ImageLightboxInput::Previous);
+        tester.send_input(ImageLightboxInput::Previous);
+        tester.send_input(ImageLightboxInput::Previous);
+        tester.process_events();
+        assert_eq!(tester.model().index, 0);
+        assert_eq!(tester.model().title(), "First");
+        assert!(!tester.model().loading);
+    }
+
+    #[gtk::test]
+    fn test_zoom_and_fit() {
+        let tester = launch(0);
+        assert_eq!(tester.model().zoom, None);
+
+        tester.send_input(ImageLightboxInput::ToggleFit);
+        tester.process_events();
+        assert_eq!(tester.model().zoom, Some(1.0));
+        assert_eq!(tester.model().picture.size_request(), (2, 1));
+
+        tester.send_input(ImageLightboxInput::ZoomIn);
+        tester.send_input(ImageLightboxInput::ZoomIn);
+        tester.process_events();
+        assert_eq!(tester.model().zoom, Some(1.5625));
+
+        tester.send_input(ImageLightboxInput::ToggleFit);
+        tester.process_events();
+        assert_eq!(tester.model().zoom, None);
+        assert_eq!(tester.model().picture.size_request(), (-1, -1));
+
+        // Moving to another image fits it to the window again
+        tester.send_input(ImageLightboxInput::ToggleFit);
+        tester.send_input(ImageLightboxInput::Next);
+        tester.process_events();
+        assert_eq!(tester.model().zoom, None);
+    }
+}
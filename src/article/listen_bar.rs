@@ -0,0 +1,335 @@
+use std::ffi::OsStr;
+
+use gtk::prelude::*;
+use relm4::gtk::{gio, glib};
+use relm4::{gtk, ComponentParts, ComponentSender, SimpleComponent};
+
+use gettextrs::gettext;
+
+use super::speech::{Backend, MAX_RATE, MIN_RATE};
+
+/// Playback controls shown below the reader while an article is read
+/// aloud, one paragraph at a time. The paragraphs come from the renderer,
+/// which is told which one to highlight as speech moves along.
+pub struct ListenBar {
+    backend: Option<Backend>,
+    paragraphs: Vec<String>,
+    index: usize,
+    playing: bool,
+    rate: f64,
+    process: Option<gio::Subprocess>,
+    /// Bumped whenever speech is interrupted, so a stopped process exiting
+    /// isn't taken for the end of its paragraph
+    generation: u64,
+}
+
+#[derive(Debug)]
+pub enum ListenBarInput {
+    Start(Vec<String>),
+    PlayPause,
+    Next,
+    Previous,
+    SetRate(f64),
+    Stop,
+    Spoken { generation: u64, finished: bool },
+}
+
+#[derive(Debug, Clone)]
+pub enum ListenBarOutput {
+    /// The paragraph being read, `None` once listening stops
+    Speaking(Option<usize>),
+    /// The last paragraph was read
+    Finished,
+    Error(String),
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for ListenBar {
+    type Init = Option<Backend>;
+    type Input = ListenBarInput;
+    type Output = ListenBarOutput;
+
+    view! {
+        gtk::ActionBar {
+            #[watch]
+            set_revealed: !model.paragraphs.is_empty(),
+
+            pack_start = &gtk::Button {
+                set_icon_name: "media-skip-backward-symbolic",
+                set_tooltip_text: Some(&gettext("Previous Paragraph")),
+                #[watch]
+                set_sensitive: model.index > 0,
+                connect_clicked => ListenBarInput::Previous,
+            },
+
+            pack_start = &gtk::Button {
+                #[watch]
+                set_icon_name: if model.playing {
+                    "media-playback-pause-symbolic"
+                } else {
+                    "media-playback-start-symbolic"
+                },
+                #[watch]
+                set_tooltip_text: Some(&if model.playing {
+                    gettext("Pause")
+                } else {
+                    gettext("Play")
+                }),
+                connect_clicked => ListenBarInput::PlayPause,
+            },
+
+            pack_start = &gtk::Button {
+                set_icon_name: "media-skip-forward-symbolic",
+                set_tooltip_text: Some(&gettext("Next Paragraph")),
+                #[watch]
+                set_sensitive: model.index + 1 < model.paragraphs.len(),
+                connect_clicked => ListenBarInput::Next,
+            },
+
+            #[wrap(Some)]
+            set_center_widget = &gtk::Label {
+                add_css_class: "dim-label",
+                add_css_class: "numeric",
+                #[watch]
+                set_label: &gettext("Paragraph {} of {}")
+                    .replacen("{}", &(model.index + 1).to_string(), 1)
+                    .replacen("{}", &model.paragraphs.len().to_string(), 1),
+            },
+
+            pack_end = &gtk::Button {
+                set_icon_name: "window-close-symbolic",
+                set_tooltip_text: Some(&gettext("Stop Listening")),
+                connect_clicked => ListenBarInput::Stop,
+            },
+
+            pack_end = &gtk::SpinButton {
+                set_adjustment: &gtk::Adjustment::new(1.0, MIN_RATE, MAX_RATE, 0.1, 0.5, 0.0),
+                set_digits: 1,
+                #[watch]
+                #[block_signal(rate_handler)]
+                set_value: model.rate,
+                connect_value_changed[sender] => move |button| {
+                    sender.input(ListenBarInput::SetRate(button.value()));
+                } @rate_handler,
+            },
+
+            pack_end = &gtk::Label {
+                set_label: &gettext("Speed"),
+            },
+        }
+    }
+
+    fn init(
+        backend: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let model = Self {
+            backend,
+            paragraphs: Vec::new(),
+            index: 0,
+            playing: false,
+            rate: 1.0,
+            process: None,
+            generation: 0,
+        };
+
+        let widgets = view_output!();
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, message: Self::Input, sender: ComponentSender<Self>) {
+        match message {
+            ListenBarInput::Start(paragraphs) => {
+                self.silence();
+                self.playing = false;
+                if self.backend.is_none() {
+                    let _ = sender.output(ListenBarOutput::Error(gettext(
+                        "No speech synthesizer found, install Speech Dispatcher or espeak-ng",
+                    )));
+                    return;
+                }
+                if paragraphs.is_empty() {
+                    let _ = sender.output(ListenBarOutput::Error(gettext(
+                        "This article has no text to read",
+                    )));
+                    return;
+                }
+
+                self.paragraphs = paragraphs;
+                self.index = 0;
+                self.speak(&sender);
+            }
+            ListenBarInput::PlayPause if self.playing => {
+                self.silence();
+                self.playing = false;
+            }
+            ListenBarInput::PlayPause => self.speak(&sender),
+            ListenBarInput::Next | ListenBarInput::Previous => {
+                if self.paragraphs.is_empty() {
+                    return;
+                }
+                self.index = match message {
+                    ListenBarInput::Next => (self.index + 1).min(self.paragraphs.len() - 1),
+                    _ => self.index.saturating_sub(1),
+                };
+
+                if self.playing {
+                    self.speak(&sender);
+                } else {
+                    let _ = sender.output(ListenBarOutput::Speaking(Some(self.index)));
+                }
+            }
+            ListenBarInput::SetRate(rate) => {
+                let rate = (rate * 10.0).round() / 10.0;
+                if rate == self.rate {
+                    return;
+                }
+                self.rate = rate;
+                // Start the paragraph over at the new speed
+                if self.playing {
+                    self.speak(&sender);
+                }
+            }
+            ListenBarInput::Stop => {
+                self.silence();
+                self.playing = false;
+                self.paragraphs.clear();
+                self.index = 0;
+                let _ = sender.output(ListenBarOutput::Speaking(None));
+            }
+            ListenBarInput::Spoken {
+                generation,
+                finished,
+            } => {
+                if generation != self.generation {
+                    return;
+                }
+                self.process = None;
+
+                if !finished {
+                    self.playing = false;
+                    let _ = sender.output(ListenBarOutput::Error(gettext(
+                        "The speech synthesizer stopped unexpectedly",
+                    )));
+                } else if self.index + 1 < self.paragraphs.len() {
+                    self.index += 1;
+                    self.speak(&sender);
+                } else {
+                    self.playing = false;
+                    self.paragraphs.clear();
+                    self.index = 0;
+                    let _ = sender.output(ListenBarOutput::Speaking(None));
+                    let _ = sender.output(ListenBarOutput::Finished);
+                }
+            }
+        }
+    }
+
+    fn shutdown(&mut self, _widgets: &mut Self::Widgets, _output: relm4::Sender<Self::Output>) {
+        // Don't keep talking once the window is gone
+        self.silence();
+    }
+}
+
+impl ListenBar {
+    fn speak(&mut self, sender: &ComponentSender<Self>) {
+        self.silence();
+
+        let (Some(backend), Some(text)) = (self.backend, self.paragraphs.get(self.index)) else {
+            return;
+        };
+        let command = backend.speak_command(text, self.rate);
+
+        match spawn(&command) {
+            Ok(process) => {
+                let generation = self.generation;
+                let waiting = process.clone();
+                let sender_clone = sender.clone();
+                glib::MainContext::default().spawn_local(async move {
+                    let finished = waiting.wait_check_future().await.is_ok();
+                    sender_clone.input(ListenBarInput::Spoken {
+                        generation,
+                        finished,
+                    });
+                });
+
+                self.process = Some(process);
+                self.playing = true;
+                let _ = sender.output(ListenBarOutput::Speaking(Some(self.index)));
+            }
+            Err(e) => {
+                self.playing = false;
+                let _ = sender.output(ListenBarOutput::Error(format!(
+                    "{}: {}",
+                    gettext("Failed to start the speech synthesizer"),
+                    e
+                )));
+            }
+        }
+    }
+
+    /// Stops the paragraph being spoken, if any.
+    fn silence(&mut self) {
+        self.generation += 1;
+
+        let Some(process) = self.process.take() else {
+            return;
+        };
+        process.force_exit();
+        if let Some(command) = self.backend.and_then(|backend| backend.cancel_command()) {
+            let _ = spawn(&command);
+        }
+    }
+}
+
+fn spawn(command: &[String]) -> Result<gio::Subprocess, glib::Error> {
+    let argv: Vec<&OsStr> = command.iter().map(OsStr::new).collect();
+    gio::Subprocess::newv(
+        &argv,
+        gio::SubprocessFlags::STDOUT_SILENCE | gio::SubprocessFlags::STDERR_SILENCE,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::ComponentTester;
+
+    #[gtk::test]
+    fn test_start_without_synthesizer() {
+        let tester = ComponentTester::<ListenBar>::launch(None);
+
+        tester.send_input(ListenBarInput::Start(vec!["Hello".to_string()]));
+        tester.process_events();
+
+        assert!(matches!(
+            tester.try_recv_output(),
+            Some(ListenBarOutput::Error(_))
+        ));
+        assert!(!tester.widget().is_revealed());
+        assert!(!tester.model().playing);
+    }
+
+    #[gtk::test]
+    fn test_controls_without_paragraphs() {
+        let tester = ComponentTester::<ListenBar>::launch(Some(Backend::Espeak));
+
+        // Nothing is spoken before an article is started
+        tester.send_input(ListenBarInput::Next);
+        tester.send_input(ListenBarInput::Previous);
+        tester.send_input(ListenBarInput::PlayPause);
+        tester.process_events();
+
+        assert!(tester.try_recv_output().is_none());
+        assert!(!tester.model().playing);
+
+        tester.send_input(ListenBarInput::Start(Vec::new()));
+        tester.process_events();
+        assert!(matches!(
+            tester.try_recv_output(),
+            Some(ListenBarOutput::Error(_))
+        ));
+    }
+}
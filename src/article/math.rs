@@ -0,0 +1,701 @@
+use html_escape::encode_text;
+use scraper::ElementRef;
+
+/// LaTeX commands rendered as a Unicode symbol.
+const SYMBOLS: &[(&str, &str)] = &[
+    ("alpha", "α"),
+    ("beta", "β"),
+    ("gamma", "γ"),
+    ("delta", "δ"),
+    ("epsilon", "ϵ"),
+    ("varepsilon", "ε"),
+    ("zeta", "ζ"),
+    ("eta", "η"),
+    ("theta", "θ"),
+    ("vartheta", "ϑ"),
+    ("iota", "ι"),
+    ("kappa", "κ"),
+    ("lambda", "λ"),
+    ("mu", "μ"),
+    ("nu", "ν"),
+    ("xi", "ξ"),
+    ("pi", "π"),
+    ("varpi", "ϖ"),
+    ("rho", "ρ"),
+    ("sigma", "σ"),
+    ("tau", "τ"),
+    ("upsilon", "υ"),
+    ("phi", "ϕ"),
+    ("varphi", "φ"),
+    ("chi", "χ"),
+    ("psi", "ψ"),
+    ("omega", "ω"),
+    ("Gamma", "Γ"),
+    ("Delta", "Δ"),
+    ("Theta", "Θ"),
+    ("Lambda", "Λ"),
+    ("Xi", "Ξ"),
+    ("Pi", "Π"),
+    ("Sigma", "Σ"),
+    ("Upsilon", "Υ"),
+    ("Phi", "Φ"),
+    ("Psi", "Ψ"),
+    ("Omega", "Ω"),
+    ("times", "×"),
+    ("cdot", "·"),
+    ("div", "÷"),
+    ("pm", "±"),
+    ("mp", "∓"),
+    ("leq", "≤"),
+    ("le", "≤"),
+    ("geq", "≥"),
+    ("ge", "≥"),
+    ("neq", "≠"),
+    ("ne", "≠"),
+    ("approx", "≈"),
+    ("equiv", "≡"),
+    ("sim", "∼"),
+    ("cong", "≅"),
+    ("propto", "∝"),
+    ("ll", "≪"),
+    ("gg", "≫"),
+    ("infty", "∞"),
+    ("partial", "∂"),
+    ("nabla", "∇"),
+    ("sum", "∑"),
+    ("prod", "∏"),
+    ("int", "∫"),
+    ("oint", "∮"),
+    ("in", "∈"),
+    ("notin", "∉"),
+    ("subset", "⊂"),
+    ("subseteq", "⊆"),
+    ("supset", "⊃"),
+    ("supseteq", "⊇"),
+    ("cup", "∪"),
+    ("cap", "∩"),
+    ("setminus", "∖"),
+    ("emptyset", "∅"),
+    ("varnothing", "∅"),
+    ("forall", "∀"),
+    ("exists", "∃"),
+    ("neg", "¬"),
+    ("lnot", "¬"),
+    ("land", "∧"),
+    ("wedge", "∧"),
+    ("lor", "∨"),
+    ("vee", "∨"),
+    ("oplus", "⊕"),
+    ("otimes", "⊗"),
+    ("circ", "∘"),
+    ("ast", "∗"),
+    ("star", "⋆"),
+    ("bullet", "•"),
+    ("to", "→"),
+    ("rightarrow", "→"),
+    ("leftarrow", "←"),
+    ("leftrightarrow", "↔"),
+    ("Rightarrow", "⇒"),
+    ("Leftarrow", "⇐"),
+    ("Leftrightarrow", "⇔"),
+    ("implies", "⇒"),
+    ("iff", "⇔"),
+    ("mapsto", "↦"),
+    ("mid", "∣"),
+    ("parallel", "∥"),
+    ("perp", "⊥"),
+    ("angle", "∠"),
+    ("ldots", "…"),
+    ("dots", "…"),
+    ("cdots", "⋯"),
+    ("langle", "⟨"),
+    ("rangle", "⟩"),
+    ("lfloor", "⌊"),
+    ("rfloor", "⌋"),
+    ("lceil", "⌈"),
+    ("rceil", "⌉"),
+    ("ell", "ℓ"),
+    ("hbar", "ℏ"),
+    ("aleph", "ℵ"),
+    ("Re", "ℜ"),
+    ("Im", "ℑ"),
+    ("prime", "′"),
+    ("{", "{"),
+    ("}", "}"),
+    ("$", "$"),
+    ("%", "%"),
+    ("&", "&"),
+    ("#", "#"),
+    ("_", "_"),
+    ("|", "‖"),
+];
+
+/// Function names set upright, like `\sin`.
+const FUNCTIONS: &[&str] = &[
+    "arccos", "arcsin", "arctan", "cos", "cosh", "cot", "det", "dim", "exp", "gcd", "inf", "ker",
+    "lg", "lim", "ln", "log", "max", "min", "mod", "sec", "sin", "sinh", "sup", "tan", "tanh",
+];
+
+/// Accent commands, rendered as a combining character after their argument.
+const ACCENTS: &[(&str, char)] = &[
+    ("hat", '\u{302}'),
+    ("widehat", '\u{302}'),
+    ("bar", '\u{304}'),
+    ("overline", '\u{305}'),
+    ("tilde", '\u{303}'),
+    ("widetilde", '\u{303}'),
+    ("dot", '\u{307}'),
+    ("ddot", '\u{308}'),
+    ("vec", '\u{20d7}'),
+];
+
+/// Operators surrounded by spaces, as TeX and browsers lay them out.
+const SPACED_OPERATORS: &str = "=<>+−±∓×÷·≤≥≠≈≡∼≅∝≪≫∈∉⊂⊆⊃⊇→←↔⇒⇐⇔↦";
+
+#[derive(Debug, PartialEq)]
+pub enum Segment<'a> {
+    Text(&'a str),
+    Math { tex: &'a str, display: bool },
+}
+
+/// Whether `element` is a formula to show on its own line.
+pub fn is_display(element: ElementRef) -> bool {
+    match element.value().name() {
+        "math" => element.value().attr("display") == Some("block"),
+        "script" => {
+            is_tex_script(element)
+                && element
+                    .value()
+                    .attr("type")
+                    .is_some_and(|t| t.contains("mode=display"))
+        }
+        _ => false,
+    }
+}
+
+/// MathJax 2 keeps the TeX source of each formula in a script tag.
+pub fn is_tex_script(element: ElementRef) -> bool {
+    element.value().name() == "script"
+        && element
+            .value()
+            .attr("type")
+            .is_some_and(|t| t.starts_with("math/tex"))
+}
+
+/// Splits text on the `$…$`, `$$…$$`, `\(…\)` and `\[…\]` math delimiters.
+///
+/// Like pandoc, a single `$` only opens a formula when followed by a
+/// non-space and only closes it when preceded by a non-space and not followed
+/// by a digit, so prices such as "$5 and $10" stay text.
+pub fn split_tex(text: &str) -> Vec<Segment<'_>> {
+    let mut segments = Vec::new();
+    let mut text_start = 0;
+    let mut i = 0;
+
+    while i < text.len() {
+        let rest = &text[i..];
+
+        let found = if rest.starts_with("\\$") {
+            i += 2;
+            continue;
+        } else if let Some(inner) = rest.strip_prefix("$$") {
+            inner.find("$$").map(|end| (2, end, 2, true))
+        } else if let Some(inner) = rest.strip_prefix("\\[") {
+            inner.find("\\]").map(|end| (2, end, 2, true))
+        } else if let Some(inner) = rest.strip_prefix("\\(") {
+            inner.find("\\)").map(|end| (2, end, 2, false))
+        } else if let Some(inner) = rest.strip_prefix('$') {
+            closing_dollar(inner).map(|end| (1, end, 1, false))
+        } else {
+            None
+        };
+
+        match found {
+            Some((open, len, close, display)) if len > 0 => {
+                if text_start < i {
+                    segments.push(Segment::Text(&text[text_start..i]));
+                }
+                segments.push(Segment::Math {
+                    tex: &text[i + open..i + open + len],
+                    display,
+                });
+                i += open + len + close;
+                text_start = i;
+            }
+            _ => i += rest.chars().next().map_or(1, char::len_utf8),
+        }
+    }
+
+    if text_start < text.len() {
+        segments.push(Segment::Text(&text[text_start..]));
+    }
+
+    segments
+}
+
+fn closing_dollar(inner: &str) -> Option<usize> {
+    if inner.starts_with(char::is_whitespace) {
+        return None;
+    }
+
+    inner.match_indices('$').map(|(end, _)| end).find(|&end| {
+        let before = inner[..end].chars().next_back();
+        let after = inner[end + 1..].chars().next();
+        end > 0
+            && before.is_some_and(|c| !c.is_whitespace() && c != '\\')
+            && !after.is_some_and(|c| c.is_ascii_digit())
+    })
+}
+
+/// Converts TeX math to Pango markup, using Unicode symbols and
+/// superscripts. Anything fancier than that degrades to readable source.
+pub fn tex_markup(tex: &str) -> String {
+    let mut parser = TexParser {
+        chars: tex.chars().collect(),
+        pos: 0,
+    };
+    parser.parse_until(None, false).trim().to_string()
+}
+
+struct TexParser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl TexParser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn parse_until(&mut self, close: Option<char>, in_script: bool) -> String {
+        let mut markup = String::new();
+        let mut variable = String::new();
+
+        while let Some(c) = self.peek() {
+            if Some(c) == close {
+                self.pos += 1;
+                break;
+            }
+
+            // Runs of letters are variables, set in italics
+            if c.is_alphabetic() {
+                variable.push(c);
+                self.pos += 1;
+                continue;
+            }
+            push_variable(&mut markup, &mut variable);
+
+            match c {
+                '^' | '_' => {
+                    self.pos += 1;
+                    let tag = if c == '^' { "sup" } else { "sub" };
+                    let argument = self.parse_argument(true);
+                    markup.push_str(&format!("<{tag}>{argument}</{tag}>"));
+                }
+                c if c.is_whitespace() => self.pos += 1,
+                _ => markup.push_str(&self.parse_atom(in_script)),
+            }
+        }
+
+        push_variable(&mut markup, &mut variable);
+        markup
+    }
+
+    /// A braced group or the next single token.
+    fn parse_argument(&mut self, in_script: bool) -> String {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+
+        match self.peek() {
+            Some('{') => {
+                self.pos += 1;
+                self.parse_until(Some('}'), in_script)
+            }
+            Some(c) if c.is_alphabetic() => {
+                self.pos += 1;
+                format!("<i>{}</i>", c)
+            }
+            Some(_) => self.parse_atom(in_script),
+            None => String::new(),
+        }
+    }
+
+    /// The source of a braced argument, for text whose spaces matter.
+    fn raw_argument(&mut self) -> String {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+        if self.peek() != Some('{') {
+            return self
+                .peek()
+                .map(|c| {
+                    self.pos += 1;
+                    c.to_string()
+                })
+                .unwrap_or_default();
+        }
+
+        self.pos += 1;
+        let mut depth = 0;
+        let mut text = String::new();
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            match c {
+                '{' => depth += 1,
+                '}' if depth == 0 => break,
+                '}' => depth -= 1,
+                _ => {}
+            }
+            text.push(c);
+        }
+        text
+    }
+
+    fn parse_atom(&mut self, in_script: bool) -> String {
+        let Some(c) = self.peek() else {
+            return String::new();
+        };
+        self.pos += 1;
+
+        match c {
+            '\\' => self.parse_command(in_script),
+            '{' => self.parse_until(Some('}'), in_script),
+            '-' => spaced("−", in_script),
+            '\'' => "′".to_string(),
+            '~' => " ".to_string(),
+            c if SPACED_OPERATORS.contains(c) => spaced(&c.to_string(), in_script),
+            c => encode_text(&c.to_string()).into_owned(),
+        }
+    }
+
+    fn parse_command(&mut self, in_script: bool) -> String {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_alphabetic()) {
+            self.pos += 1;
+        }
+        // Single character commands like \{ or \,
+        if self.pos == start && self.peek().is_some() {
+            self.pos += 1;
+        }
+        let name: String = self.chars[start..self.pos].iter().collect();
+
+        match name.as_str() {
+            "frac" | "dfrac" | "tfrac" => {
+                let numerator = self.parse_argument(in_script);
+                let denominator = self.parse_argument(in_script);
+                format!("{}⁄{}", grouped(&numerator), grouped(&denominator))
+            }
+            "sqrt" => {
+                let index = if self.peek() == Some('[') {
+                    self.pos += 1;
+                    Some(self.parse_until(Some(']'), true))
+                } else {
+                    None
+                };
+                let radicand = grouped(&self.parse_argument(in_script));
+                match index {
+                    Some(index) => format!("<sup>{}</sup>√{}", index, radicand),
+                    None => format!("√{}", radicand),
+                }
+            }
+            "text" | "textrm" | "mathrm" | "operatorname" | "mbox" => {
+                encode_text(&self.raw_argument()).into_owned()
+            }
+            "mathbf" | "textbf" | "boldsymbol" => {
+                format!("<b>{}</b>", self.parse_argument(in_script))
+            }
+            "mathit" | "textit" | "mathcal" | "mathbb" => self.parse_argument(in_script),
+            "left" | "right" | "big" | "Big" | "bigg" | "Bigg" | "displaystyle" | "limits" => {
+                String::new()
+            }
+            "," | ":" | ";" | " " | "quad" | "qquad" => " ".to_string(),
+            "!" => String::new(),
+            "\\" => "; ".to_string(),
+            _ => {
+                if let Some((_, mark)) = ACCENTS.iter().find(|(accent, _)| *accent == name) {
+                    let mut argument = self.parse_argument(in_script);
+                    // The combining mark goes after the accented letter
+                    match argument.strip_suffix("</i>") {
+                        Some(letter) => format!("{}{}</i>", letter, mark),
+                        None => {
+                            argument.push(*mark);
+                            argument
+                        }
+                    }
+                } else if let Some((_, symbol)) = SYMBOLS.iter().find(|(s, _)| *s == name) {
+                    if SPACED_OPERATORS.contains(symbol) {
+                        spaced(symbol, in_script)
+                    } else {
+                        encode_text(symbol).into_owned()
+                    }
+                } else if FUNCTIONS.contains(&name.as_str()) {
+                    format!("{}\u{2009}", name)
+                } else {
+                    encode_text(&name).into_owned()
+                }
+            }
+        }
+    }
+}
+
+fn push_variable(markup: &mut String, variable: &mut String) {
+    if !variable.is_empty() {
+        markup.push_str(&format!("<i>{}</i>", variable));
+        variable.clear();
+    }
+}
+
+fn spaced(operator: &str, in_script: bool) -> String {
+    let operator = encode_text(operator);
+    if in_script {
+        operator.into_owned()
+    } else {
+        format!(" {} ", operator)
+    }
+}
+
+/// Wraps compound fraction parts and radicands in parentheses.
+fn grouped(markup: &str) -> String {
+    let text = strip_tags(markup);
+    let is_simple = text.chars().count() <= 1
+        || text.chars().all(|c| c.is_alphanumeric())
+        || text.starts_with('(') && text.ends_with(')');
+
+    if is_simple {
+        markup.trim().to_string()
+    } else {
+        format!("({})", markup.trim())
+    }
+}
+
+fn strip_tags(markup: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in markup.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text
+}
+
+/// Converts a MathML element to Pango markup.
+pub fn mathml_markup(element: ElementRef) -> String {
+    let arguments: Vec<ElementRef> = element.children().filter_map(ElementRef::wrap).collect();
+    let argument = |index: usize| {
+        arguments
+            .get(index)
+            .map_or(String::new(), |e| mathml_markup(*e))
+    };
+
+    match element.value().name() {
+        "semantics" => argument(0),
+        "annotation" | "annotation-xml" | "mphantom" | "none" | "mprescripts" => String::new(),
+        "mi" => {
+            let text = element_text(element);
+            let is_variable = text.chars().count() == 1 && text.chars().all(char::is_alphabetic);
+            if is_variable && element.value().attr("mathvariant") != Some("normal") {
+                format!("<i>{}</i>", encode_text(&text))
+            } else {
+                encode_text(&text).into_owned()
+            }
+        }
+        "mn" | "mtext" | "ms" => encode_text(&element_text(element)).into_owned(),
+        "mo" => {
+            let mut text = element_text(element);
+            if text == "-" {
+                text = "−".to_string();
+            }
+            let is_spaced = text.chars().count() == 1 && SPACED_OPERATORS.contains(text.as_str());
+            spaced(&text, !is_spaced || is_in_script(element))
+        }
+        "mspace" => " ".to_string(),
+        "msup" => format!("{}<sup>{}</sup>", argument(0), argument(1)),
+        "msub" => format!("{}<sub>{}</sub>", argument(0), argument(1)),
+        "msubsup" | "munderover" => format!(
+            "{}<sub>{}</sub><sup>{}</sup>",
+            argument(0),
+            argument(1),
+            argument(2)
+        ),
+        "munder" => format!("{}<sub>{}</sub>", argument(0), argument(1)),
+        "mover" => {
+            let over = element_text(arguments.get(1).copied().unwrap_or(element));
+            match ACCENT_MARKS.iter().find(|(accent, _)| *accent == over) {
+                Some((_, mark)) => format!("{}{}", argument(0), mark),
+                None => format!("{}<sup>{}</sup>", argument(0), argument(1)),
+            }
+        }
+        "mfrac" => format!("{}⁄{}", grouped(&argument(0)), grouped(&argument(1))),
+        "msqrt" => {
+            let radicand: String = arguments.iter().map(|e| mathml_markup(*e)).collect();
+            format!("√{}", grouped(&radicand))
+        }
+        "mroot" => format!("<sup>{}</sup>√{}", argument(1), grouped(&argument(0))),
+        "mfenced" => {
+            let open = element.value().attr("open").unwrap_or("(");
+            let close = element.value().attr("close").unwrap_or(")");
+            let separator = element.value().attr("separators").unwrap_or(",");
+            let parts: Vec<String> = arguments.iter().map(|e| mathml_markup(*e)).collect();
+            format!(
+                "{}{}{}",
+                encode_text(open),
+                parts.join(&format!("{} ", encode_text(separator.trim()))),
+                encode_text(close)
+            )
+        }
+        "mtable" => {
+            let rows: Vec<String> = arguments
+                .iter()
+                .map(|row| {
+                    row.children()
+                        .filter_map(ElementRef::wrap)
+                        .map(mathml_markup)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                })
+                .collect();
+            format!("[{}]", rows.join("; "))
+        }
+        // math, mrow, mstyle, mtd and the like just group their children
+        _ => arguments.iter().map(|e| mathml_markup(*e)).collect(),
+    }
+}
+
+/// `mover` accents rendered as combining characters.
+const ACCENT_MARKS: &[(&str, char)] = &[
+    ("^", '\u{302}'),
+    ("ˆ", '\u{302}'),
+    ("¯", '\u{304}'),
+    ("‾", '\u{305}'),
+    ("~", '\u{303}'),
+    ("˜", '\u{303}'),
+    ("˙", '\u{307}'),
+    ("¨", '\u{308}'),
+    ("→", '\u{20d7}'),
+];
+
+fn element_text(element: ElementRef) -> String {
+    element.text().collect::<String>().trim().to_string()
+}
+
+fn is_in_script(element: ElementRef) -> bool {
+    element
+        .ancestors()
+        .filter_map(ElementRef::wrap)
+        .take_while(|e| e.value().name() != "math")
+        .any(|e| {
+            matches!(
+                e.value().name(),
+                "msup" | "msub" | "msubsup" | "munder" | "mover" | "munderover"
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scraper::{Html, Selector};
+
+    fn mathml(html: &str) -> String {
+        let document = Html::parse_fragment(html);
+        let math = document
+            .select(&Selector::parse("math").unwrap())
+            .next()
+            .unwrap();
+        mathml_markup(math)
+    }
+
+    #[test]
+    fn test_split_tex() {
+        assert_eq!(
+            split_tex(r"Energy is $E = mc^2$, or \(x\) and $$\sum x$$"),
+            vec![
+                Segment::Text("Energy is "),
+                Segment::Math {
+                    tex: "E = mc^2",
+                    display: false
+                },
+                Segment::Text(", or "),
+                Segment::Math {
+                    tex: "x",
+                    display: false
+                },
+                Segment::Text(" and "),
+                Segment::Math {
+                    tex: r"\sum x",
+                    display: true
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_tex_ignores_prices() {
+        let text = "It costs $5 and $10, or \\$3 with a coupon";
+        assert_eq!(split_tex(text), vec![Segment::Text(text)]);
+        assert_eq!(
+            split_tex("between $ 5 and 6 $"),
+            vec![Segment::Text("between $ 5 and 6 $")]
+        );
+    }
+
+    #[test]
+    fn test_tex_markup() {
+        assert_eq!(tex_markup("E = mc^2"), "<i>E</i> = <i>mc</i><sup>2</sup>");
+        assert_eq!(
+            tex_markup(r"\alpha_{i} \leq \beta"),
+            "α<sub><i>i</i></sub> ≤ β"
+        );
+        assert_eq!(tex_markup(r"\frac{1}{2}"), "1⁄2");
+        assert_eq!(tex_markup(r"\frac{a+b}{2}"), "(<i>a</i> + <i>b</i>)⁄2");
+        assert_eq!(tex_markup(r"x \text{if } y"), "<i>x</i>if <i>y</i>");
+        assert_eq!(tex_markup(r"\sqrt{x}"), "√<i>x</i>");
+        assert_eq!(
+            tex_markup(r"\sin x \to \text{max}"),
+            "sin\u{2009}<i>x</i> → max"
+        );
+        assert_eq!(tex_markup(r"\hat{x}"), "<i>x\u{302}</i>");
+        assert_eq!(tex_markup("a < b"), "<i>a</i> &lt; <i>b</i>");
+    }
+
+    #[test]
+    fn test_mathml_markup() {
+        assert_eq!(
+            mathml("<math><msup><mi>x</mi><mn>2</mn></msup><mo>+</mo><mn>1</mn></math>"),
+            "<i>x</i><sup>2</sup> + 1"
+        );
+        assert_eq!(
+            mathml(
+                "<math><mfrac><mrow><mi>a</mi><mo>-</mo><mi>b</mi></mrow><mn>2</mn></mfrac></math>"
+            ),
+            "(<i>a</i> − <i>b</i>)⁄2"
+        );
+        assert_eq!(
+            mathml("<math><msqrt><mi>x</mi></msqrt><mo>&lt;</mo><mi>sin</mi></math>"),
+            "√<i>x</i> &lt; sin"
+        );
+        assert_eq!(
+            mathml("<math><mover><mi>v</mi><mo>→</mo></mover></math>"),
+            "<i>v</i>\u{20d7}"
+        );
+    }
+
+    #[test]
+    fn test_mathml_skips_annotations() {
+        let html = r#"<math display="block"><semantics><mrow><msub><mi>a</mi><mi>n</mi></msub></mrow>
+            <annotation encoding="application/x-tex">a_n</annotation></semantics></math>"#;
+        assert_eq!(mathml(html), "<i>a</i><sub><i>n</i></sub>");
+
+        let document = Html::parse_fragment(html);
+        let math = document
+            .select(&Selector::parse("math").unwrap())
+            .next()
+            .unwrap();
+        assert!(is_display(math));
+    }
+}
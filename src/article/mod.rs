@@ -1,13 +1,29 @@
+pub mod embeds;
+pub mod find;
+pub mod footnotes;
+pub mod highlight;
+pub mod images;
+pub mod lightbox;
+pub mod listen_bar;
+pub mod math;
+pub mod outline;
+pub mod print;
+pub mod reader_style;
+pub mod reading_time;
 pub mod renderer;
+pub mod speech;
+pub mod style_popover;
+pub mod table;
 
 use relm4::adw::{prelude::ActionRowExt, ActionRow};
 use relm4::factory::{DynamicIndex, FactoryComponent, FactorySender};
 use relm4::gtk;
 use relm4::gtk::glib;
+use relm4::gtk::prelude::{CheckButtonExt, WidgetExt};
 
 use crate::network::instapaper::InstapaperBookmark;
 
-pub use renderer::{ArticleRenderer, ArticleRendererInput};
+pub use renderer::{ArticleRenderer, ArticleRendererInput, ArticleRendererOutput};
 
 #[derive(Debug, Clone)]
 pub struct ArticleInit {
@@ -17,6 +33,10 @@ pub struct ArticleInit {
     pub description: String,
     pub time: f64,
     pub tags: Vec<String>,
+    pub word_count: Option<usize>,
+    pub words_per_minute: u32,
+    pub selection_mode: bool,
+    pub selected: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -27,6 +47,10 @@ pub struct Article {
     pub description: String,
     pub time: f64,
     pub tags: Vec<String>,
+    pub word_count: Option<usize>,
+    pub words_per_minute: u32,
+    pub selection_mode: bool,
+    pub selected: bool,
 }
 
 impl Article {
@@ -80,29 +104,65 @@ impl Article {
         }
     }
 
-    fn calculate_reading_time(&self) -> String {
-        let text = format!("{} {}", self.title, self.description);
-        let word_count = text.split_whitespace().count();
-        let minutes = (word_count as f32 / 200.0).ceil() as usize;
+    fn calculate_reading_time(&self) -> Option<String> {
+        self.word_count
+            .map(|count| reading_time::format_reading_time(count, self.words_per_minute))
+    }
 
-        if minutes < 1 {
-            String::from("< 1 min read")
-        } else if minutes == 1 {
-            String::from("1 min read")
-        } else {
-            format!("{} min read", minutes)
+    fn subtitle(&self) -> String {
+        let mut parts = Vec::new();
+
+        let truncated_desc = self.truncated_description();
+        if !truncated_desc.is_empty() {
+            parts.push(truncated_desc);
+        }
+
+        if !self.tags.is_empty() {
+            let tags_display = self
+                .tags
+                .iter()
+                .map(|t| format!("#{}", t))
+                .collect::<Vec<_>>()
+                .join("  ");
+            parts.push(tags_display);
+        }
+
+        let metadata = match self.calculate_reading_time() {
+            Some(reading_time) => format!("{} · {}", self.format_date(), reading_time),
+            None => self.format_date(),
+        };
+        parts.push(metadata);
+
+        glib::markup_escape_text(&parts.join("\n")).to_string()
+    }
+
+    fn set_selected(&mut self, selected: bool, sender: &FactorySender<Self>) {
+        if self.selected == selected {
+            return;
         }
+        self.selected = selected;
+        sender
+            .output(ArticleOutput::SelectionChanged(
+                self.item_id.clone(),
+                selected,
+            ))
+            .unwrap();
     }
 }
 
 #[derive(Debug)]
 pub enum ArticleOutput {
     ArticleSelected(String, String, String, String, f64, Vec<String>),
+    SelectionChanged(String, bool),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum ArticleInput {
     ArticleSelected,
+    SetWordCount(usize),
+    SetWordsPerMinute(u32),
+    SetSelectionMode(bool),
+    SetSelected(bool),
 }
 
 #[relm4::factory(pub)]
@@ -115,32 +175,25 @@ impl FactoryComponent for Article {
 
     view! {
         #[root]
-        ActionRow::builder()
-            .activatable(true)
-            .selectable(true)
-            .title(&self.title)
-            .subtitle({
-                let mut parts = Vec::new();
-
-                let truncated_desc = self.truncated_description();
-                if !truncated_desc.is_empty() {
-                    parts.push(truncated_desc);
-                }
-
-                if !self.tags.is_empty() {
-                    let tags_display = self.tags.iter()
-                        .map(|t| format!("#{}", t))
-                        .collect::<Vec<_>>()
-                        .join("  ");
-                    parts.push(tags_display);
-                }
-
-                let metadata = format!("{} · {}", self.format_date(), self.calculate_reading_time());
-                parts.push(metadata);
+        ActionRow {
+            set_activatable: true,
+            set_selectable: true,
+            set_title: &self.title,
+            #[watch]
+            set_subtitle: &self.subtitle(),
+
+            add_prefix = &gtk::CheckButton {
+                set_valign: gtk::Align::Center,
+                #[watch]
+                set_visible: self.selection_mode,
+                #[watch]
+                #[block_signal(toggled_handler)]
+                set_active: self.selected,
+                connect_toggled[sender] => move |button| {
+                    sender.input(ArticleInput::SetSelected(button.is_active()));
+                } @toggled_handler,
+            },
 
-                glib::markup_escape_text(&parts.join("\n"))
-            })
-            .build() {
             connect_activated => ArticleInput::ArticleSelected
         }
     }
@@ -153,11 +206,18 @@ impl FactoryComponent for Article {
             description: init.description,
             time: init.time,
             tags: init.tags,
+            word_count: init.word_count,
+            words_per_minute: init.words_per_minute,
+            selection_mode: init.selection_mode,
+            selected: init.selected,
         }
     }
 
     fn update(&mut self, msg: Self::Input, sender: FactorySender<Self>) {
         match msg {
+            ArticleInput::ArticleSelected if self.selection_mode => {
+                self.set_selected(!self.selected, &sender);
+            }
             ArticleInput::ArticleSelected => {
                 sender
                     .output(ArticleOutput::ArticleSelected(
@@ -170,6 +230,19 @@ impl FactoryComponent for Article {
                     ))
                     .unwrap();
             }
+            ArticleInput::SetWordCount(word_count) => {
+                self.word_count = Some(word_count);
+            }
+            ArticleInput::SetWordsPerMinute(words_per_minute) => {
+                self.words_per_minute = words_per_minute;
+            }
+            ArticleInput::SetSelectionMode(selection_mode) => {
+                self.selection_mode = selection_mode;
+                self.selected = false;
+            }
+            ArticleInput::SetSelected(selected) => {
+                self.set_selected(selected, &sender);
+            }
         }
     }
 }
@@ -188,6 +261,10 @@ pub fn parse_instapaper_response(bookmarks: Vec<InstapaperBookmark>) -> Vec<Arti
             description: bookmark.description.clone(),
             time: bookmark.time,
             tags: bookmark.tags.iter().map(|t| t.name.clone()).collect(),
+            word_count: None,
+            words_per_minute: reading_time::DEFAULT_WORDS_PER_MINUTE,
+            selection_mode: false,
+            selected: false,
         })
         .collect();
 
@@ -290,6 +367,10 @@ mod tests {
             description: "A test article".to_owned(),
             time: 1234567890.0,
             tags: vec![],
+            word_count: None,
+            words_per_minute: 200,
+            selection_mode: false,
+            selected: false,
         });
 
         tester.get(index, |article: &Article| {
@@ -312,6 +393,10 @@ mod tests {
             description: "A test article".to_owned(),
             time: 1234567890.0,
             tags: vec![],
+            word_count: None,
+            words_per_minute: 200,
+            selection_mode: false,
+            selected: false,
         });
 
         // Send ArticleSelected input
@@ -339,6 +424,10 @@ mod tests {
             description: "".to_owned(),
             time: 0.0,
             tags: vec![],
+            word_count: None,
+            words_per_minute: 200,
+            selection_mode: false,
+            selected: false,
         });
 
         tester.get(index, |article: &Article| {
@@ -350,49 +439,146 @@ mod tests {
     fn test_calculate_reading_time() {
         let mut tester = FactoryComponentTester::<Article>::new(gtk::ListBox::default());
 
-        // Create an article with zero words (empty title and description)
-        // This should give "< 1 min read"
+        // Without a word count from the article body there is no reading time,
+        // no matter how long the description is
         let index = tester.init(ArticleInit {
-            title: "".to_owned(),
+            title: "Word".to_owned(),
             uri: "https://example.com".to_owned(),
             item_id: "1".to_owned(),
-            description: "".to_owned(),
+            description: "word ".repeat(500),
             time: 0.0,
             tags: vec![],
+            word_count: None,
+            words_per_minute: 200,
+            selection_mode: false,
+            selected: false,
         });
 
         tester.get(index, |article: &Article| {
-            assert_eq!(article.calculate_reading_time(), "< 1 min read");
+            assert_eq!(article.calculate_reading_time(), None);
         });
 
         // Test with exactly 1 word
         let index2 = tester.init(ArticleInit {
-            title: "Word".to_owned(),
+            title: "".to_owned(),
             uri: "https://example.com".to_owned(),
             item_id: "2".to_owned(),
             description: "".to_owned(),
             time: 0.0,
             tags: vec![],
+            word_count: Some(1),
+            words_per_minute: 200,
+            selection_mode: false,
+            selected: false,
         });
 
         tester.get(index2, |article: &Article| {
-            assert_eq!(article.calculate_reading_time(), "1 min read");
+            assert_eq!(
+                article.calculate_reading_time(),
+                Some("1 min read".to_owned())
+            );
         });
 
-        // Test with more words (approximately 200 words would still be 1 min due to rounding)
-        let long_description = "word ".repeat(199);
+        // A longer body with a custom reading speed
         let index3 = tester.init(ArticleInit {
             title: "".to_owned(),
             uri: "https://example.com".to_owned(),
             item_id: "3".to_owned(),
-            description: long_description,
+            description: "".to_owned(),
             time: 0.0,
             tags: vec![],
+            word_count: Some(1200),
+            words_per_minute: 300,
+            selection_mode: false,
+            selected: false,
         });
 
         tester.get(index3, |article: &Article| {
-            assert_eq!(article.calculate_reading_time(), "1 min read");
+            assert_eq!(
+                article.calculate_reading_time(),
+                Some("4 min read".to_owned())
+            );
+        });
+    }
+
+    #[gtk::test]
+    fn test_set_word_count() {
+        let mut tester = FactoryComponentTester::<Article>::new(gtk::ListBox::default());
+
+        let index = tester.init(ArticleInit {
+            title: "Test".to_owned(),
+            uri: "https://example.com".to_owned(),
+            item_id: "1".to_owned(),
+            description: "".to_owned(),
+            time: 0.0,
+            tags: vec![],
+            word_count: None,
+            words_per_minute: 200,
+            selection_mode: false,
+            selected: false,
+        });
+
+        tester.send_input(index, ArticleInput::SetWordCount(900));
+        tester.process_events();
+
+        tester.get(index, |article: &Article| {
+            assert_eq!(article.word_count, Some(900));
+            assert_eq!(
+                article.calculate_reading_time(),
+                Some("5 min read".to_owned())
+            );
         });
+
+        tester.send_input(index, ArticleInput::SetWordsPerMinute(450));
+        tester.process_events();
+
+        tester.get(index, |article: &Article| {
+            assert_eq!(
+                article.calculate_reading_time(),
+                Some("2 min read".to_owned())
+            );
+        });
+    }
+
+    #[gtk::test]
+    fn test_selection_mode() {
+        let mut tester = FactoryComponentTester::<Article>::new(gtk::ListBox::default());
+
+        let index = tester.init(ArticleInit {
+            title: "Test".to_owned(),
+            uri: "https://example.com".to_owned(),
+            item_id: "7".to_owned(),
+            description: "".to_owned(),
+            time: 0.0,
+            tags: vec![],
+            word_count: None,
+            words_per_minute: 200,
+            selection_mode: false,
+            selected: false,
+        });
+        tester.process_events();
+
+        // The checkbox is hidden outside of selection mode
+        let check_button: gtk::CheckButton = tester.find_widget_by_type().unwrap();
+        assert!(!check_button.is_visible());
+
+        tester.send_input(index, ArticleInput::SetSelectionMode(true));
+        tester.process_events();
+        assert!(check_button.is_visible());
+
+        // Activating the row toggles the selection instead of opening the article
+        tester.send_input(index, ArticleInput::ArticleSelected);
+        tester.process_events();
+
+        tester.get(index, |article: &Article| assert!(article.selected));
+        assert!(check_button.is_active());
+
+        // Leaving selection mode clears the selection
+        tester.send_input(index, ArticleInput::SetSelectionMode(false));
+        tester.process_events();
+
+        tester.get(index, |article: &Article| assert!(!article.selected));
+        assert!(!check_button.is_visible());
     }
 
     #[gtk::test]
@@ -413,6 +599,10 @@ mod tests {
             description: desc.clone(),
             time: 0.0,
             tags: vec![],
+            word_count: None,
+            words_per_minute: 200,
+            selection_mode: false,
+            selected: false,
         });
 
         // This should not panic and should produce a valid truncated string
@@ -438,6 +628,10 @@ mod tests {
             description: "First article description".to_owned(),
             time: 1234567890.0,
             tags: vec![],
+            word_count: None,
+            words_per_minute: 200,
+            selection_mode: false,
+            selected: false,
         });
 
         tester.init(ArticleInit {
@@ -447,6 +641,10 @@ mod tests {
             description: "Second article description".to_owned(),
             time: 1234567900.0,
             tags: vec![],
+            word_count: None,
+            words_per_minute: 200,
+            selection_mode: false,
+            selected: false,
         });
 
         tester.init(ArticleInit {
@@ -456,6 +654,10 @@ mod tests {
             description: "Third article description".to_owned(),
             time: 1234567910.0,
             tags: vec![],
+            word_count: None,
+            words_per_minute: 200,
+            selection_mode: false,
+            selected: false,
         });
 
         tester.process_events();
@@ -492,6 +694,10 @@ mod tests {
             description: "This is a great article about testing".to_owned(),
             time: 1234567890.0,
             tags: vec![],
+            word_count: None,
+            words_per_minute: 200,
+            selection_mode: false,
+            selected: false,
         });
 
         tester.process_events();
@@ -0,0 +1,217 @@
+use gtk::prelude::*;
+use relm4::{gtk, ComponentParts, ComponentSender, SimpleComponent};
+
+/// A heading of the article, as listed in the table of contents.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutlineEntry {
+    pub level: u8,
+    pub title: String,
+}
+
+/// The heading whose section contains `position`: the last one starting at
+/// or above it. `offsets` are the heading tops in reading order.
+pub fn current_section(offsets: &[f32], position: f32) -> Option<usize> {
+    offsets.iter().rposition(|&offset| offset <= position)
+}
+
+/// Table of contents popover in the article header. Picking a heading is
+/// reported to the parent, which scrolls the reader there.
+pub struct OutlinePopover {
+    entries: Vec<OutlineEntry>,
+    list_box: gtk::ListBox,
+}
+
+#[derive(Debug)]
+pub enum OutlinePopoverInput {
+    SetEntries(Vec<OutlineEntry>),
+    /// The section the reader is in, to highlight it
+    SetCurrent(Option<usize>),
+    Activate(usize),
+}
+
+#[derive(Debug, Clone)]
+pub enum OutlinePopoverOutput {
+    HeadingSelected(usize),
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for OutlinePopover {
+    type Init = ();
+    type Input = OutlinePopoverInput;
+    type Output = OutlinePopoverOutput;
+
+    view! {
+        gtk::Popover {
+            add_css_class: "outline-popover",
+
+            gtk::ScrolledWindow {
+                set_hscrollbar_policy: gtk::PolicyType::Never,
+                set_max_content_height: 480,
+                set_propagate_natural_height: true,
+                set_propagate_natural_width: true,
+
+                #[local_ref]
+                list_box -> gtk::ListBox {
+                    add_css_class: "navigation-sidebar",
+                    connect_row_activated[sender] => move |_, row| {
+                        sender.input(OutlinePopoverInput::Activate(row.index() as usize));
+                    },
+                },
+            },
+        }
+    }
+
+    fn init(
+        _init: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let model = Self {
+            entries: Vec::new(),
+            list_box: gtk::ListBox::default(),
+        };
+        let list_box = &model.list_box;
+
+        let widgets = view_output!();
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, message: Self::Input, sender: ComponentSender<Self>) {
+        match message {
+            OutlinePopoverInput::SetEntries(entries) => {
+                self.entries = entries;
+                self.rebuild_rows();
+            }
+            OutlinePopoverInput::SetCurrent(index) => {
+                match index.and_then(|index| self.list_box.row_at_index(index as i32)) {
+                    Some(row) => self.list_box.select_row(Some(&row)),
+                    None => self.list_box.unselect_all(),
+                }
+            }
+            OutlinePopoverInput::Activate(index) => {
+                if index >= self.entries.len() {
+                    return;
+                }
+                if let Some(popover) = self
+                    .list_box
+                    .ancestor(gtk::Popover::static_type())
+                    .and_downcast::<gtk::Popover>()
+                {
+                    popover.popdown();
+                }
+                let _ = sender.output(OutlinePopoverOutput::HeadingSelected(index));
+            }
+        }
+    }
+}
+
+impl OutlinePopover {
+    fn rebuild_rows(&self) {
+        self.list_box.remove_all();
+
+        // Articles often start at h2, indent relative to the top level used
+        let top_level = self.entries.iter().map(|e| e.level).min().unwrap_or(1);
+        for entry in &self.entries {
+            let label = gtk::Label::builder()
+                .label(&entry.title)
+                .xalign(0.0)
+                .ellipsize(gtk::pango::EllipsizeMode::End)
+                .max_width_chars(40)
+                .margin_start(12 * i32::from(entry.level - top_level))
+                .tooltip_text(&entry.title)
+                .build();
+            if entry.level == top_level {
+                label.add_css_class("heading");
+            }
+            self.list_box.append(&label);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::ComponentTester;
+
+    fn entry(level: u8, title: &str) -> OutlineEntry {
+        OutlineEntry {
+            level,
+            title: title.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_current_section() {
+        let offsets = [100.0, 400.0, 900.0];
+
+        // Above the first heading there is no section yet
+        assert_eq!(current_section(&offsets, 50.0), None);
+        assert_eq!(current_section(&offsets, 100.0), Some(0));
+        assert_eq!(current_section(&offsets, 650.0), Some(1));
+        assert_eq!(current_section(&offsets, 5000.0), Some(2));
+        assert_eq!(current_section(&[], 100.0), None);
+    }
+
+    #[gtk::test]
+    fn test_set_entries_indents_sub_headings() {
+        let tester = ComponentTester::<OutlinePopover>::launch(());
+
+        tester.send_input(OutlinePopoverInput::SetEntries(vec![
+            entry(2, "Introduction"),
+            entry(3, "Background"),
+            entry(2, "Results"),
+        ]));
+        tester.process_events();
+
+        let introduction = tester.find_label_with_text("Introduction").unwrap();
+        let background = tester.find_label_with_text("Background").unwrap();
+        assert_eq!(introduction.margin_start(), 0);
+        assert!(introduction.has_css_class("heading"));
+        assert_eq!(background.margin_start(), 12);
+        assert!(!background.has_css_class("heading"));
+
+        // A new article replaces the rows
+        tester.send_input(OutlinePopoverInput::SetEntries(vec![entry(1, "Only")]));
+        tester.process_events();
+        assert!(tester.find_label_with_text("Results").is_none());
+        assert!(tester.find_label_with_text("Only").is_some());
+    }
+
+    #[gtk::test]
+    fn test_current_section_is_selected() {
+        let tester = ComponentTester::<OutlinePopover>::launch(());
+        tester.send_input(OutlinePopoverInput::SetEntries(vec![
+            entry(1, "One"),
+            entry(1, "Two"),
+        ]));
+        tester.send_input(OutlinePopoverInput::SetCurrent(Some(1)));
+        tester.process_events();
+
+        let selected = tester.model().list_box.selected_row();
+        assert_eq!(selected.map(|row| row.index()), Some(1));
+
+        tester.send_input(OutlinePopoverInput::SetCurrent(None));
+        tester.process_events();
+        assert!(tester.model().list_box.selected_row().is_none());
+    }
+
+    #[gtk::test]
+    fn test_activate_reports_heading() {
+        let tester = ComponentTester::<OutlinePopover>::launch(());
+        tester.send_input(OutlinePopoverInput::SetEntries(vec![
+            entry(1, "One"),
+            entry(1, "Two"),
+        ]));
+        tester.send_input(OutlinePopoverInput::Activate(1));
+        tester.send_input(OutlinePopoverInput::Activate(5));
+        tester.process_events();
+
+        match tester.try_recv_output() {
+            Some(OutlinePopoverOutput::HeadingSelected(index)) => assert_eq!(index, 1),
+            None => panic!("Expected a heading to be selected"),
+        }
+        // Out of range rows are ignored
+        assert!(tester.try_recv_output().is_none());
+    }
+}
@@ -0,0 +1,411 @@
+use std::cell::RefCell;
+use std::ops::Range;
+use std::rc::Rc;
+
+use gtk::prelude::*;
+use relm4::gtk::{self, gdk, glib, graphene, pango};
+
+use gettextrs::gettext;
+
+/// Size of body text on paper, in points.
+const BODY_FONT_SIZE: f64 = 11.0;
+/// Indentation of quotes and list items per nesting level, in points.
+const INDENT: f64 = 18.0;
+/// Height reserved at the bottom of each page for its number, in points.
+const FOOTER_HEIGHT: f64 = 24.0;
+/// Screen pixels are taken as 96 per inch, points are 72.
+const POINTS_PER_PIXEL: f64 = 0.75;
+
+/// Part of an article to print, in reading order.
+#[derive(Debug, Clone)]
+pub enum PrintBlock {
+    Text {
+        /// Pango markup
+        markup: String,
+        style: TextStyle,
+        /// Nesting level of quotes and lists
+        indent: u32,
+    },
+    Code(String),
+    Image(gdk::Texture),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextStyle {
+    Title,
+    Heading(u8),
+    /// Metadata and captions
+    Caption,
+    Body,
+}
+
+impl TextStyle {
+    fn scale(self) -> f64 {
+        match self {
+            TextStyle::Title => 2.0,
+            TextStyle::Heading(1) => 1.6,
+            TextStyle::Heading(2) => 1.4,
+            TextStyle::Heading(3) => 1.2,
+            TextStyle::Heading(_) => 1.05,
+            TextStyle::Caption => 0.85,
+            TextStyle::Body => 1.0,
+        }
+    }
+
+    /// Space left above a block of this style, in points.
+    fn space_before(self) -> f64 {
+        match self {
+            TextStyle::Title => 0.0,
+            TextStyle::Heading(_) => 16.0,
+            TextStyle::Caption => 4.0,
+            TextStyle::Body => 8.0,
+        }
+    }
+}
+
+/// A line of text or an image, the unit pages are filled with.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Row {
+    pub height: f64,
+    /// Gap above the row, dropped at the top of a page
+    pub space_before: f64,
+    /// Don't end a page on this row, as for the last line of a heading
+    pub keep_with_next: bool,
+}
+
+/// Splits `rows` into pages `page_height` tall, as ranges of row indices.
+/// A row taller than a page gets a page of its own.
+pub fn paginate(rows: &[Row], page_height: f64) -> Vec<Range<usize>> {
+    let mut pages = Vec::new();
+    let mut start = 0;
+    let mut used = 0.0;
+
+    for (index, row) in rows.iter().enumerate() {
+        used = if index == start {
+            row.height
+        } else {
+            used + row.space_before + row.height
+        };
+
+        // The rows carried over may still not fit, then the page is split again
+        while used > page_height && index > start {
+            // Carry a heading over along with the row it introduces
+            let mut end = index;
+            while end > start + 1 && rows[end - 1].keep_with_next {
+                end -= 1;
+            }
+            pages.push(start..end);
+            start = end;
+            used = rows[start..=index]
+                .iter()
+                .enumerate()
+                .map(|(i, row)| row.height + if i == 0 { 0.0 } else { row.space_before })
+                .sum();
+        }
+    }
+
+    if start < rows.len() {
+        pages.push(start..rows.len());
+    }
+    pages
+}
+
+/// What a row shows, once laid out for the paper size.
+enum RowContent {
+    /// The line of `layout` starting `top` points into it
+    Line {
+        layout: pango::Layout,
+        top: f64,
+        x: f64,
+    },
+    Image {
+        texture: gdk::Texture,
+        x: f64,
+        width: f64,
+    },
+}
+
+#[derive(Default)]
+struct PrintLayout {
+    rows: Vec<Row>,
+    contents: Vec<RowContent>,
+    pages: Vec<Range<usize>>,
+}
+
+/// Opens the print dialog for an article. "Print to File" in the dialog
+/// saves it as a PDF, named after `title` by default.
+pub fn print(
+    title: &str,
+    font_family: &str,
+    blocks: Vec<PrintBlock>,
+    parent: Option<&gtk::Window>,
+) -> Result<(), glib::Error> {
+    let operation = gtk::PrintOperation::new();
+    operation.set_job_name(title);
+    operation.set_unit(gtk::Unit::Points);
+    operation.set_embed_page_setup(true);
+    operation.set_allow_async(true);
+
+    let settings = gtk::PrintSettings::new();
+    settings.set("output-basename", Some(file_name(title).as_str()));
+    operation.set_print_settings(Some(&settings));
+
+    let layout = Rc::new(RefCell::new(PrintLayout::default()));
+    let font_family = font_family.to_string();
+
+    operation.connect_begin_print({
+        let layout = layout.clone();
+        move |operation, context| {
+            let mut laid_out = lay_out(context, &font_family, &blocks);
+            laid_out.pages = paginate(&laid_out.rows, context.height() - FOOTER_HEIGHT);
+            operation.set_n_pages(laid_out.pages.len().max(1) as i32);
+            *layout.borrow_mut() = laid_out;
+        }
+    });
+
+    operation.connect_draw_page(move |_, context, page| {
+        draw_page(context, &layout.borrow(), page as usize);
+    });
+
+    operation.run(gtk::PrintOperationAction::PrintDialog, parent)?;
+    Ok(())
+}
+
+/// Breaks blocks into rows for the page width of `context`.
+fn lay_out(context: &gtk::PrintContext, font_family: &str, blocks: &[PrintBlock]) -> PrintLayout {
+    let width = context.width();
+    let mut layout = PrintLayout::default();
+
+    for block in blocks {
+        match block {
+            PrintBlock::Text {
+                markup,
+                style,
+                indent,
+            } => {
+                let x = INDENT * *indent as f64;
+                let heading = matches!(style, TextStyle::Title | TextStyle::Heading(_));
+                let mut font = pango::FontDescription::from_string(font_family);
+                font.set_size((BODY_FONT_SIZE * style.scale() * pango::SCALE as f64) as i32);
+                if heading {
+                    font.set_weight(pango::Weight::Bold);
+                }
+
+                let text = context.create_pango_layout();
+                text.set_font_description(Some(&font));
+                text.set_width(((width - x) * pango::SCALE as f64) as i32);
+                text.set_wrap(pango::WrapMode::WordChar);
+                text.set_markup(markup);
+
+                add_lines(&mut layout, &text, x, style.space_before(), heading);
+            }
+            PrintBlock::Code(code) => {
+                let mut font = pango::FontDescription::from_string("Monospace");
+                font.set_size((BODY_FONT_SIZE * 0.85 * pango::SCALE as f64) as i32);
+
+                let text = context.create_pango_layout();
+                text.set_font_description(Some(&font));
+                text.set_width((width * pango::SCALE as f64) as i32);
+                text.set_wrap(pango::WrapMode::Char);
+                text.set_text(code.trim_end());
+
+                add_lines(&mut layout, &text, 0.0, 8.0, false);
+            }
+            PrintBlock::Image(texture) => {
+                let natural = texture.width() as f64 * POINTS_PER_PIXEL;
+                let max_height = (context.height() - FOOTER_HEIGHT) * 0.8;
+                let aspect = texture.height() as f64 / texture.width().max(1) as f64;
+
+                let mut image_width = natural.min(width);
+                if image_width * aspect > max_height {
+                    image_width = max_height / aspect;
+                }
+
+                layout.rows.push(Row {
+                    height: image_width * aspect,
+                    space_before: 12.0,
+                    keep_with_next: false,
+                });
+                layout.contents.push(RowContent::Image {
+                    texture: texture.clone(),
+                    x: (width - image_width) / 2.0,
+                    width: image_width,
+                });
+            }
+        }
+    }
+
+    layout
+}
+
+fn add_lines(
+    layout: &mut PrintLayout,
+    text: &pango::Layout,
+    x: f64,
+    space_before: f64,
+    keep: bool,
+) {
+    let mut iter = text.iter();
+    let mut first = true;
+    loop {
+        let (_, extents) = iter.line_extents();
+        let last = !iter.next_line();
+
+        layout.rows.push(Row {
+            height: extents.height() as f64 / pango::SCALE as f64,
+            space_before: if first { space_before } else { 0.0 },
+            keep_with_next: keep && last,
+        });
+        layout.contents.push(RowContent::Line {
+            layout: text.clone(),
+            top: extents.y() as f64 / pango::SCALE as f64,
+            x,
+        });
+
+        first = false;
+        if last {
+            break;
+        }
+    }
+}
+
+fn draw_page(context: &gtk::PrintContext, layout: &PrintLayout, page: usize) {
+    let snapshot = gtk::Snapshot::new();
+    let ink = gdk::RGBA::BLACK;
+    let width = context.width() as f32;
+
+    let rows = layout.pages.get(page).cloned().unwrap_or_default();
+    let mut y = 0.0;
+    for index in rows.clone() {
+        let row = &layout.rows[index];
+        if index != rows.start {
+            y += row.space_before;
+        }
+
+        match &layout.contents[index] {
+            RowContent::Line { layout, top, x } => {
+                // Draw the whole paragraph, clipped to the line on this page
+                snapshot.save();
+                snapshot.translate(&graphene::Point::new(*x as f32, (y - top) as f32));
+                snapshot.push_clip(&graphene::Rect::new(
+                    0.0,
+                    *top as f32,
+                    width,
+                    row.height as f32,
+                ));
+                snapshot.append_layout(layout, &ink);
+                snapshot.pop();
+                snapshot.restore();
+            }
+            RowContent::Image { texture, x, width } => {
+                snapshot.append_texture(
+                    texture,
+                    &graphene::Rect::new(*x as f32, y as f32, *width as f32, row.height as f32),
+                );
+            }
+        }
+        y += row.height;
+    }
+
+    let footer = context.create_pango_layout();
+    let mut font = pango::FontDescription::from_string("Sans");
+    font.set_size((BODY_FONT_SIZE * 0.8 * pango::SCALE as f64) as i32);
+    footer.set_font_description(Some(&font));
+    footer.set_width((context.width() * pango::SCALE as f64) as i32);
+    footer.set_alignment(pango::Alignment::Center);
+    footer.set_text(
+        &gettext("Page {} of {}")
+            .replacen("{}", &(page + 1).to_string(), 1)
+            .replacen("{}", &layout.pages.len().max(1).to_string(), 1),
+    );
+    snapshot.save();
+    snapshot.translate(&graphene::Point::new(
+        0.0,
+        (context.height() - FOOTER_HEIGHT / 2.0) as f32,
+    ));
+    snapshot.append_layout(&footer, &gdk::RGBA::new(0.4, 0.4, 0.4, 1.0));
+    snapshot.restore();
+
+    if let Some(node) = snapshot.to_node() {
+        node.draw(&context.cairo_context());
+    }
+}
+
+/// A file name for the article, without characters file systems reject.
+pub fn file_name(title: &str) -> String {
+    let name: String = title
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '-',
+            c if c.is_control() => ' ',
+            c => c,
+        })
+        .collect();
+    let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    if name.is_empty() {
+        gettext("Article")
+    } else {
+        name.chars().take(100).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(height: f64, space_before: f64) -> Row {
+        Row {
+            height,
+            space_before,
+            keep_with_next: false,
+        }
+    }
+
+    #[test]
+    fn test_paginate() {
+        let rows = [row(40.0, 0.0), row(40.0, 10.0), row(40.0, 10.0)];
+
+        assert_eq!(paginate(&rows, 200.0), [0..3]);
+        // The gap above a row is dropped at the top of a page
+        assert_eq!(paginate(&rows, 90.0), [0..2, 2..3]);
+        assert_eq!(paginate(&rows, 50.0), [0..1, 1..2, 2..3]);
+        assert!(paginate(&[], 100.0).is_empty());
+    }
+
+    #[test]
+    fn test_paginate_oversized_row() {
+        let rows = [row(10.0, 0.0), row(500.0, 0.0), row(10.0, 0.0)];
+        assert_eq!(paginate(&rows, 100.0), [0..1, 1..2, 2..3]);
+    }
+
+    #[test]
+    fn test_paginate_keeps_headings_with_text() {
+        let heading = Row {
+            keep_with_next: true,
+            ..row(20.0, 0.0)
+        };
+        let rows = [row(40.0, 0.0), row(30.0, 0.0), heading, row(20.0, 0.0)];
+
+        assert_eq!(paginate(&rows, 100.0), [0..2, 2..4]);
+    }
+
+    #[test]
+    fn test_paginate_splits_carried_rows_that_dont_fit() {
+        let heading = Row {
+            keep_with_next: true,
+            ..row(30.0, 0.0)
+        };
+        let rows = [row(50.0, 0.0), heading, row(80.0, 0.0), row(10.0, 0.0)];
+
+        // The heading and its paragraph don't fit on one page together
+        let pages = paginate(&rows, 100.0);
+        assert_eq!(pages, [0..1, 1..2, 2..4]);
+    }
+
+    #[test]
+    fn test_file_name() {
+        assert_eq!(file_name("Rust: a/b  test?"), "Rust- a-b test-");
+        assert_eq!(file_name("  "), "Article");
+    }
+}
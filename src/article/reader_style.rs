@@ -0,0 +1,281 @@
+pub const DEFAULT_FONT_FAMILY: &str = "serif";
+pub const DEFAULT_FONT_SIZE: u32 = 12;
+pub const DEFAULT_LINE_WIDTH: u32 = 800;
+pub const DEFAULT_LINE_HEIGHT: f64 = 1.5;
+
+/// Font sizes, in points, the reader steps through when zooming.
+pub const FONT_SIZE_STEPS: &[u32] = &[8, 9, 10, 11, 12, 14, 16, 18, 20, 24, 28, 32];
+
+pub const LINE_WIDTH_NARROW: u32 = 600;
+pub const LINE_WIDTH_MEDIUM: u32 = DEFAULT_LINE_WIDTH;
+pub const LINE_WIDTH_WIDE: u32 = 1100;
+
+/// Relative font sizes of the article elements, scaled by the reader font size.
+const FONT_SCALE: &[(&str, f64)] = &[
+    ("article-title", 2.5),
+    ("article-h1", 2.0),
+    ("article-h2", 1.5),
+    ("article-h3", 1.25),
+    ("article-h4", 1.25),
+    ("article-h5", 1.2),
+    ("article-h6", 1.0),
+    ("article-description", 1.1),
+    ("article-text", 1.0),
+    ("article-table-cell", 0.9),
+    ("article-table-caption", 0.9),
+    ("article-figcaption", 0.9),
+    ("article-embed-title", 0.9),
+];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReaderTheme {
+    #[default]
+    System,
+    Light,
+    Sepia,
+    Dark,
+    Black,
+}
+
+impl ReaderTheme {
+    pub fn from_key(key: &str) -> Self {
+        match key {
+            "light" => ReaderTheme::Light,
+            "sepia" => ReaderTheme::Sepia,
+            "dark" => ReaderTheme::Dark,
+            "black" => ReaderTheme::Black,
+            _ => ReaderTheme::System,
+        }
+    }
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            ReaderTheme::System => "system",
+            ReaderTheme::Light => "light",
+            ReaderTheme::Sepia => "sepia",
+            ReaderTheme::Dark => "dark",
+            ReaderTheme::Black => "black",
+        }
+    }
+
+    /// Background and foreground colors, or `None` to follow the system style.
+    fn colors(&self) -> Option<(&'static str, &'static str)> {
+        match self {
+            ReaderTheme::System => None,
+            ReaderTheme::Light => Some(("#ffffff", "#1d1f1c")),
+            ReaderTheme::Sepia => Some(("#f4ecd8", "#5b4636")),
+            ReaderTheme::Dark => Some(("#242424", "rgba(255, 255, 255, 0.9)")),
+            ReaderTheme::Black => Some(("#000000", "#d8d8d8")),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReaderStyle {
+    pub font_family: String,
+    /// Body font size in points
+    pub font_size: u32,
+    /// Maximum width of the text column in pixels
+    pub line_width: u32,
+    /// Line height of the body text, relative to the font size
+    pub line_height: f64,
+    pub theme: ReaderTheme,
+}
+
+impl Default for ReaderStyle {
+    fn default() -> Self {
+        Self {
+            font_family: DEFAULT_FONT_FAMILY.to_string(),
+            font_size: DEFAULT_FONT_SIZE,
+            line_width: DEFAULT_LINE_WIDTH,
+            line_height: DEFAULT_LINE_HEIGHT,
+            theme: ReaderTheme::default(),
+        }
+    }
+}
+
+impl ReaderStyle {
+    /// Builds the stylesheet applied on top of `native_style.css`. Every rule is
+    /// scoped to the `.article-view` root so the rest of the window is untouched.
+    pub fn to_css(&self) -> String {
+        let font_family = css_font_family(&self.font_family);
+        let mut css = String::new();
+
+        for (class, scale) in FONT_SCALE {
+            css.push_str(&format!(
+                ".article-view .{} {{ font-family: {}; font-size: {:.1}pt; }}\n",
+                class,
+                font_family,
+                self.font_size as f64 * scale
+            ));
+        }
+
+        css.push_str(&format!(
+            ".article-view .article-text, .article-view .article-description {{ line-height: {:.2}; }}\n",
+            self.line_height
+        ));
+
+        if let Some((background, foreground)) = self.theme.colors() {
+            css.push_str(&format!(
+                ".article-view, .article-view > viewport {{ background-color: {}; }}\n",
+                background
+            ));
+            css.push_str(&format!(
+                ".article-view label, .article-view text {{ color: {}; }}\n",
+                foreground
+            ));
+        }
+
+        css
+    }
+
+    /// The next larger font size step, or the largest one.
+    pub fn zoomed_in(&self) -> u32 {
+        FONT_SIZE_STEPS
+            .iter()
+            .copied()
+            .find(|size| *size > self.font_size)
+            .unwrap_or(
+                self.font_size
+                    .max(FONT_SIZE_STEPS[FONT_SIZE_STEPS.len() - 1]),
+            )
+    }
+
+    /// The next smaller font size step, or the smallest one.
+    pub fn zoomed_out(&self) -> u32 {
+        FONT_SIZE_STEPS
+            .iter()
+            .rev()
+            .copied()
+            .find(|size| *size < self.font_size)
+            .unwrap_or(self.font_size.min(FONT_SIZE_STEPS[0]))
+    }
+
+    pub fn zoom_percentage(&self) -> u32 {
+        (self.font_size as f64 * 100.0 / DEFAULT_FONT_SIZE as f64).round() as u32
+    }
+}
+
+/// Quotes a font family for CSS, leaving the generic families untouched.
+fn css_font_family(family: &str) -> String {
+    let family = family.trim();
+
+    match family {
+        "" => DEFAULT_FONT_FAMILY.to_string(),
+        "serif" | "sans-serif" | "monospace" | "cursive" | "fantasy" | "system-ui" => {
+            family.to_string()
+        }
+        _ => format!("\"{}\"", family.replace('\\', "\\\\").replace('"', "\\\"")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reader_theme_from_key() {
+        assert_eq!(ReaderTheme::from_key("light"), ReaderTheme::Light);
+        assert_eq!(ReaderTheme::from_key("sepia"), ReaderTheme::Sepia);
+        assert_eq!(ReaderTheme::from_key("dark"), ReaderTheme::Dark);
+        assert_eq!(ReaderTheme::from_key("system"), ReaderTheme::System);
+        assert_eq!(ReaderTheme::from_key("black"), ReaderTheme::Black);
+        assert_eq!(ReaderTheme::from_key("unknown"), ReaderTheme::System);
+
+        for theme in [
+            ReaderTheme::System,
+            ReaderTheme::Light,
+            ReaderTheme::Sepia,
+            ReaderTheme::Dark,
+            ReaderTheme::Black,
+        ] {
+            assert_eq!(ReaderTheme::from_key(theme.key()), theme);
+        }
+    }
+
+    #[test]
+    fn test_zoom_steps() {
+        let style = ReaderStyle::default();
+        assert_eq!(style.zoomed_in(), 14);
+        assert_eq!(style.zoomed_out(), 11);
+        assert_eq!(style.zoom_percentage(), 100);
+
+        // Sizes between two steps snap to the neighbouring ones
+        let style = ReaderStyle {
+            font_size: 13,
+            ..Default::default()
+        };
+        assert_eq!(style.zoomed_in(), 14);
+        assert_eq!(style.zoomed_out(), 12);
+
+        // Both ends of the range are sticky
+        let largest = ReaderStyle {
+            font_size: 32,
+            ..Default::default()
+        };
+        assert_eq!(largest.zoomed_in(), 32);
+        let smallest = ReaderStyle {
+            font_size: 8,
+            ..Default::default()
+        };
+        assert_eq!(smallest.zoomed_out(), 8);
+        assert_eq!(smallest.zoom_percentage(), 67);
+    }
+
+    #[test]
+    fn test_css_line_height() {
+        let style = ReaderStyle {
+            line_height: 1.8,
+            ..Default::default()
+        };
+
+        assert!(style.to_css().contains(".article-view .article-text, .article-view .article-description { line-height: 1.80; }"));
+    }
+
+    #[test]
+    fn test_css_scales_font_size() {
+        let style = ReaderStyle {
+            font_size: 10,
+            ..Default::default()
+        };
+        let css = style.to_css();
+
+        assert!(
+            css.contains(".article-view .article-text { font-family: serif; font-size: 10.0pt; }")
+        );
+        assert!(
+            css.contains(".article-view .article-h1 { font-family: serif; font-size: 20.0pt; }")
+        );
+        assert!(
+            css.contains(".article-view .article-title { font-family: serif; font-size: 25.0pt; }")
+        );
+    }
+
+    #[test]
+    fn test_css_quotes_font_family() {
+        let style = ReaderStyle {
+            font_family: "Noto \"Serif\"".to_string(),
+            ..Default::default()
+        };
+
+        assert!(style
+            .to_css()
+            .contains("font-family: \"Noto \\\"Serif\\\"\";"));
+        assert_eq!(css_font_family("sans-serif"), "sans-serif");
+        assert_eq!(css_font_family("  "), "serif");
+    }
+
+    #[test]
+    fn test_css_theme_colors() {
+        let system = ReaderStyle::default().to_css();
+        assert!(!system.contains("background-color"));
+
+        let sepia = ReaderStyle {
+            theme: ReaderTheme::Sepia,
+            ..Default::default()
+        }
+        .to_css();
+        assert!(sepia.contains("background-color: #f4ecd8;"));
+        assert!(sepia.contains("color: #5b4636;"));
+    }
+}
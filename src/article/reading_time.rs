@@ -0,0 +1,150 @@
+use gettextrs::{gettext, ngettext};
+use scraper::{ElementRef, Html, Node};
+
+pub const DEFAULT_WORDS_PER_MINUTE: u32 = 200;
+
+/// Counts the words in a plain text string.
+///
+/// Runs of alphanumeric characters separated by whitespace count as one word,
+/// while every Han, Hiragana and Katakana character counts as a word on its own,
+/// since those scripts are not separated by spaces.
+pub fn count_words(text: &str) -> usize {
+    let mut count = 0;
+    let mut in_word = false;
+
+    for c in text.chars() {
+        if is_cjk(c) {
+            count += 1;
+            in_word = false;
+        } else if c.is_whitespace() {
+            in_word = false;
+        } else if c.is_alphanumeric() && !in_word {
+            count += 1;
+            in_word = true;
+        }
+    }
+
+    count
+}
+
+/// Counts the words in the visible text of an HTML document, skipping
+/// scripts, styles and other non-rendered elements.
+pub fn count_html_words(html: &str) -> usize {
+    let document = Html::parse_document(html);
+    let mut text = String::new();
+    collect_visible_text(document.root_element(), &mut text);
+    count_words(&text)
+}
+
+pub fn reading_minutes(word_count: usize, words_per_minute: u32) -> usize {
+    let words_per_minute = words_per_minute.max(1) as f32;
+    (word_count as f32 / words_per_minute).ceil() as usize
+}
+
+pub fn format_reading_time(word_count: usize, words_per_minute: u32) -> String {
+    let minutes = reading_minutes(word_count, words_per_minute);
+
+    if minutes < 1 {
+        gettext("< 1 min read")
+    } else {
+        ngettext("{} min read", "{} min read", minutes as u32).replace("{}", &minutes.to_string())
+    }
+}
+
+pub fn format_word_count(word_count: usize) -> String {
+    ngettext("{} word", "{} words", word_count as u32).replace("{}", &word_count.to_string())
+}
+
+fn collect_visible_text(element: ElementRef, text: &mut String) {
+    for child in element.children() {
+        match child.value() {
+            Node::Text(t) => {
+                text.push_str(t);
+                text.push(' ');
+            }
+            Node::Element(e) => {
+                if matches!(
+                    e.name(),
+                    "script" | "style" | "noscript" | "template" | "head"
+                ) {
+                    continue;
+                }
+                if let Some(child_element) = ElementRef::wrap(child) {
+                    collect_visible_text(child_element, text);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{309F}' // Hiragana
+        | '\u{30A0}'..='\u{30FF}' // Katakana
+        | '\u{3400}'..='\u{4DBF}' // CJK Unified Ideographs Extension A
+        | '\u{4E00}'..='\u{9FFF}' // CJK Unified Ideographs
+        | '\u{F900}'..='\u{FAFF}' // CJK Compatibility Ideographs
+        | '\u{20000}'..='\u{2FA1F}' // CJK Unified Ideographs Extension B and beyond
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_words_latin() {
+        assert_eq!(count_words(""), 0);
+        assert_eq!(count_words("   "), 0);
+        assert_eq!(count_words("Hello world"), 2);
+        assert_eq!(count_words("  don't   stop\nbelieving\t"), 3);
+        assert_eq!(count_words("one — two"), 2);
+    }
+
+    #[test]
+    fn test_count_words_cjk() {
+        // Every ideograph counts as a word
+        assert_eq!(count_words("日本語"), 3);
+        assert_eq!(count_words("ひらがなカタカナ"), 8);
+        // Mixed scripts
+        assert_eq!(count_words("Rust は 速い"), 4);
+    }
+
+    #[test]
+    fn test_count_html_words_skips_non_visible_elements() {
+        let html = r#"
+            <html>
+              <head><title>Ignored title</title><style>p { color: red; }</style></head>
+              <body>
+                <h1>A heading</h1>
+                <p>Some <strong>bold</strong> text.</p>
+                <script>var ignored = "words";</script>
+              </body>
+            </html>
+        "#;
+
+        assert_eq!(count_html_words(html), 5);
+    }
+
+    #[test]
+    fn test_format_reading_time() {
+        assert_eq!(format_reading_time(0, 200), "< 1 min read");
+        assert_eq!(format_reading_time(1, 200), "1 min read");
+        assert_eq!(format_reading_time(200, 200), "1 min read");
+        assert_eq!(format_reading_time(201, 200), "2 min read");
+        assert_eq!(format_reading_time(1000, 250), "4 min read");
+    }
+
+    #[test]
+    fn test_reading_minutes_with_zero_words_per_minute() {
+        // A zero setting must not divide by zero
+        assert_eq!(reading_minutes(10, 0), 10);
+    }
+
+    #[test]
+    fn test_format_word_count() {
+        assert_eq!(format_word_count(1), "1 word");
+        assert_eq!(format_word_count(42), "42 words");
+    }
+}
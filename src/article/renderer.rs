@@ -1,15 +1,111 @@
+use ego_tree::NodeId;
 use gtk::prelude::*;
 use html_escape::encode_text;
-use relm4::{gtk, ComponentParts, ComponentSender, SimpleComponent};
+use relm4::{adw, gtk, Component, ComponentParts, ComponentSender, SimpleComponent};
 use scraper::{ElementRef, Html, Node, Selector};
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::rc::Rc;
+use url::Url;
 
 use gettextrs::{gettext, ngettext};
 
+use super::embeds::{self, EmbedKind};
+use super::find;
+use super::footnotes;
+use super::highlight::{self, Language, TokenKind};
+use super::images;
+use super::lightbox::{ImageLightbox, LightboxImage, LightboxInit};
+use super::math::{self, Segment};
+use super::outline::{self, OutlineEntry};
+use super::print::{self, PrintBlock, TextStyle};
+use super::reader_style::ReaderStyle;
+use super::reading_time::{self, DEFAULT_WORDS_PER_MINUTE};
+use super::speech;
+use super::table;
+use crate::network;
+use crate::preferences::{ImageLoading, TranslationLayout};
+
 pub struct ArticleRenderer {
     content_box: gtk::Box,
     title_label: gtk::Label,
     metadata_box: gtk::Box,
+    clamp: adw::Clamp,
     vadjustment: gtk::Adjustment,
+    metadata: Option<ArticleMetadata>,
+    words_per_minute: u32,
+    style: ReaderStyle,
+    style_provider: gtk::CssProvider,
+    image_loading: ImageLoading,
+    /// Image cache budget, in megabytes
+    image_cache_size: u32,
+    /// URL relative image sources are resolved against
+    base_url: Option<Url>,
+    /// Every image of the article in reading order, for the lightbox
+    gallery: Rc<RefCell<Vec<LightboxImage>>>,
+    anchors: Anchors,
+    /// Elements of the current document laid out as blocks
+    blocks: HashSet<NodeId>,
+    find: FindState,
+    /// Headings in reading order, for the table of contents
+    headings: Rc<RefCell<Vec<(u8, gtk::Label)>>>,
+    /// Index of the heading whose section is at the top of the view
+    current_heading: Rc<Cell<Option<usize>>>,
+    /// Paragraphs being read aloud, and the one currently spoken
+    spoken: Vec<gtk::Label>,
+    speaking: Option<gtk::Label>,
+    /// Paragraphs sent for translation, in the order of `TranslationText`
+    translatable: Vec<gtk::Label>,
+    /// Paragraphs showing a translation, to put the original back
+    translated: Vec<TranslatedParagraph>,
+    output: relm4::Sender<ArticleRendererOutput>,
+}
+
+/// Matches of the find bar query, in reading order.
+#[derive(Default)]
+struct FindState {
+    query: String,
+    matches: Vec<FindMatch>,
+    /// Index of the match last scrolled to
+    current: usize,
+}
+
+struct FindMatch {
+    widget: FindTarget,
+    /// Byte range in the text of the widget
+    range: Range<usize>,
+}
+
+#[derive(Clone, PartialEq)]
+enum FindTarget {
+    Label(gtk::Label),
+    Code(gtk::TextView),
+}
+
+struct TranslatedParagraph {
+    label: gtk::Label,
+    /// Markup of the original text
+    markup: String,
+    /// Row holding the original next to its translation, when shown side
+    /// by side
+    row: Option<gtk::Box>,
+}
+
+/// Targets of in-page links, filled in as the article is laid out.
+#[derive(Clone, Default)]
+struct Anchors {
+    /// The widget showing each element id
+    widgets: Rc<RefCell<HashMap<String, gtk::Widget>>>,
+    /// Footnote text by id
+    footnotes: Rc<RefCell<HashMap<String, String>>>,
+}
+
+struct ArticleMetadata {
+    url: String,
+    description: String,
+    time: f64,
+    word_count: Option<usize>,
 }
 
 #[derive(Debug)]
@@ -20,13 +116,56 @@ pub enum ArticleRendererInput {
         url: String,
         description: String,
         time: f64,
+        word_count: Option<usize>,
+    },
+    SetWordCount(usize),
+    SetWordsPerMinute(u32),
+    SetStyle(ReaderStyle),
+    SetImageLoading(ImageLoading),
+    SetImageCacheSize(u32),
+    Find(String),
+    FindNext,
+    FindPrevious,
+    ClearFind,
+    ScrollToHeading(usize),
+    /// Collect the text to read aloud, reported as `ListenText`
+    StartListening,
+    HighlightParagraph(Option<usize>),
+    /// Collect the text to translate, reported as `TranslationText`
+    StartTranslation,
+    /// Show translated paragraphs, in the order of `TranslationText`
+    ShowTranslation {
+        paragraphs: Vec<String>,
+        layout: TranslationLayout,
     },
+    ShowOriginal,
+    /// Open the print dialog, which also saves PDFs
+    Print,
+}
+
+#[derive(Debug, Clone)]
+pub enum ArticleRendererOutput {
+    /// Save a link from the article to Instapaper
+    SaveLink(String),
+    /// The link under the pointer changed, `None` once it leaves
+    LinkHovered(Option<String>),
+    /// Where the find bar is, as the 1-based current match and the total
+    FindResult { current: usize, total: usize },
+    /// The headings of a newly rendered article
+    Outline(Vec<OutlineEntry>),
+    /// The heading whose section the reader scrolled to
+    CurrentHeading(Option<usize>),
+    /// The paragraphs of the article to read aloud, in reading order
+    ListenText(Vec<String>),
+    /// The paragraphs of the article to translate, in reading order
+    TranslationText(Vec<String>),
+    PrintFailed(String),
 }
 
 impl SimpleComponent for ArticleRenderer {
     type Init = ();
     type Input = ArticleRendererInput;
-    type Output = ();
+    type Output = ArticleRendererOutput;
     type Root = gtk::ScrolledWindow;
     type Widgets = ArticleRendererWidgets;
 
@@ -36,19 +175,18 @@ impl SimpleComponent for ArticleRenderer {
             .vscrollbar_policy(gtk::PolicyType::Automatic)
             .propagate_natural_height(true)
             .hexpand(true)
+            .css_classes(["article-view"])
             .build()
     }
 
     fn init(
         _init: Self::Init,
         root: Self::Root,
-        _sender: ComponentSender<Self>,
+        sender: ComponentSender<Self>,
     ) -> ComponentParts<Self> {
         let main_box = gtk::Box::builder()
             .orientation(gtk::Orientation::Vertical)
             .spacing(0)
-            .margin_start(48)
-            .margin_end(48)
             .margin_top(16)
             .margin_bottom(16)
             .build();
@@ -79,18 +217,82 @@ impl SimpleComponent for ArticleRenderer {
         main_box.append(&metadata_box);
         main_box.append(&content_box);
 
-        root.set_child(Some(&main_box));
+        let style = ReaderStyle::default();
+
+        let clamp = adw::Clamp::builder()
+            .maximum_size(style.line_width as i32)
+            .margin_start(48)
+            .margin_end(48)
+            .child(&main_box)
+            .build();
+
+        root.set_child(Some(&clamp));
 
         Self::load_css();
 
+        let style_provider = gtk::CssProvider::new();
+        if let Some(display) = gtk::gdk::Display::default() {
+            // Above the bundled stylesheet, so reader preferences always win
+            gtk::style_context_add_provider_for_display(
+                &display,
+                &style_provider,
+                gtk::STYLE_PROVIDER_PRIORITY_APPLICATION + 1,
+            );
+        }
+
         let vadjustment = root.vadjustment();
+        let headings: Rc<RefCell<Vec<(u8, gtk::Label)>>> = Rc::default();
+        let current_heading: Rc<Cell<Option<usize>>> = Rc::default();
+        vadjustment.connect_value_changed({
+            let headings = headings.clone();
+            let current_heading = current_heading.clone();
+            let clamp = clamp.clone();
+            let output = sender.output_sender().clone();
+            move |vadjustment| {
+                let offsets: Vec<f32> = headings
+                    .borrow()
+                    .iter()
+                    .filter_map(|(_, label)| {
+                        label.compute_point(&clamp, &gtk::graphene::Point::new(0.0, 0.0))
+                    })
+                    .map(|point| point.y())
+                    .collect();
+                // A heading just below the top edge already counts as read
+                let position = vadjustment.value() as f32 + 48.0;
+                let current = outline::current_section(&offsets, position);
+                if current_heading.replace(current) != current {
+                    output.emit(ArticleRendererOutput::CurrentHeading(current));
+                }
+            }
+        });
 
         let model = Self {
             content_box,
             title_label,
             metadata_box,
+            clamp,
             vadjustment,
+            metadata: None,
+            words_per_minute: DEFAULT_WORDS_PER_MINUTE,
+            style,
+            style_provider,
+            // The app sends the user preference right away
+            image_loading: ImageLoading::Never,
+            image_cache_size: 0,
+            base_url: None,
+            gallery: Rc::default(),
+            anchors: Anchors::default(),
+            blocks: HashSet::new(),
+            find: FindState::default(),
+            headings,
+            current_heading,
+            spoken: Vec::new(),
+            speaking: None,
+            translatable: Vec::new(),
+            translated: Vec::new(),
+            output: sender.output_sender().clone(),
         };
+        model.apply_style();
         let widgets = ArticleRendererWidgets {};
 
         ComponentParts { model, widgets }
@@ -105,13 +307,118 @@ impl SimpleComponent for ArticleRenderer {
             ArticleRendererInput::SetContent(html) => {
                 self.render_html(&html);
                 self.vadjustment.set_value(0.0);
+                // Keep finding the same text in the next article
+                if !self.find.query.is_empty() {
+                    self.find(self.find.query.clone());
+                }
             }
             ArticleRendererInput::SetMetadata {
                 url,
                 description,
                 time,
+                word_count,
             } => {
-                self.render_metadata(&url, &description, time);
+                self.base_url = Url::parse(&url).ok();
+                self.metadata = Some(ArticleMetadata {
+                    url,
+                    description,
+                    time,
+                    word_count,
+                });
+                self.render_metadata();
+            }
+            ArticleRendererInput::SetWordCount(word_count) => {
+                if let Some(metadata) = self.metadata.as_mut() {
+                    metadata.word_count = Some(word_count);
+                }
+                self.render_metadata();
+            }
+            ArticleRendererInput::SetWordsPerMinute(words_per_minute) => {
+                self.words_per_minute = words_per_minute;
+                self.render_metadata();
+            }
+            ArticleRendererInput::SetStyle(style) => {
+                self.style = style;
+                self.apply_style();
+            }
+            ArticleRendererInput::SetImageLoading(image_loading) => {
+                self.image_loading = image_loading;
+            }
+            ArticleRendererInput::SetImageCacheSize(image_cache_size) => {
+                self.image_cache_size = image_cache_size;
+            }
+            ArticleRendererInput::Find(query) => self.find(query),
+            ArticleRendererInput::FindNext => self.find_step(true),
+            ArticleRendererInput::FindPrevious => self.find_step(false),
+            ArticleRendererInput::ClearFind => {
+                self.clear_find_highlights();
+                self.find = FindState::default();
+            }
+            ArticleRendererInput::StartListening => {
+                let (labels, paragraphs) = self.text_paragraphs();
+                self.spoken = labels;
+                self.output
+                    .emit(ArticleRendererOutput::ListenText(paragraphs));
+            }
+            ArticleRendererInput::HighlightParagraph(index) => {
+                if let Some(label) = self.speaking.take() {
+                    label.remove_css_class("article-speaking");
+                }
+
+                let Some(label) = index.and_then(|index| self.spoken.get(index)) else {
+                    return;
+                };
+                label.add_css_class("article-speaking");
+                Self::scroll_into_view(label.upcast_ref(), 0.0, &self.clamp, &self.vadjustment);
+                self.speaking = Some(label.clone());
+            }
+            ArticleRendererInput::StartTranslation => {
+                self.show_original();
+                let (labels, paragraphs) = self.text_paragraphs();
+                self.translatable = labels;
+                self.output
+                    .emit(ArticleRendererOutput::TranslationText(paragraphs));
+            }
+            ArticleRendererInput::ShowTranslation { paragraphs, layout } => {
+                self.show_original();
+                let labels = std::mem::take(&mut self.translatable);
+                for (label, text) in labels.into_iter().zip(paragraphs) {
+                    self.show_translated_paragraph(label, &text, layout);
+                }
+                self.refresh_find();
+            }
+            ArticleRendererInput::ShowOriginal => {
+                self.show_original();
+                self.refresh_find();
+            }
+            ArticleRendererInput::Print => {
+                let blocks = self.print_blocks();
+                if blocks.is_empty() {
+                    return;
+                }
+                let window = self.clamp.root().and_downcast::<gtk::Window>();
+                if let Err(e) = print::print(
+                    &self.title_label.text(),
+                    &self.style.font_family,
+                    blocks,
+                    window.as_ref(),
+                ) {
+                    self.output.emit(ArticleRendererOutput::PrintFailed(format!(
+                        "{}: {}",
+                        gettext("Failed to print the article"),
+                        e
+                    )));
+                }
+            }
+            ArticleRendererInput::ScrollToHeading(index) => {
+                let heading = self
+                    .headings
+                    .borrow()
+                    .get(index)
+                    .map(|(_, label)| label.clone());
+                if let Some(heading) = heading {
+                    Self::scroll_to(heading.upcast_ref(), &self.clamp, &self.vadjustment);
+                }
             }
         }
     }
@@ -164,14 +471,23 @@ impl ArticleRenderer {
         }
     }
 
-    fn render_metadata(&self, url: &str, description: &str, time: f64) {
+    fn apply_style(&self) {
+        self.style_provider.load_from_string(&self.style.to_css());
+        self.clamp.set_maximum_size(self.style.line_width as i32);
+    }
+
+    fn render_metadata(&self) {
+        let Some(metadata) = self.metadata.as_ref() else {
+            return;
+        };
+
         // Clear existing metadata
         while let Some(child) = self.metadata_box.first_child() {
             self.metadata_box.remove(&child);
         }
 
         // Extract domain from URL
-        let domain = Self::extract_domain(url);
+        let domain = Self::extract_domain(&metadata.url);
 
         // Create metadata row with domain and date
         let metadata_row = gtk::Box::builder()
@@ -194,19 +510,37 @@ impl ArticleRenderer {
         }
 
         let date_label = gtk::Label::builder()
-            .label(&Self::format_date(time))
+            .label(&Self::format_date(metadata.time))
             .xalign(0.0)
             .selectable(true)
             .build();
         date_label.add_css_class("article-date");
         metadata_row.append(&date_label);
 
+        if let Some(word_count) = metadata.word_count {
+            let separator = gtk::Label::builder().label("·").build();
+            separator.add_css_class("article-metadata-separator");
+            metadata_row.append(&separator);
+
+            let reading_time_label = gtk::Label::builder()
+                .label(format!(
+                    "{} · {}",
+                    reading_time::format_word_count(word_count),
+                    reading_time::format_reading_time(word_count, self.words_per_minute)
+                ))
+                .xalign(0.0)
+                .selectable(true)
+                .build();
+            reading_time_label.add_css_class("article-reading-time");
+            metadata_row.append(&reading_time_label);
+        }
+
         self.metadata_box.append(&metadata_row);
 
         // Add description if available
-        if !description.is_empty() {
+        if !metadata.description.is_empty() {
             let desc_label = gtk::Label::builder()
-                .label(description)
+                .label(&metadata.description)
                 .wrap(true)
                 .xalign(0.0)
                 .selectable(true)
@@ -265,10 +599,14 @@ impl ArticleRenderer {
         }
     }
 
-    fn render_html(&self, html: &str) {
+    fn render_html(&mut self, html: &str) {
         while let Some(child) = self.content_box.first_child() {
             self.content_box.remove(&child);
         }
+        // Downloads still running for the previous article check the URL
+        // before filling in their entry
+        self.gallery = Rc::default();
+        self.anchors = Anchors::default();
 
         eprintln!(
             "Rendering HTML (first 500 chars): {}",
@@ -277,8 +615,44 @@ impl ArticleRenderer {
 
         let document = Html::parse_document(html);
 
+        // Honour <base href>, itself relative to the article URL
+        let base_selector = Selector::parse("base[href]").unwrap();
+        if let Some(href) = document
+            .select(&base_selector)
+            .next()
+            .and_then(|base| base.value().attr("href"))
+        {
+            let base_url = match &self.base_url {
+                Some(url) => url.join(href).ok(),
+                None => Url::parse(href).ok(),
+            };
+            if base_url.is_some() {
+                self.base_url = base_url;
+            }
+        }
+
+        *self.anchors.footnotes.borrow_mut() = footnotes::collect(&document);
+
+        self.headings.borrow_mut().clear();
+        self.current_heading.set(None);
+        self.spoken.clear();
+        self.speaking = None;
+        self.translatable.clear();
+        self.translated.clear();
+        self.blocks = block_nodes(&document);
         self.process_elements(&document);
 
+        let outline = self
+            .headings
+            .borrow()
+            .iter()
+            .map(|(level, label)| OutlineEntry {
+                level: *level,
+                title: collapse_whitespace(&label.text()).trim().to_string(),
+            })
+            .collect();
+        self.output.emit(ArticleRendererOutput::Outline(outline));
+
         let child_count = self.content_box.observe_children().n_items();
         eprintln!("Content box now has {} children", child_count);
 
@@ -292,37 +666,450 @@ impl ArticleRenderer {
     }
 
     fn process_elements(&self, document: &Html) {
-        let body_selector = Selector::parse("body > *").unwrap();
-        let mut found_elements = false;
+        self.append_blocks(document.root_element(), &self.content_box);
+    }
+
+    /// Lays out the children of `element` into `container`. Runs of text and
+    /// inline elements between blocks are gathered into paragraphs, so text
+    /// sitting directly in a `div` or `section` isn't lost.
+    fn append_blocks(&self, element: ElementRef, container: &gtk::Box) {
+        let mut inline = String::new();
+        // Elements of the pending paragraph, annotated once it's a label
+        let mut inline_elements = Vec::new();
+
+        for child in element.children() {
+            match child.value() {
+                Node::Text(text) => inline.push_str(&text_markup(text)),
+                Node::Element(_) => {
+                    let Some(child_element) = ElementRef::wrap(child) else {
+                        continue;
+                    };
+                    if is_skipped(child_element) {
+                        continue;
+                    }
+
+                    if self.blocks.contains(&child_element.id()) {
+                        self.flush_inline(&mut inline, &mut inline_elements, container);
+                        self.append_block(child_element, container);
+                    } else {
+                        inline.push_str(&self.inline_markup(child_element));
+                        inline_elements.push(child_element);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        self.flush_inline(&mut inline, &mut inline_elements, container);
+    }
+
+    fn append_block(&self, element: ElementRef, container: &gtk::Box) {
+        match self.element_to_widget(element) {
+            Some(widget) => {
+                self.register_anchors(element, &widget);
+                if let Some(label) = widget.downcast_ref::<gtk::Label>() {
+                    set_abbreviation_tooltip(label, &[element]);
+                }
+                container.append(&widget);
+            }
+            // Frames hold raw text and media fallback content for browsers
+            // without support, neither is part of the article
+            None if matches!(element.value().name(), "audio" | "iframe" | "video") => {}
+            // A label can't hold the images or paragraphs of a link, so the
+            // link follows them
+            None if element.value().name() == "a" => {
+                self.append_blocks(element, container);
+                if let Some(href) = element.value().attr("href") {
+                    let label = self.create_text_label(&format!(
+                        "<a href=\"{}\">{}</a>",
+                        encode_text(&self.resolve_href(href)),
+                        encode_text(&gettext("Open link"))
+                    ));
+                    label.add_css_class("article-block-link");
+                    container.append(&label);
+                }
+            }
+            // Containers and elements we have no widget for are flattened
+            None => self.append_blocks(element, container),
+        }
+    }
+
+    fn has_block_children(&self, element: ElementRef) -> bool {
+        element
+            .children()
+            .filter_map(ElementRef::wrap)
+            .any(|child| self.blocks.contains(&child.id()))
+    }
+
+    /// Resolves a link against the article URL. In-page links stay as they
+    /// are, and so do those that can't be resolved.
+    fn resolve_href(&self, href: &str) -> String {
+        match href.starts_with('#') {
+            true => None,
+            false => images::resolve_url(href, self.base_url.as_ref()),
+        }
+        .unwrap_or_else(|| href.to_string())
+    }
+
+    fn flush_inline(
+        &self,
+        inline: &mut String,
+        elements: &mut Vec<ElementRef>,
+        container: &gtk::Box,
+    ) {
+        let text = inline.trim();
+        if !text.is_empty() {
+            let label = self.create_text_label(text);
+            for element in elements.iter() {
+                self.register_anchors(*element, label.upcast_ref());
+            }
+            set_abbreviation_tooltip(&label, elements);
+            container.append(&label);
+        }
+        inline.clear();
+        elements.clear();
+    }
+
+    /// Records `widget` as the place to scroll to for the ids in `element`.
+    /// Nested blocks are registered first and keep their own, closer widget.
+    fn register_anchors(&self, element: ElementRef, widget: &gtk::Widget) {
+        let mut widgets = self.anchors.widgets.borrow_mut();
+        for id in element
+            .descendants()
+            .filter_map(ElementRef::wrap)
+            .filter_map(|e| e.value().attr("id"))
+        {
+            widgets
+                .entry(id.to_string())
+                .or_insert_with(|| widget.clone());
+        }
+    }
+
+    /// Keeps links inside the reader: footnotes pop over the text, other
+    /// parts of the article are scrolled to and web links get a menu to open,
+    /// save or copy them. The link under the pointer goes to the status bar.
+    fn connect_links(&self, label: &gtk::Label) {
+        let anchors = self.anchors.clone();
+        let page_url = self
+            .metadata
+            .as_ref()
+            .and_then(|metadata| Url::parse(&metadata.url).ok());
+        let clamp = self.clamp.clone();
+        let vadjustment = self.vadjustment.clone();
+        let output = self.output.clone();
+
+        // Where the link was clicked, so its menu points there
+        let pointer = Rc::new(Cell::new((0.0, 0.0)));
+        let click = gtk::GestureClick::new();
+        let pointer_clone = pointer.clone();
+        click.connect_pressed(move |_, _, x, y| pointer_clone.set((x, y)));
+        label.add_controller(click);
+
+        let hovered: Rc<RefCell<Option<String>>> = Rc::default();
+        let motion = gtk::EventControllerMotion::new();
+        let hovered_clone = hovered.clone();
+        let output_clone = output.clone();
+        motion.connect_motion(move |controller, _, _| {
+            let Some(label) = controller.widget().and_downcast::<gtk::Label>() else {
+                return;
+            };
+            let uri = label.current_uri().map(|uri| uri.to_string());
+            if *hovered_clone.borrow() != uri {
+                hovered_clone.replace(uri.clone());
+                output_clone.emit(ArticleRendererOutput::LinkHovered(uri));
+            }
+        });
+        let output_clone = output.clone();
+        motion.connect_leave(move |_| {
+            if hovered.take().is_some() {
+                output_clone.emit(ArticleRendererOutput::LinkHovered(None));
+            }
+        });
+        label.add_controller(motion);
+
+        label.connect_activate_link(move |label, uri| {
+            let Some(id) = footnotes::fragment_target(uri, page_url.as_ref()) else {
+                if !is_web_link(uri) {
+                    // mailto: and friends go to their default handler
+                    return gtk::glib::Propagation::Proceed;
+                }
+                Self::show_link_menu(label, uri, pointer.get(), &output);
+                return gtk::glib::Propagation::Stop;
+            };
+
+            let target = anchors.widgets.borrow().get(&id).cloned();
+            let note = anchors.footnotes.borrow().get(&id).cloned();
+
+            match (note, target) {
+                (Some(note), target) => {
+                    Self::show_footnote(label, &note, target, &clamp, &vadjustment);
+                }
+                (None, Some(target)) => Self::scroll_to(&target, &clamp, &vadjustment),
+                // Nothing in the article has that id, and no page to open either
+                (None, None) => {}
+            }
+
+            gtk::glib::Propagation::Stop
+        });
+    }
 
-        for element in document.select(&body_selector) {
-            if let Some(widget) = self.element_to_widget(element) {
-                self.content_box.append(&widget);
-                found_elements = true;
+    fn show_link_menu(
+        label: &gtk::Label,
+        uri: &str,
+        (x, y): (f64, f64),
+        output: &relm4::Sender<ArticleRendererOutput>,
+    ) {
+        let menu = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .build();
+
+        let popover = gtk::Popover::builder()
+            .child(&menu)
+            .position(gtk::PositionType::Bottom)
+            .build();
+        popover.add_css_class("menu");
+        popover.add_css_class("article-link-popover");
+        popover.set_pointing_to(Some(&gtk::gdk::Rectangle::new(x as i32, y as i32, 1, 1)));
+
+        let add_item = |title: String, action: Box<dyn Fn()>| {
+            let button = gtk::Button::builder().label(title).build();
+            button.add_css_class("flat");
+            if let Some(label) = button.child().and_downcast::<gtk::Label>() {
+                label.set_xalign(0.0);
             }
+
+            let popover = popover.clone();
+            button.connect_clicked(move |_| {
+                popover.popdown();
+                action();
+            });
+            menu.append(&button);
+        };
+
+        let url = uri.to_string();
+        add_item(
+            gettext("Open in Browser"),
+            Box::new(move || {
+                if let Err(e) = open::that(&url) {
+                    eprintln!("Failed to open {}: {}", url, e);
+                }
+            }),
+        );
+        let url = uri.to_string();
+        let output = output.clone();
+        add_item(
+            gettext("Save to Instapaper"),
+            Box::new(move || output.emit(ArticleRendererOutput::SaveLink(url.clone()))),
+        );
+        let url = uri.to_string();
+        add_item(
+            gettext("Copy Link"),
+            Box::new(move || {
+                crate::persistence::clipboard::copy(&url);
+            }),
+        );
+
+        popover.set_parent(label);
+        popover.connect_closed(|popover| popover.unparent());
+        popover.popup();
+    }
+
+    fn show_footnote(
+        label: &gtk::Label,
+        note: &str,
+        target: Option<gtk::Widget>,
+        clamp: &adw::Clamp,
+        vadjustment: &gtk::Adjustment,
+    ) {
+        let content = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(8)
+            .margin_top(6)
+            .margin_bottom(6)
+            .margin_start(6)
+            .margin_end(6)
+            .build();
+
+        let text = gtk::Label::builder()
+            .label(note)
+            .wrap(true)
+            .max_width_chars(50)
+            .xalign(0.0)
+            .selectable(true)
+            .build();
+        text.add_css_class("article-footnote-text");
+        content.append(&text);
+
+        let popover = gtk::Popover::builder()
+            .child(&content)
+            .position(gtk::PositionType::Top)
+            .build();
+        popover.add_css_class("article-footnote-popover");
+
+        if let Some(target) = target {
+            let button = gtk::Button::builder()
+                .label(gettext("Go to Footnote"))
+                .halign(gtk::Align::End)
+                .build();
+            button.add_css_class("flat");
+
+            let popover_clone = popover.clone();
+            let clamp = clamp.clone();
+            let vadjustment = vadjustment.clone();
+            button.connect_clicked(move |_| {
+                popover_clone.popdown();
+                Self::scroll_to(&target, &clamp, &vadjustment);
+            });
+            content.append(&button);
+        }
+
+        popover.set_parent(label);
+        popover.connect_closed(|popover| popover.unparent());
+        popover.popup();
+    }
+
+    fn scroll_to(target: &gtk::Widget, clamp: &adw::Clamp, vadjustment: &gtk::Adjustment) {
+        if let Some(point) = target.compute_point(clamp, &gtk::graphene::Point::new(0.0, 0.0)) {
+            // Leave a little room above so the target isn't glued to the edge
+            vadjustment.set_value((point.y() as f64 - 24.0).max(0.0));
+        }
+    }
+
+    fn find(&mut self, query: String) {
+        self.clear_find_highlights();
+
+        let mut targets = Vec::new();
+        if let Some(main_box) = self.clamp.child() {
+            collect_find_targets(&main_box, &mut targets);
+        }
+
+        let matches = targets
+            .into_iter()
+            .flat_map(|widget| {
+                find::find_matches(&widget.text(), &query)
+                    .into_iter()
+                    .map(move |range| FindMatch {
+                        widget: widget.clone(),
+                        range,
+                    })
+            })
+            .collect();
+
+        self.find = FindState {
+            query,
+            matches,
+            current: 0,
+        };
+        self.show_current_match();
+    }
+
+    fn find_step(&mut self, forward: bool) {
+        let total = self.find.matches.len();
+        if total == 0 {
+            return;
         }
 
-        if !found_elements {
-            let root_selector = Selector::parse("html > *").unwrap();
-            for element in document.select(&root_selector) {
-                if let Some(widget) = self.element_to_widget(element) {
-                    self.content_box.append(&widget);
-                    found_elements = true;
+        self.find.current = match forward {
+            true => (self.find.current + 1) % total,
+            false => (self.find.current + total - 1) % total,
+        };
+        self.show_current_match();
+    }
+
+    /// Highlights every match, the current one brighter, brings the current
+    /// one into view and reports where the find bar is.
+    fn show_current_match(&self) {
+        self.clear_find_highlights();
+
+        let mut label_attributes: Vec<(gtk::Label, gtk::pango::AttrList)> = Vec::new();
+
+        for (index, found) in self.find.matches.iter().enumerate() {
+            let is_current = index == self.find.current;
+
+            match &found.widget {
+                FindTarget::Label(label) => {
+                    let attributes = match label_attributes.iter().find(|(l, _)| l == label) {
+                        Some((_, attributes)) => attributes.clone(),
+                        None => {
+                            let attributes = gtk::pango::AttrList::new();
+                            label_attributes.push((label.clone(), attributes.clone()));
+                            attributes
+                        }
+                    };
+                    let color = if is_current {
+                        FIND_CURRENT_COLOR
+                    } else {
+                        MARK_COLOR
+                    };
+                    for attribute in highlight_attributes(color, &found.range) {
+                        attributes.insert(attribute);
+                    }
                 }
+                FindTarget::Code(view) => {
+                    let buffer = view.buffer();
+                    let (match_tag, current_tag) = find_tags(&buffer);
+                    let text = found.widget.text();
+                    let char_offset = |index: usize| text[..index].chars().count() as i32;
+                    buffer.apply_tag(
+                        if is_current { &current_tag } else { &match_tag },
+                        &buffer.iter_at_offset(char_offset(found.range.start)),
+                        &buffer.iter_at_offset(char_offset(found.range.end)),
+                    );
+                }
+            }
+        }
+
+        for (label, attributes) in label_attributes {
+            label.set_attributes(Some(&attributes));
+        }
+
+        if let Some(found) = self.find.matches.get(self.find.current) {
+            if let Some(y) = found.widget.offset_of(found.range.start) {
+                Self::scroll_into_view(found.widget.widget(), y, &self.clamp, &self.vadjustment);
             }
         }
 
-        if !found_elements {
-            let all_selector =
-                Selector::parse("p, h1, h2, h3, h4, h5, h6, pre, blockquote, ul, ol, img").unwrap();
-            for element in document.select(&all_selector) {
-                if let Some(widget) = self.element_to_widget(element) {
-                    self.content_box.append(&widget);
+        let total = self.find.matches.len();
+        self.output.emit(ArticleRendererOutput::FindResult {
+            current: if total == 0 { 0 } else { self.find.current + 1 },
+            total,
+        });
+    }
+
+    fn clear_find_highlights(&self) {
+        for found in &self.find.matches {
+            match &found.widget {
+                FindTarget::Label(label) => label.set_attributes(None),
+                FindTarget::Code(view) => {
+                    let buffer = view.buffer();
+                    let (start, end) = buffer.bounds();
+                    let (match_tag, current_tag) = find_tags(&buffer);
+                    buffer.remove_tag(&match_tag, &start, &end);
+                    buffer.remove_tag(&current_tag, &start, &end);
                 }
             }
         }
     }
 
+    /// Scrolls so that `y` within `target` is in view, unless it already is.
+    fn scroll_into_view(
+        target: &gtk::Widget,
+        y: f32,
+        clamp: &adw::Clamp,
+        vadjustment: &gtk::Adjustment,
+    ) {
+        let Some(point) = target.compute_point(clamp, &gtk::graphene::Point::new(0.0, y)) else {
+            return;
+        };
+        let y = point.y() as f64;
+        let top = vadjustment.value();
+        let page_size = vadjustment.page_size();
+
+        if y < top + 24.0 || y > top + page_size - 48.0 {
+            vadjustment.set_value((y - page_size / 3.0).max(0.0));
+        }
+    }
+
     fn element_to_widget(&self, element: ElementRef) -> Option<gtk::Widget> {
         match element.value().name() {
             "h1" => Some(self.create_heading(element, 1).upcast()),
@@ -331,217 +1118,592 @@ impl ArticleRenderer {
             "h4" => Some(self.create_heading(element, 4).upcast()),
             "h5" => Some(self.create_heading(element, 5).upcast()),
             "h6" => Some(self.create_heading(element, 6).upcast()),
-            "p" => Some(self.create_paragraph(element).upcast()),
+            // Paragraphs holding images or other blocks are walked instead
+            "p" if !self.has_block_children(element) => {
+                Some(self.create_paragraph(element).upcast())
+            }
             "pre" => Some(self.create_code_block(element).upcast()),
-            "blockquote" => Some(self.create_blockquote(element).upcast()),
+            "blockquote" => match self.create_embed(element) {
+                Some(card) => Some(card.upcast()),
+                None => Some(self.create_blockquote(element).upcast()),
+            },
+            "audio" | "iframe" | "video" => self.create_embed(element).map(Cast::upcast),
             "ul" => Some(self.create_list(element, false).upcast()),
             "ol" => Some(self.create_list(element, true).upcast()),
-            "img" => Some(self.create_image(element).upcast()),
+            "dl" => Some(self.create_definition_list(element).upcast()),
+            "details" => Some(self.create_details(element).upcast()),
+            "hr" => Some(self.create_separator().upcast()),
+            "math" | "script" if math::is_display(element) => {
+                Some(self.create_math_block(element).upcast())
+            }
+            "img" | "picture" => Some(self.create_image(element).upcast()),
+            "figure" => Some(self.create_figure(element).upcast()),
+            "figcaption" => Some(self.create_figcaption(element).upcast()),
+            "table" => Some(self.create_table(element).upcast()),
             _ => None,
         }
     }
 
     fn create_heading(&self, element: ElementRef, level: u8) -> gtk::Label {
-        let text = self.extract_text_with_formatting(element);
-        let label = gtk::Label::builder()
-            .label(&text)
-            .use_markup(true)
-            .wrap(true)
-            .xalign(0.0)
-            .selectable(true)
-            .build();
-
+        let label = self.create_markup_label(&self.extract_text_with_formatting(element));
+        label.set_xalign(0.0);
         label.add_css_class(&format!("article-h{}", level));
+        if !label.text().trim().is_empty() {
+            self.headings.borrow_mut().push((level, label.clone()));
+        }
         label
     }
 
+    /// The labels of the article holding text, with that text on one line.
+    /// Code blocks are left out, they are neither read aloud nor translated.
+    fn text_paragraphs(&self) -> (Vec<gtk::Label>, Vec<String>) {
+        let mut targets = Vec::new();
+        if let Some(main_box) = self.clamp.child() {
+            collect_find_targets(&main_box, &mut targets);
+        }
+
+        targets
+            .into_iter()
+            .filter_map(|target| match target {
+                FindTarget::Label(label) => {
+                    speech::spoken_text(&label.text()).map(|text| (label, text))
+                }
+                FindTarget::Code(_) => None,
+            })
+            .unzip()
+    }
+
+    fn show_translated_paragraph(
+        &mut self,
+        label: gtk::Label,
+        text: &str,
+        layout: TranslationLayout,
+    ) {
+        let markup = label.label().to_string();
+        let parent = label.parent().and_downcast::<gtk::Box>();
+
+        let row = match (layout, parent) {
+            (TranslationLayout::SideBySide, Some(parent)) => {
+                let translation = self.create_text_label(&encode_text(text));
+                for class in label.css_classes() {
+                    translation.add_css_class(&class);
+                }
+                translation.add_css_class("article-translation");
+
+                let row = gtk::Box::builder()
+                    .orientation(gtk::Orientation::Horizontal)
+                    .homogeneous(true)
+                    .spacing(24)
+                    .build();
+                parent.insert_child_after(&row, Some(&label));
+                parent.remove(&label);
+                row.append(&label);
+                row.append(&translation);
+                Some(row)
+            }
+            // Labels laid out by something other than a box, like table
+            // cells, are translated in place
+            _ => {
+                label.set_label(&encode_text(text));
+                None
+            }
+        };
+
+        self.translated
+            .push(TranslatedParagraph { label, markup, row });
+    }
+
+    /// The article as it is shown, title and metadata included, for printing.
+    fn print_blocks(&self) -> Vec<PrintBlock> {
+        let mut blocks = Vec::new();
+        if let Some(main_box) = self.clamp.child() {
+            collect_print_blocks(&main_box, 0, &mut blocks);
+        }
+        blocks
+    }
+
+    /// Puts the original text back where a translation is shown.
+    fn show_original(&mut self) {
+        for paragraph in self.translated.drain(..) {
+            let Some(row) = paragraph.row else {
+                paragraph.label.set_label(&paragraph.markup);
+                continue;
+            };
+            row.remove(&paragraph.label);
+            if let Some(parent) = row.parent().and_downcast::<gtk::Box>() {
+                parent.insert_child_after(&paragraph.label, Some(&row));
+                parent.remove(&row);
+            }
+        }
+    }
+
+    /// Finds the query again after the text of the article changed.
+    fn refresh_find(&mut self) {
+        if !self.find.query.is_empty() {
+            self.find(self.find.query.clone());
+        }
+    }
+
     fn create_paragraph(&self, element: ElementRef) -> gtk::Label {
-        let text = self.extract_text_with_formatting(element);
+        self.create_text_label(self.extract_text_with_formatting(element).trim())
+    }
+
+    fn create_text_label(&self, markup: &str) -> gtk::Label {
+        let label = self.create_markup_label(markup);
+        label.set_xalign(0.0);
+        label.add_css_class("article-text");
+        label
+    }
+
+    /// Every label showing article markup is made here, so the links it may
+    /// hold are followed inside the reader.
+    fn create_markup_label(&self, markup: &str) -> gtk::Label {
         let label = gtk::Label::builder()
-            .label(&text)
+            .label(markup)
             .use_markup(true)
             .wrap(true)
-            .xalign(0.0)
             .selectable(true)
             .build();
+        self.connect_links(&label);
+        label
+    }
+
+    fn create_math_block(&self, element: ElementRef) -> gtk::Label {
+        let markup = match element.value().name() {
+            "script" => math::tex_markup(&element.text().collect::<String>()),
+            _ => math::mathml_markup(element),
+        };
 
+        let label = self.create_markup_label(markup.trim());
+        label.set_justify(gtk::Justification::Center);
         label.add_css_class("article-text");
+        label.add_css_class("article-math");
         label
     }
 
     fn create_code_block(&self, element: ElementRef) -> gtk::Box {
         let code_text = element.text().collect::<String>();
+        let code_text = code_text.trim_end_matches('\n').to_string();
+        let language = highlight::detect_language(element, &code_text);
 
         let buffer = gtk::TextBuffer::builder().text(&code_text).build();
+        if let Some(language) = language {
+            Self::highlight_buffer(&buffer, &code_text, language);
+        }
 
         let text_view = gtk::TextView::builder()
             .buffer(&buffer)
             .editable(false)
             .cursor_visible(false)
-            .wrap_mode(gtk::WrapMode::Word)
+            .wrap_mode(gtk::WrapMode::None)
             .monospace(true)
             .build();
 
         text_view.add_css_class("article-code-block");
 
-        let container = gtk::Box::new(gtk::Orientation::Vertical, 0);
-        container.append(&text_view);
-        container
-    }
-
-    fn create_blockquote(&self, element: ElementRef) -> gtk::Box {
-        let container = gtk::Box::builder()
-            .orientation(gtk::Orientation::Vertical)
-            .spacing(8)
+        // Long lines scroll sideways unless wrapping is turned on
+        let scrolled_window = gtk::ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Automatic)
+            .vscrollbar_policy(gtk::PolicyType::Never)
+            .propagate_natural_height(true)
+            .child(&text_view)
             .build();
 
-        container.add_css_class("article-blockquote");
+        let header = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .spacing(4)
+            .build();
+        header.add_css_class("article-code-header");
 
-        for child in element.children() {
-            if let Some(child_element) = ElementRef::wrap(child) {
-                if let Some(widget) = self.element_to_widget(child_element) {
-                    container.append(&widget);
+        let language_label = gtk::Label::builder()
+            .label(language.map(Language::display_name).unwrap_or_default())
+            .xalign(0.0)
+            .hexpand(true)
+            .build();
+        language_label.add_css_class("article-code-language");
+        language_label.add_css_class("dim-label");
+        header.append(&language_label);
+
+        let wrap_button = gtk::ToggleButton::builder()
+            .icon_name("view-wrapped-symbolic")
+            .tooltip_text(gettext("Wrap Lines"))
+            .css_classes(["flat"])
+            .build();
+        wrap_button.connect_toggled({
+            let text_view = text_view.clone();
+            let scrolled_window = scrolled_window.clone();
+            move |button| {
+                if button.is_active() {
+                    text_view.set_wrap_mode(gtk::WrapMode::WordChar);
+                    scrolled_window.set_hscrollbar_policy(gtk::PolicyType::Never);
+                } else {
+                    text_view.set_wrap_mode(gtk::WrapMode::None);
+                    scrolled_window.set_hscrollbar_policy(gtk::PolicyType::Automatic);
                 }
             }
-        }
+        });
+        header.append(&wrap_button);
 
-        container
-    }
+        let copy_button = gtk::Button::builder()
+            .icon_name("edit-copy-symbolic")
+            .tooltip_text(gettext("Copy Code"))
+            .css_classes(["flat"])
+            .build();
+        copy_button.connect_clicked(move |button| {
+            crate::persistence::clipboard::copy(&code_text);
+
+            // Briefly confirm the copy in place of a toast
+            button.set_icon_name("object-select-symbolic");
+            let button = button.clone();
+            gtk::glib::timeout_add_local_once(std::time::Duration::from_secs(2), move || {
+                button.set_icon_name("edit-copy-symbolic");
+            });
+        });
+        header.append(&copy_button);
+
+        let container = gtk::Box::new(gtk::Orientation::Vertical, 0);
+        container.add_css_class("article-code-container");
+        container.append(&header);
+        container.append(&scrolled_window);
+        container
+    }
+
+    fn highlight_buffer(buffer: &gtk::TextBuffer, code: &str, language: Language) {
+        let tag_table = buffer.tag_table();
+        for (kind, color) in CODE_COLORS {
+            let tag = gtk::TextTag::builder()
+                .name(kind.tag_name())
+                .foreground(*color)
+                .build();
+            if *kind == TokenKind::Comment {
+                tag.set_style(gtk::pango::Style::Italic);
+            }
+            tag_table.add(&tag);
+        }
+
+        for token in highlight::highlight(code, language) {
+            let start = buffer.iter_at_offset(token.start as i32);
+            let end = buffer.iter_at_offset(token.end as i32);
+            buffer.apply_tag_by_name(token.kind.tag_name(), &start, &end);
+        }
+    }
+
+    fn create_blockquote(&self, element: ElementRef) -> gtk::Box {
+        let container = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(8)
+            .build();
+
+        container.add_css_class("article-blockquote");
+        self.append_blocks(element, &container);
+
+        container
+    }
 
     fn create_list(&self, element: ElementRef, ordered: bool) -> gtk::Box {
         let container = gtk::Box::builder()
             .orientation(gtk::Orientation::Vertical)
             .spacing(4)
             .build();
+        container.add_css_class("article-list");
+
+        let depth = element
+            .ancestors()
+            .filter_map(ElementRef::wrap)
+            .filter(|e| matches!(e.value().name(), "ul" | "ol"))
+            .count();
+        if depth > 0 {
+            container.add_css_class("article-list-nested");
+        }
+
+        let item_count = element
+            .children()
+            .filter_map(ElementRef::wrap)
+            .filter(|e| e.value().name() == "li")
+            .count();
+        let reversed = ordered && element.value().attr("reversed").is_some();
+        let mut number = element
+            .value()
+            .attr("start")
+            .and_then(|start| start.trim().parse::<i64>().ok())
+            .unwrap_or(if reversed { item_count as i64 } else { 1 });
+
+        for child in element.children().filter_map(ElementRef::wrap) {
+            if is_skipped(child) {
+                continue;
+            }
+            if child.value().name() != "li" {
+                self.append_block(child, &container);
+                continue;
+            }
+
+            // <li value> restarts the numbering from that item on
+            if let Some(value) = child
+                .value()
+                .attr("value")
+                .and_then(|value| value.trim().parse::<i64>().ok())
+            {
+                number = value;
+            }
 
-        let li_selector = Selector::parse("li").unwrap();
-        for (index, li) in element.select(&li_selector).enumerate() {
             let item_box = gtk::Box::builder()
                 .orientation(gtk::Orientation::Horizontal)
                 .spacing(8)
                 .build();
 
             let prefix = if ordered {
-                format!("{}.", index + 1)
+                format!("{}.", number)
             } else {
-                "•".to_string()
+                BULLETS[depth % BULLETS.len()].to_string()
             };
 
             let bullet = gtk::Label::new(Some(&prefix));
             bullet.set_xalign(0.0);
             bullet.set_valign(gtk::Align::Start);
             bullet.add_css_class("article-text");
+            bullet.add_css_class("article-list-marker");
             item_box.append(&bullet);
 
-            let text = self.extract_text_with_formatting(li);
-            let content = gtk::Label::builder()
-                .label(&text)
-                .use_markup(true)
-                .wrap(true)
-                .xalign(0.0)
+            let content = gtk::Box::builder()
+                .orientation(gtk::Orientation::Vertical)
+                .spacing(4)
                 .hexpand(true)
-                .selectable(true)
                 .build();
-
-            content.add_css_class("article-text");
+            self.append_blocks(child, &content);
 
             item_box.append(&content);
+            self.register_anchors(child, item_box.upcast_ref());
             container.append(&item_box);
+
+            number += if reversed { -1 } else { 1 };
         }
 
         container
     }
 
-    fn create_image(&self, element: ElementRef) -> gtk::Box {
-        let img_url = element.value().attr("src").unwrap_or("").to_string();
+    fn create_definition_list(&self, element: ElementRef) -> gtk::Box {
+        let container = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(4)
+            .build();
+        container.add_css_class("article-definition-list");
+
+        for child in element.children().filter_map(ElementRef::wrap) {
+            match child.value().name() {
+                "dt" => {
+                    let term =
+                        self.create_text_label(self.extract_text_with_formatting(child).trim());
+                    term.add_css_class("article-definition-term");
+                    container.append(&term);
+                }
+                "dd" => {
+                    let definition = gtk::Box::builder()
+                        .orientation(gtk::Orientation::Vertical)
+                        .spacing(4)
+                        .margin_start(24)
+                        .build();
+                    definition.add_css_class("article-definition");
+                    self.append_blocks(child, &definition);
+                    container.append(&definition);
+                }
+                // Groups of terms and definitions may be wrapped in a div
+                "div" => {
+                    let group = self.create_definition_list(child);
+                    group.remove_css_class("article-definition-list");
+                    container.append(&group);
+                }
+                _ if is_skipped(child) => {}
+                _ => self.append_block(child, &container),
+            }
+        }
+
+        container
+    }
+
+    fn create_details(&self, element: ElementRef) -> gtk::Expander {
+        let summary = element
+            .children()
+            .filter_map(ElementRef::wrap)
+            .find(|e| e.value().name() == "summary");
+
+        let label = self.create_markup_label(&match summary {
+            Some(summary) => self
+                .extract_text_with_formatting(summary)
+                .trim()
+                .to_string(),
+            None => encode_text(&gettext("Details")).to_string(),
+        });
+        // A selectable summary would take the clicks meant to expand it
+        label.set_selectable(false);
+        label.set_xalign(0.0);
+        label.add_css_class("article-text");
+        label.add_css_class("article-details-summary");
+
+        let content = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(4)
+            .margin_start(24)
+            .build();
+        self.append_blocks(element, &content);
+
+        let expander = gtk::Expander::builder()
+            .label_widget(&label)
+            .child(&content)
+            .expanded(element.value().attr("open").is_some())
+            .build();
+        expander.add_css_class("article-details");
+        expander
+    }
+
+    fn create_separator(&self) -> gtk::Separator {
+        let separator = gtk::Separator::new(gtk::Orientation::Horizontal);
+        separator.add_css_class("article-separator");
+        separator
+    }
+
+    fn create_table(&self, element: ElementRef) -> gtk::Box {
+        let container = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(6)
+            .build();
+        container.add_css_class("article-table");
+
+        if let Some(caption) = table::caption(element) {
+            let label = self.create_markup_label(&self.extract_text_with_formatting(caption));
+            label.set_xalign(0.0);
+            label.add_css_class("article-table-caption");
+            container.append(&label);
+        }
+
+        let grid = gtk::Grid::builder().halign(gtk::Align::Start).build();
+        grid.add_css_class("article-table-grid");
+
+        for cell in table::layout(element) {
+            let label =
+                self.create_markup_label(self.extract_text_with_formatting(cell.element).trim());
+            label.set_max_width_chars(40);
+            label.set_xalign(0.0);
+            label.set_yalign(0.0);
+            label.set_hexpand(true);
+            label.set_vexpand(true);
+            label.add_css_class("article-table-cell");
+            if cell.header {
+                label.add_css_class("article-table-header");
+            }
+
+            grid.attach(
+                &label,
+                cell.column as i32,
+                cell.row as i32,
+                cell.column_span as i32,
+                cell.row_span as i32,
+            );
+        }
+
+        // Wide tables scroll sideways instead of stretching the whole article
+        let scrolled_window = gtk::ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::Automatic)
+            .vscrollbar_policy(gtk::PolicyType::Never)
+            .propagate_natural_height(true)
+            .child(&grid)
+            .build();
+        container.append(&scrolled_window);
+
+        container
+    }
+
+    fn create_figure(&self, element: ElementRef) -> gtk::Box {
+        let container = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(6)
+            .build();
+        container.add_css_class("article-figure");
+        self.append_blocks(element, &container);
+
+        container
+    }
+
+    fn create_figcaption(&self, element: ElementRef) -> gtk::Label {
+        let label = self.create_markup_label(self.extract_text_with_formatting(element).trim());
+        label.set_xalign(0.5);
+        label.set_justify(gtk::Justification::Center);
+        label.add_css_class("article-figcaption");
+        label
+    }
 
+    fn create_image(&self, element: ElementRef) -> gtk::Box {
         let container = gtk::Box::new(gtk::Orientation::Vertical, 0);
         container.add_css_class("article-image");
 
-        if !img_url.is_empty() {
+        let Some(source) = images::resolve(
+            element,
+            self.base_url.as_ref(),
+            self.style.line_width,
+            self.content_box.scale_factor(),
+        ) else {
+            return container;
+        };
+
+        if let Some(alt) = source.alt.as_deref() {
+            container.update_property(&[gtk::accessible::Property::Label(alt)]);
+        }
+
+        let gallery = self.gallery.clone();
+        let index = {
+            let mut images = gallery.borrow_mut();
+            images.push(LightboxImage {
+                url: source.url.clone(),
+                alt: source.alt.clone(),
+                texture: None,
+            });
+            images.len() - 1
+        };
+
+        let max_width = self.style.line_width;
+        let cache_size = self.image_cache_size;
+        let network_metered = gtk::gio::NetworkMonitor::default().is_network_metered();
+
+        if self.image_loading.loads_automatically(network_metered) {
+            let spinner = adw::Spinner::builder()
+                .halign(gtk::Align::Center)
+                .valign(gtk::Align::Center)
+                .vexpand(true)
+                .margin_top(20)
+                .margin_bottom(20)
+                .build();
+            container.append(&spinner);
+            Self::load_image(
+                &container,
+                source,
+                spinner.upcast(),
+                max_width,
+                (gallery, index),
+                cache_size,
+            );
+        } else {
             let button = gtk::Button::builder()
                 .label("Load Image")
                 .halign(gtk::Align::Center)
+                .valign(gtk::Align::Center)
+                .vexpand(true)
                 .margin_top(20)
                 .margin_bottom(20)
                 .build();
+            button.set_tooltip_text(source.alt.as_deref());
 
             let container_clone = container.clone();
-            let url_owned = img_url.clone();
 
             button.connect_clicked(move |btn| {
                 btn.set_sensitive(false);
                 btn.set_label("Loading...");
-
-                let container_clone2 = container_clone.clone();
-                let url_for_load = url_owned.clone();
-                let url_for_error = url_owned.clone();
-                let btn_clone = btn.clone();
-
-                gtk::glib::MainContext::default().spawn_local(async move {
-                    let result =
-                        gtk::gio::spawn_blocking(move || Self::download_image_bytes(&url_for_load))
-                            .await;
-
-                    container_clone2.remove(&btn_clone);
-
-                    match result {
-                        Ok(Ok(bytes)) => match Self::bytes_to_texture(&bytes) {
-                            Ok(texture) => {
-                                eprintln!(
-                                    "Texture created successfully, size: {}x{}",
-                                    texture.width(),
-                                    texture.height()
-                                );
-
-                                let picture = gtk::Picture::new();
-                                picture.set_paintable(Some(&texture));
-                                picture.set_content_fit(gtk::ContentFit::Contain);
-                                picture.set_can_shrink(true);
-                                picture.set_halign(gtk::Align::Center);
-                                picture.set_margin_top(20);
-                                picture.set_margin_bottom(20);
-                                picture.add_css_class("article-image-picture");
-
-                                let natural_width = 2048
-                                    .min(texture.width().min(container_clone2.allocated_width()));
-                                let aspect_ratio = texture.height() as f64 / texture.width() as f64;
-                                let natural_height = (natural_width as f64 * aspect_ratio) as i32;
-                                picture.set_size_request(-1, natural_height);
-
-                                container_clone2.append(&picture);
-                                eprintln!("Picture widget added with height: {}", natural_height);
-                            }
-                            Err(e) => {
-                                eprintln!("Failed to create pixbuf: {}", e);
-                                let error_icon = gtk::Image::from_icon_name("image-missing");
-                                error_icon.set_pixel_size(48);
-                                error_icon.set_halign(gtk::Align::Center);
-                                error_icon.set_margin_top(20);
-                                error_icon.set_margin_bottom(20);
-                                container_clone2.append(&error_icon);
-                            }
-                        },
-                        Ok(Err(e)) => {
-                            eprintln!("Failed to load image from {}: {}", url_for_error, e);
-                            let error_icon = gtk::Image::from_icon_name("image-missing");
-                            error_icon.set_pixel_size(48);
-                            error_icon.set_halign(gtk::Align::Center);
-                            error_icon.set_margin_top(20);
-                            error_icon.set_margin_bottom(20);
-                            container_clone2.append(&error_icon);
-                        }
-                        Err(_) => {
-                            eprintln!("Failed to spawn blocking task");
-                            let error_icon = gtk::Image::from_icon_name("image-missing");
-                            error_icon.set_pixel_size(48);
-                            error_icon.set_halign(gtk::Align::Center);
-                            error_icon.set_margin_top(20);
-                            error_icon.set_margin_bottom(20);
-                            container_clone2.append(&error_icon);
-                        }
-                    }
-                });
+                Self::load_image(
+                    &container_clone,
+                    source.clone(),
+                    btn.clone().upcast(),
+                    max_width,
+                    (gallery.clone(), index),
+                    cache_size,
+                );
             });
 
             container.append(&button);
@@ -550,22 +1712,237 @@ impl ArticleRenderer {
         container
     }
 
-    fn download_image_bytes(
-        url: &str,
-    ) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
-        eprintln!("Loading image from: {}", url);
+    /// Downloads the image in the background, then replaces `placeholder`
+    /// with the picture or with a "missing image" icon on failure. When the
+    /// page tells the image size, that space is reserved meanwhile so the
+    /// text below doesn't jump once it loads. Clicking the picture opens it
+    /// in the lightbox at its `gallery` position.
+    fn load_image(
+        container: &gtk::Box,
+        source: images::ImageSource,
+        placeholder: gtk::Widget,
+        max_width: u32,
+        (gallery, index): (Rc<RefCell<Vec<LightboxImage>>>, usize),
+        cache_size: u32,
+    ) {
+        let container = container.clone();
+
+        if let Some((_, height)) = source.placeholder_size(max_width) {
+            container.set_size_request(-1, height);
+        }
+
+        gtk::glib::MainContext::default().spawn_local(async move {
+            let url = source.url.clone();
+            let result =
+                relm4::spawn(async move { network::images::fetch(&url, cache_size).await }).await;
+
+            container.remove(&placeholder);
+            container.set_size_request(-1, -1);
+
+            match result {
+                Ok(Ok(bytes)) => match Self::bytes_to_texture(&bytes) {
+                    Ok(texture) => {
+                        let picture = gtk::Picture::new();
+                        picture.set_paintable(Some(&texture));
+                        picture.set_content_fit(gtk::ContentFit::Contain);
+                        picture.set_can_shrink(true);
+                        picture.set_halign(gtk::Align::Center);
+                        picture.set_margin_top(20);
+                        picture.set_margin_bottom(20);
+                        picture.add_css_class("article-image-picture");
+                        picture.set_alternative_text(source.alt.as_deref());
+
+                        // Not allocated yet when the image comes straight from the cache
+                        let available_width = match container.allocated_width() {
+                            width if width > 0 => width,
+                            _ => max_width as i32,
+                        };
+                        let natural_width = 2048.min(texture.width().min(available_width));
+                        let aspect_ratio = texture.height() as f64 / texture.width() as f64;
+                        let natural_height = (natural_width as f64 * aspect_ratio) as i32;
+                        picture.set_size_request(-1, natural_height);
+
+                        if let Some(image) = gallery.borrow_mut().get_mut(index) {
+                            if image.url == source.url {
+                                image.texture = Some(texture.clone());
+                            }
+                        }
+
+                        picture.set_cursor_from_name(Some("zoom-in"));
+                        let click = gtk::GestureClick::new();
+                        click.connect_released(move |_, _, _, _| {
+                            Self::open_lightbox(&gallery, index, cache_size);
+                        });
+                        picture.add_controller(click);
+
+                        container.append(&picture);
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to create pixbuf: {}", e);
+                        container.append(&Self::missing_image_icon());
+                    }
+                },
+                Ok(Err(e)) => {
+                    eprintln!("Failed to load image from {}: {}", source.url, e);
+                    container.append(&Self::missing_image_icon());
+                }
+                Err(_) => {
+                    eprintln!("Image download task failed");
+                    container.append(&Self::missing_image_icon());
+                }
+            }
+        });
+    }
+
+    /// Shows media and social embeds as a card naming the provider, with a
+    /// thumbnail when the URL gives one away. Audio and video sources play
+    /// in place, anything else opens in the browser.
+    fn create_embed(&self, element: ElementRef) -> Option<gtk::Box> {
+        let embed = embeds::resolve(element, self.base_url.as_ref())?;
+
+        let card = gtk::Box::new(gtk::Orientation::Vertical, 0);
+        card.add_css_class("card");
+        card.add_css_class("article-embed");
+
+        // Holds the thumbnail, then the player once playback starts
+        let media_box = gtk::Box::new(gtk::Orientation::Vertical, 0);
+        card.append(&media_box);
+
+        let video_height = (self.style.line_width * 9 / 16) as i32;
+        let network_metered = gtk::gio::NetworkMonitor::default().is_network_metered();
+        if let Some(thumbnail) = embed.thumbnail.clone() {
+            if self.image_loading.loads_automatically(network_metered) {
+                Self::load_thumbnail(&media_box, thumbnail, video_height, self.image_cache_size);
+            }
+        }
+
+        let row = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .spacing(12)
+            .margin_top(12)
+            .margin_bottom(12)
+            .margin_start(12)
+            .margin_end(12)
+            .build();
+
+        let icon = gtk::Image::from_icon_name(match embed.kind {
+            EmbedKind::Video => "video-x-generic-symbolic",
+            EmbedKind::Audio => "audio-x-generic-symbolic",
+            EmbedKind::Page => "text-x-generic-symbolic",
+        });
+        icon.add_css_class("dim-label");
+        row.append(&icon);
+
+        let text_box = gtk::Box::builder()
+            .orientation(gtk::Orientation::Vertical)
+            .spacing(2)
+            .hexpand(true)
+            .valign(gtk::Align::Center)
+            .build();
+
+        let provider_label = gtk::Label::builder()
+            .label(embed.provider.display_name())
+            .xalign(0.0)
+            .build();
+        provider_label.add_css_class("article-embed-provider");
+        text_box.append(&provider_label);
+
+        let title_label = gtk::Label::builder()
+            .label(embed.title.as_deref().unwrap_or(&embed.url))
+            .xalign(0.0)
+            .wrap(true)
+            .wrap_mode(gtk::pango::WrapMode::WordChar)
+            .selectable(true)
+            .build();
+        title_label.add_css_class("article-embed-title");
+        text_box.append(&title_label);
+
+        row.append(&text_box);
+
+        if matches!(embed.kind, EmbedKind::Video | EmbedKind::Audio) {
+            let play_button = gtk::Button::builder()
+                .icon_name("media-playback-start-symbolic")
+                .tooltip_text(gettext("Play"))
+                .valign(gtk::Align::Center)
+                .build();
+            play_button.add_css_class("circular");
+
+            let url = embed.url.clone();
+            let kind = embed.kind;
+            let media_box = media_box.clone();
+            play_button.connect_clicked(move |button| {
+                while let Some(child) = media_box.first_child() {
+                    media_box.remove(&child);
+                }
+
+                let video = gtk::Video::for_file(Some(&gtk::gio::File::for_uri(&url)));
+                video.set_autoplay(true);
+                video.add_css_class("article-embed-player");
+                if kind == EmbedKind::Video {
+                    video.set_size_request(-1, video_height);
+                }
+                media_box.append(&video);
+                button.set_visible(false);
+            });
+            row.append(&play_button);
+        }
+
+        let open_button = gtk::Button::builder()
+            .icon_name("web-browser-symbolic")
+            .tooltip_text(gettext("Open in Browser"))
+            .valign(gtk::Align::Center)
+            .build();
+        open_button.add_css_class("flat");
+        let url = embed.url.clone();
+        open_button.connect_clicked(move |_| {
+            if let Err(e) = open::that(&url) {
+                eprintln!("Failed to open {}: {}", url, e);
+            }
+        });
+        row.append(&open_button);
 
-        let client = reqwest::blocking::Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
-            .build()?;
+        card.append(&row);
+        Some(card)
+    }
 
-        let response = client.get(url).send()?;
-        eprintln!("Got response with status: {}", response.status());
+    fn load_thumbnail(media_box: &gtk::Box, url: String, height: i32, cache_size: u32) {
+        let media_box = media_box.clone();
+
+        gtk::glib::MainContext::default().spawn_local(async move {
+            let result =
+                relm4::spawn(async move { network::images::fetch(&url, cache_size).await }).await;
+
+            // No thumbnail is better than a broken one on a card
+            if let Ok(Ok(bytes)) = result {
+                if let Ok(texture) = Self::bytes_to_texture(&bytes) {
+                    let picture = gtk::Picture::for_paintable(&texture);
+                    picture.set_content_fit(gtk::ContentFit::Cover);
+                    picture.set_can_shrink(true);
+                    picture.set_size_request(-1, height);
+                    picture.add_css_class("article-embed-thumbnail");
+                    media_box.append(&picture);
+                }
+            }
+        });
+    }
 
-        let bytes = response.bytes()?;
-        eprintln!("Downloaded {} bytes", bytes.len());
+    fn open_lightbox(gallery: &RefCell<Vec<LightboxImage>>, index: usize, cache_size: u32) {
+        ImageLightbox::builder()
+            .launch(LightboxInit {
+                images: gallery.borrow().clone(),
+                index,
+                cache_size,
+            })
+            .detach();
+    }
 
-        Ok(bytes.to_vec())
+    fn missing_image_icon() -> gtk::Image {
+        let error_icon = gtk::Image::from_icon_name("image-missing");
+        error_icon.set_pixel_size(48);
+        error_icon.set_halign(gtk::Align::Center);
+        error_icon.set_margin_top(20);
+        error_icon.set_margin_bottom(20);
+        error_icon
     }
 
     fn bytes_to_texture(bytes: &[u8]) -> Result<gtk::gdk::Texture, Box<dyn std::error::Error>> {
@@ -602,45 +1979,11 @@ impl ArticleRenderer {
         for child in element.children() {
             match child.value() {
                 Node::Text(text) => {
-                    result.push_str(&encode_text(text.text.as_ref()));
+                    result.push_str(&text_markup(text));
                 }
                 Node::Element(_) => {
                     if let Some(child_element) = ElementRef::wrap(child) {
-                        let tag_name = child_element.value().name();
-                        match tag_name {
-                            "strong" | "b" => {
-                                result.push_str("<b>");
-                                result.push_str(&self.extract_text_with_formatting(child_element));
-                                result.push_str("</b>");
-                            }
-                            "em" | "i" => {
-                                result.push_str("<i>");
-                                result.push_str(&self.extract_text_with_formatting(child_element));
-                                result.push_str("</i>");
-                            }
-                            "code" => {
-                                result.push_str("<tt>");
-                                let code_text = child_element.text().collect::<String>();
-                                result.push_str(&encode_text(&code_text));
-                                result.push_str("</tt>");
-                            }
-                            "a" => {
-                                if let Some(href) = child_element.value().attr("href") {
-                                    result.push_str(&format!(
-                                        "<a href=\"{}\">{}</a>",
-                                        encode_text(href),
-                                        self.extract_text_with_formatting(child_element)
-                                    ));
-                                } else {
-                                    result.push_str(
-                                        &self.extract_text_with_formatting(child_element),
-                                    );
-                                }
-                            }
-                            _ => {
-                                result.push_str(&child_element.text().collect::<String>());
-                            }
-                        }
+                        result.push_str(&self.inline_markup(child_element));
                     }
                 }
                 _ => {}
@@ -649,75 +1992,575 @@ impl ArticleRenderer {
 
         result
     }
+
+    /// Converts an inline element to Pango markup.
+    fn inline_markup(&self, element: ElementRef) -> String {
+        match element.value().name() {
+            "strong" | "b" => format!("<b>{}</b>", self.extract_text_with_formatting(element)),
+            "em" | "i" | "cite" | "dfn" | "var" => {
+                format!("<i>{}</i>", self.extract_text_with_formatting(element))
+            }
+            "code" => {
+                let code_text = element.text().collect::<String>();
+                format!("<tt>{}</tt>", encode_text(&code_text))
+            }
+            "kbd" | "samp" => format!("<tt>{}</tt>", self.extract_text_with_formatting(element)),
+            "sup" => format!("<sup>{}</sup>", self.extract_text_with_formatting(element)),
+            "sub" => format!("<sub>{}</sub>", self.extract_text_with_formatting(element)),
+            "s" | "del" | "strike" => {
+                format!("<s>{}</s>", self.extract_text_with_formatting(element))
+            }
+            "u" | "ins" => format!("<u>{}</u>", self.extract_text_with_formatting(element)),
+            "small" => format!(
+                "<small>{}</small>",
+                self.extract_text_with_formatting(element)
+            ),
+            "mark" => format!(
+                "<span background=\"{}\" foreground=\"#000000\">{}</span>",
+                MARK_COLOR,
+                self.extract_text_with_formatting(element)
+            ),
+            // The expansion goes in the label tooltip
+            "abbr" if element.value().attr("title").is_some() => format!(
+                "<span underline=\"low\">{}</span>",
+                self.extract_text_with_formatting(element)
+            ),
+            "q" => {
+                // Alternate double and single quotes when nested
+                let depth = element
+                    .ancestors()
+                    .filter_map(ElementRef::wrap)
+                    .filter(|e| e.value().name() == "q")
+                    .count();
+                let (open, close) = if depth % 2 == 0 {
+                    ("“", "”")
+                } else {
+                    ("‘", "’")
+                };
+                format!(
+                    "{}{}{}",
+                    open,
+                    self.extract_text_with_formatting(element),
+                    close
+                )
+            }
+            "a" if footnotes::reference_target(element).is_some() => {
+                let number = encode_text(element.text().collect::<String>().trim()).into_owned();
+                let in_sup = element
+                    .parent()
+                    .and_then(ElementRef::wrap)
+                    .is_some_and(|parent| parent.value().name() == "sup");
+
+                format!(
+                    "<a href=\"{}\">{}</a>",
+                    encode_text(element.value().attr("href").unwrap_or_default()),
+                    if in_sup {
+                        number
+                    } else {
+                        format!("<sup>{}</sup>", number)
+                    }
+                )
+            }
+            "a" if footnotes::is_backlink(element) => format!(
+                "<a href=\"{}\" title=\"{}\">{}</a>",
+                encode_text(element.value().attr("href").unwrap_or_default()),
+                encode_text(&gettext("Back to text")),
+                self.extract_text_with_formatting(element)
+            ),
+            "a" => {
+                if let Some(href) = element.value().attr("href") {
+                    format!(
+                        "<a href=\"{}\">{}</a>",
+                        encode_text(&self.resolve_href(href)),
+                        self.extract_text_with_formatting(element)
+                    )
+                } else {
+                    self.extract_text_with_formatting(element)
+                }
+            }
+            "br" => "\n".to_string(),
+            "math" => math::mathml_markup(element),
+            "script" if math::is_tex_script(element) => {
+                math::tex_markup(&element.text().collect::<String>())
+            }
+            _ if is_skipped(element) || embeds::is_embed(element) => String::new(),
+            // Keep the text of anything we don't style
+            _ => self.extract_text_with_formatting(element),
+        }
+    }
 }
 
-pub struct ArticleRendererWidgets {}
+/// Elements laid out as blocks. Anything else flows as inline text, unless it
+/// wraps one of these.
+const BLOCK_ELEMENTS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "audio",
+    "blockquote",
+    "body",
+    "center",
+    "dd",
+    "details",
+    "dialog",
+    "div",
+    "dl",
+    "dt",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hgroup",
+    "hr",
+    "html",
+    "iframe",
+    "img",
+    "li",
+    "main",
+    "nav",
+    "ol",
+    "p",
+    "picture",
+    "pre",
+    "section",
+    "summary",
+    "table",
+    "ul",
+    "video",
+];
+
+/// Elements with nothing worth showing in the reader.
+const SKIPPED_ELEMENTS: &[&str] = &[
+    "button", "canvas", "embed", "head", "input", "noscript", "object", "script", "select",
+    "style", "svg", "template", "textarea",
+];
+
+/// Syntax colors, mid-tones from the GNOME palette that read fine on both
+/// light and dark reader themes.
+const CODE_COLORS: &[(TokenKind, &str)] = &[
+    (TokenKind::Keyword, "#c061cb"),
+    (TokenKind::Literal, "#e66100"),
+    (TokenKind::String, "#2ec27e"),
+    (TokenKind::Number, "#e66100"),
+    (TokenKind::Comment, "#868686"),
+    (TokenKind::Function, "#3584e4"),
+];
+
+/// Highlighter yellow from the GNOME palette, behind `mark` text.
+const MARK_COLOR: &str = "#f8e45c";
+
+/// Orange from the GNOME palette, behind the match the find bar is on.
+const FIND_CURRENT_COLOR: &str = "#ffa348";
+
+/// Unordered list markers, cycling with the nesting depth.
+const BULLETS: &[&str] = &["•", "◦", "▪"];
+
+/// Collapses whitespace the way browsers do, so the source indentation of the
+/// HTML doesn't end up as line breaks in the labels.
+fn collapse_whitespace(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut last_was_space = false;
+
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !last_was_space {
+                result.push(' ');
+            }
+            last_was_space = true;
+        } else {
+            result.push(c);
+            last_was_space = false;
+        }
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::testing::ComponentTester;
-    use gtk::prelude::*;
+    result
+}
 
-    #[gtk::test]
-    fn test_init_component() {
-        let tester = ComponentTester::<ArticleRenderer>::launch(());
+/// Spells out the abbreviations in a label in its tooltip, as Pango can't
+/// attach one to a span of text.
+fn set_abbreviation_tooltip(label: &gtk::Label, elements: &[ElementRef]) {
+    let mut lines: Vec<String> = Vec::new();
 
-        // Verify the root widget is a ScrolledWindow
-        let root = tester.widget();
-        assert_eq!(root.type_().name(), "GtkScrolledWindow");
+    let abbreviations = elements
+        .iter()
+        .flat_map(|element| element.descendants())
+        .filter_map(ElementRef::wrap)
+        .filter(|e| e.value().name() == "abbr");
+
+    for abbr in abbreviations {
+        let Some(title) = abbr.value().attr("title").map(str::trim) else {
+            continue;
+        };
+        let text = abbr.text().collect::<String>();
+        let line = format!("{}: {}", collapse_whitespace(&text).trim(), title);
+        if !title.is_empty() && !lines.contains(&line) {
+            lines.push(line);
+        }
     }
 
-    #[gtk::test]
-    fn test_set_title() {
-        let tester = ComponentTester::<ArticleRenderer>::launch(());
+    if !lines.is_empty() {
+        label.set_tooltip_text(Some(&lines.join("\n")));
+    }
+}
 
-        // Send SetTitle message
-        tester.send_input(ArticleRendererInput::SetTitle(
-            "Test Article Title".to_string(),
-        ));
-        tester.process_events();
+/// Escapes text for Pango markup, typesetting any TeX formulas in it.
+fn text_markup(text: &str) -> String {
+    let text = collapse_whitespace(text);
+
+    math::split_tex(&text)
+        .into_iter()
+        .map(|segment| match segment {
+            Segment::Text(text) => encode_text(text).into_owned(),
+            Segment::Math { tex, display: true } => format!("\n{}\n", math::tex_markup(tex)),
+            Segment::Math { tex, .. } => math::tex_markup(tex),
+        })
+        .collect()
+}
 
-        // Deep assertions: verify the title label was updated and is visible
-        let title_label = tester.find_label_by_css_class("article-title");
-        assert!(title_label.is_some(), "Title label should exist");
+/// Collects the elements laid out as blocks: block elements and anything
+/// wrapping one. Done in one pass so walking the document stays linear.
+fn block_nodes(document: &Html) -> HashSet<NodeId> {
+    let mut blocks = HashSet::new();
+    mark_blocks(document.root_element(), &mut blocks);
+    blocks
+}
 
-        let title_label = title_label.unwrap();
-        assert_eq!(title_label.text().as_str(), "Test Article Title");
-        assert!(title_label.is_visible(), "Title label should be visible");
+fn mark_blocks(element: ElementRef, blocks: &mut HashSet<NodeId>) -> bool {
+    let mut wraps_block = false;
+    for child in element.children().filter_map(ElementRef::wrap) {
+        wraps_block |= mark_blocks(child, blocks);
     }
 
-    #[gtk::test]
-    fn test_set_metadata() {
-        let tester = ComponentTester::<ArticleRenderer>::launch(());
-
-        // Send SetMetadata message
-        tester.send_input(ArticleRendererInput::SetMetadata {
-            url: "https://www.example.com/article".to_string(),
-            description: "A sample article description".to_string(),
-            time: 1234567890.0,
-        });
-        tester.process_events();
+    let is_block = wraps_block || is_block_element(element);
+    if is_block {
+        blocks.insert(element.id());
+    }
+    is_block
+}
 
-        // Deep assertions: verify metadata box is visible
-        let metadata_box = tester.find_widget_by_css_class("article-metadata");
-        assert!(metadata_box.is_some(), "Metadata box should exist");
-        assert!(
-            metadata_box.unwrap().is_visible(),
-            "Metadata box should be visible"
-        );
+fn is_block_element(element: ElementRef) -> bool {
+    BLOCK_ELEMENTS.contains(&element.value().name()) || math::is_display(element)
+}
 
-        // Verify domain label exists and contains the domain
-        let domain_label = tester.find_label_by_css_class("article-domain");
-        assert!(domain_label.is_some(), "Domain label should exist");
-        assert_eq!(domain_label.unwrap().text().as_str(), "example.com");
+impl FindTarget {
+    fn widget(&self) -> &gtk::Widget {
+        match self {
+            FindTarget::Label(label) => label.upcast_ref(),
+            FindTarget::Code(view) => view.upcast_ref(),
+        }
+    }
 
-        // Verify date label exists
-        let date_label = tester.find_label_by_css_class("article-date");
-        assert!(date_label.is_some(), "Date label should exist");
+    fn text(&self) -> String {
+        match self {
+            FindTarget::Label(label) => label.text().to_string(),
+            FindTarget::Code(view) => {
+                let buffer = view.buffer();
+                let (start, end) = buffer.bounds();
+                buffer.text(&start, &end, false).to_string()
+            }
+        }
+    }
 
-        // Verify description label exists and has the correct text
-        let desc_label = tester.find_label_by_css_class("article-description");
+    /// How far down the widget the text at byte `index` is drawn.
+    fn offset_of(&self, index: usize) -> Option<f32> {
+        match self {
+            FindTarget::Label(label) => {
+                let (_, layout_y) = label.layout_offsets();
+                let position = label.layout().index_to_pos(index as i32);
+                Some(layout_y as f32 + position.y() as f32 / gtk::pango::SCALE as f32)
+            }
+            FindTarget::Code(view) => {
+                let text = self.text();
+                let offset = text.get(..index)?.chars().count() as i32;
+                let location = view.iter_location(&view.buffer().iter_at_offset(offset));
+                let (_, y) =
+                    view.buffer_to_window_coords(gtk::TextWindowType::Widget, 0, location.y());
+                Some(y as f32)
+            }
+        }
+    }
+}
+
+/// The text widgets under `widget` the find bar searches, in reading order.
+fn collect_find_targets(widget: &gtk::Widget, targets: &mut Vec<FindTarget>) {
+    if let Some(label) = widget.downcast_ref::<gtk::Label>() {
+        // Button and caption chrome isn't selectable, article text is
+        if label.is_selectable() && label.is_visible() {
+            targets.push(FindTarget::Label(label.clone()));
+        }
+        return;
+    }
+    if let Some(view) = widget.downcast_ref::<gtk::TextView>() {
+        targets.push(FindTarget::Code(view.clone()));
+        return;
+    }
+
+    let mut child = widget.first_child();
+    while let Some(c) = child {
+        collect_find_targets(&c, targets);
+        child = c.next_sibling();
+    }
+}
+
+/// Adds what `widget` shows to `blocks`, depth-first. Buttons are left out,
+/// they only make sense on screen.
+fn collect_print_blocks(widget: &gtk::Widget, indent: u32, blocks: &mut Vec<PrintBlock>) {
+    if !widget.is_visible() || widget.is::<gtk::Button>() {
+        return;
+    }
+
+    if let Some(label) = widget.downcast_ref::<gtk::Label>() {
+        if label.text().trim().is_empty() {
+            return;
+        }
+        let markup = if label.uses_markup() {
+            label.label().to_string()
+        } else {
+            encode_text(&label.label()).to_string()
+        };
+        blocks.push(PrintBlock::Text {
+            markup,
+            style: print_text_style(label),
+            indent,
+        });
+        return;
+    }
+    if let Some(view) = widget.downcast_ref::<gtk::TextView>() {
+        let buffer = view.buffer();
+        let (start, end) = buffer.bounds();
+        blocks.push(PrintBlock::Code(
+            buffer.text(&start, &end, false).to_string(),
+        ));
+        return;
+    }
+    if let Some(picture) = widget.downcast_ref::<gtk::Picture>() {
+        if let Some(texture) = picture.paintable().and_downcast::<gtk::gdk::Texture>() {
+            blocks.push(PrintBlock::Image(texture));
+        }
+        return;
+    }
+
+    let children: Vec<gtk::Widget> =
+        std::iter::successors(widget.first_child(), |child| child.next_sibling())
+            .filter(|child| child.is_visible())
+            .collect();
+
+    if let Some(row) = widget.downcast_ref::<gtk::Box>() {
+        if row.orientation() == gtk::Orientation::Horizontal {
+            // A row of labels, like the metadata, prints as one line
+            let labels: Option<Vec<gtk::Label>> = children
+                .iter()
+                .map(|child| child.clone().downcast::<gtk::Label>().ok())
+                .collect();
+            if let Some(labels) = labels.filter(|labels| labels.len() > 1) {
+                let text: Vec<String> = labels
+                    .iter()
+                    .map(|label| label.text().to_string())
+                    .collect();
+                blocks.push(PrintBlock::Text {
+                    markup: encode_text(&text.join(" ")).to_string(),
+                    style: print_text_style(&labels[0]),
+                    indent,
+                });
+                return;
+            }
+
+            // List items put their marker in front of the first paragraph
+            if let Some(marker) = children
+                .first()
+                .and_then(|child| child.downcast_ref::<gtk::Label>())
+                .filter(|label| label.has_css_class("article-list-marker"))
+            {
+                let first = blocks.len();
+                for child in &children[1..] {
+                    collect_print_blocks(child, indent + 1, blocks);
+                }
+                if let Some(PrintBlock::Text { markup, .. }) = blocks.get_mut(first) {
+                    *markup = format!("{} {}", encode_text(&marker.text()), markup);
+                }
+                return;
+            }
+        }
+    }
+
+    let indent = if widget.has_css_class("article-blockquote") {
+        indent + 1
+    } else {
+        indent
+    };
+    for child in &children {
+        collect_print_blocks(child, indent, blocks);
+    }
+}
+
+fn print_text_style(label: &gtk::Label) -> TextStyle {
+    if label.has_css_class("article-title") {
+        return TextStyle::Title;
+    }
+    if let Some(level) = (1..=6).find(|level| label.has_css_class(&format!("article-h{}", level))) {
+        return TextStyle::Heading(level);
+    }
+
+    let caption = [
+        "article-domain",
+        "article-date",
+        "article-reading-time",
+        "article-description",
+        "article-figcaption",
+        "article-table-caption",
+        "dim-label",
+    ];
+    if caption.iter().any(|class| label.has_css_class(class)) {
+        TextStyle::Caption
+    } else {
+        TextStyle::Body
+    }
+}
+
+/// Background and foreground attributes marking `range` of a label.
+fn highlight_attributes(color: &str, range: &Range<usize>) -> Vec<gtk::pango::Attribute> {
+    let Ok(color) = gtk::pango::Color::parse(color) else {
+        return Vec::new();
+    };
+
+    let mut background =
+        gtk::pango::AttrColor::new_background(color.red(), color.green(), color.blue());
+    // Dark text stays readable on the highlight in dark themes too
+    let mut foreground = gtk::pango::AttrColor::new_foreground(0, 0, 0);
+    for attribute in [&mut background, &mut foreground] {
+        attribute.set_start_index(range.start as u32);
+        attribute.set_end_index(range.end as u32);
+    }
+
+    vec![background.into(), foreground.into()]
+}
+
+/// The tags highlighting find matches in a code block, and the current one
+/// on top.
+fn find_tags(buffer: &gtk::TextBuffer) -> (gtk::TextTag, gtk::TextTag) {
+    let table = buffer.tag_table();
+    // Created in this order, so the current match wins where they meet
+    let [match_tag, current_tag] = [
+        ("find-match", MARK_COLOR),
+        ("find-current", FIND_CURRENT_COLOR),
+    ]
+    .map(|(name, color)| {
+        table.lookup(name).unwrap_or_else(|| {
+            let tag = gtk::TextTag::builder()
+                .name(name)
+                .background(color)
+                .foreground("black")
+                .build();
+            table.add(&tag);
+            tag
+        })
+    });
+    (match_tag, current_tag)
+}
+
+/// Whether a link leads to a web page rather than somewhere in the article.
+fn is_web_link(uri: &str) -> bool {
+    Url::parse(uri).is_ok_and(|url| matches!(url.scheme(), "http" | "https"))
+}
+
+fn is_skipped(element: ElementRef) -> bool {
+    let name = element.value().name();
+    // A details summary becomes the expander label
+    let is_summary = name == "summary"
+        && element
+            .parent()
+            .and_then(ElementRef::wrap)
+            .is_some_and(|parent| parent.value().name() == "details");
+
+    // KaTeX draws formulas twice, the MathML copy is the one we read
+    let is_katex_drawing = element.value().classes().any(|c| c == "katex-html")
+        && element
+            .prev_siblings()
+            .filter_map(ElementRef::wrap)
+            .any(|sibling| sibling.value().classes().any(|c| c == "katex-mathml"));
+
+    is_summary
+        || is_katex_drawing
+        || (SKIPPED_ELEMENTS.contains(&name) && !math::is_tex_script(element))
+}
+
+pub struct ArticleRendererWidgets {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::article::reader_style::ReaderTheme;
+    use crate::testing::ComponentTester;
+    use gtk::prelude::*;
+
+    #[gtk::test]
+    fn test_init_component() {
+        let tester = ComponentTester::<ArticleRenderer>::launch(());
+
+        // Verify the root widget is a ScrolledWindow
+        let root = tester.widget();
+        assert_eq!(root.type_().name(), "GtkScrolledWindow");
+    }
+
+    #[gtk::test]
+    fn test_set_title() {
+        let tester = ComponentTester::<ArticleRenderer>::launch(());
+
+        // Send SetTitle message
+        tester.send_input(ArticleRendererInput::SetTitle(
+            "Test Article Title".to_string(),
+        ));
+        tester.process_events();
+
+        // Deep assertions: verify the title label was updated and is visible
+        let title_label = tester.find_label_by_css_class("article-title");
+        assert!(title_label.is_some(), "Title label should exist");
+
+        let title_label = title_label.unwrap();
+        assert_eq!(title_label.text().as_str(), "Test Article Title");
+        assert!(title_label.is_visible(), "Title label should be visible");
+    }
+
+    #[gtk::test]
+    fn test_set_metadata() {
+        let tester = ComponentTester::<ArticleRenderer>::launch(());
+
+        // Send SetMetadata message
+        tester.send_input(ArticleRendererInput::SetMetadata {
+            url: "https://www.example.com/article".to_string(),
+            description: "A sample article description".to_string(),
+            time: 1234567890.0,
+            word_count: None,
+        });
+        tester.process_events();
+
+        // Deep assertions: verify metadata box is visible
+        let metadata_box = tester.find_widget_by_css_class("article-metadata");
+        assert!(metadata_box.is_some(), "Metadata box should exist");
+        assert!(
+            metadata_box.unwrap().is_visible(),
+            "Metadata box should be visible"
+        );
+
+        // Verify domain label exists and contains the domain
+        let domain_label = tester.find_label_by_css_class("article-domain");
+        assert!(domain_label.is_some(), "Domain label should exist");
+        assert_eq!(domain_label.unwrap().text().as_str(), "example.com");
+
+        // Verify date label exists
+        let date_label = tester.find_label_by_css_class("article-date");
+        assert!(date_label.is_some(), "Date label should exist");
+
+        // Verify description label exists and has the correct text
+        let desc_label = tester.find_label_by_css_class("article-description");
         assert!(desc_label.is_some(), "Description label should exist");
         assert_eq!(
             desc_label.unwrap().text().as_str(),
@@ -725,6 +2568,73 @@ mod tests {
         );
     }
 
+    #[gtk::test]
+    fn test_set_word_count_shows_reading_time() {
+        let tester = ComponentTester::<ArticleRenderer>::launch(());
+
+        tester.send_input(ArticleRendererInput::SetMetadata {
+            url: "https://example.com/article".to_string(),
+            description: String::new(),
+            time: 1234567890.0,
+            word_count: None,
+        });
+        tester.process_events();
+
+        // No reading time until the body has been counted
+        assert!(!tester.has_widget_with_css_class("article-reading-time"));
+
+        tester.send_input(ArticleRendererInput::SetWordCount(1000));
+        tester.process_events();
+
+        let reading_time = tester
+            .find_label_by_css_class("article-reading-time")
+            .expect("Reading time label should exist");
+        assert_eq!(reading_time.text().as_str(), "1000 words · 5 min read");
+
+        // Changing the reading speed updates the estimate in place
+        tester.send_input(ArticleRendererInput::SetWordsPerMinute(250));
+        tester.process_events();
+
+        let reading_time = tester
+            .find_label_by_css_class("article-reading-time")
+            .expect("Reading time label should exist");
+        assert_eq!(reading_time.text().as_str(), "1000 words · 4 min read");
+    }
+
+    #[gtk::test]
+    fn test_set_style_updates_line_width() {
+        let tester = ComponentTester::<ArticleRenderer>::launch(());
+
+        tester.send_input(ArticleRendererInput::SetStyle(ReaderStyle {
+            font_family: "sans-serif".to_string(),
+            font_size: 14,
+            line_width: 640,
+            line_height: 1.5,
+            theme: ReaderTheme::Sepia,
+        }));
+        tester.process_events();
+
+        let clamp = tester.find_widget_by_type::<adw::Clamp>();
+        assert!(clamp.is_some(), "Content should be wrapped in a clamp");
+        assert_eq!(clamp.unwrap().maximum_size(), 640);
+        assert_eq!(tester.model().style.font_size, 14);
+        assert!(tester.widget().has_css_class("article-view"));
+    }
+
+    #[gtk::test]
+    fn test_auto_load_images_skips_load_button() {
+        let tester = ComponentTester::<ArticleRenderer>::launch(());
+
+        tester.send_input(ArticleRendererInput::SetImageLoading(ImageLoading::Always));
+        tester.send_input(ArticleRendererInput::SetContent(
+            r#"<html><body><img src="http://127.0.0.1:9/missing.png"></body></html>"#.to_string(),
+        ));
+        tester.process_events();
+
+        assert!(tester.find_widget_by_type::<gtk::Button>().is_none());
+        assert!(tester.has_widget_with_css_class("article-image"));
+    }
+
     #[test]
     fn test_extract_domain() {
         // Test normal URL
@@ -836,6 +2746,7 @@ mod tests {
             url: "https://example.com".to_string(),
             description: "Description here".to_string(),
             time: 1234567890.0,
+            word_count: None,
         });
         tester.send_input(ArticleRendererInput::SetContent(
             "<h1>Header</h1><p>Content</p>".to_string(),
@@ -926,6 +2837,773 @@ mod tests {
         assert!(first_para.uses_markup(), "Paragraph should use markup");
     }
 
+    #[gtk::test]
+    fn test_set_content_with_table() {
+        let tester = ComponentTester::<ArticleRenderer>::launch(());
+
+        let html = r#"
+            <table>
+                <caption>Quarterly results</caption>
+                <thead><tr><th>Quarter</th><th colspan="2">Revenue</th></tr></thead>
+                <tbody>
+                    <tr><td rowspan="2">Q1</td><td><strong>10</strong></td><td>12</td></tr>
+                    <tr><td>11</td><td>13</td></tr>
+                </tbody>
+            </table>
+        "#;
+
+        tester.send_input(ArticleRendererInput::SetContent(html.to_string()));
+        tester.process_events();
+
+        let grid = tester
+            .find_widget_by_type::<gtk::Grid>()
+            .expect("Table should be rendered as a grid");
+
+        let headers = tester.find_all_widgets_by_css_class("article-table-header");
+        assert_eq!(headers.len(), 2, "Should have two header cells");
+
+        let cells = tester.find_all_widgets_by_css_class("article-table-cell");
+        assert_eq!(cells.len(), 7);
+
+        // The spanning header covers the second and third columns
+        let revenue = grid
+            .child_at(2, 0)
+            .and_then(|w| w.downcast::<gtk::Label>().ok())
+            .expect("Revenue header should span two columns");
+        assert_eq!(revenue.text().as_str(), "Revenue");
+
+        // The second body row starts after the cell spanning down from the first
+        let shifted = grid
+            .child_at(1, 2)
+            .and_then(|w| w.downcast::<gtk::Label>().ok())
+            .unwrap();
+        assert_eq!(shifted.text().as_str(), "11");
+
+        // Inline formatting is kept
+        let bold = grid
+            .child_at(1, 1)
+            .and_then(|w| w.downcast::<gtk::Label>().ok())
+            .unwrap();
+        assert!(bold.uses_markup());
+        assert_eq!(bold.label().as_str(), "<b>10</b>");
+
+        let caption = tester
+            .find_label_by_css_class("article-table-caption")
+            .expect("Caption should be rendered");
+        assert_eq!(caption.text().as_str(), "Quarterly results");
+
+        assert!(
+            tester
+                .find_all_widgets_by_type::<gtk::ScrolledWindow>()
+                .iter()
+                .any(|w| w.hscrollbar_policy() == gtk::PolicyType::Automatic),
+            "Table should scroll horizontally"
+        );
+    }
+
+    fn label_texts(tester: &ComponentTester<ArticleRenderer>, css_class: &str) -> Vec<String> {
+        tester
+            .find_all_widgets_by_css_class(css_class)
+            .into_iter()
+            .filter_map(|w| w.downcast::<gtk::Label>().ok())
+            .map(|label| label.text().to_string())
+            .collect()
+    }
+
+    #[gtk::test]
+    fn test_set_content_descends_into_containers() {
+        let tester = ComponentTester::<ArticleRenderer>::launch(());
+
+        let html = r#"
+            <div class="post">
+                <section>
+                    Loose   text with <b>bold</b>
+                    <p>A paragraph</p>
+                </section>
+                <hr>
+                <article><div><div>Deeply nested</div></div></article>
+                <script>var hidden = true;</script>
+            </div>
+        "#;
+
+        tester.send_input(ArticleRendererInput::SetContent(html.to_string()));
+        tester.process_events();
+
+        assert_eq!(
+            label_texts(&tester, "article-text"),
+            vec!["Loose text with bold", "A paragraph", "Deeply nested"]
+        );
+        assert!(tester.has_widget_with_css_class("article-separator"));
+    }
+
+    #[gtk::test]
+    fn test_set_content_with_nested_lists() {
+        let tester = ComponentTester::<ArticleRenderer>::launch(());
+
+        let html = r#"
+            <ol start="3">
+                <li>Three
+                    <ul><li>Inner</li><li>Other</li></ul>
+                </li>
+                <li>Four</li>
+            </ol>
+            <ol reversed><li>Two</li><li>One</li></ol>
+        "#;
+
+        tester.send_input(ArticleRendererInput::SetContent(html.to_string()));
+        tester.process_events();
+
+        assert_eq!(
+            label_texts(&tester, "article-list-marker"),
+            vec!["3.", "◦", "◦", "4.", "2.", "1."]
+        );
+        assert_eq!(
+            tester
+                .find_all_widgets_by_css_class("article-list-nested")
+                .len(),
+            1
+        );
+        // Nested items are not flattened into their parent
+        assert!(tester.find_label_with_text("Three").is_some());
+        assert!(tester.find_label_with_text("Inner").is_some());
+    }
+
+    #[gtk::test]
+    fn test_set_content_with_definition_list_and_details() {
+        let tester = ComponentTester::<ArticleRenderer>::launch(());
+
+        let html = r#"
+            <dl>
+                <dt>Term</dt>
+                <dd>Definition</dd>
+            </dl>
+            <details>
+                <summary>Show more</summary>
+                <p>Hidden content</p>
+            </details>
+        "#;
+
+        tester.send_input(ArticleRendererInput::SetContent(html.to_string()));
+        tester.process_events();
+
+        assert_eq!(
+            label_texts(&tester, "article-definition-term"),
+            vec!["Term"]
+        );
+        assert!(tester.find_label_with_text("Definition").is_some());
+
+        let expander = tester
+            .find_widget_by_type::<gtk::Expander>()
+            .expect("Details should be rendered as an expander");
+        assert!(!expander.is_expanded());
+        assert_eq!(
+            label_texts(&tester, "article-details-summary"),
+            vec!["Show more"]
+        );
+        assert!(tester.find_label_with_text("Hidden content").is_some());
+    }
+
+    #[gtk::test]
+    fn test_set_content_with_code_block() {
+        let tester = ComponentTester::<ArticleRenderer>::launch(());
+
+        tester.send_input(ArticleRendererInput::SetContent(
+            r#"<pre><code class="language-rust">fn main() {
+    let answer = 42;
+}
+</code></pre>"#
+                .to_string(),
+        ));
+        tester.process_events();
+
+        let language = tester
+            .find_label_by_css_class("article-code-language")
+            .unwrap();
+        assert_eq!(language.text().as_str(), "Rust");
+
+        let text_view = tester.find_widget_by_type::<gtk::TextView>().unwrap();
+        let buffer = text_view.buffer();
+        // The trailing newline of the block is dropped
+        assert_eq!(
+            buffer
+                .text(&buffer.start_iter(), &buffer.end_iter(), false)
+                .as_str(),
+            "fn main() {\n    let answer = 42;\n}"
+        );
+
+        let keyword = buffer.tag_table().lookup("code-keyword").unwrap();
+        assert!(buffer.iter_at_offset(0).has_tag(&keyword));
+        assert!(!buffer.iter_at_offset(3).has_tag(&keyword));
+
+        // Long lines scroll until wrapping is turned on
+        assert_eq!(text_view.wrap_mode(), gtk::WrapMode::None);
+        let wrap_button = tester.find_widget_by_type::<gtk::ToggleButton>().unwrap();
+        wrap_button.set_active(true);
+        assert_eq!(text_view.wrap_mode(), gtk::WrapMode::WordChar);
+    }
+
+    #[gtk::test]
+    fn test_set_content_with_figure() {
+        let tester = ComponentTester::<ArticleRenderer>::launch(());
+
+        tester.send_input(ArticleRendererInput::SetMetadata {
+            url: "https://example.com/posts/cats.html".to_string(),
+            description: String::new(),
+            time: 0.0,
+            word_count: None,
+        });
+        tester.send_input(ArticleRendererInput::SetContent(
+            r#"<html><body>
+                <figure>
+                    <img src="data:image/gif;base64,R0lGOD" data-src="cat.jpg" alt="A sleeping cat">
+                    <figcaption>My cat, <em>asleep</em></figcaption>
+                </figure>
+            </body></html>"#
+                .to_string(),
+        ));
+        tester.process_events();
+
+        assert!(tester.has_widget_with_css_class("article-figure"));
+
+        let caption = tester
+            .find_label_by_css_class("article-figcaption")
+            .expect("Caption should be rendered beneath the image");
+        assert_eq!(caption.text().as_str(), "My cat, asleep");
+
+        let button = tester
+            .find_widget_by_type::<gtk::Button>()
+            .expect("Lazy-loaded image should get a load button");
+        assert_eq!(
+            button.tooltip_text().as_deref(),
+            Some("A sleeping cat"),
+            "Alt text should describe the image"
+        );
+
+        // The image is part of the lightbox gallery before it even loads
+        let gallery = tester.model().gallery.borrow().clone();
+        assert_eq!(gallery.len(), 1);
+        assert_eq!(gallery[0].url, "https://example.com/posts/cat.jpg");
+        assert_eq!(gallery[0].alt.as_deref(), Some("A sleeping cat"));
+        assert!(gallery[0].texture.is_none());
+    }
+
+    #[gtk::test]
+    fn test_image_without_usable_source() {
+        let tester = ComponentTester::<ArticleRenderer>::launch(());
+
+        // Relative URLs can't be fetched without knowing the article URL
+        tester.send_input(ArticleRendererInput::SetContent(
+            r#"<html><body><img src="cat.jpg"></body></html>"#.to_string(),
+        ));
+        tester.process_events();
+
+        assert!(tester.has_widget_with_css_class("article-image"));
+        assert!(tester.find_widget_by_type::<gtk::Button>().is_none());
+    }
+
+    #[gtk::test]
+    fn test_set_content_with_embeds() {
+        let tester = ComponentTester::<ArticleRenderer>::launch(());
+
+        tester.send_input(ArticleRendererInput::SetMetadata {
+            url: "https://example.com/posts/music.html".to_string(),
+            description: String::new(),
+            time: 0.0,
+            word_count: None,
+        });
+        tester.send_input(ArticleRendererInput::SetContent(
+            r#"<html><body>
+                <p>Watch this:</p>
+                <iframe src="https://www.youtube.com/embed/abc123" title="Live set">Frame text</iframe>
+                <audio controls src="episode.mp3">Your browser does not support audio</audio>
+                <iframe src="https://ads.example.com/pixel" width="1" height="1"></iframe>
+                <blockquote class="twitter-tweet">
+                    <p>Great show tonight</p>
+                    <a href="https://twitter.com/band/status/42">June 1, 2024</a>
+                </blockquote>
+            </body></html>"#
+                .to_string(),
+        ));
+        tester.process_events();
+
+        assert_eq!(
+            tester.find_all_widgets_by_css_class("article-embed").len(),
+            3
+        );
+
+        assert_eq!(
+            label_texts(&tester, "article-embed-provider"),
+            ["YouTube", "example.com", "Twitter"]
+        );
+        assert_eq!(
+            label_texts(&tester, "article-embed-title"),
+            [
+                "Live set",
+                "https://example.com/posts/episode.mp3",
+                "Great show tonight"
+            ]
+        );
+
+        // Only the audio can be played in place
+        let play_icon = Some("media-playback-start-symbolic".into());
+        let play_buttons = tester
+            .find_all_widgets_by_type::<gtk::Button>()
+            .into_iter()
+            .filter(|button| button.icon_name() == play_icon)
+            .count();
+        assert_eq!(play_buttons, 1);
+
+        // Neither frame text nor fallback content leaks into the article
+        assert!(tester.find_label_containing_text("Frame text").is_none());
+        assert!(tester
+            .find_label_containing_text("does not support audio")
+            .is_none());
+        assert!(tester.find_label_with_text("Watch this:").is_some());
+    }
+
+    #[gtk::test]
+    fn test_set_content_hides_unplayable_media() {
+        let tester = ComponentTester::<ArticleRenderer>::launch(());
+
+        tester.send_input(ArticleRendererInput::SetContent(
+            r#"<html><body>
+                <p>Before</p>
+                <video controls>Your browser does not support the video tag</video>
+                <audio data-src="lazy.mp3">Your browser does not support audio</audio>
+                <p>After</p>
+            </body></html>"#
+                .to_string(),
+        ));
+        tester.process_events();
+
+        assert!(tester
+            .find_all_widgets_by_css_class("article-embed")
+            .is_empty());
+        assert!(tester
+            .find_label_containing_text("does not support")
+            .is_none());
+        assert!(tester.find_label_with_text("Before").is_some());
+        assert!(tester.find_label_with_text("After").is_some());
+    }
+
+    #[gtk::test]
+    fn test_set_content_with_inline_formatting() {
+        let tester = ComponentTester::<ArticleRenderer>::launch(());
+
+        tester.send_input(ArticleRendererInput::SetContent(
+            r#"<p>H<sub>2</sub>O, x<sup>2</sup>, <del>old</del> <ins>new</ins>,
+                <mark>key</mark>, <small>fine print</small>, press <kbd>Ctrl</kbd>,
+                <abbr title="HyperText Markup Language">HTML</abbr>,
+                <q>she said <q>hi</q></q>,
+                <span class="x">a <b>bold <i>move</i></b></span><br>next line</p>"#
+                .to_string(),
+        ));
+        tester.process_events();
+
+        let label = tester
+            .find_label_containing_text("next line")
+            .expect("Paragraph should be rendered");
+        let markup = label.label();
+
+        for expected in [
+            "H<sub>2</sub>O",
+            "x<sup>2</sup>",
+            "<s>old</s> <u>new</u>",
+            ">key</span>",
+            "<small>fine print</small>",
+            "<tt>Ctrl</tt>",
+            "“she said ‘hi’”",
+            "a <b>bold <i>move</i></b>\nnext line",
+        ] {
+            assert!(
+                markup.contains(expected),
+                "{:?} should contain {:?}",
+                markup,
+                expected
+            );
+        }
+
+        assert_eq!(
+            label.tooltip_text().as_deref(),
+            Some("HTML: HyperText Markup Language")
+        );
+    }
+
+    #[gtk::test]
+    fn test_set_content_with_footnotes_and_math() {
+        let tester = ComponentTester::<ArticleRenderer>::launch(());
+
+        tester.send_input(ArticleRendererInput::SetContent(
+            r##"<html><body>
+                <p>Rust is fast<sup id="fnref1"><a href="#fn1">1</a></sup>, as $O(n^2)$ is rare.</p>
+                <math display="block"><msup><mi>e</mi><mi>x</mi></msup></math>
+                <section class="footnotes"><ol>
+                    <li id="fn1"><p>Benchmarks vary. <a href="#fnref1" class="footnote-backref">↩</a></p></li>
+                </ol></section>
+            </body></html>"##
+                .to_string(),
+        ));
+        tester.process_events();
+
+        let paragraph = tester
+            .find_label_containing_text("Rust is fast")
+            .expect("Paragraph should be rendered");
+        let markup = paragraph.label();
+        assert!(markup.contains(r##"<sup><a href="#fn1">1</a></sup>"##));
+        assert!(markup.contains("<i>O</i>(<i>n</i><sup>2</sup>)"));
+
+        // Display math gets a line of its own
+        assert_eq!(label_texts(&tester, "article-math"), ["ex"]);
+
+        let model = tester.model();
+        assert_eq!(model.anchors.footnotes.borrow()["fn1"], "Benchmarks vary.");
+
+        // Both ends of the footnote link can be scrolled to
+        let widgets = model.anchors.widgets.borrow();
+        assert_eq!(widgets["fnref1"], paragraph.upcast::<gtk::Widget>());
+        assert!(widgets.contains_key("fn1"));
+    }
+
+    #[gtk::test]
+    fn test_linked_image_keeps_link() {
+        let tester = ComponentTester::<ArticleRenderer>::launch(());
+
+        tester.send_input(ArticleRendererInput::SetMetadata {
+            url: "https://example.com/posts/rust.html".to_string(),
+            description: String::new(),
+            time: 0.0,
+            word_count: None,
+        });
+        tester.send_input(ArticleRendererInput::SetContent(
+            r#"<html><body>
+                <a href="/full.png"><img src="http://127.0.0.1:9/thumb.png"></a>
+            </body></html>"#
+                .to_string(),
+        ));
+        tester.process_events();
+
+        assert!(tester.has_widget_with_css_class("article-image"));
+        let link = tester
+            .find_label_by_css_class("article-block-link")
+            .expect("Link should follow the image");
+        assert!(link
+            .label()
+            .contains(r#"<a href="https://example.com/full.png">"#));
+    }
+
+    #[gtk::test]
+    fn test_web_link_menu() {
+        let tester = ComponentTester::<ArticleRenderer>::launch(());
+
+        tester.send_input(ArticleRendererInput::SetMetadata {
+            url: "https://example.com/posts/rust.html".to_string(),
+            description: String::new(),
+            time: 0.0,
+            word_count: None,
+        });
+        tester.send_input(ArticleRendererInput::SetContent(
+            r#"<html><body>
+                <p>Read <a href="/about">about us</a> or <a href="mailto:me@example.com">write</a>.</p>
+            </body></html>"#
+                .to_string(),
+        ));
+        tester.process_events();
+
+        // Relative links are resolved against the article
+        let paragraph = tester
+            .find_label_containing_text("about us")
+            .expect("Paragraph should be rendered");
+        let markup = paragraph.label();
+        assert!(markup.contains(r#"<a href="https://example.com/about">"#));
+        assert!(markup.contains(r#"<a href="mailto:me@example.com">"#));
+
+        // Other schemes are left to the default handler
+        assert!(!paragraph.emit_by_name::<bool>("activate-link", &[&"mailto:me@example.com"]));
+        assert!(tester
+            .find_widget_by_css_class("article-link-popover")
+            .is_none());
+
+        assert!(paragraph.emit_by_name::<bool>("activate-link", &[&"https://example.com/about"]));
+        assert!(tester
+            .find_widget_by_css_class("article-link-popover")
+            .is_some());
+
+        let save_button = tester
+            .find_label_with_text("Save to Instapaper")
+            .and_then(|label| label.parent())
+            .and_downcast::<gtk::Button>()
+            .expect("Link menu should offer saving");
+        while tester.try_recv_output().is_some() {}
+        save_button.emit_clicked();
+        tester.process_events();
+
+        match tester.try_recv_output() {
+            Some(ArticleRendererOutput::SaveLink(url)) => {
+                assert_eq!(url, "https://example.com/about")
+            }
+            other => panic!("Expected SaveLink, got {:?}", other),
+        }
+    }
+
+    #[gtk::test]
+    fn test_find_in_article() {
+        let tester = ComponentTester::<ArticleRenderer>::launch(());
+
+        tester.send_input(ArticleRendererInput::SetContent(
+            r#"<html><body>
+                <p>Rust is fast. RUST is safe.</p>
+                <pre><code>fn main() { println!("rust"); }</code></pre>
+            </body></html>"#
+                .to_string(),
+        ));
+        tester.process_events();
+        // Skip the outline of the new content
+        while tester.try_recv_output().is_some() {}
+
+        let find_result = |input| {
+            tester.send_input(input);
+            tester.process_events();
+            match tester.try_recv_output() {
+                Some(ArticleRendererOutput::FindResult { current, total }) => (current, total),
+                other => panic!("Expected FindResult, got {:?}", other),
+            }
+        };
+
+        assert_eq!(
+            find_result(ArticleRendererInput::Find("rust".into())),
+            (1, 3)
+        );
+
+        let paragraph = tester
+            .find_label_containing_text("Rust is fast")
+            .expect("Paragraph should be rendered");
+        assert!(
+            paragraph.attributes().is_some(),
+            "Matches should be highlighted"
+        );
+
+        assert_eq!(find_result(ArticleRendererInput::FindNext), (2, 3));
+        assert_eq!(find_result(ArticleRendererInput::FindNext), (3, 3));
+
+        // The last match is in the code block
+        let code_view: gtk::TextView = tester.find_widget_by_type().unwrap();
+        let buffer = code_view.buffer();
+        let current_tag = buffer.tag_table().lookup("find-current").unwrap();
+        let is_current = || buffer.iter_at_offset(22).has_tag(&current_tag);
+        assert!(is_current());
+
+        // Navigation wraps around both ways
+        assert_eq!(find_result(ArticleRendererInput::FindNext), (1, 3));
+        assert_eq!(find_result(ArticleRendererInput::FindPrevious), (3, 3));
+
+        assert_eq!(
+            find_result(ArticleRendererInput::Find("missing".into())),
+            (0, 0)
+        );
+        assert!(paragraph.attributes().is_none());
+        assert!(!is_current());
+
+        tester.send_input(ArticleRendererInput::Find("safe".into()));
+        tester.send_input(ArticleRendererInput::ClearFind);
+        tester.process_events();
+        assert!(paragraph.attributes().is_none());
+    }
+
+    #[gtk::test]
+    fn test_set_content_reports_outline() {
+        let tester = ComponentTester::<ArticleRenderer>::launch(());
+
+        tester.send_input(ArticleRendererInput::SetContent(
+            r#"<html><body>
+                <h2>Getting <em>started</em></h2>
+                <p>Some text.</p>
+                <h3>Installing</h3>
+                <h2></h2>
+                <h2>Next steps</h2>
+            </body></html>"#
+                .to_string(),
+        ));
+        tester.process_events();
+
+        match tester.try_recv_output() {
+            Some(ArticleRendererOutput::Outline(entries)) => {
+                let titles: Vec<_> = entries
+                    .iter()
+                    .map(|entry| (entry.level, entry.title.as_str()))
+                    .collect();
+                // Empty headings are left out
+                assert_eq!(
+                    titles,
+                    [(2, "Getting started"), (3, "Installing"), (2, "Next steps")]
+                );
+            }
+            other => panic!("Expected the outline, got {:?}", other),
+        }
+        assert_eq!(tester.model().headings.borrow().len(), 3);
+
+        // Out of range headings are ignored
+        tester.send_input(ArticleRendererInput::ScrollToHeading(2));
+        tester.send_input(ArticleRendererInput::ScrollToHeading(10));
+        tester.process_events();
+    }
+
+    #[gtk::test]
+    fn test_listen_highlights_paragraphs() {
+        let tester = ComponentTester::<ArticleRenderer>::launch(());
+
+        tester.send_input(ArticleRendererInput::SetContent(
+            r#"<html><body>
+                <h2>Why   Rust</h2>
+                <p>It is fast.</p>
+                <pre><code>fn main() {}</code></pre>
+                <p>It is safe.</p>
+            </body></html>"#
+                .to_string(),
+        ));
+        tester.process_events();
+        while tester.try_recv_output().is_some() {}
+
+        tester.send_input(ArticleRendererInput::StartListening);
+        tester.process_events();
+
+        match tester.try_recv_output() {
+            Some(ArticleRendererOutput::ListenText(paragraphs)) => {
+                // Code blocks are skipped
+                assert_eq!(paragraphs, ["Why Rust", "It is fast.", "It is safe."]);
+            }
+            other => panic!("Expected the text to listen to, got {:?}", other),
+        }
+
+        let safe = tester.find_label_with_text("It is safe.").unwrap();
+        tester.send_input(ArticleRendererInput::HighlightParagraph(Some(2)));
+        tester.process_events();
+        assert!(safe.has_css_class("article-speaking"));
+
+        tester.send_input(ArticleRendererInput::HighlightParagraph(None));
+        tester.process_events();
+        assert!(!safe.has_css_class("article-speaking"));
+    }
+
+    #[gtk::test]
+    fn test_translation_side_by_side_and_back() {
+        let tester = ComponentTester::<ArticleRenderer>::launch(());
+
+        tester.send_input(ArticleRendererInput::SetContent(
+            r#"<html><body>
+                <p>Bonjour le <b>monde</b>.</p>
+                <pre><code>fn main() {}</code></pre>
+                <p>Au revoir.</p>
+            </body></html>"#
+                .to_string(),
+        ));
+        tester.process_events();
+        while tester.try_recv_output().is_some() {}
+
+        tester.send_input(ArticleRendererInput::StartTranslation);
+        tester.process_events();
+        match tester.try_recv_output() {
+            Some(ArticleRendererOutput::TranslationText(paragraphs)) => {
+                assert_eq!(paragraphs, ["Bonjour le monde.", "Au revoir."]);
+            }
+            other => panic!("Expected the text to translate, got {:?}", other),
+        }
+
+        let original = tester.find_label_with_text("Au revoir.").unwrap();
+        tester.send_input(ArticleRendererInput::ShowTranslation {
+            paragraphs: vec!["Hello <world>.".to_string(), "Goodbye.".to_string()],
+            layout: TranslationLayout::SideBySide,
+        });
+        tester.process_events();
+
+        let translation = tester.find_label_with_text("Goodbye.").unwrap();
+        assert!(translation.has_css_class("article-translation"));
+        assert!(tester.find_label_with_text("Hello <world>.").is_some());
+        let row = original.parent().unwrap();
+        assert_eq!(translation.parent(), Some(row.clone()));
+        assert_eq!(
+            row.parent(),
+            Some(tester.model().content_box.clone().upcast())
+        );
+
+        tester.send_input(ArticleRendererInput::ShowOriginal);
+        tester.process_events();
+
+        assert!(tester.find_label_with_text("Goodbye.").is_none());
+        assert_eq!(
+            original.parent(),
+            Some(tester.model().content_box.clone().upcast())
+        );
+        assert!(tester.model().translated.is_empty());
+    }
+
+    #[gtk::test]
+    fn test_translation_replaces_text() {
+        let tester = ComponentTester::<ArticleRenderer>::launch(());
+
+        tester.send_input(ArticleRendererInput::SetContent(
+            "<html><body><p>Ciao <i>mondo</i></p></body></html>".to_string(),
+        ));
+        tester.send_input(ArticleRendererInput::StartTranslation);
+        tester.process_events();
+        while tester.try_recv_output().is_some() {}
+
+        let label = tester.find_label_with_text("Ciao mondo").unwrap();
+        let markup = label.label();
+
+        tester.send_input(ArticleRendererInput::ShowTranslation {
+            paragraphs: vec!["Hello world".to_string()],
+            layout: TranslationLayout::Replace,
+        });
+        tester.process_events();
+        assert_eq!(label.text(), "Hello world");
+
+        // The original formatting comes back
+        tester.send_input(ArticleRendererInput::ShowOriginal);
+        tester.process_events();
+        assert_eq!(label.label(), markup);
+    }
+
+    #[gtk::test]
+    fn test_print_blocks() {
+        let tester = ComponentTester::<ArticleRenderer>::launch(());
+
+        tester.send_input(ArticleRendererInput::SetTitle("Printing".to_string()));
+        tester.send_input(ArticleRendererInput::SetContent(
+            r#"<html><body>
+                <h2>Paper</h2>
+                <p>Some <b>bold</b> text.</p>
+                <ul><li>Item</li></ul>
+                <blockquote><p>Quoted</p></blockquote>
+                <pre><code>let x = 1;</code></pre>
+            </body></html>"#
+                .to_string(),
+        ));
+        tester.process_events();
+
+        let blocks = tester.model().print_blocks();
+        let text: Vec<(String, TextStyle, u32)> = blocks
+            .iter()
+            .filter_map(|block| match block {
+                PrintBlock::Text {
+                    markup,
+                    style,
+                    indent,
+                } => Some((markup.clone(), *style, *indent)),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(text[0], ("Printing".to_string(), TextStyle::Title, 0));
+        assert!(text.contains(&("Paper".to_string(), TextStyle::Heading(2), 0)));
+        assert!(text.contains(&("Some <b>bold</b> text.".to_string(), TextStyle::Body, 0)));
+        assert!(text.contains(&("• Item".to_string(), TextStyle::Body, 1)));
+        assert!(text.contains(&("Quoted".to_string(), TextStyle::Body, 1)));
+        assert!(matches!(
+            blocks.last(),
+            Some(PrintBlock::Code(code)) if code.contains("let x = 1;")
+        ));
+    }
+
     #[gtk::test]
     fn test_visibility_changes() {
         let tester = ComponentTester::<ArticleRenderer>::launch(());
@@ -950,6 +3628,7 @@ mod tests {
             url: "https://test.com".to_string(),
             description: "Test description".to_string(),
             time: 1234567890.0,
+            word_count: None,
         });
         tester.process_events();
 
@@ -0,0 +1,115 @@
+use relm4::gtk::glib;
+
+/// Slowest and fastest speech rates offered, relative to the voice default.
+pub const MIN_RATE: f64 = 0.5;
+pub const MAX_RATE: f64 = 2.0;
+
+/// Words per minute of espeak-ng at its default rate.
+const ESPEAK_DEFAULT_WPM: f64 = 175.0;
+
+/// The speech synthesizers articles can be read aloud with, driven through
+/// their command line tools so nothing has to be linked in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    /// Speech Dispatcher, with whatever voice the desktop is set up for
+    SpeechDispatcher,
+    /// espeak-ng directly, which works offline without a speech server
+    Espeak,
+}
+
+impl Backend {
+    /// The first synthesizer installed, preferring Speech Dispatcher.
+    pub fn detect() -> Option<Self> {
+        [Backend::SpeechDispatcher, Backend::Espeak]
+            .into_iter()
+            .find(|backend| glib::find_program_in_path(backend.program()).is_some())
+    }
+
+    fn program(&self) -> &'static str {
+        match self {
+            Backend::SpeechDispatcher => "spd-say",
+            Backend::Espeak => "espeak-ng",
+        }
+    }
+
+    /// The command speaking `text` at `rate`, that only exits once done.
+    pub fn speak_command(&self, text: &str, rate: f64) -> Vec<String> {
+        let rate = rate.clamp(MIN_RATE, MAX_RATE);
+        let mut command = vec![self.program().to_string()];
+
+        match self {
+            Backend::SpeechDispatcher => {
+                // spd-say takes -100 to 100 around the voice default
+                let rate = ((rate - 1.0) * 100.0).round() as i32;
+                command.extend(["--wait".to_string(), "--rate".to_string()]);
+                command.push(rate.to_string());
+            }
+            Backend::Espeak => {
+                command.push("-s".to_string());
+                command.push(((ESPEAK_DEFAULT_WPM * rate).round() as u32).to_string());
+            }
+        }
+
+        // Text starting with a dash must not be taken for an option
+        command.push("--".to_string());
+        command.push(text.to_string());
+        command
+    }
+
+    /// The command silencing speech already handed over to the synthesizer,
+    /// for backends that keep talking after the speaking process is gone.
+    pub fn cancel_command(&self) -> Option<Vec<String>> {
+        match self {
+            Backend::SpeechDispatcher => {
+                Some(vec![self.program().to_string(), "--cancel".to_string()])
+            }
+            Backend::Espeak => None,
+        }
+    }
+}
+
+/// Paragraph text as it should be spoken: on one line, or `None` when
+/// there is nothing to say.
+pub fn spoken_text(text: &str) -> Option<String> {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    text.chars().any(char::is_alphanumeric).then_some(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_speech_dispatcher_command() {
+        assert_eq!(
+            Backend::SpeechDispatcher.speak_command("Hello", 1.0),
+            ["spd-say", "--wait", "--rate", "0", "--", "Hello"]
+        );
+        assert_eq!(
+            Backend::SpeechDispatcher.speak_command("-1 degrees", 1.5)[3..],
+            ["50", "--", "-1 degrees"]
+        );
+        // Out of range rates are clamped
+        assert_eq!(Backend::SpeechDispatcher.speak_command("Hi", 9.0)[3], "100");
+    }
+
+    #[test]
+    fn test_espeak_command() {
+        assert_eq!(
+            Backend::Espeak.speak_command("Hello", 1.0),
+            ["espeak-ng", "-s", "175", "--", "Hello"]
+        );
+        assert_eq!(Backend::Espeak.speak_command("Hello", 0.5)[2], "88");
+        assert_eq!(Backend::Espeak.cancel_command(), None);
+    }
+
+    #[test]
+    fn test_spoken_text() {
+        assert_eq!(
+            spoken_text("  Rust is\n   fast. "),
+            Some("Rust is fast.".to_string())
+        );
+        assert_eq!(spoken_text("• — "), None);
+        assert_eq!(spoken_text(""), None);
+    }
+}
@@ -0,0 +1,331 @@
+use gtk::prelude::{BoxExt, ButtonExt, OrientableExt, ToggleButtonExt, WidgetExt};
+use relm4::{gtk, ComponentParts, ComponentSender, RelmWidgetExt, SimpleComponent};
+
+use gettextrs::gettext;
+
+use super::reader_style::{
+    ReaderStyle, ReaderTheme, DEFAULT_FONT_SIZE, LINE_WIDTH_MEDIUM, LINE_WIDTH_NARROW,
+    LINE_WIDTH_WIDE,
+};
+
+/// Popover in the article header to tweak the reader typography. It doesn't
+/// persist anything itself: every change is reported to the parent, which
+/// stores it and sends the resulting style back.
+pub struct ReaderStylePopover {
+    style: ReaderStyle,
+}
+
+#[derive(Debug)]
+pub enum ReaderStylePopoverInput {
+    SetStyle(ReaderStyle),
+    ZoomIn,
+    ZoomOut,
+    ZoomReset,
+    SetFontFamily(&'static str),
+    SetLineHeight(f64),
+    SetLineWidth(u32),
+    SetTheme(ReaderTheme),
+}
+
+#[derive(Debug, Clone)]
+pub enum ReaderStylePopoverOutput {
+    StyleChanged(ReaderStyle),
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for ReaderStylePopover {
+    type Init = ReaderStyle;
+    type Input = ReaderStylePopoverInput;
+    type Output = ReaderStylePopoverOutput;
+
+    view! {
+        gtk::Popover {
+            gtk::Box {
+                set_orientation: gtk::Orientation::Vertical,
+                set_spacing: 12,
+                set_margin_all: 6,
+
+                gtk::Box {
+                    set_orientation: gtk::Orientation::Horizontal,
+                    set_spacing: 6,
+
+                    gtk::Button {
+                        set_icon_name: "zoom-out-symbolic",
+                        set_tooltip_text: Some(&gettext("Smaller Text")),
+                        add_css_class: "flat",
+                        connect_clicked => ReaderStylePopoverInput::ZoomOut,
+                    },
+
+                    gtk::Button {
+                        #[watch]
+                        set_label: &format!("{}%", model.style.zoom_percentage()),
+                        set_tooltip_text: Some(&gettext("Reset Text Size")),
+                        set_hexpand: true,
+                        add_css_class: "flat",
+                        add_css_class: "reader-zoom-level",
+                        connect_clicked => ReaderStylePopoverInput::ZoomReset,
+                    },
+
+                    gtk::Button {
+                        set_icon_name: "zoom-in-symbolic",
+                        set_tooltip_text: Some(&gettext("Larger Text")),
+                        add_css_class: "flat",
+                        connect_clicked => ReaderStylePopoverInput::ZoomIn,
+                    },
+                },
+
+                gtk::Box {
+                    set_orientation: gtk::Orientation::Horizontal,
+                    set_homogeneous: true,
+                    add_css_class: "linked",
+
+                    gtk::ToggleButton {
+                        set_label: &gettext("Serif"),
+                        #[watch]
+                        #[block_signal(serif_handler)]
+                        set_active: model.style.font_family == "serif",
+                        connect_toggled => ReaderStylePopoverInput::SetFontFamily("serif") @serif_handler,
+                    },
+
+                    gtk::ToggleButton {
+                        set_label: &gettext("Sans"),
+                        #[watch]
+                        #[block_signal(sans_handler)]
+                        set_active: model.style.font_family == "sans-serif",
+                        connect_toggled => ReaderStylePopoverInput::SetFontFamily("sans-serif") @sans_handler,
+                    },
+
+                    gtk::ToggleButton {
+                        set_label: &gettext("Mono"),
+                        #[watch]
+                        #[block_signal(mono_handler)]
+                        set_active: model.style.font_family == "monospace",
+                        connect_toggled => ReaderStylePopoverInput::SetFontFamily("monospace") @mono_handler,
+                    },
+                },
+
+                gtk::Box {
+                    set_orientation: gtk::Orientation::Horizontal,
+                    set_spacing: 12,
+
+                    gtk::Label {
+                        set_label: &gettext("Line Height"),
+                        set_hexpand: true,
+                        set_xalign: 0.0,
+                    },
+
+                    gtk::SpinButton {
+                        set_adjustment: &gtk::Adjustment::new(1.5, 1.0, 2.5, 0.1, 0.5, 0.0),
+                        set_digits: 1,
+                        #[watch]
+                        #[block_signal(line_height_handler)]
+                        set_value: model.style.line_height,
+                        connect_value_changed[sender] => move |button| {
+                            sender.input(ReaderStylePopoverInput::SetLineHeight(button.value()));
+                        } @line_height_handler,
+                    },
+                },
+
+                gtk::Box {
+                    set_orientation: gtk::Orientation::Horizontal,
+                    set_homogeneous: true,
+                    add_css_class: "linked",
+
+                    gtk::ToggleButton {
+                        set_label: &gettext("Narrow"),
+                        #[watch]
+                        #[block_signal(narrow_handler)]
+                        set_active: model.style.line_width == LINE_WIDTH_NARROW,
+                        connect_toggled => ReaderStylePopoverInput::SetLineWidth(LINE_WIDTH_NARROW) @narrow_handler,
+                    },
+
+                    gtk::ToggleButton {
+                        set_label: &gettext("Medium"),
+                        #[watch]
+                        #[block_signal(medium_handler)]
+                        set_active: model.style.line_width == LINE_WIDTH_MEDIUM,
+                        connect_toggled => ReaderStylePopoverInput::SetLineWidth(LINE_WIDTH_MEDIUM) @medium_handler,
+                    },
+
+                    gtk::ToggleButton {
+                        set_label: &gettext("Wide"),
+                        #[watch]
+                        #[block_signal(wide_handler)]
+                        set_active: model.style.line_width == LINE_WIDTH_WIDE,
+                        connect_toggled => ReaderStylePopoverInput::SetLineWidth(LINE_WIDTH_WIDE) @wide_handler,
+                    },
+                },
+
+                gtk::Box {
+                    set_orientation: gtk::Orientation::Horizontal,
+                    set_spacing: 12,
+                    set_halign: gtk::Align::Center,
+
+                    gtk::ToggleButton {
+                        set_tooltip_text: Some(&gettext("Follow System")),
+                        add_css_class: "reader-theme-button",
+                        add_css_class: "reader-theme-system",
+                        #[watch]
+                        #[block_signal(system_theme_handler)]
+                        set_active: model.style.theme == ReaderTheme::System,
+                        connect_toggled => ReaderStylePopoverInput::SetTheme(ReaderTheme::System) @system_theme_handler,
+                    },
+
+                    gtk::ToggleButton {
+                        set_tooltip_text: Some(&gettext("Light")),
+                        add_css_class: "reader-theme-button",
+                        add_css_class: "reader-theme-light",
+                        #[watch]
+                        #[block_signal(light_theme_handler)]
+                        set_active: model.style.theme == ReaderTheme::Light,
+                        connect_toggled => ReaderStylePopoverInput::SetTheme(ReaderTheme::Light) @light_theme_handler,
+                    },
+
+                    gtk::ToggleButton {
+                        set_tooltip_text: Some(&gettext("Sepia")),
+                        add_css_class: "reader-theme-button",
+                        add_css_class: "reader-theme-sepia",
+                        #[watch]
+                        #[block_signal(sepia_theme_handler)]
+                        set_active: model.style.theme == ReaderTheme::Sepia,
+                        connect_toggled => ReaderStylePopoverInput::SetTheme(ReaderTheme::Sepia) @sepia_theme_handler,
+                    },
+
+                    gtk::ToggleButton {
+                        set_tooltip_text: Some(&gettext("Dark")),
+                        add_css_class: "reader-theme-button",
+                        add_css_class: "reader-theme-dark",
+                        #[watch]
+                        #[block_signal(dark_theme_handler)]
+                        set_active: model.style.theme == ReaderTheme::Dark,
+                        connect_toggled => ReaderStylePopoverInput::SetTheme(ReaderTheme::Dark) @dark_theme_handler,
+                    },
+
+                    gtk::ToggleButton {
+                        set_tooltip_text: Some(&gettext("Black")),
+                        add_css_class: "reader-theme-button",
+                        add_css_class: "reader-theme-black",
+                        #[watch]
+                        #[block_signal(black_theme_handler)]
+                        set_active: model.style.theme == ReaderTheme::Black,
+                        connect_toggled => ReaderStylePopoverInput::SetTheme(ReaderTheme::Black) @black_theme_handler,
+                    },
+                },
+            },
+        }
+    }
+
+    fn init(
+        style: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let model = Self { style };
+
+        let widgets = view_output!();
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, message: Self::Input, sender: ComponentSender<Self>) {
+        let mut style = self.style.clone();
+
+        match message {
+            ReaderStylePopoverInput::SetStyle(style) => {
+                self.style = style;
+                return;
+            }
+            ReaderStylePopoverInput::ZoomIn => style.font_size = self.style.zoomed_in(),
+            ReaderStylePopoverInput::ZoomOut => style.font_size = self.style.zoomed_out(),
+            ReaderStylePopoverInput::ZoomReset => style.font_size = DEFAULT_FONT_SIZE,
+            ReaderStylePopoverInput::SetFontFamily(family) => {
+                style.font_family = family.to_string()
+            }
+            ReaderStylePopoverInput::SetLineHeight(line_height) => {
+                // Keep the spin button steps, floating point noise would cause
+                // a settings write on every redraw otherwise
+                style.line_height = (line_height * 10.0).round() / 10.0
+            }
+            ReaderStylePopoverInput::SetLineWidth(line_width) => style.line_width = line_width,
+            ReaderStylePopoverInput::SetTheme(theme) => style.theme = theme,
+        }
+
+        // Toggling an already active button only re-syncs the view
+        if style != self.style {
+            self.style = style.clone();
+            let _ = sender.output(ReaderStylePopoverOutput::StyleChanged(style));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::ComponentTester;
+
+    #[gtk::test]
+    fn test_zoom_in_reports_new_style() {
+        let tester = ComponentTester::<ReaderStylePopover>::launch(ReaderStyle::default());
+
+        tester.send_input(ReaderStylePopoverInput::ZoomIn);
+        tester.process_events();
+
+        match tester.try_recv_output() {
+            Some(ReaderStylePopoverOutput::StyleChanged(style)) => {
+                assert_eq!(style.font_size, 14)
+            }
+            None => panic!("Expected a style change"),
+        }
+        assert!(tester.find_label_with_text("117%").is_some());
+    }
+
+    #[gtk::test]
+    fn test_zoom_reset() {
+        let tester = ComponentTester::<ReaderStylePopover>::launch(ReaderStyle {
+            font_size: 20,
+            ..Default::default()
+        });
+
+        tester.send_input(ReaderStylePopoverInput::ZoomReset);
+        tester.process_events();
+
+        assert_eq!(tester.model().style.font_size, DEFAULT_FONT_SIZE);
+        assert!(tester.try_recv_output().is_some());
+    }
+
+    #[gtk::test]
+    fn test_unchanged_style_is_not_reported() {
+        let tester = ComponentTester::<ReaderStylePopover>::launch(ReaderStyle::default());
+
+        tester.send_input(ReaderStylePopoverInput::SetFontFamily("serif"));
+        tester.send_input(ReaderStylePopoverInput::SetTheme(ReaderTheme::System));
+        tester.process_events();
+
+        assert!(tester.try_recv_output().is_none());
+    }
+
+    #[gtk::test]
+    fn test_set_style_from_parent_is_not_reported() {
+        let tester = ComponentTester::<ReaderStylePopover>::launch(ReaderStyle::default());
+
+        tester.send_input(ReaderStylePopoverInput::SetStyle(ReaderStyle {
+            theme: ReaderTheme::Sepia,
+            line_width: LINE_WIDTH_WIDE,
+            ..Default::default()
+        }));
+        tester.process_events();
+
+        assert!(tester.try_recv_output().is_none());
+        assert_eq!(tester.model().style.theme, ReaderTheme::Sepia);
+    }
+
+    #[gtk::test]
+    fn test_line_height_is_rounded() {
+        let tester = ComponentTester::<ReaderStylePopover>::launch(ReaderStyle::default());
+
+        tester.send_input(ReaderStylePopoverInput::SetLineHeight(1.7000000000000002));
+        tester.process_events();
+
+        assert_eq!(tester.model().style.line_height, 1.7);
+    }
+}
@@ -0,0 +1,231 @@
+use scraper::ElementRef;
+
+/// HTML caps spans at these values, anything above is treated as the maximum.
+const MAX_COLSPAN: usize = 1000;
+const MAX_ROWSPAN: usize = 65534;
+
+/// A table cell together with its position in the grid.
+#[derive(Debug)]
+pub struct TableCell<'a> {
+    pub element: ElementRef<'a>,
+    pub row: usize,
+    pub column: usize,
+    pub row_span: usize,
+    pub column_span: usize,
+    pub header: bool,
+}
+
+/// Returns the caption of a table, if any.
+pub fn caption(table: ElementRef) -> Option<ElementRef> {
+    child_elements(table).find(|e| e.value().name() == "caption")
+}
+
+/// Computes the grid position of every cell of a table, resolving `colspan`
+/// and `rowspan` the way browsers do: a cell takes the first column of its
+/// row that isn't already covered by a cell spanning down from above.
+///
+/// Only the rows of `table` itself are laid out. A nested table stays inside
+/// the cell that holds it, whose text includes the nested table's text.
+pub fn layout(table: ElementRef) -> Vec<TableCell> {
+    let rows = rows(table);
+    let row_count = rows.len();
+
+    let mut occupied: Vec<Vec<bool>> = vec![Vec::new(); row_count];
+    let mut cells = Vec::new();
+
+    for (row, (tr, header_row)) in rows.into_iter().enumerate() {
+        let mut column = 0;
+
+        for cell in child_elements(tr).filter(|e| matches!(e.value().name(), "td" | "th")) {
+            while is_occupied(&occupied[row], column) {
+                column += 1;
+            }
+
+            let column_span = span(cell, "colspan", MAX_COLSPAN).unwrap_or(1).max(1);
+            // rowspan="0" stretches the cell down to the last row
+            let row_span = match span(cell, "rowspan", MAX_ROWSPAN) {
+                Some(0) => row_count - row,
+                Some(span) => span.min(row_count - row),
+                None => 1,
+            };
+
+            for covered in occupied.iter_mut().skip(row).take(row_span) {
+                if covered.len() < column + column_span {
+                    covered.resize(column + column_span, false);
+                }
+                for slot in covered.iter_mut().skip(column).take(column_span) {
+                    *slot = true;
+                }
+            }
+
+            cells.push(TableCell {
+                element: cell,
+                row,
+                column,
+                row_span,
+                column_span,
+                header: header_row || cell.value().name() == "th",
+            });
+
+            column += column_span;
+        }
+    }
+
+    cells
+}
+
+/// Collects the rows of a table in display order (head, body, foot), flagging
+/// the ones that belong to `thead`.
+fn rows(table: ElementRef) -> Vec<(ElementRef, bool)> {
+    let mut head = Vec::new();
+    let mut body = Vec::new();
+    let mut foot = Vec::new();
+
+    for child in child_elements(table) {
+        match child.value().name() {
+            "thead" => head.extend(section_rows(child).map(|tr| (tr, true))),
+            "tbody" => body.extend(section_rows(child).map(|tr| (tr, false))),
+            "tfoot" => foot.extend(section_rows(child).map(|tr| (tr, false))),
+            "tr" => body.push((child, false)),
+            _ => {}
+        }
+    }
+
+    head.into_iter().chain(body).chain(foot).collect()
+}
+
+fn section_rows<'a>(section: ElementRef<'a>) -> impl Iterator<Item = ElementRef<'a>> {
+    child_elements(section).filter(|e| e.value().name() == "tr")
+}
+
+fn child_elements<'a>(element: ElementRef<'a>) -> impl Iterator<Item = ElementRef<'a>> {
+    element.children().filter_map(ElementRef::wrap)
+}
+
+fn span(cell: ElementRef, attribute: &str, max: usize) -> Option<usize> {
+    cell.value()
+        .attr(attribute)
+        .and_then(|value| value.trim().parse::<usize>().ok())
+        .map(|span| span.min(max))
+}
+
+fn is_occupied(row: &[bool], column: usize) -> bool {
+    row.get(column).copied().unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scraper::{Html, Selector};
+
+    fn positions(html: &str) -> Vec<(String, usize, usize, usize, usize, bool)> {
+        let document = Html::parse_fragment(html);
+        let selector = Selector::parse("table").unwrap();
+        let table = document.select(&selector).next().unwrap();
+
+        layout(table)
+            .into_iter()
+            .map(|cell| {
+                (
+                    cell.element.text().collect::<String>(),
+                    cell.row,
+                    cell.column,
+                    cell.row_span,
+                    cell.column_span,
+                    cell.header,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_layout_simple_table_with_header() {
+        let cells = positions(
+            "<table><thead><tr><th>A</th><th>B</th></tr></thead>
+             <tbody><tr><td>1</td><td>2</td></tr></tbody></table>",
+        );
+
+        assert_eq!(
+            cells,
+            vec![
+                ("A".to_string(), 0, 0, 1, 1, true),
+                ("B".to_string(), 0, 1, 1, 1, true),
+                ("1".to_string(), 1, 0, 1, 1, false),
+                ("2".to_string(), 1, 1, 1, 1, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_layout_colspan_and_rowspan() {
+        let cells = positions(
+            r#"<table>
+                <tr><td rowspan="2">A</td><td colspan="2">B</td></tr>
+                <tr><td>C</td><td>D</td></tr>
+                <tr><td>E</td><td>F</td><td>G</td></tr>
+            </table>"#,
+        );
+
+        assert_eq!(
+            cells,
+            vec![
+                ("A".to_string(), 0, 0, 2, 1, false),
+                ("B".to_string(), 0, 1, 1, 2, false),
+                // Shifted right by the cell spanning down from the first row
+                ("C".to_string(), 1, 1, 1, 1, false),
+                ("D".to_string(), 1, 2, 1, 1, false),
+                ("E".to_string(), 2, 0, 1, 1, false),
+                ("F".to_string(), 2, 1, 1, 1, false),
+                ("G".to_string(), 2, 2, 1, 1, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_layout_rowspan_clamped_to_table() {
+        let cells = positions(
+            r#"<table>
+                <tr><td rowspan="10">A</td><td>B</td></tr>
+                <tr><td>C</td></tr>
+                <tr><td rowspan="0">D</td></tr>
+            </table>"#,
+        );
+
+        assert_eq!(cells[0].3, 3);
+        assert_eq!(cells[3], ("D".to_string(), 2, 1, 1, 1, false));
+    }
+
+    #[test]
+    fn test_layout_footer_goes_last() {
+        let cells = positions(
+            "<table><tfoot><tr><td>Total</td></tr></tfoot>
+             <tbody><tr><td>Row</td></tr></tbody></table>",
+        );
+
+        assert_eq!(cells[0].0, "Row");
+        assert_eq!(cells[1].0, "Total");
+        assert_eq!(cells[1].1, 1);
+    }
+
+    #[test]
+    fn test_layout_ignores_nested_tables() {
+        let cells = positions(
+            "<table><tr><td>Outer<table><tr><td>Inner</td></tr></table></td></tr></table>",
+        );
+
+        assert_eq!(cells.len(), 1);
+    }
+
+    #[test]
+    fn test_caption() {
+        let document =
+            Html::parse_fragment("<table><caption>Results</caption><tr><td>1</td></tr></table>");
+        let table = document
+            .select(&Selector::parse("table").unwrap())
+            .next()
+            .unwrap();
+
+        let caption = caption(table).unwrap();
+        assert_eq!(caption.text().collect::<String>(), "Results");
+    }
+}
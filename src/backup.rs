@@ -0,0 +1,812 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::{self, Write};
+
+use relm4::gtk::{gio, glib, prelude::SettingsExt};
+use serde::{Deserialize, Serialize};
+
+use crate::export::zip::{self, ZipWriter};
+use crate::network::instapaper::{
+    self, InstapaperBookmark, InstapaperError, InstapaperFolder, InstapaperHighlight,
+};
+use crate::persistence::article_cache::{self, CachedArticle};
+use crate::persistence::articles::PersistedArticle;
+use crate::persistence::token::TokenPair;
+
+/// Version of the archive layout, bumped when older releases could no
+/// longer read it.
+const FORMAT_VERSION: u32 = 1;
+
+const LIBRARY_ENTRY: &str = "library.json";
+const BODIES_DIR: &str = "articles/";
+
+/// Settings left out of backups: credentials, and what only makes sense on
+/// the machine they were set on.
+const EXCLUDED_SETTINGS: &[&str] = &[
+    "access-token",
+    "translation-api-key",
+    "window-width",
+    "window-height",
+    "is-maximized",
+    "markdown-export-folder",
+];
+
+/// Everything a backup holds besides the cached article bodies.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Library {
+    pub version: u32,
+    /// When the backup was made, as RFC 3339
+    pub created: String,
+    pub bookmarks: Vec<BackupBookmark>,
+    #[serde(default)]
+    pub folders: Vec<BackupFolder>,
+    /// Settings in GVariant text format, by key
+    #[serde(default)]
+    pub settings: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupBookmark {
+    #[serde(flatten)]
+    pub article: PersistedArticle,
+    /// How far it was read, between 0 and 1
+    #[serde(default)]
+    pub progress: f64,
+    /// When the progress was saved, in seconds since the epoch
+    #[serde(default)]
+    pub progress_timestamp: i64,
+    #[serde(default)]
+    pub highlights: Vec<BackupHighlight>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackupHighlight {
+    pub text: String,
+    #[serde(default)]
+    pub position: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackupFolder {
+    pub title: String,
+    /// Bookmarks filed in the folder
+    #[serde(default)]
+    pub bookmark_ids: Vec<String>,
+}
+
+pub struct Backup {
+    pub library: Library,
+    /// Cached article bodies, by bookmark id
+    pub bodies: Vec<(String, CachedArticle)>,
+    /// Whether Instapaper couldn't give all the progress, folders or highlights
+    pub incomplete: bool,
+}
+
+/// What a restore added to the library.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RestoreReport {
+    pub bookmarks: usize,
+    pub bodies: usize,
+    pub settings: usize,
+    pub progress: usize,
+    pub highlights: usize,
+    pub folders: usize,
+    /// Whether Instapaper stopped taking the rest
+    pub incomplete: bool,
+}
+
+impl From<PersistedArticle> for BackupBookmark {
+    fn from(article: PersistedArticle) -> Self {
+        Self {
+            article,
+            progress: 0.0,
+            progress_timestamp: 0,
+            highlights: Vec::new(),
+        }
+    }
+}
+
+impl From<InstapaperHighlight> for BackupHighlight {
+    fn from(highlight: InstapaperHighlight) -> Self {
+        Self {
+            text: highlight.text,
+            position: highlight.position,
+        }
+    }
+}
+
+/// Gathers the library: the bookmarks and their cached bodies from disk, and
+/// with `tokens` the reading progress, folders and highlights from Instapaper.
+pub async fn collect(
+    articles: Vec<PersistedArticle>,
+    settings: BTreeMap<String, String>,
+    tokens: Option<&TokenPair>,
+    mut on_progress: impl FnMut(usize, usize),
+) -> Backup {
+    let bodies = articles
+        .iter()
+        .filter_map(|article| {
+            article_cache::read(&article.item_id).map(|body| (article.item_id.clone(), body))
+        })
+        .collect();
+    let mut bookmarks: Vec<BackupBookmark> =
+        articles.into_iter().map(BackupBookmark::from).collect();
+    let mut folders = Vec::new();
+    let mut incomplete = false;
+
+    if let Some(tokens) = tokens {
+        let client = instapaper::client();
+
+        match instapaper::get_bookmarks(&client, tokens, instapaper::BASE_URL).await {
+            Ok(remote) => {
+                let remote: HashMap<String, InstapaperBookmark> = remote
+                    .into_iter()
+                    .map(|bookmark| (bookmark.bookmark_id.to_string(), bookmark))
+                    .collect();
+                for bookmark in &mut bookmarks {
+                    if let Some(remote) = remote.get(&bookmark.article.item_id) {
+                        bookmark.progress = remote.progress;
+                        bookmark.progress_timestamp = progress_timestamp(remote);
+                    }
+                }
+            }
+            Err(_) => incomplete = true,
+        }
+
+        match instapaper::get_folders(&client, tokens, instapaper::BASE_URL).await {
+            Ok(remote) => {
+                for folder in remote {
+                    let bookmark_ids = match instapaper::get_folder_bookmarks(
+                        &client,
+                        tokens,
+                        folder.folder_id,
+                        instapaper::BASE_URL,
+                    )
+                    .await
+                    {
+                        Ok(bookmarks) => bookmarks
+                            .iter()
+                            .map(|bookmark| bookmark.bookmark_id.to_string())
+                            .collect(),
+                        Err(_) => {
+                            incomplete = true;
+                            Vec::new()
+                        }
+                    };
+                    folders.push(BackupFolder {
+                        title: folder.title,
+                        bookmark_ids,
+                    });
+                }
+            }
+            Err(_) => incomplete = true,
+        }
+
+        let total = bookmarks.len();
+        for (done, bookmark) in bookmarks.iter_mut().enumerate() {
+            on_progress(done, total);
+            let Ok(bookmark_id) = bookmark.article.item_id.parse() else {
+                continue;
+            };
+            match instapaper::get_highlights(&client, tokens, bookmark_id, instapaper::BASE_URL)
+                .await
+            {
+                Ok(highlights) => {
+                    bookmark.highlights = highlights.into_iter().map(Into::into).collect();
+                }
+                Err(InstapaperError::RateLimited | InstapaperError::InvalidCredentials) => {
+                    incomplete = true;
+                    break;
+                }
+                Err(_) => incomplete = true,
+            }
+        }
+        on_progress(total, total);
+    }
+
+    Backup {
+        library: Library {
+            version: FORMAT_VERSION,
+            created: chrono::Utc::now().to_rfc3339(),
+            bookmarks,
+            folders,
+            settings,
+        },
+        bodies,
+        incomplete,
+    }
+}
+
+/// Writes the backup as a zip archive: the library as JSON, and a file per
+/// cached article body.
+pub fn write<W: Write>(backup: &Backup, writer: W) -> io::Result<W> {
+    let mut zip = ZipWriter::new(writer);
+    zip.add_file(LIBRARY_ENTRY, &serde_json::to_vec_pretty(&backup.library)?)?;
+    for (item_id, body) in &backup.bodies {
+        zip.add_file(
+            &format!("{}{}.json", BODIES_DIR, safe_id(item_id)),
+            &serde_json::to_vec(body)?,
+        )?;
+    }
+    zip.finish()
+}
+
+/// Reads back an archive made by [`write`].
+pub fn read(bytes: &[u8]) -> io::Result<Backup> {
+    let mut library: Option<Library> = None;
+    let mut bodies = Vec::new();
+
+    for (name, contents) in zip::read_entries(bytes)? {
+        if name == LIBRARY_ENTRY {
+            library = Some(serde_json::from_slice(&contents)?);
+        } else if let Some(item_id) = name
+            .strip_prefix(BODIES_DIR)
+            .and_then(|name| name.strip_suffix(".json"))
+        {
+            // A damaged body only costs a download later
+            if let Ok(body) = serde_json::from_slice(&contents) {
+                bodies.push((safe_id(item_id), body));
+            }
+        }
+    }
+
+    let library = library
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Not a Cauldron backup"))?;
+    if library.version > FORMAT_VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "The backup was made by a newer version of Cauldron",
+        ));
+    }
+
+    Ok(Backup {
+        library,
+        bodies,
+        incomplete: false,
+    })
+}
+
+/// The backed up bookmarks missing from the library, in backup order.
+pub fn missing_bookmarks(library: &Library, existing: &[String]) -> Vec<PersistedArticle> {
+    let existing: HashSet<&str> = existing.iter().map(String::as_str).collect();
+    library
+        .bookmarks
+        .iter()
+        .filter(|bookmark| !existing.contains(bookmark.article.item_id.as_str()))
+        .map(|bookmark| bookmark.article.clone())
+        .collect()
+}
+
+/// Writes the backed up bodies that aren't cached yet, returning how many.
+pub fn restore_bodies(bodies: &[(String, CachedArticle)]) -> usize {
+    let mut restored = 0;
+    for (item_id, body) in bodies {
+        if article_cache::read(item_id).is_none() && article_cache::write(item_id, body).is_ok() {
+            restored += 1;
+        }
+    }
+    restored
+}
+
+/// The settings to back up, in GVariant text format by key.
+pub fn settings_snapshot(settings: &gio::Settings) -> BTreeMap<String, String> {
+    let Some(schema) = settings.settings_schema() else {
+        return BTreeMap::new();
+    };
+    schema
+        .list_keys()
+        .into_iter()
+        .filter(|key| !EXCLUDED_SETTINGS.contains(&key.as_str()))
+        .map(|key| {
+            (
+                key.to_string(),
+                settings.value(&key).print(true).to_string(),
+            )
+        })
+        .collect()
+}
+
+/// Applies the backed up settings the user hasn't changed here, returning
+/// how many were applied.
+pub fn restore_settings(settings: &gio::Settings, saved: &BTreeMap<String, String>) -> usize {
+    let Some(schema) = settings.settings_schema() else {
+        return 0;
+    };
+    saved
+        .iter()
+        .filter(|(key, _)| !EXCLUDED_SETTINGS.contains(&key.as_str()) && schema.has_key(key))
+        .filter(|(key, _)| settings.user_value(key).is_none())
+        .filter_map(|(key, text)| {
+            let value_type = schema.key(key).value_type();
+            glib::Variant::parse(Some(&*value_type), text)
+                .ok()
+                .map(|value| (key, value))
+        })
+        .filter(|(key, value)| settings.set_value(key, value).is_ok())
+        .count()
+}
+
+/// Sends Instapaper what it lacks compared to the backup: the bookmarks
+/// themselves, then their reading progress, highlights and folders, adding
+/// them up in `report`. Returns the ids the bookmarks have on Instapaper, by
+/// backed up id, for those whose id changed.
+pub async fn restore_to_instapaper(
+    library: &Library,
+    tokens: &TokenPair,
+    report: &mut RestoreReport,
+    mut on_progress: impl FnMut(usize, usize),
+) -> HashMap<String, String> {
+    let client = instapaper::client();
+    let mut remote = match instapaper::get_bookmarks(&client, tokens, instapaper::BASE_URL).await {
+        Ok(remote) => remote,
+        Err(_) => {
+            report.incomplete = true;
+            return HashMap::new();
+        }
+    };
+
+    let result = async {
+        let missing = missing_remote(library, &remote);
+        let highlighted: Vec<&BackupBookmark> = library
+            .bookmarks
+            .iter()
+            .filter(|bookmark| !bookmark.highlights.is_empty())
+            .collect();
+        let total = missing.len() + highlighted.len();
+
+        for (done, bookmark) in missing.iter().enumerate() {
+            on_progress(done, total);
+            let added = instapaper::add_bookmark(
+                &client,
+                tokens,
+                &bookmark.article.uri,
+                &bookmark.article.title,
+                &bookmark.article.tags,
+                instapaper::BASE_URL,
+            )
+            .await?;
+            remote.push(added);
+        }
+
+        let matched = match_remote(library, &remote);
+
+        for (bookmark_id, progress, timestamp) in progress_updates(library, &matched) {
+            instapaper::update_read_progress(
+                &client,
+                tokens,
+                bookmark_id,
+                progress,
+                timestamp,
+                instapaper::BASE_URL,
+            )
+            .await?;
+            report.progress += 1;
+        }
+
+        for (done, bookmark) in highlighted.iter().enumerate() {
+            on_progress(missing.len() + done, total);
+            let Some(bookmark_id) = matched
+                .get(&bookmark.article.item_id)
+                .map(|remote| remote.bookmark_id)
+            else {
+                continue;
+            };
+            let existing =
+                instapaper::get_highlights(&client, tokens, bookmark_id, instapaper::BASE_URL)
+                    .await?;
+            for highlight in missing_highlights(&bookmark.highlights, &existing) {
+                instapaper::add_highlight(
+                    &client,
+                    tokens,
+                    bookmark_id,
+                    &highlight.text,
+                    highlight.position,
+                    instapaper::BASE_URL,
+                )
+                .await?;
+                report.highlights += 1;
+            }
+        }
+        on_progress(total, total);
+
+        let folders = instapaper::get_folders(&client, tokens, instapaper::BASE_URL).await?;
+        for folder in missing_folders(library, &folders) {
+            let created =
+                instapaper::add_folder(&client, tokens, &folder.title, instapaper::BASE_URL)
+                    .await?;
+            report.folders += 1;
+            for remote in folder.bookmark_ids.iter().filter_map(|id| matched.get(id)) {
+                instapaper::move_bookmark(
+                    &client,
+                    tokens,
+                    remote.bookmark_id,
+                    created.folder_id,
+                    instapaper::BASE_URL,
+                )
+                .await?;
+            }
+        }
+
+        Ok::<_, InstapaperError>(())
+    }
+    .await;
+
+    if result.is_err() {
+        report.incomplete = true;
+    }
+
+    match_remote(library, &remote)
+        .into_iter()
+        .map(|(item_id, remote)| (item_id, remote.bookmark_id.to_string()))
+        .filter(|(item_id, bookmark_id)| item_id != bookmark_id)
+        .collect()
+}
+
+/// Gives the backed up bookmarks, folders and bodies the ids Instapaper
+/// knows them by, so the next refresh keeps them.
+pub fn rename_bookmarks(
+    library: &mut Library,
+    bodies: &mut [(String, CachedArticle)],
+    renamed: &HashMap<String, String>,
+) {
+    let rename = |item_id: &mut String| {
+        if let Some(new_id) = renamed.get(item_id.as_str()) {
+            *item_id = new_id.clone();
+        }
+    };
+    for bookmark in &mut library.bookmarks {
+        rename(&mut bookmark.article.item_id);
+    }
+    for folder in &mut library.folders {
+        folder.bookmark_ids.iter_mut().for_each(rename);
+    }
+    for (item_id, _) in bodies {
+        rename(item_id);
+    }
+}
+
+/// The Instapaper bookmark each backed up bookmark stands for, matched by id
+/// or else by URL, by backed up id.
+fn match_remote<'a>(
+    library: &Library,
+    remote: &'a [InstapaperBookmark],
+) -> HashMap<String, &'a InstapaperBookmark> {
+    let by_id: HashMap<String, &InstapaperBookmark> = remote
+        .iter()
+        .map(|bookmark| (bookmark.bookmark_id.to_string(), bookmark))
+        .collect();
+    let by_url: HashMap<&str, &InstapaperBookmark> = remote
+        .iter()
+        .map(|bookmark| (bookmark.url.as_str(), bookmark))
+        .collect();
+    library
+        .bookmarks
+        .iter()
+        .filter_map(|bookmark| {
+            let remote = by_id
+                .get(&bookmark.article.item_id)
+                .or_else(|| by_url.get(bookmark.article.uri.as_str()))?;
+            Some((bookmark.article.item_id.clone(), *remote))
+        })
+        .collect()
+}
+
+/// The backed up bookmarks Instapaper doesn't have, to add them again.
+fn missing_remote<'a>(
+    library: &'a Library,
+    remote: &[InstapaperBookmark],
+) -> Vec<&'a BackupBookmark> {
+    let matched = match_remote(library, remote);
+    library
+        .bookmarks
+        .iter()
+        .filter(|bookmark| !matched.contains_key(&bookmark.article.item_id))
+        .collect()
+}
+
+/// Bookmarks read further in the backup than on Instapaper, with the
+/// progress to send.
+fn progress_updates(
+    library: &Library,
+    matched: &HashMap<String, &InstapaperBookmark>,
+) -> Vec<(i64, f64, i64)> {
+    library
+        .bookmarks
+        .iter()
+        .filter_map(|bookmark| {
+            let remote = matched.get(&bookmark.article.item_id)?;
+            (bookmark.progress > remote.progress + 0.01
+                && bookmark.progress_timestamp > progress_timestamp(remote))
+            .then_some((
+                remote.bookmark_id,
+                bookmark.progress,
+                bookmark.progress_timestamp,
+            ))
+        })
+        .collect()
+}
+
+fn missing_highlights<'a>(
+    saved: &'a [BackupHighlight],
+    existing: &[InstapaperHighlight],
+) -> Vec<&'a BackupHighlight> {
+    saved
+        .iter()
+        .filter(|highlight| {
+            !existing
+                .iter()
+                .any(|e| e.text.trim() == highlight.text.trim())
+        })
+        .collect()
+}
+
+fn missing_folders<'a>(
+    library: &'a Library,
+    existing: &[InstapaperFolder],
+) -> Vec<&'a BackupFolder> {
+    library
+        .folders
+        .iter()
+        .filter(|folder| {
+            !existing
+                .iter()
+                .any(|e| e.title.eq_ignore_ascii_case(&folder.title))
+        })
+        .collect()
+}
+
+fn progress_timestamp(bookmark: &InstapaperBookmark) -> i64 {
+    bookmark
+        .extra
+        .get("progress_timestamp")
+        .and_then(serde_json::Value::as_i64)
+        .unwrap_or(0)
+}
+
+fn safe_id(item_id: &str) -> String {
+    item_id
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn article(item_id: &str) -> PersistedArticle {
+        PersistedArticle {
+            title: format!("Article {}", item_id),
+            uri: format!("https://example.com/{}", item_id),
+            item_id: item_id.to_string(),
+            description: String::new(),
+            time: 1650000000.0,
+            tags: vec!["rust".to_string()],
+            word_count: Some(1200),
+        }
+    }
+
+    fn library(bookmarks: Vec<BackupBookmark>) -> Library {
+        Library {
+            version: FORMAT_VERSION,
+            created: "2026-10-18T12:00:00+00:00".to_string(),
+            bookmarks,
+            folders: vec![BackupFolder {
+                title: "Reading list".to_string(),
+                bookmark_ids: vec!["1".to_string()],
+            }],
+            settings: BTreeMap::from([("reader-font-size".to_string(), "uint32 18".to_string())]),
+        }
+    }
+
+    fn remote_bookmark(bookmark_id: i64, progress: f64, timestamp: i64) -> InstapaperBookmark {
+        serde_json::from_value(serde_json::json!({
+            "bookmark_id": bookmark_id,
+            "title": "Remote",
+            "url": "https://example.com",
+            "progress": progress,
+            "progress_timestamp": timestamp,
+        }))
+        .unwrap()
+    }
+
+    fn remote_at(bookmark_id: i64, url: &str) -> InstapaperBookmark {
+        serde_json::from_value(serde_json::json!({
+            "bookmark_id": bookmark_id,
+            "title": "Remote",
+            "url": url,
+        }))
+        .unwrap()
+    }
+
+    fn remote_highlight(text: &str) -> InstapaperHighlight {
+        serde_json::from_value(serde_json::json!({
+            "highlight_id": 1,
+            "text": text,
+            "position": 0,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_write_and_read() {
+        let mut bookmark = BackupBookmark::from(article("1"));
+        bookmark.progress = 0.5;
+        bookmark.highlights = vec![BackupHighlight {
+            text: "A passage".to_string(),
+            position: 3,
+        }];
+        let backup = Backup {
+            library: library(vec![bookmark, article("2").into()]),
+            bodies: vec![(
+                "1".to_string(),
+                CachedArticle {
+                    html: "<p>Body</p>".to_string(),
+                    author: Some("Ada".to_string()),
+                },
+            )],
+            incomplete: false,
+        };
+
+        let bytes = write(&backup, Vec::new()).unwrap();
+        let read = read(&bytes).unwrap();
+
+        assert_eq!(read.library.bookmarks.len(), 2);
+        let first = &read.library.bookmarks[0];
+        assert_eq!(first.article.uri, "https://example.com/1");
+        assert_eq!(first.article.tags, vec!["rust"]);
+        assert_eq!(first.article.word_count, Some(1200));
+        assert_eq!(first.progress, 0.5);
+        assert_eq!(first.highlights, backup.library.bookmarks[0].highlights);
+        assert_eq!(read.library.folders, backup.library.folders);
+        assert_eq!(read.library.settings, backup.library.settings);
+        assert_eq!(read.bodies, backup.bodies);
+    }
+
+    #[test]
+    fn test_backup_never_holds_tokens() {
+        let backup = Backup {
+            library: library(vec![article("1").into()]),
+            bodies: Vec::new(),
+            incomplete: false,
+        };
+
+        let bytes = write(&backup, Vec::new()).unwrap();
+        let entries = zip::read_entries(&bytes).unwrap();
+
+        let names: Vec<&str> = entries.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec![LIBRARY_ENTRY]);
+        let library = String::from_utf8(entries[0].1.clone()).unwrap();
+        assert!(!library.contains("oauth"));
+        assert!(EXCLUDED_SETTINGS.contains(&"access-token"));
+        assert!(EXCLUDED_SETTINGS.contains(&"translation-api-key"));
+    }
+
+    #[test]
+    fn test_read_rejects_other_archives() {
+        let mut zip = ZipWriter::new(Vec::new());
+        zip.add_file("mimetype", b"application/epub+zip").unwrap();
+        let bytes = zip.finish().unwrap();
+        assert!(read(&bytes).is_err());
+
+        let mut newer = library(Vec::new());
+        newer.version = FORMAT_VERSION + 1;
+        let mut zip = ZipWriter::new(Vec::new());
+        zip.add_file(LIBRARY_ENTRY, &serde_json::to_vec(&newer).unwrap())
+            .unwrap();
+        let bytes = zip.finish().unwrap();
+        assert!(read(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_missing_bookmarks() {
+        let library = library(vec![article("1").into(), article("2").into()]);
+
+        let missing = missing_bookmarks(&library, &["2".to_string(), "3".to_string()]);
+
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].item_id, "1");
+    }
+
+    #[test]
+    fn test_progress_updates() {
+        let mut ahead = BackupBookmark::from(article("1"));
+        ahead.progress = 0.8;
+        ahead.progress_timestamp = 200;
+        let mut behind = BackupBookmark::from(article("2"));
+        behind.progress = 0.2;
+        behind.progress_timestamp = 200;
+        let mut gone = BackupBookmark::from(article("3"));
+        gone.progress = 0.9;
+        gone.progress_timestamp = 200;
+        let library = library(vec![ahead, behind, gone]);
+
+        let remote = vec![remote_bookmark(1, 0.1, 100), remote_bookmark(2, 0.6, 100)];
+
+        let matched = match_remote(&library, &remote);
+        assert_eq!(progress_updates(&library, &matched), vec![(1, 0.8, 200)]);
+    }
+
+    #[test]
+    fn test_restore_bookmarks_missing_remotely() {
+        let mut moved = BackupBookmark::from(article("2"));
+        moved.progress = 0.7;
+        moved.progress_timestamp = 200;
+        let mut gone = BackupBookmark::from(article("3"));
+        gone.progress = 0.5;
+        gone.progress_timestamp = 200;
+        let mut library = library(vec![article("1").into(), moved, gone]);
+        library.folders[0].bookmark_ids = vec!["2".to_string(), "3".to_string()];
+
+        // 2 was saved again under another id, 3 is gone
+        let mut remote = vec![
+            remote_bookmark(1, 0.0, 0),
+            remote_at(20, "https://example.com/2"),
+        ];
+        let missing = missing_remote(&library, &remote);
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].article.item_id, "3");
+
+        // What Instapaper answers once 3 is added again
+        remote.push(remote_at(30, "https://example.com/3"));
+        let matched = match_remote(&library, &remote);
+        assert!(missing_remote(&library, &remote).is_empty());
+        assert_eq!(
+            progress_updates(&library, &matched),
+            vec![(20, 0.7, 200), (30, 0.5, 200)]
+        );
+
+        let renamed: HashMap<String, String> = matched
+            .into_iter()
+            .map(|(item_id, remote)| (item_id, remote.bookmark_id.to_string()))
+            .filter(|(item_id, bookmark_id)| item_id != bookmark_id)
+            .collect();
+        let mut bodies = vec![(
+            "3".to_string(),
+            CachedArticle {
+                html: "<p>Body</p>".to_string(),
+                author: None,
+            },
+        )];
+        rename_bookmarks(&mut library, &mut bodies, &renamed);
+
+        let ids: Vec<&str> = library
+            .bookmarks
+            .iter()
+            .map(|bookmark| bookmark.article.item_id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["1", "20", "30"]);
+        assert_eq!(library.folders[0].bookmark_ids, vec!["20", "30"]);
+        assert_eq!(bodies[0].0, "30");
+    }
+
+    #[test]
+    fn test_missing_highlights() {
+        let saved = vec![
+            BackupHighlight {
+                text: "Kept".to_string(),
+                position: 0,
+            },
+            BackupHighlight {
+                text: "Lost".to_string(),
+                position: 1,
+            },
+        ];
+
+        let missing = missing_highlights(&saved, &[remote_highlight(" Kept ")]);
+
+        assert_eq!(missing, vec![&saved[1]]);
+    }
+
+    #[test]
+    fn test_missing_folders() {
+        let library = library(Vec::new());
+        let existing: InstapaperFolder = serde_json::from_value(serde_json::json!({
+            "folder_id": 1,
+            "title": "reading list",
+        }))
+        .unwrap();
+
+        assert!(missing_folders(&library, &[existing]).is_empty());
+        assert_eq!(missing_folders(&library, &[]).len(), 1);
+    }
+}
@@ -0,0 +1,468 @@
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+use chrono::{DateTime, Utc};
+use gettextrs::gettext;
+use html_escape::{encode_double_quoted_attribute, encode_text};
+use url::Url;
+
+use crate::export::zip::ZipWriter;
+use crate::export::{saved_date, xhtml, ExportArticle};
+use crate::network::{content, images};
+use crate::persistence::token::TokenPair;
+
+const CONTAINER: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles>
+    <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+  </rootfiles>
+</container>
+"#;
+
+const STYLESHEET: &str = "body { line-height: 1.5; }
+header { margin-bottom: 2em; }
+header h1 { margin-bottom: 0.5em; }
+header p { margin: 0.2em 0; font-size: 0.9em; }
+img { max-width: 100%; height: auto; }
+figure { margin: 1em 0; }
+figcaption { font-size: 0.85em; }
+blockquote { margin-left: 1em; padding-left: 1em; border-left: 2px solid #888; }
+pre { white-space: pre-wrap; font-size: 0.85em; }
+table { border-collapse: collapse; }
+td, th { border: 1px solid #888; padding: 0.2em 0.4em; }
+";
+
+/// A book ready to be written out.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Book {
+    pub title: String,
+    pub language: String,
+    pub modified: DateTime<Utc>,
+    pub chapters: Vec<Chapter>,
+    pub images: Vec<Image>,
+}
+
+/// An article, converted to an XHTML fragment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chapter {
+    pub title: String,
+    pub url: String,
+    pub author: Option<String>,
+    pub saved: Option<String>,
+    pub body: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    /// Path inside the book, relative to the chapters
+    pub path: String,
+    pub media_type: &'static str,
+    pub bytes: Vec<u8>,
+}
+
+/// Downloads the articles along with their images and puts them together
+/// into a book, leaving out those that can't be downloaded and returning
+/// how many were. `progress` is called with the articles done and the total
+/// after each one. Images are taken from the reader's cache when there but
+/// never written to it, so exports don't evict what's kept for reading.
+pub async fn collect(
+    title: &str,
+    articles: &[ExportArticle],
+    instapaper_tokens: Option<&TokenPair>,
+    cache_size: u32,
+    progress: impl Fn(usize, usize),
+) -> (Book, usize) {
+    let mut book = Book {
+        title: title.to_string(),
+        language: crate::preferences::desktop_language(),
+        modified: Utc::now(),
+        chapters: Vec::new(),
+        images: Vec::new(),
+    };
+    let mut image_files: HashMap<String, String> = HashMap::new();
+    let mut failed = 0;
+
+    for (index, article) in articles.iter().enumerate() {
+        let cached = match content::load(
+            &article.item_id,
+            &article.url,
+            instapaper_tokens,
+            cache_size,
+        )
+        .await
+        {
+            Ok(cached) => cached,
+            Err(e) => {
+                eprintln!("Failed to download {} for export: {}", article.url, e);
+                failed += 1;
+                progress(index + 1, articles.len());
+                continue;
+            }
+        };
+        let base = Url::parse(&article.url).ok();
+
+        for url in xhtml::image_urls(&cached.html, base.as_ref()) {
+            if image_files.contains_key(&url) {
+                continue;
+            }
+            let bytes = match images::fetch(&url, 0).await {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    eprintln!("Failed to download image {} for export: {}", url, e);
+                    continue;
+                }
+            };
+            let Some((media_type, extension)) = image_type(&bytes) else {
+                continue;
+            };
+
+            let path = format!("images/{}.{}", book.images.len() + 1, extension);
+            image_files.insert(url, path.clone());
+            book.images.push(Image {
+                path,
+                media_type,
+                bytes,
+            });
+        }
+
+        book.chapters.push(Chapter {
+            title: article.title.clone(),
+            url: article.url.clone(),
+            author: cached.author,
+            saved: saved_date(article.time),
+            body: xhtml::to_xhtml(&cached.html, base.as_ref(), &image_files),
+        });
+        progress(index + 1, articles.len());
+    }
+
+    (book, failed)
+}
+
+/// Writes `book` as an EPUB 3 file and returns the writer.
+pub fn write<W: Write>(book: &Book, writer: W) -> io::Result<W> {
+    let mut zip = ZipWriter::new(writer);
+
+    // Readers identify the format by this first, uncompressed entry
+    zip.add_file("mimetype", b"application/epub+zip")?;
+    zip.add_file("META-INF/container.xml", CONTAINER.as_bytes())?;
+    zip.add_file("OEBPS/content.opf", package(book).as_bytes())?;
+    zip.add_file("OEBPS/nav.xhtml", navigation(book).as_bytes())?;
+    zip.add_file("OEBPS/toc.ncx", ncx(book).as_bytes())?;
+    zip.add_file("OEBPS/style.css", STYLESHEET.as_bytes())?;
+
+    for (index, chapter) in book.chapters.iter().enumerate() {
+        zip.add_file(
+            &format!("OEBPS/{}", chapter_file(index)),
+            chapter_document(&book.language, chapter).as_bytes(),
+        )?;
+    }
+    for image in &book.images {
+        zip.add_file(&format!("OEBPS/{}", image.path), &image.bytes)?;
+    }
+
+    zip.finish()
+}
+
+/// The media type and file extension of image data, for the formats EPUB
+/// readers have to support.
+fn image_type(bytes: &[u8]) -> Option<(&'static str, &'static str)> {
+    if bytes.starts_with(b"\x89PNG") {
+        Some(("image/png", "png"))
+    } else if bytes.starts_with(&[0xff, 0xd8, 0xff]) {
+        Some(("image/jpeg", "jpg"))
+    } else if bytes.starts_with(b"GIF8") {
+        Some(("image/gif", "gif"))
+    } else if bytes.len() > 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some(("image/webp", "webp"))
+    } else {
+        let start = String::from_utf8_lossy(&bytes[..bytes.len().min(512)]).to_lowercase();
+        start.contains("<svg").then_some(("image/svg+xml", "svg"))
+    }
+}
+
+fn chapter_file(index: usize) -> String {
+    format!("chapter-{}.xhtml", index + 1)
+}
+
+fn book_id(book: &Book) -> String {
+    format!("urn:cauldron:export:{}", book.modified.timestamp())
+}
+
+fn package(book: &Book) -> String {
+    let mut manifest = String::from(concat!(
+        "    <item id=\"nav\" href=\"nav.xhtml\" media-type=\"application/xhtml+xml\" properties=\"nav\"/>\n",
+        "    <item id=\"ncx\" href=\"toc.ncx\" media-type=\"application/x-dtbncx+xml\"/>\n",
+        "    <item id=\"style\" href=\"style.css\" media-type=\"text/css\"/>\n",
+    ));
+    let mut spine = String::from("    <itemref idref=\"nav\"/>\n");
+
+    for index in 0..book.chapters.len() {
+        manifest.push_str(&format!(
+            "    <item id=\"chapter-{0}\" href=\"{1}\" media-type=\"application/xhtml+xml\"/>\n",
+            index + 1,
+            chapter_file(index)
+        ));
+        spine.push_str(&format!("    <itemref idref=\"chapter-{}\"/>\n", index + 1));
+    }
+    for (index, image) in book.images.iter().enumerate() {
+        manifest.push_str(&format!(
+            "    <item id=\"image-{}\" href=\"{}\" media-type=\"{}\"/>\n",
+            index + 1,
+            encode_double_quoted_attribute(&image.path),
+            image.media_type
+        ));
+    }
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0" unique-identifier="book-id" xml:lang="{language}">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+    <dc:identifier id="book-id">{id}</dc:identifier>
+    <dc:title>{title}</dc:title>
+    <dc:language>{language}</dc:language>
+    <meta property="dcterms:modified">{modified}</meta>
+  </metadata>
+  <manifest>
+{manifest}  </manifest>
+  <spine toc="ncx">
+{spine}  </spine>
+</package>
+"#,
+        language = encode_double_quoted_attribute(&book.language),
+        id = book_id(book),
+        title = encode_text(&book.title),
+        modified = book.modified.format("%Y-%m-%dT%H:%M:%SZ"),
+    )
+}
+
+fn navigation(book: &Book) -> String {
+    let entries: String = book
+        .chapters
+        .iter()
+        .enumerate()
+        .map(|(index, chapter)| {
+            format!(
+                "        <li><a href=\"{}\">{}</a></li>\n",
+                chapter_file(index),
+                encode_text(&chapter.title)
+            )
+        })
+        .collect();
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops" lang="{language}" xml:lang="{language}">
+  <head>
+    <meta charset="UTF-8"/>
+    <title>{title}</title>
+    <link rel="stylesheet" type="text/css" href="style.css"/>
+  </head>
+  <body>
+    <nav epub:type="toc" id="toc">
+      <h1>{contents}</h1>
+      <ol>
+{entries}      </ol>
+    </nav>
+  </body>
+</html>
+"#,
+        language = encode_double_quoted_attribute(&book.language),
+        title = encode_text(&book.title),
+        contents = encode_text(&gettext("Contents")),
+    )
+}
+
+/// The EPUB 2 table of contents, for older readers.
+fn ncx(book: &Book) -> String {
+    let points: String = book
+        .chapters
+        .iter()
+        .enumerate()
+        .map(|(index, chapter)| {
+            format!(
+                "    <navPoint id=\"point-{0}\" playOrder=\"{0}\">\n      <navLabel><text>{1}</text></navLabel>\n      <content src=\"{2}\"/>\n    </navPoint>\n",
+                index + 1,
+                encode_text(&chapter.title),
+                chapter_file(index)
+            )
+        })
+        .collect();
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<ncx xmlns="http://www.daisy.org/z3986/2005/ncx/" version="2005-1">
+  <head>
+    <meta name="dtb:uid" content="{id}"/>
+  </head>
+  <docTitle><text>{title}</text></docTitle>
+  <navMap>
+{points}  </navMap>
+</ncx>
+"#,
+        id = book_id(book),
+        title = encode_text(&book.title),
+    )
+}
+
+fn chapter_document(language: &str, chapter: &Chapter) -> String {
+    let mut header = format!("<h1>{}</h1>\n", encode_text(&chapter.title));
+    if let Some(author) = &chapter.author {
+        header.push_str(&format!(
+            "<p class=\"author\">{}</p>\n",
+            encode_text(&gettext("By {}").replace("{}", author))
+        ));
+    }
+    if let Ok(url) = Url::parse(&chapter.url) {
+        header.push_str(&format!(
+            "<p class=\"source\"><a href=\"{}\">{}</a></p>\n",
+            encode_double_quoted_attribute(url.as_str()),
+            encode_text(url.host_str().unwrap_or(url.as_str()))
+        ));
+    }
+    if let Some(saved) = &chapter.saved {
+        header.push_str(&format!(
+            "<p class=\"saved\">{}</p>\n",
+            encode_text(&gettext("Saved on {}").replace("{}", saved))
+        ));
+    }
+
+    let body = if chapter.body.trim().is_empty() {
+        format!(
+            "<p>{}</p>",
+            encode_text(&gettext(
+                "The content of this article couldn't be downloaded."
+            ))
+        )
+    } else {
+        chapter.body.clone()
+    };
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml" lang="{language}" xml:lang="{language}">
+<head>
+<meta charset="UTF-8"/>
+<title>{title}</title>
+<link rel="stylesheet" type="text/css" href="style.css"/>
+</head>
+<body>
+<section>
+<header>
+{header}</header>
+{body}
+</section>
+</body>
+</html>
+"#,
+        language = encode_double_quoted_attribute(language),
+        title = encode_text(&chapter.title),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn book() -> Book {
+        Book {
+            title: "Weekend & more".to_string(),
+            language: "en".to_string(),
+            modified: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+            chapters: vec![
+                Chapter {
+                    title: "First <one>".to_string(),
+                    url: "https://example.com/first".to_string(),
+                    author: Some("Ada".to_string()),
+                    saved: Some("2023-11-14".to_string()),
+                    body: "<p>Hello</p><img src=\"images/1.png\" alt=\"\"/>".to_string(),
+                },
+                Chapter {
+                    title: "Second".to_string(),
+                    url: "https://example.com/second".to_string(),
+                    author: None,
+                    saved: None,
+                    body: String::new(),
+                },
+            ],
+            images: vec![Image {
+                path: "images/1.png".to_string(),
+                media_type: "image/png",
+                bytes: b"\x89PNG".to_vec(),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_image_type() {
+        assert_eq!(image_type(b"\x89PNG\r\n"), Some(("image/png", "png")));
+        assert_eq!(
+            image_type(&[0xff, 0xd8, 0xff, 0xe0]),
+            Some(("image/jpeg", "jpg"))
+        );
+        assert_eq!(image_type(b"GIF89a"), Some(("image/gif", "gif")));
+        assert_eq!(
+            image_type(b"RIFF\0\0\0\0WEBPVP8 "),
+            Some(("image/webp", "webp"))
+        );
+        assert_eq!(
+            image_type(b"<?xml version=\"1.0\"?><svg></svg>"),
+            Some(("image/svg+xml", "svg"))
+        );
+        assert_eq!(image_type(b"<html>"), None);
+    }
+
+    #[test]
+    fn test_package_lists_everything() {
+        let opf = package(&book());
+
+        assert!(opf.contains("<dc:title>Weekend &amp; more</dc:title>"));
+        assert!(opf.contains("<dc:language>en</dc:language>"));
+        assert!(opf.contains("<meta property=\"dcterms:modified\">2023-11-14T22:13:20Z</meta>"));
+        assert!(opf.contains("href=\"chapter-2.xhtml\""));
+        assert!(opf.contains("href=\"images/1.png\" media-type=\"image/png\""));
+        assert!(opf.contains("<itemref idref=\"chapter-1\"/>"));
+    }
+
+    #[test]
+    fn test_navigation() {
+        let nav = navigation(&book());
+
+        assert!(nav.contains("epub:type=\"toc\""));
+        assert!(nav.contains("<li><a href=\"chapter-1.xhtml\">First &lt;one&gt;</a></li>"));
+        assert!(nav.contains("<li><a href=\"chapter-2.xhtml\">Second</a></li>"));
+    }
+
+    #[test]
+    fn test_chapter_metadata() {
+        let book = book();
+        let first = chapter_document("en", &book.chapters[0]);
+
+        assert!(first.contains("<h1>First &lt;one&gt;</h1>"));
+        assert!(first.contains("By Ada"));
+        assert!(first.contains("<a href=\"https://example.com/first\">example.com</a>"));
+        assert!(first.contains("2023-11-14"));
+        assert!(first.contains("<p>Hello</p>"));
+
+        let second = chapter_document("en", &book.chapters[1]);
+        assert!(!second.contains("class=\"author\""));
+        assert!(second.contains("couldn't be downloaded"));
+    }
+
+    #[test]
+    fn test_write_starts_with_mimetype() {
+        let bytes = write(&book(), Vec::new()).unwrap();
+
+        assert_eq!(&bytes[30..38], b"mimetype");
+        assert_eq!(&bytes[38..58], b"application/epub+zip");
+        let contains = |name: &str| {
+            bytes
+                .windows(name.len())
+                .any(|window| window == name.as_bytes())
+        };
+        assert!(contains("OEBPS/content.opf"));
+        assert!(contains("OEBPS/chapter-2.xhtml"));
+        assert!(contains("OEBPS/images/1.png"));
+    }
+}
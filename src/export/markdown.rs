@@ -0,0 +1,460 @@
+use scraper::{ElementRef, Html, Node};
+use url::Url;
+
+use super::xhtml::{BLOCKS, DROPPED, IMAGE_WIDTH};
+use crate::article::highlight;
+use crate::article::images;
+
+/// Elements that start a block of their own, besides the sectioning ones.
+const BLOCK_ELEMENTS: &[&str] = &[
+    "p",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "blockquote",
+    "ul",
+    "ol",
+    "li",
+    "pre",
+    "hr",
+    "table",
+    "figure",
+    "figcaption",
+    "dl",
+    "dt",
+    "dd",
+    "summary",
+    "address",
+];
+
+/// Converts article HTML to CommonMark, with the `~~` and `==` extensions
+/// Obsidian understands. Links and images keep pointing at the web.
+pub fn to_markdown(html: &str, base: Option<&Url>) -> String {
+    let document = Html::parse_document(html);
+    blocks(document.root_element(), base).join("\n\n")
+}
+
+fn is_block(name: &str) -> bool {
+    BLOCK_ELEMENTS.contains(&name) || BLOCKS.contains(&name)
+}
+
+/// Whether an inline element wraps blocks, as sites do with links around
+/// whole teasers.
+fn contains_blocks(element: ElementRef) -> bool {
+    element
+        .descendants()
+        .filter_map(ElementRef::wrap)
+        .any(|descendant| is_block(descendant.value().name()))
+}
+
+/// The content of `element` as Markdown blocks, to be separated by blank
+/// lines.
+fn blocks(element: ElementRef, base: Option<&Url>) -> Vec<String> {
+    let mut blocks = Vec::new();
+    let mut paragraph = String::new();
+
+    for child in element.children() {
+        match child.value() {
+            Node::Text(text) => paragraph.push_str(&inline_text(text)),
+            Node::Element(_) => {
+                let Some(child) = ElementRef::wrap(child) else {
+                    continue;
+                };
+                let name = child.value().name();
+                if DROPPED.contains(&name) {
+                    continue;
+                }
+
+                if is_block(name) || contains_blocks(child) {
+                    flush(&mut paragraph, &mut blocks);
+                    blocks.extend(block(child, base));
+                } else {
+                    paragraph.push_str(&inline(child, base));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    flush(&mut paragraph, &mut blocks);
+    blocks
+}
+
+fn flush(paragraph: &mut String, blocks: &mut Vec<String>) {
+    let text = paragraph.trim();
+    if !text.is_empty() {
+        blocks.push(escape_line_start(text));
+    }
+    paragraph.clear();
+}
+
+fn block(element: ElementRef, base: Option<&Url>) -> Vec<String> {
+    let name = element.value().name();
+
+    match name {
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+            let level = name[1..].parse().unwrap_or(1);
+            let text = inline_children(element, base);
+            let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+            if text.is_empty() {
+                return Vec::new();
+            }
+            vec![format!("{} {}", "#".repeat(level), text)]
+        }
+        "blockquote" => {
+            let quoted = blocks(element, base).join("\n\n");
+            if quoted.is_empty() {
+                return Vec::new();
+            }
+            let lines: Vec<String> = quoted
+                .lines()
+                .map(|line| {
+                    if line.is_empty() {
+                        ">".to_string()
+                    } else {
+                        format!("> {}", line)
+                    }
+                })
+                .collect();
+            vec![lines.join("\n")]
+        }
+        "ul" | "ol" => list(element, name == "ol", base),
+        "pre" => vec![code_block(element)],
+        "hr" => vec!["---".to_string()],
+        "table" => table(element, base),
+        _ => blocks(element, base),
+    }
+}
+
+fn list(element: ElementRef, ordered: bool, base: Option<&Url>) -> Vec<String> {
+    let start: usize = element
+        .value()
+        .attr("start")
+        .and_then(|start| start.trim().parse().ok())
+        .unwrap_or(1);
+
+    let items: Vec<String> = element
+        .children()
+        .filter_map(ElementRef::wrap)
+        .filter(|child| child.value().name() == "li")
+        .enumerate()
+        .map(|(index, item)| {
+            let marker = if ordered {
+                format!("{}. ", start + index)
+            } else {
+                "- ".to_string()
+            };
+            list_item(&marker, &blocks(item, base).join("\n\n"))
+        })
+        .collect();
+
+    if items.is_empty() {
+        Vec::new()
+    } else {
+        vec![items.join("\n")]
+    }
+}
+
+/// Puts `marker` before the first line of `content` and indents the rest
+/// to line up with it.
+fn list_item(marker: &str, content: &str) -> String {
+    if content.is_empty() {
+        return marker.trim_end().to_string();
+    }
+
+    let indent = " ".repeat(marker.len());
+    content
+        .lines()
+        .enumerate()
+        .map(|(index, line)| match index {
+            0 => format!("{}{}", marker, line),
+            _ if line.is_empty() => String::new(),
+            _ => format!("{}{}", indent, line),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn code_block(pre: ElementRef) -> String {
+    let code: String = pre.text().collect();
+    let code = code.trim_end_matches('\n');
+    let language = highlight::detect_language(pre, code)
+        .map(|language| language.display_name().to_lowercase())
+        .unwrap_or_default();
+
+    let fence = "`".repeat(longest_backtick_run(code).max(2) + 1);
+    format!("{}{}\n{}\n{}", fence, language, code, fence)
+}
+
+fn table(element: ElementRef, base: Option<&Url>) -> Vec<String> {
+    let mut blocks = Vec::new();
+
+    let caption = element
+        .children()
+        .filter_map(ElementRef::wrap)
+        .find(|child| child.value().name() == "caption");
+    if let Some(caption) = caption {
+        blocks.extend(self::blocks(caption, base));
+    }
+
+    let rows: Vec<Vec<String>> = element
+        .descendants()
+        .filter_map(ElementRef::wrap)
+        .filter(|descendant| descendant.value().name() == "tr")
+        .map(|row| {
+            row.children()
+                .filter_map(ElementRef::wrap)
+                .filter(|cell| matches!(cell.value().name(), "td" | "th"))
+                .map(|cell| {
+                    inline_children(cell, base)
+                        .split_whitespace()
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                        .replace('|', "\\|")
+                })
+                .collect()
+        })
+        .filter(|cells: &Vec<String>| !cells.is_empty())
+        .collect();
+
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    if columns == 0 {
+        return blocks;
+    }
+
+    let line = |cells: &[String]| {
+        let mut cells = cells.to_vec();
+        cells.resize(columns, String::new());
+        format!("| {} |", cells.join(" | "))
+    };
+    let mut lines = vec![line(&rows[0]), line(&vec!["---".to_string(); columns])];
+    lines.extend(rows[1..].iter().map(|row| line(row)));
+
+    blocks.push(lines.join("\n"));
+    blocks
+}
+
+fn inline(element: ElementRef, base: Option<&Url>) -> String {
+    let name = element.value().name();
+
+    match name {
+        _ if DROPPED.contains(&name) => String::new(),
+        // A hard line break
+        "br" => "  \n".to_string(),
+        "em" | "i" | "cite" | "var" => wrap(&inline_children(element, base), "*"),
+        "strong" | "b" => wrap(&inline_children(element, base), "**"),
+        "del" | "s" | "strike" => wrap(&inline_children(element, base), "~~"),
+        "mark" => wrap(&inline_children(element, base), "=="),
+        "code" | "kbd" | "samp" => code_span(&element.text().collect::<String>()),
+        "img" | "picture" => match images::resolve(element, base, IMAGE_WIDTH, 1) {
+            Some(source) => format!(
+                "![{}]({})",
+                escape(source.alt.as_deref().unwrap_or_default()),
+                link_destination(&source.url)
+            ),
+            None => String::new(),
+        },
+        "a" => {
+            let text = inline_children(element, base);
+            let href = element
+                .value()
+                .attr("href")
+                .filter(|href| !href.trim_start().starts_with('#'))
+                .and_then(|href| images::resolve_url(href, base));
+
+            match href {
+                Some(href) if text.trim().is_empty() => {
+                    format!("<{}>", link_destination(&href))
+                }
+                Some(href) => format!("[{}]({})", text.trim(), link_destination(&href)),
+                None => text,
+            }
+        }
+        _ => inline_children(element, base),
+    }
+}
+
+fn inline_children(element: ElementRef, base: Option<&Url>) -> String {
+    let mut out = String::new();
+    for child in element.children() {
+        match child.value() {
+            Node::Text(text) => out.push_str(&inline_text(text)),
+            Node::Element(_) => {
+                if let Some(child) = ElementRef::wrap(child) {
+                    out.push_str(&inline(child, base));
+                }
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+/// Text with its whitespace collapsed, the way browsers show it, and the
+/// characters Markdown would take for syntax escaped.
+fn inline_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut in_space = false;
+
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !in_space {
+                out.push(' ');
+            }
+            in_space = true;
+            continue;
+        }
+        in_space = false;
+        if !c.is_control() {
+            push_escaped(&mut out, c);
+        }
+    }
+
+    out
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        push_escaped(&mut out, c);
+    }
+    out
+}
+
+fn push_escaped(out: &mut String, c: char) {
+    if matches!(c, '\\' | '*' | '_' | '`' | '[' | ']' | '<') {
+        out.push('\\');
+    }
+    out.push(c);
+}
+
+/// Escapes what would make a paragraph read as a heading, quote or list.
+fn escape_line_start(text: &str) -> String {
+    if text.starts_with(['#', '>', '-', '+']) {
+        return format!("\\{}", text);
+    }
+
+    let digits = text.chars().take_while(char::is_ascii_digit).count();
+    let rest = &text[digits..];
+    if digits > 0 && (rest.starts_with(". ") || rest.starts_with(") ")) {
+        return format!("{}\\{}", &text[..digits], rest);
+    }
+
+    text.to_string()
+}
+
+/// Wraps `inner` in an emphasis `delimiter`, keeping surrounding spaces
+/// outside of it since Markdown doesn't allow them inside.
+fn wrap(inner: &str, delimiter: &str) -> String {
+    let trimmed = inner.trim();
+    if trimmed.is_empty() {
+        return inner.to_string();
+    }
+
+    let leading = &inner[..inner.len() - inner.trim_start().len()];
+    let trailing = &inner[inner.trim_end().len()..];
+    format!(
+        "{}{}{}{}{}",
+        leading, delimiter, trimmed, delimiter, trailing
+    )
+}
+
+fn code_span(code: &str) -> String {
+    let code = code.split_whitespace().collect::<Vec<_>>().join(" ");
+    if code.is_empty() {
+        return String::new();
+    }
+
+    let fence = "`".repeat(longest_backtick_run(&code) + 1);
+    if code.starts_with('`') || code.ends_with('`') {
+        format!("{} {} {}", fence, code, fence)
+    } else {
+        format!("{}{}{}", fence, code, fence)
+    }
+}
+
+fn longest_backtick_run(text: &str) -> usize {
+    text.split(|c| c != '`').map(str::len).max().unwrap_or(0)
+}
+
+/// A URL that can't end the link early.
+fn link_destination(url: &str) -> String {
+    url.replace('(', "%28")
+        .replace(')', "%29")
+        .replace(' ', "%20")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paragraphs_and_inline_formatting() {
+        let html = r#"<html><head><title>T</title></head><body>
+            <h2>A   <em>title</em></h2>
+            <p>Some <strong>bold </strong>and <i>italic</i> text,
+               with <code>a_b</code> and a <a href="/about">link</a>.</p>
+            <p>Line one<br>line two</p>
+            <script>alert(1)</script>
+            <p>2 * 3 = [six]</p>
+        </body></html>"#;
+        let base = Url::parse("https://example.com/post").unwrap();
+
+        assert_eq!(
+            to_markdown(html, Some(&base)),
+            "## A *title*\n\n\
+             Some **bold** and *italic* text, with `a_b` and a [link](https://example.com/about).\n\n\
+             Line one  \nline two\n\n\
+             2 \\* 3 = \\[six\\]"
+        );
+    }
+
+    #[test]
+    fn test_lists_and_quotes() {
+        let html = r#"<ul><li>One</li><li>Two<ul><li>Nested</li></ul></li></ul>
+            <ol start="3"><li>Three</li><li>Four</li></ol>
+            <blockquote><p>Quoted</p><p>Twice</p></blockquote>"#;
+
+        assert_eq!(
+            to_markdown(html, None),
+            "- One\n- Two\n\n  - Nested\n\n\
+             3. Three\n4. Four\n\n\
+             > Quoted\n>\n> Twice"
+        );
+    }
+
+    #[test]
+    fn test_code_blocks() {
+        let html = "<pre><code class=\"language-rust\">fn main() {\n    println!(\"```\");\n}\n</code></pre>";
+
+        assert_eq!(
+            to_markdown(html, None),
+            "````rust\nfn main() {\n    println!(\"```\");\n}\n````"
+        );
+        assert_eq!(code_span("a`b"), "``a`b``");
+    }
+
+    #[test]
+    fn test_images_and_tables() {
+        let html = r#"<figure><img src="cat (1).png" alt="A cat"><figcaption>Cat</figcaption></figure>
+            <table><tr><th>Name</th><th>Value</th></tr><tr><td>a|b</td></tr></table>"#;
+        let base = Url::parse("https://example.com/").unwrap();
+
+        assert_eq!(
+            to_markdown(html, Some(&base)),
+            "![A cat](https://example.com/cat%20%281%29.png)\n\n\
+             Cat\n\n\
+             | Name | Value |\n| --- | --- |\n| a\\|b |  |"
+        );
+    }
+
+    #[test]
+    fn test_escape_line_start() {
+        assert_eq!(escape_line_start("# not a heading"), "\\# not a heading");
+        assert_eq!(escape_line_start("1984. A year"), "1984\\. A year");
+        assert_eq!(escape_line_start("1984 was a year"), "1984 was a year");
+    }
+}
@@ -0,0 +1,51 @@
+pub mod epub;
+pub mod markdown;
+pub mod vault;
+pub mod xhtml;
+pub mod zip;
+
+use crate::article::Article;
+use crate::network::instapaper::InstapaperBookmark;
+
+/// A bookmark picked for export.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportArticle {
+    pub item_id: String,
+    pub title: String,
+    pub url: String,
+    /// When it was saved, in seconds since the epoch
+    pub time: f64,
+    pub tags: Vec<String>,
+}
+
+impl From<&Article> for ExportArticle {
+    fn from(article: &Article) -> Self {
+        Self {
+            item_id: article.item_id.clone(),
+            title: article.title.clone(),
+            url: article.uri.clone(),
+            time: article.time,
+            tags: article.tags.clone(),
+        }
+    }
+}
+
+impl From<InstapaperBookmark> for ExportArticle {
+    fn from(bookmark: InstapaperBookmark) -> Self {
+        Self {
+            item_id: bookmark.bookmark_id.to_string(),
+            title: bookmark.title,
+            url: bookmark.url,
+            time: bookmark.time,
+            tags: bookmark.tags.into_iter().map(|tag| tag.name).collect(),
+        }
+    }
+}
+
+/// The day an article was saved, as `YYYY-MM-DD`.
+pub fn saved_date(time: f64) -> Option<String> {
+    if time <= 0.0 {
+        return None;
+    }
+    chrono::DateTime::from_timestamp(time as i64, 0).map(|date| date.format("%Y-%m-%d").to_string())
+}
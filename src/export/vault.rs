@@ -0,0 +1,419 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use url::Url;
+
+use crate::article::print;
+use crate::export::{markdown, ExportArticle};
+use crate::network::{content, instapaper};
+use crate::persistence::token::TokenPair;
+
+/// Separates the exported article from the reader's own notes. Everything
+/// below it is kept when exporting again, so it must never change.
+pub const NOTES_MARKER: &str =
+    "<!-- cauldron:notes - anything below this line is kept when exporting again -->";
+
+/// Front matter key notes are matched to their bookmark by.
+const ID_KEY: &str = "cauldron_id";
+
+/// An article, converted to a Markdown note.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Note {
+    pub item_id: String,
+    pub title: String,
+    pub url: String,
+    pub author: Option<String>,
+    /// When the bookmark was saved, in seconds since the epoch
+    pub time: f64,
+    pub tags: Vec<String>,
+    pub highlights: Vec<String>,
+    pub body: String,
+}
+
+/// What happened to the notes of an export.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct VaultReport {
+    pub created: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    /// Notes left alone because their marker was removed
+    pub skipped: usize,
+}
+
+enum Merge {
+    Write(String),
+    Unchanged,
+    Skip,
+}
+
+/// Downloads the articles and converts them to notes, leaving out those
+/// that can't be downloaded and returning how many were. Highlights are
+/// fetched from Instapaper when `tokens` are given. `progress` is called
+/// with the articles done and the total after each one.
+pub async fn collect(
+    articles: &[ExportArticle],
+    tokens: Option<&TokenPair>,
+    instapaper_tokens: Option<&TokenPair>,
+    cache_size: u32,
+    progress: impl Fn(usize, usize),
+) -> (Vec<Note>, usize) {
+    let client = instapaper::client();
+    let mut notes = Vec::with_capacity(articles.len());
+    let mut failed = 0;
+
+    for (index, article) in articles.iter().enumerate() {
+        let cached = match content::load(
+            &article.item_id,
+            &article.url,
+            instapaper_tokens,
+            cache_size,
+        )
+        .await
+        {
+            Ok(cached) => cached,
+            Err(e) => {
+                eprintln!("Failed to download {} for export: {}", article.url, e);
+                failed += 1;
+                progress(index + 1, articles.len());
+                continue;
+            }
+        };
+        let base = Url::parse(&article.url).ok();
+
+        let highlights = match (tokens, article.item_id.parse::<i64>()) {
+            (Some(tokens), Ok(bookmark_id)) => {
+                match instapaper::get_highlights(&client, tokens, bookmark_id, instapaper::BASE_URL)
+                    .await
+                {
+                    Ok(highlights) => highlights.into_iter().map(|h| h.text).collect(),
+                    Err(e) => {
+                        eprintln!("Failed to get highlights of {}: {}", article.item_id, e);
+                        Vec::new()
+                    }
+                }
+            }
+            _ => Vec::new(),
+        };
+
+        notes.push(Note {
+            item_id: article.item_id.clone(),
+            title: article.title.clone(),
+            url: article.url.clone(),
+            author: cached.author,
+            time: article.time,
+            tags: article.tags.clone(),
+            highlights,
+            body: markdown::to_markdown(&cached.html, base.as_ref()),
+        });
+        progress(index + 1, articles.len());
+    }
+
+    (notes, failed)
+}
+
+/// Writes one file per note into `dir`. Notes exported before are found
+/// by their id and updated in place, keeping whatever the reader wrote
+/// below the marker.
+pub fn write_all(dir: &Path, notes: &[Note]) -> io::Result<VaultReport> {
+    fs::create_dir_all(dir)?;
+
+    let mut existing = existing_notes(dir)?;
+    let mut taken: HashSet<PathBuf> = existing.values().cloned().collect();
+    let mut report = VaultReport::default();
+
+    for note in notes {
+        let (path, current) = match existing.remove(&note.item_id) {
+            Some(path) => {
+                let current = fs::read_to_string(&path)?;
+                (path, Some(current))
+            }
+            None => {
+                let path = unique_path(dir, &note.title, &taken);
+                taken.insert(path.clone());
+                (path, None)
+            }
+        };
+
+        match merge(current.as_deref(), &render(note)) {
+            Merge::Write(contents) => {
+                fs::write(&path, contents)?;
+                if current.is_some() {
+                    report.updated += 1;
+                } else {
+                    report.created += 1;
+                }
+            }
+            Merge::Unchanged => report.unchanged += 1,
+            Merge::Skip => report.skipped += 1,
+        }
+    }
+
+    Ok(report)
+}
+
+/// The generated part of a note, up to and including the marker.
+fn render(note: &Note) -> String {
+    let mut out = String::from("---\n");
+    out.push_str(&format!("title: {}\n", yaml_string(&note.title)));
+    out.push_str(&format!("url: {}\n", yaml_string(&note.url)));
+    if let Some(author) = &note.author {
+        out.push_str(&format!("author: {}\n", yaml_string(author)));
+    }
+    if let Some(saved) = saved_time(note.time) {
+        out.push_str(&format!("saved: {}\n", saved));
+    }
+    // Obsidian tags can't contain spaces
+    let tags: Vec<String> = note
+        .tags
+        .iter()
+        .map(|tag| tag.split_whitespace().collect::<Vec<_>>().join("-"))
+        .filter(|tag| !tag.is_empty())
+        .collect();
+    out.push_str(&yaml_list("tags", &tags));
+    out.push_str(&yaml_list("highlights", &note.highlights));
+    out.push_str(&format!("{}: {}\n", ID_KEY, yaml_string(&note.item_id)));
+    out.push_str("---\n\n");
+
+    out.push_str(&format!(
+        "# {}\n\n",
+        note.title.split_whitespace().collect::<Vec<_>>().join(" ")
+    ));
+    if !note.body.is_empty() {
+        out.push_str(&note.body);
+        out.push_str("\n\n");
+    }
+    out.push_str(NOTES_MARKER);
+    out
+}
+
+fn merge(current: Option<&str>, generated: &str) -> Merge {
+    let Some(current) = current else {
+        return Merge::Write(format!("{}\n", generated));
+    };
+
+    // Without the marker there's no telling the reader's edits apart
+    let Some(start) = current
+        .match_indices(NOTES_MARKER)
+        .map(|(start, _)| start)
+        .find(|&start| start == 0 || current[..start].ends_with('\n'))
+    else {
+        return Merge::Skip;
+    };
+
+    let notes = &current[start + NOTES_MARKER.len()..];
+    let merged = format!("{}{}", generated, notes);
+    if merged == current {
+        Merge::Unchanged
+    } else {
+        Merge::Write(merged)
+    }
+}
+
+/// The notes in `dir` exported before, by bookmark id.
+fn existing_notes(dir: &Path) -> io::Result<HashMap<String, PathBuf>> {
+    let mut notes = HashMap::new();
+
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_none_or(|extension| extension != "md") {
+            continue;
+        }
+        let Ok(contents) = fs::read_to_string(&path) else {
+            continue;
+        };
+        if let Some(id) = note_id(&contents) {
+            notes.insert(id, path);
+        }
+    }
+
+    Ok(notes)
+}
+
+/// The bookmark id in the front matter of a note.
+fn note_id(contents: &str) -> Option<String> {
+    let front_matter = contents.strip_prefix("---\n")?;
+    let end = front_matter.find("\n---")?;
+
+    front_matter[..end].lines().find_map(|line| {
+        let value = line.strip_prefix(ID_KEY)?.strip_prefix(':')?.trim();
+        let value = value.trim_matches('"');
+        (!value.is_empty()).then(|| value.to_string())
+    })
+}
+
+/// A file name for a new note that no other note uses.
+fn unique_path(dir: &Path, title: &str, taken: &HashSet<PathBuf>) -> PathBuf {
+    // Characters that break Obsidian's wiki links
+    let name: String = print::file_name(title)
+        .chars()
+        .map(|c| match c {
+            '#' | '^' | '[' | ']' => '-',
+            c => c,
+        })
+        .collect();
+
+    (1..)
+        .map(|n| match n {
+            1 => dir.join(format!("{}.md", name)),
+            n => dir.join(format!("{} ({}).md", name, n)),
+        })
+        .find(|path| !taken.contains(path) && !path.exists())
+        .unwrap_or_else(|| dir.join(format!("{}.md", name)))
+}
+
+fn saved_time(time: f64) -> Option<String> {
+    if time <= 0.0 {
+        return None;
+    }
+    chrono::DateTime::from_timestamp(time as i64, 0)
+        .map(|date| date.format("%Y-%m-%dT%H:%M:%SZ").to_string())
+}
+
+fn yaml_list(key: &str, items: &[String]) -> String {
+    if items.is_empty() {
+        return format!("{}: []\n", key);
+    }
+
+    let mut out = format!("{}:\n", key);
+    for item in items {
+        out.push_str(&format!("  - {}\n", yaml_string(item)));
+    }
+    out
+}
+
+/// A double quoted YAML scalar, which can hold any text once escaped.
+fn yaml_string(value: &str) -> String {
+    let mut out = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::temp_dir::TestDir;
+
+    fn note(item_id: &str, title: &str, body: &str) -> Note {
+        Note {
+            item_id: item_id.to_string(),
+            title: title.to_string(),
+            url: format!("https://example.com/{}", item_id),
+            author: None,
+            time: 1_700_000_000.0,
+            tags: vec!["to read".to_string()],
+            highlights: vec!["A \"quoted\" passage".to_string()],
+            body: body.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_render_front_matter() {
+        let rendered = render(&Note {
+            author: Some("Ada".to_string()),
+            ..note("42", "Hello: world", "Body")
+        });
+
+        assert_eq!(
+            rendered,
+            format!(
+                "---\n\
+                 title: \"Hello: world\"\n\
+                 url: \"https://example.com/42\"\n\
+                 author: \"Ada\"\n\
+                 saved: 2023-11-14T22:13:20Z\n\
+                 tags:\n  - \"to-read\"\n\
+                 highlights:\n  - \"A \\\"quoted\\\" passage\"\n\
+                 cauldron_id: \"42\"\n\
+                 ---\n\n\
+                 # Hello: world\n\n\
+                 Body\n\n\
+                 {}",
+                NOTES_MARKER
+            )
+        );
+        assert_eq!(note_id(&rendered), Some("42".to_string()));
+    }
+
+    #[test]
+    fn test_reexport_keeps_notes() {
+        let dir = TestDir::new("reexport");
+
+        let report = write_all(&dir, &[note("1", "First", "Body")]).unwrap();
+        assert_eq!(report.created, 1);
+        let path = dir.join("First.md");
+        assert!(fs::read_to_string(&path)
+            .unwrap()
+            .ends_with(&format!("{}\n", NOTES_MARKER)));
+
+        // Nothing changed
+        let report = write_all(&dir, &[note("1", "First", "Body")]).unwrap();
+        assert_eq!(report.unchanged, 1);
+
+        // The reader adds notes, then the article changes
+        let mut contents = fs::read_to_string(&path).unwrap();
+        contents.push_str("My thoughts\n");
+        fs::write(&path, contents).unwrap();
+
+        let report = write_all(&dir, &[note("1", "First, renamed", "New body")]).unwrap();
+        assert_eq!(report.updated, 1);
+        let contents = fs::read_to_string(&path).unwrap();
+        assert!(contents.contains("New body"));
+        assert!(contents.contains("# First, renamed"));
+        assert!(contents.ends_with(&format!("{}\nMy thoughts\n", NOTES_MARKER)));
+        // Found by id, not by title
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_notes_without_marker_are_skipped() {
+        let dir = TestDir::new("skip");
+
+        write_all(&dir, &[note("1", "First", "Body")]).unwrap();
+        let path = dir.join("First.md");
+        let edited = fs::read_to_string(&path)
+            .unwrap()
+            .replace(NOTES_MARKER, "Rewritten by hand");
+        fs::write(&path, &edited).unwrap();
+
+        let report = write_all(&dir, &[note("1", "First", "New body")]).unwrap();
+        assert_eq!(report.skipped, 1);
+        assert_eq!(fs::read_to_string(&path).unwrap(), edited);
+    }
+
+    #[test]
+    fn test_same_titles_get_their_own_files() {
+        let dir = TestDir::new("titles");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("Notes.md"), "Not from Cauldron").unwrap();
+
+        let report = write_all(
+            &dir,
+            &[note("1", "Notes", "One"), note("2", "Notes", "Two")],
+        )
+        .unwrap();
+
+        assert_eq!(report.created, 2);
+        assert_eq!(
+            fs::read_to_string(dir.join("Notes.md")).unwrap(),
+            "Not from Cauldron"
+        );
+        assert!(fs::read_to_string(dir.join("Notes (2).md"))
+            .unwrap()
+            .contains("One"));
+        assert!(fs::read_to_string(dir.join("Notes (3).md"))
+            .unwrap()
+            .contains("Two"));
+    }
+}
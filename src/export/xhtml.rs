@@ -0,0 +1,285 @@
+use std::collections::HashMap;
+
+use html_escape::{encode_double_quoted_attribute, encode_text};
+use scraper::{ElementRef, Html, Node};
+use url::Url;
+
+use crate::article::images;
+
+/// Width `srcset` candidates are picked for, about the resolution of an
+/// e-ink reader.
+pub(super) const IMAGE_WIDTH: u32 = 1200;
+
+/// Elements kept as they are, minus their attributes.
+const KEPT: &[&str] = &[
+    "p",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "em",
+    "strong",
+    "b",
+    "i",
+    "u",
+    "s",
+    "del",
+    "ins",
+    "sub",
+    "sup",
+    "small",
+    "mark",
+    "q",
+    "cite",
+    "abbr",
+    "code",
+    "kbd",
+    "samp",
+    "var",
+    "pre",
+    "blockquote",
+    "ul",
+    "li",
+    "dl",
+    "dt",
+    "dd",
+    "figure",
+    "figcaption",
+    "table",
+    "caption",
+    "thead",
+    "tbody",
+    "tfoot",
+    "tr",
+];
+
+/// Sectioning elements, kept as plain blocks.
+pub(super) const BLOCKS: &[&str] = &[
+    "div", "section", "article", "aside", "header", "footer", "main", "details",
+];
+
+/// Elements dropped along with everything inside them.
+pub(super) const DROPPED: &[&str] = &[
+    "head", "script", "style", "noscript", "template", "iframe", "object", "embed", "video",
+    "audio", "canvas", "svg", "form", "button", "input", "select", "textarea", "source",
+];
+
+/// The URLs of the images in article HTML, in reading order, as they are
+/// picked for export.
+pub fn image_urls(html: &str, base: Option<&Url>) -> Vec<String> {
+    let document = Html::parse_document(html);
+    let mut urls: Vec<String> = Vec::new();
+
+    for element in document
+        .root_element()
+        .descendants()
+        .filter_map(ElementRef::wrap)
+    {
+        let name = element.value().name();
+        // Images inside <picture> are resolved along with it
+        let in_picture = element
+            .parent()
+            .and_then(ElementRef::wrap)
+            .is_some_and(|parent| parent.value().name() == "picture");
+
+        if name == "picture" || (name == "img" && !in_picture) {
+            if let Some(source) = images::resolve(element, base, IMAGE_WIDTH, 1) {
+                if !urls.contains(&source.url) {
+                    urls.push(source.url);
+                }
+            }
+        }
+    }
+
+    urls
+}
+
+/// Converts article HTML to well-formed XHTML for an EPUB chapter, keeping
+/// the structure and dropping scripts, embeds and attributes. Images point
+/// at the file `images` maps their URL to, and are left out when it has
+/// none.
+pub fn to_xhtml(html: &str, base: Option<&Url>, images: &HashMap<String, String>) -> String {
+    let document = Html::parse_document(html);
+    let mut out = String::new();
+    write_children(document.root_element(), base, images, &mut out);
+    out
+}
+
+fn write_children(
+    element: ElementRef,
+    base: Option<&Url>,
+    images: &HashMap<String, String>,
+    out: &mut String,
+) {
+    for child in element.children() {
+        match child.value() {
+            Node::Text(text) => {
+                // Control characters other than whitespace aren't valid XML
+                let text: String = text
+                    .chars()
+                    .filter(|c| !c.is_control() || c.is_whitespace())
+                    .collect();
+                out.push_str(&encode_text(&text));
+            }
+            Node::Element(_) => {
+                if let Some(child) = ElementRef::wrap(child) {
+                    write_element(child, base, images, out);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn write_element(
+    element: ElementRef,
+    base: Option<&Url>,
+    images: &HashMap<String, String>,
+    out: &mut String,
+) {
+    let name = element.value().name();
+    let attribute = |name| element.value().attr(name);
+
+    match name {
+        _ if DROPPED.contains(&name) => {}
+        "br" | "hr" => {
+            out.push_str(&format!("<{}/>", name));
+        }
+        "img" | "picture" => {
+            let Some(source) = images::resolve(element, base, IMAGE_WIDTH, 1) else {
+                return;
+            };
+            let Some(file) = images.get(&source.url) else {
+                return;
+            };
+            out.push_str(&format!(
+                "<img src=\"{}\" alt=\"{}\"/>",
+                encode_double_quoted_attribute(file),
+                encode_double_quoted_attribute(source.alt.as_deref().unwrap_or_default())
+            ));
+        }
+        "a" => match attribute("href")
+            .filter(|href| !href.trim_start().starts_with('#'))
+            .and_then(|href| images::resolve_url(href, base))
+        {
+            Some(href) => {
+                out.push_str(&format!(
+                    "<a href=\"{}\">",
+                    encode_double_quoted_attribute(&href)
+                ));
+                write_children(element, base, images, out);
+                out.push_str("</a>");
+            }
+            // In-page links point at ids that aren't kept
+            None => write_children(element, base, images, out),
+        },
+        "ol" => {
+            match attribute("start").and_then(|start| start.trim().parse::<i64>().ok()) {
+                Some(start) => out.push_str(&format!("<ol start=\"{}\">", start)),
+                None => out.push_str("<ol>"),
+            }
+            write_children(element, base, images, out);
+            out.push_str("</ol>");
+        }
+        "td" | "th" => {
+            out.push_str(&format!("<{}", name));
+            for span in ["colspan", "rowspan"] {
+                if let Some(value) = attribute(span).and_then(|v| v.trim().parse::<u32>().ok()) {
+                    out.push_str(&format!(" {}=\"{}\"", span, value));
+                }
+            }
+            out.push('>');
+            write_children(element, base, images, out);
+            out.push_str(&format!("</{}>", name));
+        }
+        "summary" => {
+            out.push_str("<p>");
+            write_children(element, base, images, out);
+            out.push_str("</p>");
+        }
+        _ if KEPT.contains(&name) || BLOCKS.contains(&name) => {
+            let tag = if BLOCKS.contains(&name) { "div" } else { name };
+            out.push_str(&format!("<{}>", tag));
+            write_children(element, base, images, out);
+            out.push_str(&format!("</{}>", tag));
+        }
+        // Anything else, like <span> or <font>, only contributes its content
+        _ => write_children(element, base, images, out),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_xhtml_cleans_markup() {
+        let html = r#"<html><head><title>T</title></head><body>
+            <div class="x"><p style="color: red">One<br>two &amp; <span>three</span></p></div>
+            <script>alert(1)</script>
+            <hr>
+            <ol start="3"><li>Item</li></ol>
+            <table><tr><td colspan="2" onclick="x()">Cell</td></tr></table>
+        </body></html>"#;
+
+        let xhtml = to_xhtml(html, None, &HashMap::new());
+
+        assert!(xhtml.contains("<div><p>One<br/>two &amp; three</p></div>"));
+        assert!(!xhtml.contains("alert"));
+        assert!(!xhtml.contains("<title>"));
+        assert!(xhtml.contains("<hr/>"));
+        assert!(xhtml.contains(r#"<ol start="3"><li>Item</li></ol>"#));
+        assert!(xhtml.contains(r#"<td colspan="2">Cell</td>"#));
+    }
+
+    #[test]
+    fn test_to_xhtml_links() {
+        let base = Url::parse("https://example.com/post/").unwrap();
+        let html = r##"<p><a href="../about">About</a> <a href="#note">note</a>
+            <a href="javascript:alert(1)">bad</a></p>"##;
+
+        let xhtml = to_xhtml(html, Some(&base), &HashMap::new());
+
+        assert!(xhtml.contains(r#"<a href="https://example.com/about">About</a>"#));
+        assert!(xhtml.contains(" note"));
+        assert!(!xhtml.contains("javascript"));
+        assert_eq!(xhtml.matches("<a ").count(), 1);
+    }
+
+    #[test]
+    fn test_images() {
+        let base = Url::parse("https://example.com/post/").unwrap();
+        let html = r#"<figure><img src="a.png" alt="A &quot;cat&quot;"><figcaption>Cat</figcaption></figure>
+            <picture><source srcset="b.webp"><img src="b.jpg"></picture>
+            <img src="a.png"><img src="missing.png">"#;
+
+        assert_eq!(
+            image_urls(html, Some(&base)),
+            [
+                "https://example.com/post/a.png",
+                "https://example.com/post/b.webp",
+                "https://example.com/post/missing.png"
+            ]
+        );
+
+        let files = HashMap::from([
+            (
+                "https://example.com/post/a.png".to_string(),
+                "images/1.png".to_string(),
+            ),
+            (
+                "https://example.com/post/b.webp".to_string(),
+                "images/2.webp".to_string(),
+            ),
+        ]);
+        let xhtml = to_xhtml(html, Some(&base), &files);
+
+        assert!(xhtml.contains(r#"<img src="images/1.png" alt="A &quot;cat&quot;"/>"#));
+        assert!(xhtml.contains(r#"<img src="images/2.webp" alt=""/>"#));
+        // Images that couldn't be downloaded are left out
+        assert!(!xhtml.contains("missing"));
+        assert_eq!(xhtml.matches("<img").count(), 3);
+    }
+}
@@ -0,0 +1,190 @@
+use std::io::{self, Cursor, Read, Write};
+
+use ::zip::write::SimpleFileOptions;
+use ::zip::{CompressionMethod, ZipArchive};
+
+/// Writes a zip archive with every entry stored uncompressed, which is
+/// all EPUB needs and keeps us from pulling in a compression library.
+///
+/// The archive is put together in memory and copied to the writer by
+/// [`ZipWriter::finish`], so it can go to writers that can't seek.
+pub struct ZipWriter<W: Write> {
+    writer: W,
+    archive: ::zip::ZipWriter<Cursor<Vec<u8>>>,
+}
+
+impl<W: Write> ZipWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            archive: ::zip::ZipWriter::new(Cursor::new(Vec::new())),
+        }
+    }
+
+    pub fn add_file(&mut self, name: &str, contents: &[u8]) -> io::Result<()> {
+        let options = SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Stored)
+            .large_file(contents.len() > u32::MAX as usize);
+        self.archive.start_file(name, options)?;
+        self.archive.write_all(contents)
+    }
+
+    /// Writes the central directory and returns the underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        let bytes = self.archive.finish()?.into_inner();
+        self.writer.write_all(&bytes)?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Reads the entries of an archive, such as one written by [`ZipWriter`],
+/// as names and contents. Only stored entries are supported.
+pub fn read_entries(bytes: &[u8]) -> io::Result<Vec<(String, Vec<u8>)>> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes))?;
+
+    let mut entries = Vec::with_capacity(archive.len());
+    for index in 0..archive.len() {
+        let mut file = archive.by_index(index)?;
+        if file.is_dir() {
+            continue;
+        }
+        let mut contents = Vec::new();
+        file.read_to_end(&mut contents)?;
+        entries.push((file.name().to_string(), contents));
+    }
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    use crate::testing::temp_dir::TestDir;
+
+    fn archive(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut zip = ZipWriter::new(Vec::new());
+        for (name, contents) in files {
+            zip.add_file(name, contents).unwrap();
+        }
+        zip.finish().unwrap()
+    }
+
+    #[test]
+    fn test_stored_archive_layout() {
+        let bytes = archive(&[("mimetype", b"application/epub+zip"), ("a/b.txt", b"hello")]);
+
+        // EPUB readers expect the first entry's name and contents right
+        // after its 30 byte header, with no extra field in between
+        assert_eq!(&bytes[..4], b"PK\x03\x04");
+        assert_eq!(u16::from_le_bytes([bytes[8], bytes[9]]), 0);
+        assert_eq!(&bytes[30..38], b"mimetype");
+        assert_eq!(&bytes[38..58], b"application/epub+zip");
+
+        // The end record counts both entries and points at the directory
+        let end = &bytes[bytes.len() - 22..];
+        assert_eq!(&end[..4], b"PK\x05\x06");
+        assert_eq!(u16::from_le_bytes([end[10], end[11]]), 2);
+        let directory = u32::from_le_bytes([end[16], end[17], end[18], end[19]]) as usize;
+        assert_eq!(&bytes[directory..directory + 4], b"PK\x01\x02");
+    }
+
+    #[test]
+    fn test_read_entries() {
+        let bytes = archive(&[
+            ("library.json", b"{}"),
+            ("articles/1.json", "caf\u{e9}".as_bytes()),
+        ]);
+
+        let entries = read_entries(&bytes).unwrap();
+
+        assert_eq!(
+            entries,
+            vec![
+                ("library.json".to_string(), b"{}".to_vec()),
+                (
+                    "articles/1.json".to_string(),
+                    "caf\u{e9}".as_bytes().to_vec()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_entries_rejects_damaged_archives() {
+        let mut bytes = archive(&[("library.json", b"{\"bookmarks\":[]}")]);
+
+        assert!(read_entries(&bytes[..bytes.len() - 4]).is_err());
+        assert!(read_entries(b"not an archive").is_err());
+        assert!(read_entries(b"").is_err());
+
+        bytes[45] ^= 0xff;
+        assert_eq!(
+            read_entries(&bytes).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    fn test_read_entries_survives_truncation_and_corruption() {
+        let bytes = archive(&[
+            ("library.json", b"{\"bookmarks\":[]}"),
+            ("articles/1.json", b"{\"html\":\"<p>Hi</p>\"}"),
+        ]);
+
+        // Backups come from anywhere, so no damage may panic or read garbage
+        for length in 0..bytes.len() {
+            if let Ok(entries) = read_entries(&bytes[..length]) {
+                assert!(entries.len() <= 2);
+            }
+        }
+        for at in 0..bytes.len() {
+            for flip in [0x01, 0x80, 0xff] {
+                let mut damaged = bytes.clone();
+                damaged[at] ^= flip;
+                let _ = read_entries(&damaged);
+            }
+        }
+    }
+
+    #[test]
+    fn test_read_entries_from_zip_tool() {
+        let dir = TestDir::new("zip-tool");
+        std::fs::create_dir_all(dir.join("articles")).unwrap();
+        std::fs::write(dir.join("library.json"), "{}").unwrap();
+        std::fs::write(dir.join("articles").join("1.json"), "caf\u{e9}").unwrap();
+
+        // Info-ZIP adds extra fields and a directory entry of its own
+        let stored = Command::new("zip")
+            .current_dir(&dir)
+            .args(["-q", "-0", "-r", "stored.zip", "library.json", "articles"])
+            .status();
+        if !stored.is_ok_and(|status| status.success()) {
+            eprintln!("zip is not installed, skipping");
+            return;
+        }
+        let entries = read_entries(&std::fs::read(dir.join("stored.zip")).unwrap()).unwrap();
+        assert_eq!(
+            entries,
+            vec![
+                ("library.json".to_string(), b"{}".to_vec()),
+                (
+                    "articles/1.json".to_string(),
+                    "caf\u{e9}".as_bytes().to_vec()
+                ),
+            ]
+        );
+
+        // Compressed entries are refused rather than read as garbage
+        std::fs::write(dir.join("long.json"), "{}".repeat(1000)).unwrap();
+        Command::new("zip")
+            .current_dir(&dir)
+            .args(["-q", "-9", "deflated.zip", "long.json"])
+            .status()
+            .unwrap();
+        let error = read_entries(&std::fs::read(dir.join("deflated.zip")).unwrap()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::Unsupported);
+    }
+}
@@ -0,0 +1,184 @@
+use chrono::{NaiveDate, NaiveDateTime};
+
+use super::{split_tags, ImportItem};
+
+/// Reads a CSV file with a header row naming its columns, such as Pocket's
+/// `part_000000.csv` (`title,url,time_added,tags,status`).
+///
+/// Only the url column is required; title, tags and time are picked up when
+/// present under one of their usual names.
+pub fn parse(contents: &str) -> Vec<ImportItem> {
+    let mut rows = records(contents).into_iter();
+    let Some(header) = rows.next() else {
+        return Vec::new();
+    };
+
+    let column = |names: &[&str]| {
+        header
+            .iter()
+            .position(|field| names.contains(&field.trim().to_ascii_lowercase().as_str()))
+    };
+    let Some(url_column) = column(&["url", "uri", "link", "href"]) else {
+        return Vec::new();
+    };
+    let title_column = column(&["title", "name"]);
+    let tags_column = column(&["tags", "tag", "labels"]);
+    let time_column = column(&["time", "time_added", "date", "added", "created"]);
+
+    let field = |row: &[String], index: Option<usize>| -> Option<String> {
+        index
+            .and_then(|index| row.get(index))
+            .map(|field| field.trim().to_string())
+            .filter(|field| !field.is_empty())
+    };
+
+    rows.filter_map(|row| {
+        let url = field(&row, Some(url_column))?;
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            return None;
+        }
+
+        Some(ImportItem {
+            url,
+            title: field(&row, title_column).unwrap_or_default(),
+            tags: field(&row, tags_column)
+                .map(|tags| split_tags(&tags))
+                .unwrap_or_default(),
+            time: field(&row, time_column).and_then(|time| parse_time(&time)),
+        })
+    })
+    .collect()
+}
+
+/// Splits the file into rows of fields, following RFC 4180 quoting.
+fn records(contents: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = contents.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted => {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    field.push('"');
+                } else {
+                    quoted = false;
+                }
+            }
+            '"' if field.is_empty() => quoted = true,
+            ',' if !quoted => record.push(std::mem::take(&mut field)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                record.push(std::mem::take(&mut field));
+                if record.iter().any(|field| !field.is_empty()) {
+                    records.push(std::mem::take(&mut record));
+                } else {
+                    record.clear();
+                }
+            }
+            c => field.push(c),
+        }
+    }
+
+    record.push(field);
+    if record.iter().any(|field| !field.is_empty()) {
+        records.push(record);
+    }
+    records
+}
+
+/// Reads a timestamp in seconds or milliseconds since the epoch, RFC 3339,
+/// or a plain date with an optional time.
+fn parse_time(time: &str) -> Option<f64> {
+    if let Ok(seconds) = time.parse::<f64>() {
+        if seconds <= 0.0 {
+            return None;
+        }
+        return Some(if seconds > 1e11 {
+            seconds / 1000.0
+        } else {
+            seconds
+        });
+    }
+
+    if let Ok(date) = chrono::DateTime::parse_from_rfc3339(time) {
+        return Some(date.timestamp() as f64);
+    }
+    if let Ok(date) = NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M:%S") {
+        return Some(date.and_utc().timestamp() as f64);
+    }
+    NaiveDate::parse_from_str(time, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|date| date.and_utc().timestamp() as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pocket_csv() {
+        let csv = "title,url,time_added,tags,status\r\n\
+                   \"Hello, World\",https://example.com/hello,1650000000,rust|gtk,unread\r\n\
+                   \"She said \"\"hi\"\"\",https://example.com/quote,1650000001,,archive\r\n";
+
+        let items = parse(csv);
+
+        assert_eq!(items.len(), 2);
+        assert_eq!(
+            items[0],
+            ImportItem {
+                url: "https://example.com/hello".to_string(),
+                title: "Hello, World".to_string(),
+                tags: vec!["rust".to_string(), "gtk".to_string()],
+                time: Some(1650000000.0),
+            }
+        );
+        assert_eq!(items[1].title, "She said \"hi\"");
+        assert!(items[1].tags.is_empty());
+    }
+
+    #[test]
+    fn test_parse_generic_csv() {
+        let csv = "URL,Tags,Date\n\
+                   https://example.com/a,\"news, tech\",2024-03-01\n\
+                   \n\
+                   not-a-link,,\n\
+                   https://example.com/b\n";
+
+        let items = parse(csv);
+
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].title, "");
+        assert_eq!(items[0].tags, vec!["news", "tech"]);
+        assert_eq!(items[0].time, Some(1709251200.0));
+        assert_eq!(items[1].url, "https://example.com/b");
+        assert_eq!(items[1].time, None);
+    }
+
+    #[test]
+    fn test_parse_without_url_column() {
+        assert!(parse("title,tags\nSomething,rust\n").is_empty());
+        assert!(parse("").is_empty());
+    }
+
+    #[test]
+    fn test_multiline_field() {
+        let records = records("url,title\nhttps://example.com,\"two\nlines\"");
+
+        assert_eq!(records[1], vec!["https://example.com", "two\nlines"]);
+    }
+
+    #[test]
+    fn test_parse_time() {
+        assert_eq!(parse_time("1650000000"), Some(1650000000.0));
+        assert_eq!(parse_time("1650000000000"), Some(1650000000.0));
+        assert_eq!(parse_time("2024-03-01T12:00:00Z"), Some(1709294400.0));
+        assert_eq!(parse_time("2024-03-01 12:00:00"), Some(1709294400.0));
+        assert_eq!(parse_time("yesterday"), None);
+    }
+}
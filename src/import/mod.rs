@@ -0,0 +1,401 @@
+pub mod csv;
+pub mod netscape;
+
+use std::collections::HashSet;
+use std::future::Future;
+use std::time::Duration;
+
+use url::Url;
+
+use crate::network::instapaper::InstapaperError;
+use crate::persistence::import_queue::{PendingBookmark, PendingImport};
+
+/// A bookmark read from an export file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportItem {
+    pub url: String,
+    /// Empty when the file doesn't name the bookmark
+    pub title: String,
+    pub tags: Vec<String>,
+    /// When it was saved, in seconds since the epoch
+    pub time: Option<f64>,
+}
+
+impl From<ImportItem> for PendingBookmark {
+    fn from(item: ImportItem) -> Self {
+        Self {
+            url: item.url,
+            title: item.title,
+            tags: item.tags,
+        }
+    }
+}
+
+/// Reads the bookmarks out of a Pocket export, a browser bookmark file or a
+/// CSV file, telling them apart by their contents.
+pub fn parse(contents: &str) -> Vec<ImportItem> {
+    let contents = contents.trim_start_matches('\u{feff}');
+
+    if contents.trim_start().starts_with('<') {
+        netscape::parse(contents)
+    } else {
+        csv::parse(contents)
+    }
+}
+
+/// The form of `url` used to spot the same page saved twice: only http and
+/// https links, without the fragment or a trailing slash.
+pub fn normalize_url(url: &str) -> Option<String> {
+    let mut url = Url::parse(url.trim()).ok()?;
+    if url.scheme() != "http" && url.scheme() != "https" {
+        return None;
+    }
+    url.set_fragment(None);
+
+    let path = url.path();
+    if path.len() > 1 && path.ends_with('/') {
+        let trimmed = path.trim_end_matches('/').to_string();
+        url.set_path(&trimmed);
+    }
+    Some(url.into())
+}
+
+/// Drops the items already in `existing` and repeated ones, returning what
+/// is left and how many were dropped.
+pub fn dedupe(items: Vec<ImportItem>, existing: &[String]) -> (Vec<ImportItem>, usize) {
+    let mut seen: HashSet<String> = existing
+        .iter()
+        .filter_map(|uri| normalize_url(uri))
+        .collect();
+    let total = items.len();
+
+    let unique: Vec<ImportItem> = items
+        .into_iter()
+        .filter(|item| normalize_url(&item.url).is_some_and(|url| seen.insert(url)))
+        .collect();
+    let duplicates = total - unique.len();
+
+    (unique, duplicates)
+}
+
+/// Orders items oldest first, those without a date last. Instapaper dates
+/// bookmarks when they are added, so importing in this order keeps them in
+/// the order they were saved in, though not their dates.
+pub fn oldest_first(mut items: Vec<ImportItem>) -> Vec<ImportItem> {
+    items.sort_by(|a, b| match (a.time, b.time) {
+        (Some(a), Some(b)) => a.total_cmp(&b),
+        (a, b) => b.is_some().cmp(&a.is_some()),
+    });
+    items
+}
+
+/// Splits a tag list on commas, pipes or semicolons.
+pub(crate) fn split_tags(tags: &str) -> Vec<String> {
+    let mut split: Vec<String> = Vec::new();
+    for tag in tags.split([',', '|', ';']).map(str::trim) {
+        if !tag.is_empty() && !split.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            split.push(tag.to_string());
+        }
+    }
+    split
+}
+
+/// How fast bookmarks are sent to Instapaper.
+pub struct Throttle {
+    /// Pause between two bookmarks
+    pub delay: Duration,
+    /// Pause after being rate limited, doubled on every retry
+    pub backoff: Duration,
+    /// Retries of a rate limited bookmark before giving up for now
+    pub retries: u32,
+}
+
+pub const THROTTLE: Throttle = Throttle {
+    delay: Duration::from_secs(2),
+    backoff: Duration::from_secs(30),
+    retries: 3,
+};
+
+/// Why an import stopped before the end of its queue.
+#[derive(Debug)]
+pub enum ImportError {
+    Instapaper(InstapaperError),
+    /// The queue couldn't be saved, so the import couldn't resume later
+    Save(anyhow::Error),
+}
+
+/// Sends the bookmarks left in `queue` through `perform` one at a time,
+/// calling `save` after each one so the import can resume where it stopped.
+///
+/// Bookmarks that fail are recorded and skipped. When Instapaper keeps
+/// refusing requests, or the credentials stop working, the import stops
+/// with the error and the rest of the queue is left for later. It also
+/// stops as soon as `save` fails.
+pub async fn run<F, Fut>(
+    queue: &mut PendingImport,
+    throttle: &Throttle,
+    mut perform: F,
+    mut save: impl FnMut(&PendingImport) -> anyhow::Result<()>,
+    mut on_progress: impl FnMut(usize, usize),
+) -> Result<(), ImportError>
+where
+    F: FnMut(PendingBookmark) -> Fut,
+    Fut: Future<Output = Result<(), InstapaperError>>,
+{
+    on_progress(queue.done(), queue.total);
+
+    while let Some(bookmark) = queue.bookmarks.front().cloned() {
+        let mut backoff = throttle.backoff;
+        let mut retries = 0;
+
+        let result = loop {
+            match perform(bookmark.clone()).await {
+                Err(InstapaperError::RateLimited) if retries < throttle.retries => {
+                    retries += 1;
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                }
+                result => break result,
+            }
+        };
+
+        match result {
+            Ok(()) => queue.added += 1,
+            Err(e @ (InstapaperError::RateLimited | InstapaperError::InvalidCredentials)) => {
+                save(queue).map_err(ImportError::Save)?;
+                return Err(ImportError::Instapaper(e));
+            }
+            Err(e) => queue.failed.push((bookmark.url, e.to_string())),
+        }
+
+        queue.bookmarks.pop_front();
+        save(queue).map_err(ImportError::Save)?;
+        on_progress(queue.done(), queue.total);
+
+        if !queue.bookmarks.is_empty() {
+            tokio::time::sleep(throttle.delay).await;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(url: &str) -> ImportItem {
+        ImportItem {
+            url: url.to_string(),
+            title: String::new(),
+            tags: Vec::new(),
+            time: None,
+        }
+    }
+
+    const NO_WAIT: Throttle = Throttle {
+        delay: Duration::ZERO,
+        backoff: Duration::ZERO,
+        retries: 2,
+    };
+
+    fn queue(urls: &[&str]) -> PendingImport {
+        PendingImport::new(urls.iter().map(|url| item(url).into()).collect())
+    }
+
+    #[test]
+    fn test_parse_detects_format() {
+        let html = "<!DOCTYPE NETSCAPE-Bookmark-file-1>\n<DL><p>\n<DT><A HREF=\"https://example.com/a\">A</A>\n</DL>";
+        assert_eq!(parse(html)[0].url, "https://example.com/a");
+
+        let csv = "\u{feff}url,title\nhttps://example.com/b,B\n";
+        assert_eq!(parse(csv)[0].title, "B");
+    }
+
+    #[test]
+    fn test_normalize_url() {
+        assert_eq!(
+            normalize_url("https://example.com/post/#comments").as_deref(),
+            Some("https://example.com/post")
+        );
+        assert_eq!(
+            normalize_url(" https://example.com ").as_deref(),
+            Some("https://example.com/")
+        );
+        assert_eq!(
+            normalize_url("https://example.com/?id=1").as_deref(),
+            Some("https://example.com/?id=1")
+        );
+        assert_eq!(normalize_url("ftp://example.com/file"), None);
+        assert_eq!(normalize_url("not a url"), None);
+    }
+
+    #[test]
+    fn test_dedupe() {
+        let items = vec![
+            item("https://example.com/saved/"),
+            item("https://example.com/new"),
+            item("https://example.com/new#top"),
+            item("javascript:alert(1)"),
+            item("https://example.com/other"),
+        ];
+        let existing = vec!["https://example.com/saved".to_string()];
+
+        let (unique, dropped) = dedupe(items, &existing);
+
+        let urls: Vec<&str> = unique.iter().map(|item| item.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            vec!["https://example.com/new", "https://example.com/other"]
+        );
+        assert_eq!(dropped, 3);
+    }
+
+    #[test]
+    fn test_oldest_first() {
+        let dated = |url: &str, time: Option<f64>| ImportItem { time, ..item(url) };
+        let items = vec![
+            dated("https://example.com/undated", None),
+            dated("https://example.com/new", Some(200.0)),
+            dated("https://example.com/old", Some(100.0)),
+        ];
+
+        let urls: Vec<String> = oldest_first(items)
+            .into_iter()
+            .map(|item| item.url)
+            .collect();
+        assert_eq!(
+            urls,
+            vec![
+                "https://example.com/old",
+                "https://example.com/new",
+                "https://example.com/undated"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_tags() {
+        assert_eq!(split_tags("rust|gtk"), vec!["rust", "gtk"]);
+        assert_eq!(split_tags("rust, Rust; gtk ,"), vec!["rust", "gtk"]);
+        assert!(split_tags("").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_run_saves_after_each_bookmark() {
+        let mut pending = queue(&["https://a.example", "https://b.example"]);
+        let mut saved = Vec::new();
+        let mut progress = Vec::new();
+
+        let result = run(
+            &mut pending,
+            &NO_WAIT,
+            |bookmark| async move {
+                if bookmark.url == "https://b.example" {
+                    Err(InstapaperError::ServiceUnavailable)
+                } else {
+                    Ok(())
+                }
+            },
+            |queue| {
+                saved.push(queue.bookmarks.len());
+                Ok(())
+            },
+            |done, total| progress.push((done, total)),
+        )
+        .await;
+
+        assert!(result.is_ok());
+        assert_eq!(pending.added, 1);
+        assert_eq!(pending.failed.len(), 1);
+        assert_eq!(pending.failed[0].0, "https://b.example");
+        assert!(pending.bookmarks.is_empty());
+        assert_eq!(saved, vec![1, 0]);
+        assert_eq!(progress, vec![(0, 2), (1, 2), (2, 2)]);
+    }
+
+    #[tokio::test]
+    async fn test_run_retries_when_rate_limited() {
+        let mut pending = queue(&["https://a.example"]);
+        let mut attempts = 0;
+
+        let result = run(
+            &mut pending,
+            &NO_WAIT,
+            |_| {
+                attempts += 1;
+                let attempt = attempts;
+                async move {
+                    if attempt < 3 {
+                        Err(InstapaperError::RateLimited)
+                    } else {
+                        Ok(())
+                    }
+                }
+            },
+            |_| Ok(()),
+            |_, _| {},
+        )
+        .await;
+
+        assert!(result.is_ok());
+        assert_eq!(attempts, 3);
+        assert_eq!(pending.added, 1);
+    }
+
+    #[tokio::test]
+    async fn test_run_stops_and_keeps_the_rest_when_rate_limited() {
+        let mut pending = queue(&[
+            "https://a.example",
+            "https://b.example",
+            "https://c.example",
+        ]);
+
+        let result = run(
+            &mut pending,
+            &NO_WAIT,
+            |bookmark| async move {
+                if bookmark.url == "https://a.example" {
+                    Ok(())
+                } else {
+                    Err(InstapaperError::RateLimited)
+                }
+            },
+            |_| Ok(()),
+            |_, _| {},
+        )
+        .await;
+
+        assert!(matches!(
+            result,
+            Err(ImportError::Instapaper(InstapaperError::RateLimited))
+        ));
+        assert_eq!(pending.added, 1);
+        assert!(pending.failed.is_empty());
+        let left: Vec<&str> = pending.bookmarks.iter().map(|b| b.url.as_str()).collect();
+        assert_eq!(left, vec!["https://b.example", "https://c.example"]);
+        assert_eq!(pending.done(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_run_stops_when_the_queue_cant_be_saved() {
+        let mut pending = queue(&["https://a.example", "https://b.example"]);
+        let mut attempts = 0;
+
+        let result = run(
+            &mut pending,
+            &NO_WAIT,
+            |_| {
+                attempts += 1;
+                async { Ok(()) }
+            },
+            |_| Err(anyhow::anyhow!("disk full")),
+            |_, _| {},
+        )
+        .await;
+
+        assert!(matches!(result, Err(ImportError::Save(_))));
+        assert_eq!(attempts, 1);
+        assert_eq!(pending.bookmarks.len(), 1);
+    }
+}
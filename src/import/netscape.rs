@@ -0,0 +1,109 @@
+use scraper::{Html, Selector};
+
+use super::{split_tags, ImportItem};
+
+/// Reads the links of a Netscape bookmark file, as exported by browsers, or
+/// of Pocket's `ril_export.html`.
+pub fn parse(contents: &str) -> Vec<ImportItem> {
+    let document = Html::parse_document(contents);
+    let links = Selector::parse("a[href]").unwrap();
+
+    document
+        .select(&links)
+        .filter_map(|link| {
+            let element = link.value();
+            let url = element.attr("href")?.trim();
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                return None;
+            }
+
+            let time = element
+                .attr("add_date")
+                .or_else(|| element.attr("time_added"))
+                .and_then(|time| time.trim().parse::<f64>().ok())
+                .map(|time| {
+                    // Some browsers write microseconds
+                    if time > 1e11 {
+                        time / 1e6
+                    } else {
+                        time
+                    }
+                });
+
+            Some(ImportItem {
+                url: url.to_string(),
+                title: link.text().collect::<String>().trim().to_string(),
+                tags: element.attr("tags").map(split_tags).unwrap_or_default(),
+                time,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_browser_bookmarks() {
+        let html = r#"<!DOCTYPE NETSCAPE-Bookmark-file-1>
+<META HTTP-EQUIV="Content-Type" CONTENT="text/html; charset=UTF-8">
+<TITLE>Bookmarks</TITLE>
+<H1>Bookmarks</H1>
+<DL><p>
+    <DT><H3 ADD_DATE="1600000000">Reading</H3>
+    <DL><p>
+        <DT><A HREF="https://example.com/one" ADD_DATE="1600000001" TAGS="rust,gtk">One &amp; Only</A>
+        <DT><A HREF="place:sort=8">Recent</A>
+        <DT><A HREF="http://example.com/two">Two</A>
+    </DL><p>
+</DL><p>"#;
+
+        let items = parse(html);
+
+        assert_eq!(items.len(), 2);
+        assert_eq!(
+            items[0],
+            ImportItem {
+                url: "https://example.com/one".to_string(),
+                title: "One & Only".to_string(),
+                tags: vec!["rust".to_string(), "gtk".to_string()],
+                time: Some(1600000001.0),
+            }
+        );
+        assert_eq!(items[1].url, "http://example.com/two");
+        assert!(items[1].tags.is_empty());
+        assert_eq!(items[1].time, None);
+    }
+
+    #[test]
+    fn test_parse_pocket_export() {
+        let html = r#"<!DOCTYPE html>
+<html><head><title>Pocket Export</title></head>
+<body>
+<h1>Unread</h1>
+<ul>
+<li><a href="https://example.com/article" time_added="1650000000" tags="longread">An Article</a></li>
+</ul>
+<h1>Read Archive</h1>
+<ul>
+<li><a href="https://example.com/old" time_added="1500000000" tags="">Old</a></li>
+</ul>
+</body></html>"#;
+
+        let items = parse(html);
+
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].title, "An Article");
+        assert_eq!(items[0].tags, vec!["longread"]);
+        assert_eq!(items[0].time, Some(1650000000.0));
+        assert!(items[1].tags.is_empty());
+    }
+
+    #[test]
+    fn test_parse_microsecond_dates() {
+        let html = r#"<DT><A HREF="https://example.com" ADD_DATE="1600000000000000">Example</A>"#;
+
+        assert_eq!(parse(html)[0].time, Some(1600000000.0));
+    }
+}
@@ -2,9 +2,13 @@
 mod config;
 mod app;
 mod article;
+mod backup;
+mod export;
+mod import;
 mod modals;
 mod network;
 mod persistence;
+mod preferences;
 
 #[cfg(test)]
 pub mod testing;
@@ -193,7 +193,16 @@ impl Component for AddBookmarkDialog {
                 sender.oneshot_command(async move {
                     let client = instapaper::client();
 
-                    match instapaper::add_bookmark(&client, &tokens, &url, &tags).await {
+                    match instapaper::add_bookmark(
+                        &client,
+                        &tokens,
+                        &url,
+                        "",
+                        &tags,
+                        instapaper::BASE_URL,
+                    )
+                    .await
+                    {
                         Ok(_) => AddBookmarkCommandOutput::AddSuccess,
                         Err(instapaper::InstapaperError::InvalidCredentials) => {
                             AddBookmarkCommandOutput::AddFailed(gettext(
@@ -242,7 +251,7 @@ impl Component for AddBookmarkDialog {
     }
 }
 
-fn parse_tags(input: &str) -> Vec<String> {
+pub(crate) fn parse_tags(input: &str) -> Vec<String> {
     input
         .split(',')
         .map(|s| s.trim().to_string())
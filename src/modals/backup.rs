@@ -0,0 +1,537 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::PathBuf;
+
+use gtk::prelude::{BoxExt, ButtonExt, CastNone, OrientableExt, ProgressBarExt, WidgetExt};
+use relm4::{
+    adw,
+    adw::prelude::{ActionRowExt, AdwDialogExt, PreferencesGroupExt, PreferencesRowExt},
+    gtk::{self, gio, glib},
+    Component, ComponentParts, ComponentSender, RelmWidgetExt,
+};
+
+use gettextrs::{gettext, ngettext};
+
+use crate::backup::{self, Library, RestoreReport};
+use crate::persistence::articles::PersistedArticle;
+use crate::persistence::token::TokenPair;
+
+pub struct BackupInit {
+    pub articles: Vec<PersistedArticle>,
+    /// The settings to save, as taken by [`backup::settings_snapshot`]
+    pub settings: BTreeMap<String, String>,
+    pub tokens: Option<TokenPair>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Task {
+    BackUp,
+    Restore,
+}
+
+pub struct BackupDialog {
+    init: BackupInit,
+    task: Option<Task>,
+    /// Steps done and total while working
+    progress: Option<(usize, usize)>,
+    error_message: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum BackupInput {
+    ChooseBackupFile,
+    ChooseRestoreFile,
+    BackUpTo(PathBuf),
+    RestoreFrom(PathBuf),
+    Cancel,
+}
+
+#[derive(Debug, Clone)]
+pub enum BackupOutput {
+    /// A summary of what was saved
+    BackedUp(String),
+    /// The restored library, to merge the bookmarks and settings from, and
+    /// what was restored so far
+    Restored(Box<Library>, RestoreReport),
+    Cancelled,
+}
+
+#[derive(Debug)]
+pub enum BackupCommandOutput {
+    Progress(usize, usize),
+    BackedUp(Result<String, String>),
+    Restored(Result<(Box<Library>, RestoreReport), String>),
+}
+
+#[relm4::component(pub)]
+impl Component for BackupDialog {
+    type Init = BackupInit;
+    type Input = BackupInput;
+    type Output = BackupOutput;
+    type CommandOutput = BackupCommandOutput;
+
+    view! {
+        adw::Dialog {
+            set_title: &gettext("Back Up and Restore"),
+            set_content_width: 450,
+
+            #[wrap(Some)]
+            set_child = &adw::ToolbarView {
+                add_top_bar = &adw::HeaderBar {
+                    #[wrap(Some)]
+                    set_title_widget = &adw::WindowTitle {
+                        set_title: &gettext("Back Up and Restore"),
+                    },
+                },
+
+                #[wrap(Some)]
+                set_content = &gtk::Box {
+                    set_orientation: gtk::Orientation::Vertical,
+                    set_margin_all: 24,
+                    set_spacing: 16,
+
+                    adw::PreferencesGroup {
+                        set_description: Some(&gettext(
+                            "A backup holds your bookmarks, tags, folders, highlights, reading progress, settings and downloaded articles. Your login is never included.",
+                        )),
+
+                        adw::ActionRow {
+                            set_title: &gettext("Back Up Library"),
+                            set_subtitle: &gettext("Save everything to a single file"),
+
+                            add_suffix = &gtk::Button {
+                                set_label: &gettext("Back Up…"),
+                                set_valign: gtk::Align::Center,
+                                #[watch]
+                                set_sensitive: model.task.is_none(),
+                                connect_clicked => BackupInput::ChooseBackupFile,
+                            },
+                        },
+
+                        adw::ActionRow {
+                            set_title: &gettext("Restore Library"),
+                            set_subtitle: &gettext("Add what is missing here from a backup"),
+
+                            add_suffix = &gtk::Button {
+                                set_label: &gettext("Restore…"),
+                                set_valign: gtk::Align::Center,
+                                #[watch]
+                                set_sensitive: model.task.is_none(),
+                                connect_clicked => BackupInput::ChooseRestoreFile,
+                            },
+                        },
+                    },
+
+                    gtk::ProgressBar {
+                        #[watch]
+                        set_visible: model.task.is_some(),
+                        #[watch]
+                        set_fraction: model.progress_fraction(),
+                        set_show_text: true,
+                        #[watch]
+                        set_text: Some(&model.progress_text()),
+                    },
+
+                    gtk::Label {
+                        #[watch]
+                        set_visible: model.error_message.is_some(),
+                        #[watch]
+                        set_label: model.error_message.as_deref().unwrap_or(""),
+                        add_css_class: "error",
+                        set_wrap: true,
+                    },
+
+                    gtk::Box {
+                        set_orientation: gtk::Orientation::Horizontal,
+                        set_halign: gtk::Align::End,
+                        set_valign: gtk::Align::End,
+                        set_vexpand: true,
+
+                        gtk::Button {
+                            set_label: &gettext("Close"),
+                            connect_clicked => BackupInput::Cancel,
+                        },
+                    },
+                },
+            },
+
+            connect_closed[sender] => move |_| {
+                sender.input(BackupInput::Cancel);
+            },
+        }
+    }
+
+    fn init(
+        init: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let model = Self {
+            init,
+            task: None,
+            progress: None,
+            error_message: None,
+        };
+
+        let widgets = view_output!();
+
+        // Only present the dialog if we're not in a test environment
+        if !cfg!(test) {
+            root.present(Some(&relm4::main_application().windows()[0]));
+        }
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, message: Self::Input, sender: ComponentSender<Self>, root: &Self::Root) {
+        match message {
+            BackupInput::ChooseBackupFile => {
+                let dialog = gtk::FileDialog::builder()
+                    .title(gettext("Back Up Library"))
+                    .initial_name(format!(
+                        "cauldron-backup-{}.zip",
+                        chrono::Local::now().format("%Y-%m-%d")
+                    ))
+                    .modal(true)
+                    .build();
+
+                let window = root.root().and_downcast::<gtk::Window>();
+                let sender = sender.clone();
+                dialog.save(
+                    window.as_ref(),
+                    gio::Cancellable::NONE,
+                    move |result: Result<gio::File, glib::Error>| {
+                        if let Some(path) = result.ok().and_then(|file| file.path()) {
+                            sender.input(BackupInput::BackUpTo(path));
+                        }
+                    },
+                );
+            }
+            BackupInput::ChooseRestoreFile => {
+                let filter = gtk::FileFilter::new();
+                filter.set_name(Some(&gettext("Cauldron Backups")));
+                filter.add_pattern("*.zip");
+                let filters = gio::ListStore::new::<gtk::FileFilter>();
+                filters.append(&filter);
+
+                let dialog = gtk::FileDialog::builder()
+                    .title(gettext("Restore Library"))
+                    .filters(&filters)
+                    .modal(true)
+                    .build();
+
+                let window = root.root().and_downcast::<gtk::Window>();
+                let sender = sender.clone();
+                dialog.open(
+                    window.as_ref(),
+                    gio::Cancellable::NONE,
+                    move |result: Result<gio::File, glib::Error>| {
+                        if let Some(path) = result.ok().and_then(|file| file.path()) {
+                            sender.input(BackupInput::RestoreFrom(path));
+                        }
+                    },
+                );
+            }
+            BackupInput::BackUpTo(path) => {
+                if self.task.is_some() {
+                    return;
+                }
+                if self.init.articles.is_empty() {
+                    self.error_message = Some(gettext("There are no bookmarks to back up"));
+                    return;
+                }
+
+                self.task = Some(Task::BackUp);
+                self.progress = None;
+                self.error_message = None;
+
+                let articles = self.init.articles.clone();
+                let settings = self.init.settings.clone();
+                let tokens = self.init.tokens.clone();
+
+                sender.command(move |out, shutdown| {
+                    shutdown
+                        .register(async move {
+                            let backup = backup::collect(
+                                articles,
+                                settings,
+                                tokens.as_ref(),
+                                |done, total| {
+                                    let _ = out.send(BackupCommandOutput::Progress(done, total));
+                                },
+                            )
+                            .await;
+
+                            let result = File::create(&path)
+                                .and_then(|file| backup::write(&backup, BufWriter::new(file)))
+                                .map(|_| backup_summary(&backup))
+                                .map_err(|e| {
+                                    format!("{}: {}", gettext("Failed to write the backup"), e)
+                                });
+                            let _ = out.send(BackupCommandOutput::BackedUp(result));
+                        })
+                        .drop_on_shutdown()
+                });
+            }
+            BackupInput::RestoreFrom(path) => {
+                if self.task.is_some() {
+                    return;
+                }
+
+                self.task = Some(Task::Restore);
+                self.progress = None;
+                self.error_message = None;
+
+                let tokens = self.init.tokens.clone();
+
+                sender.command(move |out, shutdown| {
+                    shutdown
+                        .register(async move {
+                            let result = async {
+                                let backup::Backup {
+                                    mut library,
+                                    mut bodies,
+                                    ..
+                                } = std::fs::read(&path)
+                                    .and_then(|bytes| backup::read(&bytes))
+                                    .map_err(|e| {
+                                        format!("{}: {}", gettext("Failed to read the backup"), e)
+                                    })?;
+
+                                // The bookmarks and settings are merged by the app
+                                let mut report = RestoreReport::default();
+                                if let Some(tokens) = &tokens {
+                                    let renamed = backup::restore_to_instapaper(
+                                        &library,
+                                        tokens,
+                                        &mut report,
+                                        |done, total| {
+                                            let _ = out
+                                                .send(BackupCommandOutput::Progress(done, total));
+                                        },
+                                    )
+                                    .await;
+                                    backup::rename_bookmarks(&mut library, &mut bodies, &renamed);
+                                }
+                                report.bodies = backup::restore_bodies(&bodies);
+
+                                Ok((Box::new(library), report))
+                            }
+                            .await;
+                            let _ = out.send(BackupCommandOutput::Restored(result));
+                        })
+                        .drop_on_shutdown()
+                });
+            }
+            BackupInput::Cancel => {
+                root.close();
+                let _ = sender.output(BackupOutput::Cancelled);
+            }
+        }
+    }
+
+    fn update_cmd(
+        &mut self,
+        message: Self::CommandOutput,
+        sender: ComponentSender<Self>,
+        root: &Self::Root,
+    ) {
+        match message {
+            BackupCommandOutput::Progress(done, total) => {
+                self.progress = Some((done, total));
+            }
+            BackupCommandOutput::BackedUp(Ok(summary)) => {
+                self.task = None;
+                root.close();
+                let _ = sender.output(BackupOutput::BackedUp(summary));
+            }
+            BackupCommandOutput::Restored(Ok((library, report))) => {
+                self.task = None;
+                root.close();
+                let _ = sender.output(BackupOutput::Restored(library, report));
+            }
+            BackupCommandOutput::BackedUp(Err(error))
+            | BackupCommandOutput::Restored(Err(error)) => {
+                self.task = None;
+                self.error_message = Some(error);
+            }
+        }
+    }
+}
+
+impl BackupDialog {
+    fn progress_fraction(&self) -> f64 {
+        match self.progress {
+            Some((done, total)) if total > 0 => done as f64 / total as f64,
+            _ => 0.0,
+        }
+    }
+
+    fn progress_text(&self) -> String {
+        match (self.task, self.progress) {
+            (Some(Task::BackUp), Some((done, total))) if total > 0 => {
+                gettext("Fetching highlights, {} of {}")
+                    .replacen("{}", &done.to_string(), 1)
+                    .replacen("{}", &total.to_string(), 1)
+            }
+            (Some(Task::Restore), Some((done, total))) if total > 0 => {
+                gettext("Restoring to Instapaper, {} of {}")
+                    .replacen("{}", &done.to_string(), 1)
+                    .replacen("{}", &total.to_string(), 1)
+            }
+            _ => gettext("Preparing…"),
+        }
+    }
+}
+
+fn backup_summary(backup: &backup::Backup) -> String {
+    let count = backup.library.bookmarks.len();
+    let mut summary = ngettext(
+        "Backed up {} bookmark",
+        "Backed up {} bookmarks",
+        count as u32,
+    )
+    .replace("{}", &count.to_string());
+    if backup.incomplete {
+        summary = format!(
+            "{}. {}",
+            summary,
+            gettext(
+                "Some highlights, folders or reading progress could not be fetched from Instapaper"
+            )
+        );
+    }
+    summary
+}
+
+/// What a restore brought back, once the bookmarks and settings are merged.
+pub fn restore_summary(report: &RestoreReport) -> String {
+    let counts = [
+        (
+            report.bookmarks,
+            ngettext("{} bookmark", "{} bookmarks", report.bookmarks as u32),
+        ),
+        (
+            report.bodies,
+            ngettext(
+                "{} downloaded article",
+                "{} downloaded articles",
+                report.bodies as u32,
+            ),
+        ),
+        (
+            report.settings,
+            ngettext("{} setting", "{} settings", report.settings as u32),
+        ),
+        (
+            report.progress,
+            ngettext(
+                "{} reading position",
+                "{} reading positions",
+                report.progress as u32,
+            ),
+        ),
+        (
+            report.highlights,
+            ngettext("{} highlight", "{} highlights", report.highlights as u32),
+        ),
+        (
+            report.folders,
+            ngettext("{} folder", "{} folders", report.folders as u32),
+        ),
+    ];
+    let restored: Vec<String> = counts
+        .into_iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, text)| text.replace("{}", &count.to_string()))
+        .collect();
+
+    let mut summary = if restored.is_empty() {
+        gettext("Nothing was missing from this library")
+    } else {
+        format!("{}: {}", gettext("Restored"), restored.join(", "))
+    };
+    if report.incomplete {
+        summary = format!(
+            "{}. {}",
+            summary,
+            gettext("Instapaper did not take everything, restore again later to finish")
+        );
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::ComponentTester;
+
+    fn init(articles: Vec<PersistedArticle>) -> BackupInit {
+        BackupInit {
+            articles,
+            settings: BTreeMap::new(),
+            tokens: None,
+        }
+    }
+
+    #[gtk::test]
+    fn test_back_up_empty_library() {
+        let tester = ComponentTester::<BackupDialog>::launch(init(Vec::new()));
+        tester.send_input(BackupInput::BackUpTo(
+            std::env::temp_dir().join(format!("cauldron-backup-empty-{}.zip", std::process::id())),
+        ));
+        tester.process_events();
+
+        assert_eq!(
+            tester.model().error_message,
+            Some(gettext("There are no bookmarks to back up"))
+        );
+        assert_eq!(tester.model().task, None);
+    }
+
+    #[test]
+    fn test_backup_summary() {
+        let mut backup = backup::Backup {
+            library: Library {
+                version: 1,
+                created: String::new(),
+                bookmarks: Vec::new(),
+                folders: Vec::new(),
+                settings: BTreeMap::new(),
+            },
+            bodies: Vec::new(),
+            incomplete: false,
+        };
+        assert_eq!(backup_summary(&backup), "Backed up 0 bookmarks");
+
+        backup.incomplete = true;
+        assert_eq!(
+            backup_summary(&backup),
+            "Backed up 0 bookmarks. Some highlights, folders or reading progress could not be fetched from Instapaper"
+        );
+    }
+
+    #[test]
+    fn test_restore_summary() {
+        let report = RestoreReport {
+            bookmarks: 3,
+            bodies: 1,
+            highlights: 2,
+            ..RestoreReport::default()
+        };
+        assert_eq!(
+            restore_summary(&report),
+            "Restored: 3 bookmarks, 1 downloaded article, 2 highlights"
+        );
+
+        let report = RestoreReport {
+            incomplete: true,
+            ..RestoreReport::default()
+        };
+        assert_eq!(
+            restore_summary(&report),
+            "Nothing was missing from this library. Instapaper did not take everything, restore again later to finish"
+        );
+    }
+}
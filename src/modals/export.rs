@@ -0,0 +1,727 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+use gtk::prelude::{
+    BoxExt, ButtonExt, CastNone, EditableExt, OrientableExt, ProgressBarExt, WidgetExt,
+};
+use relm4::{
+    adw,
+    adw::prelude::{AdwDialogExt, ComboRowExt, PreferencesGroupExt, PreferencesRowExt},
+    gtk::{self, gio, glib},
+    Component, ComponentParts, ComponentSender, RelmWidgetExt,
+};
+
+use gettextrs::{gettext, ngettext};
+
+use crate::article::print;
+use crate::export::{epub, vault, ExportArticle};
+use crate::network::instapaper::{self, InstapaperFolder};
+use crate::persistence::token::TokenPair;
+use crate::preferences::ContentSource;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// A single e-book
+    Epub,
+    /// A note per article in a folder, such as an Obsidian vault
+    Markdown,
+}
+
+const FORMATS: &[ExportFormat] = &[ExportFormat::Epub, ExportFormat::Markdown];
+
+/// Which bookmarks go into the export.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExportScope {
+    /// The articles the list shows, with the search and tag filter applied
+    CurrentList,
+    Tag(String),
+    Folder(i64),
+}
+
+pub struct ExportInit {
+    /// The articles in the list right now
+    pub visible: Vec<ExportArticle>,
+    /// Every article, to pick from by tag
+    pub all: Vec<ExportArticle>,
+    pub tags: Vec<String>,
+    pub folders: Vec<InstapaperFolder>,
+    pub tokens: Option<TokenPair>,
+    pub content_source: ContentSource,
+    pub cache_size: u32,
+    /// Folder of the last Markdown export, offered again to update it
+    pub markdown_folder: Option<PathBuf>,
+}
+
+pub struct ExportDialog {
+    init: ExportInit,
+    format: ExportFormat,
+    scopes: Vec<ExportScope>,
+    scope: usize,
+    title: String,
+    /// Articles done and total while exporting
+    progress: Option<(usize, usize)>,
+    error_message: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum ExportInput {
+    SetFormat(usize),
+    SetScope(usize),
+    SetTitle(String),
+    ChooseFile,
+    ExportTo(PathBuf),
+    Cancel,
+}
+
+#[derive(Debug, Clone)]
+pub enum ExportOutput {
+    /// A summary of what was written
+    Exported(String),
+    MarkdownFolderChosen(PathBuf),
+    Cancelled,
+}
+
+#[derive(Debug)]
+pub enum ExportCommandOutput {
+    Progress(usize, usize),
+    Finished(Result<String, String>),
+}
+
+#[relm4::component(pub)]
+impl Component for ExportDialog {
+    type Init = ExportInit;
+    type Input = ExportInput;
+    type Output = ExportOutput;
+    type CommandOutput = ExportCommandOutput;
+
+    view! {
+        adw::Dialog {
+            set_title: &gettext("Export Articles"),
+            set_content_width: 450,
+
+            #[wrap(Some)]
+            set_child = &adw::ToolbarView {
+                add_top_bar = &adw::HeaderBar {
+                    #[wrap(Some)]
+                    set_title_widget = &adw::WindowTitle {
+                        set_title: &gettext("Export Articles"),
+                    },
+                },
+
+                #[wrap(Some)]
+                set_content = &gtk::Box {
+                    set_orientation: gtk::Orientation::Vertical,
+                    set_margin_all: 24,
+                    set_spacing: 16,
+
+                    adw::PreferencesGroup {
+                        #[watch]
+                        set_description: Some(&model.format_description()),
+
+                        adw::ComboRow {
+                            set_title: &gettext("Format"),
+                            set_model: Some(&gtk::StringList::new(&[
+                                &gettext("EPUB E-Book"),
+                                &gettext("Markdown Notes"),
+                            ])),
+                            #[watch]
+                            set_sensitive: model.progress.is_none(),
+                            connect_selected_notify[sender] => move |row| {
+                                sender.input(ExportInput::SetFormat(row.selected() as usize));
+                            },
+                        },
+
+                        adw::ComboRow {
+                            set_title: &gettext("Articles"),
+                            set_model: Some(&scope_model),
+                            #[watch]
+                            set_sensitive: model.progress.is_none(),
+                            connect_selected_notify[sender] => move |row| {
+                                sender.input(ExportInput::SetScope(row.selected() as usize));
+                            },
+                        },
+
+                        adw::EntryRow {
+                            set_title: &gettext("Book Title"),
+                            set_text: &model.title,
+                            #[watch]
+                            set_visible: model.format == ExportFormat::Epub,
+                            #[watch]
+                            set_sensitive: model.progress.is_none(),
+                            connect_changed[sender] => move |entry| {
+                                sender.input(ExportInput::SetTitle(entry.text().to_string()));
+                            },
+                        },
+                    },
+
+                    gtk::ProgressBar {
+                        #[watch]
+                        set_visible: model.progress.is_some(),
+                        #[watch]
+                        set_fraction: model.progress_fraction(),
+                        set_show_text: true,
+                        #[watch]
+                        set_text: Some(&model.progress_text()),
+                    },
+
+                    gtk::Label {
+                        #[watch]
+                        set_visible: model.error_message.is_some(),
+                        #[watch]
+                        set_label: model.error_message.as_deref().unwrap_or(""),
+                        add_css_class: "error",
+                        set_wrap: true,
+                    },
+
+                    gtk::Box {
+                        set_orientation: gtk::Orientation::Horizontal,
+                        set_spacing: 12,
+                        set_halign: gtk::Align::End,
+                        set_valign: gtk::Align::End,
+                        set_vexpand: true,
+
+                        gtk::Button {
+                            set_label: &gettext("Cancel"),
+                            connect_clicked => ExportInput::Cancel,
+                        },
+
+                        gtk::Button {
+                            set_label: &gettext("Export…"),
+                            add_css_class: "suggested-action",
+                            #[watch]
+                            set_sensitive: model.progress.is_none(),
+                            connect_clicked => ExportInput::ChooseFile,
+                        },
+                    },
+                },
+            },
+
+            connect_closed[sender] => move |_| {
+                sender.input(ExportInput::Cancel);
+            },
+        }
+    }
+
+    fn init(
+        init: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let scopes = scopes(&init.tags, &init.folders);
+        let labels: Vec<String> = scopes
+            .iter()
+            .map(|scope| scope_label(scope, &init.folders))
+            .collect();
+        let scope_model =
+            gtk::StringList::new(&labels.iter().map(String::as_str).collect::<Vec<_>>());
+
+        let model = Self {
+            init,
+            format: ExportFormat::Epub,
+            scopes,
+            scope: 0,
+            title: format!(
+                "{} {}",
+                gettext("Cauldron"),
+                chrono::Local::now().format("%Y-%m-%d")
+            ),
+            progress: None,
+            error_message: None,
+        };
+
+        let widgets = view_output!();
+
+        // Only present the dialog if we're not in a test environment
+        if !cfg!(test) {
+            root.present(Some(&relm4::main_application().windows()[0]));
+        }
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, message: Self::Input, sender: ComponentSender<Self>, root: &Self::Root) {
+        match message {
+            ExportInput::SetFormat(index) => {
+                self.format = FORMATS.get(index).copied().unwrap_or(ExportFormat::Epub);
+                self.error_message = None;
+            }
+            ExportInput::SetScope(index) => {
+                self.scope = index.min(self.scopes.len().saturating_sub(1));
+                self.error_message = None;
+            }
+            ExportInput::SetTitle(title) => {
+                self.title = title;
+            }
+            ExportInput::ChooseFile => {
+                let window = root.root().and_downcast::<gtk::Window>();
+                let sender = sender.clone();
+                let chosen = move |result: Result<gio::File, glib::Error>| {
+                    if let Some(path) = result.ok().and_then(|file| file.path()) {
+                        sender.input(ExportInput::ExportTo(path));
+                    }
+                };
+
+                match self.format {
+                    ExportFormat::Epub => {
+                        if self.title.trim().is_empty() {
+                            self.error_message = Some(gettext("Please enter a title"));
+                            return;
+                        }
+
+                        let dialog = gtk::FileDialog::builder()
+                            .title(gettext("Export to EPUB"))
+                            .initial_name(format!("{}.epub", print::file_name(&self.title)))
+                            .modal(true)
+                            .build();
+                        dialog.save(window.as_ref(), gio::Cancellable::NONE, chosen);
+                    }
+                    ExportFormat::Markdown => {
+                        let dialog = gtk::FileDialog::builder()
+                            .title(gettext("Export Markdown Notes"))
+                            .modal(true)
+                            .build();
+                        if let Some(folder) = &self.init.markdown_folder {
+                            dialog.set_initial_folder(Some(&gio::File::for_path(folder)));
+                        }
+                        dialog.select_folder(window.as_ref(), gio::Cancellable::NONE, chosen);
+                    }
+                }
+            }
+            ExportInput::ExportTo(path) => {
+                if self.progress.is_some() {
+                    return;
+                }
+
+                let scope = self.scopes[self.scope].clone();
+                let articles = self.articles(&scope);
+                if articles.as_ref().is_some_and(Vec::is_empty) {
+                    self.error_message = Some(gettext("There are no articles to export"));
+                    return;
+                }
+
+                self.error_message = None;
+                self.progress = Some((0, articles.as_ref().map_or(0, Vec::len)));
+
+                if self.format == ExportFormat::Markdown {
+                    self.init.markdown_folder = Some(path.clone());
+                    let _ = sender.output(ExportOutput::MarkdownFolderChosen(path.clone()));
+                }
+
+                let format = self.format;
+                let title = self.title.trim().to_string();
+                let tokens = self.init.tokens.clone();
+                let instapaper_tokens = match self.init.content_source {
+                    ContentSource::Instapaper => self.init.tokens.clone(),
+                    ContentSource::Original => None,
+                };
+                let cache_size = self.init.cache_size;
+
+                sender.command(move |out, shutdown| {
+                    shutdown
+                        .register(async move {
+                            let result = async {
+                                let articles = match (articles, scope) {
+                                    (Some(articles), _) => articles,
+                                    (None, ExportScope::Folder(folder_id)) => {
+                                        folder_articles(tokens.as_ref(), folder_id).await?
+                                    }
+                                    (None, _) => Vec::new(),
+                                };
+                                if articles.is_empty() {
+                                    return Err(gettext("There are no articles to export"));
+                                }
+
+                                let progress = |done, total| {
+                                    let _ = out.send(ExportCommandOutput::Progress(done, total));
+                                };
+
+                                match format {
+                                    ExportFormat::Epub => {
+                                        let (book, failed) = epub::collect(
+                                            &title,
+                                            &articles,
+                                            instapaper_tokens.as_ref(),
+                                            cache_size,
+                                            progress,
+                                        )
+                                        .await;
+                                        if book.chapters.is_empty() {
+                                            return Err(gettext(
+                                                "None of the articles could be downloaded",
+                                            ));
+                                        }
+
+                                        let count = book.chapters.len();
+                                        let file = path.clone();
+                                        relm4::spawn_blocking(move || {
+                                            File::create(&file).and_then(|file| {
+                                                epub::write(&book, BufWriter::new(file))
+                                            })
+                                        })
+                                        .await
+                                        .map_err(|e| e.to_string())
+                                        .and_then(
+                                            |written| {
+                                                written.map_err(|e| {
+                                                    format!(
+                                                        "{}: {}",
+                                                        gettext("Failed to write the e-book"),
+                                                        e
+                                                    )
+                                                })
+                                            },
+                                        )?;
+                                        Ok(epub_summary(count, failed, &path))
+                                    }
+                                    ExportFormat::Markdown => {
+                                        let (notes, failed) = vault::collect(
+                                            &articles,
+                                            tokens.as_ref(),
+                                            instapaper_tokens.as_ref(),
+                                            cache_size,
+                                            progress,
+                                        )
+                                        .await;
+                                        if notes.is_empty() {
+                                            return Err(gettext(
+                                                "None of the articles could be downloaded",
+                                            ));
+                                        }
+
+                                        let report = relm4::spawn_blocking(move || {
+                                            vault::write_all(&path, &notes)
+                                        })
+                                        .await
+                                        .map_err(|e| e.to_string())
+                                        .and_then(|written| {
+                                            written.map_err(|e| {
+                                                format!(
+                                                    "{}: {}",
+                                                    gettext("Failed to write the notes"),
+                                                    e
+                                                )
+                                            })
+                                        })?;
+                                        Ok(markdown_summary(&report, failed))
+                                    }
+                                }
+                            }
+                            .await;
+                            let _ = out.send(ExportCommandOutput::Finished(result));
+                        })
+                        .drop_on_shutdown()
+                });
+            }
+            ExportInput::Cancel => {
+                root.close();
+                let _ = sender.output(ExportOutput::Cancelled);
+            }
+        }
+    }
+
+    fn update_cmd(
+        &mut self,
+        message: Self::CommandOutput,
+        sender: ComponentSender<Self>,
+        root: &Self::Root,
+    ) {
+        match message {
+            ExportCommandOutput::Progress(done, total) => {
+                self.progress = Some((done, total));
+            }
+            ExportCommandOutput::Finished(Ok(summary)) => {
+                self.progress = None;
+                root.close();
+                let _ = sender.output(ExportOutput::Exported(summary));
+            }
+            ExportCommandOutput::Finished(Err(error)) => {
+                self.progress = None;
+                self.error_message = Some(error);
+            }
+        }
+    }
+}
+
+impl ExportDialog {
+    /// The articles in `scope`, or `None` when they have to be fetched
+    /// from Instapaper first.
+    fn articles(&self, scope: &ExportScope) -> Option<Vec<ExportArticle>> {
+        match scope {
+            ExportScope::CurrentList => Some(self.init.visible.clone()),
+            ExportScope::Tag(tag) => Some(
+                self.init
+                    .all
+                    .iter()
+                    .filter(|article| article.tags.contains(tag))
+                    .cloned()
+                    .collect(),
+            ),
+            ExportScope::Folder(_) => None,
+        }
+    }
+
+    fn format_description(&self) -> String {
+        match self.format {
+            ExportFormat::Epub => gettext("Saves the articles with their images as a single e-book"),
+            ExportFormat::Markdown => gettext(
+                "Saves a note per article in a folder, such as an Obsidian vault. Exporting to the same folder again updates the notes and keeps what you wrote below the marker line.",
+            ),
+        }
+    }
+
+    fn progress_fraction(&self) -> f64 {
+        match self.progress {
+            Some((done, total)) if total > 0 => done as f64 / total as f64,
+            _ => 0.0,
+        }
+    }
+
+    fn progress_text(&self) -> String {
+        match self.progress {
+            Some((_, 0)) | None => gettext("Preparing…"),
+            Some((done, total)) => ngettext("{} of {} article", "{} of {} articles", total as u32)
+                .replacen("{}", &done.to_string(), 1)
+                .replacen("{}", &total.to_string(), 1),
+        }
+    }
+}
+
+fn epub_summary(count: usize, failed: usize, path: &Path) -> String {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let summary = ngettext(
+        "Exported {} article to {}",
+        "Exported {} articles to {}",
+        count as u32,
+    )
+    .replacen("{}", &count.to_string(), 1)
+    .replacen("{}", &file_name, 1);
+
+    match failed_summary(failed) {
+        Some(failed) => format!("{}, {}", summary, failed),
+        None => summary,
+    }
+}
+
+fn markdown_summary(report: &vault::VaultReport, failed: usize) -> String {
+    let mut parts = vec![gettext("{} new, {} updated, {} unchanged")
+        .replacen("{}", &report.created.to_string(), 1)
+        .replacen("{}", &report.updated.to_string(), 1)
+        .replacen("{}", &report.unchanged.to_string(), 1)];
+    if report.skipped > 0 {
+        parts.push(
+            ngettext(
+                "{} note without marker left alone",
+                "{} notes without marker left alone",
+                report.skipped as u32,
+            )
+            .replace("{}", &report.skipped.to_string()),
+        );
+    }
+    parts.extend(failed_summary(failed));
+    format!("{}: {}", gettext("Notes exported"), parts.join(", "))
+}
+
+fn failed_summary(failed: usize) -> Option<String> {
+    (failed > 0).then(|| {
+        ngettext(
+            "{} article couldn't be downloaded",
+            "{} articles couldn't be downloaded",
+            failed as u32,
+        )
+        .replace("{}", &failed.to_string())
+    })
+}
+
+/// The scopes offered, the current list first.
+fn scopes(tags: &[String], folders: &[InstapaperFolder]) -> Vec<ExportScope> {
+    std::iter::once(ExportScope::CurrentList)
+        .chain(tags.iter().cloned().map(ExportScope::Tag))
+        .chain(
+            folders
+                .iter()
+                .map(|folder| ExportScope::Folder(folder.folder_id)),
+        )
+        .collect()
+}
+
+fn scope_label(scope: &ExportScope, folders: &[InstapaperFolder]) -> String {
+    match scope {
+        ExportScope::CurrentList => gettext("Current List"),
+        ExportScope::Tag(tag) => gettext("Tag: {}").replace("{}", tag),
+        ExportScope::Folder(folder_id) => {
+            let title = folders
+                .iter()
+                .find(|folder| folder.folder_id == *folder_id)
+                .map(|folder| folder.title.as_str())
+                .unwrap_or_default();
+            gettext("Folder: {}").replace("{}", title)
+        }
+    }
+}
+
+async fn folder_articles(
+    tokens: Option<&TokenPair>,
+    folder_id: i64,
+) -> Result<Vec<ExportArticle>, String> {
+    let Some(tokens) = tokens else {
+        return Err(gettext("Log in to export folders"));
+    };
+
+    let client = instapaper::client();
+    match instapaper::get_folder_bookmarks(&client, tokens, folder_id, instapaper::BASE_URL).await {
+        Ok(bookmarks) => Ok(bookmarks.into_iter().map(ExportArticle::from).collect()),
+        Err(e) => Err(format!("{}: {}", gettext("Failed to load the folder"), e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::ComponentTester;
+    use std::collections::HashMap;
+
+    fn article(item_id: &str, tags: &[&str]) -> ExportArticle {
+        ExportArticle {
+            item_id: item_id.to_string(),
+            title: format!("Article {}", item_id),
+            url: format!("https://example.com/{}", item_id),
+            time: 0.0,
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+        }
+    }
+
+    fn init(visible: Vec<ExportArticle>) -> ExportInit {
+        ExportInit {
+            visible,
+            all: vec![
+                article("1", &["rust"]),
+                article("2", &[]),
+                article("3", &["rust"]),
+            ],
+            tags: vec!["rust".to_string()],
+            folders: vec![InstapaperFolder {
+                folder_id: 42,
+                title: "Later".to_string(),
+                extra: HashMap::new(),
+            }],
+            tokens: None,
+            content_source: ContentSource::Original,
+            cache_size: 0,
+            markdown_folder: None,
+        }
+    }
+
+    #[test]
+    fn test_scopes() {
+        let folders = init(Vec::new()).folders;
+        let scopes = scopes(&["rust".to_string()], &folders);
+
+        assert_eq!(
+            scopes,
+            [
+                ExportScope::CurrentList,
+                ExportScope::Tag("rust".to_string()),
+                ExportScope::Folder(42)
+            ]
+        );
+        assert_eq!(scope_label(&scopes[1], &folders), "Tag: rust");
+        assert_eq!(scope_label(&scopes[2], &folders), "Folder: Later");
+    }
+
+    #[gtk::test]
+    fn test_articles_by_scope() {
+        let tester = ComponentTester::<ExportDialog>::launch(init(vec![article("2", &[])]));
+        tester.process_events();
+
+        let model = tester.model();
+        assert_eq!(
+            model.articles(&ExportScope::CurrentList),
+            Some(vec![article("2", &[])])
+        );
+        let tagged = model
+            .articles(&ExportScope::Tag("rust".to_string()))
+            .unwrap();
+        assert_eq!(
+            tagged
+                .iter()
+                .map(|a| a.item_id.as_str())
+                .collect::<Vec<_>>(),
+            ["1", "3"]
+        );
+        assert_eq!(model.articles(&ExportScope::Folder(42)), None);
+    }
+
+    #[gtk::test]
+    fn test_export_empty_list() {
+        let tester = ComponentTester::<ExportDialog>::launch(init(Vec::new()));
+        tester.send_input(ExportInput::ExportTo(PathBuf::from("/tmp/empty.epub")));
+        tester.process_events();
+
+        assert_eq!(
+            tester.model().error_message,
+            Some(gettext("There are no articles to export"))
+        );
+        assert_eq!(tester.model().progress, None);
+    }
+
+    #[gtk::test]
+    fn test_set_format() {
+        let tester = ComponentTester::<ExportDialog>::launch(init(Vec::new()));
+        tester.send_input(ExportInput::SetFormat(1));
+        tester.process_events();
+        assert_eq!(tester.model().format, ExportFormat::Markdown);
+
+        tester.send_input(ExportInput::SetFormat(0));
+        tester.process_events();
+        assert_eq!(tester.model().format, ExportFormat::Epub);
+    }
+
+    #[test]
+    fn test_markdown_summary() {
+        let report = vault::VaultReport {
+            created: 2,
+            updated: 1,
+            unchanged: 0,
+            skipped: 1,
+        };
+        assert_eq!(
+            markdown_summary(&report, 0),
+            "Notes exported: 2 new, 1 updated, 0 unchanged, 1 note without marker left alone"
+        );
+        assert_eq!(
+            markdown_summary(&report, 2),
+            "Notes exported: 2 new, 1 updated, 0 unchanged, 1 note without marker left alone, \
+             2 articles couldn't be downloaded"
+        );
+    }
+
+    #[test]
+    fn test_epub_summary() {
+        let path = Path::new("/tmp/Reading.epub");
+        assert_eq!(
+            epub_summary(3, 0, path),
+            "Exported 3 articles to Reading.epub"
+        );
+        assert_eq!(
+            epub_summary(1, 1, path),
+            "Exported 1 article to Reading.epub, 1 article couldn't be downloaded"
+        );
+    }
+
+    #[gtk::test]
+    fn test_empty_title() {
+        let tester = ComponentTester::<ExportDialog>::launch(init(Vec::new()));
+        tester.send_input(ExportInput::SetTitle("  ".to_string()));
+        tester.send_input(ExportInput::ChooseFile);
+        tester.process_events();
+
+        assert_eq!(
+            tester.model().error_message,
+            Some(gettext("Please enter a title"))
+        );
+    }
+}
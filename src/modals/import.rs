@@ -0,0 +1,657 @@
+use std::path::PathBuf;
+
+use gtk::prelude::{BoxExt, ButtonExt, CastNone, OrientableExt, ProgressBarExt, WidgetExt};
+use relm4::{
+    adw,
+    adw::prelude::{ActionRowExt, AdwDialogExt, PreferencesGroupExt, PreferencesRowExt},
+    gtk::{self, gio, glib},
+    Component, ComponentParts, ComponentSender, RelmWidgetExt,
+};
+
+use gettextrs::{gettext, ngettext};
+
+use crate::import::{self, ImportError, ImportItem};
+use crate::network::instapaper;
+use crate::persistence::import_queue::{self, PendingBookmark, PendingImport};
+use crate::persistence::token::TokenPair;
+
+/// Rows shown in the preview, the rest are only counted.
+const PREVIEW_LIMIT: usize = 100;
+
+pub struct ImportInit {
+    pub tokens: TokenPair,
+    /// URLs of the saved bookmarks, left out of the import
+    pub existing: Vec<String>,
+    /// An import that was interrupted, offered to resume
+    pub pending: Option<PendingImport>,
+}
+
+pub struct ImportDialog {
+    tokens: TokenPair,
+    existing: Vec<String>,
+    pending: Option<PendingImport>,
+    file_name: Option<String>,
+    items: Vec<ImportItem>,
+    /// Bookmarks of the file already saved or listed twice
+    duplicates: usize,
+    loading: bool,
+    /// Bookmarks sent and total while importing
+    progress: Option<(usize, usize)>,
+    error_message: Option<String>,
+    preview_list: gtk::ListBox,
+}
+
+#[derive(Debug)]
+pub enum ImportInput {
+    ChooseFile,
+    Load(PathBuf),
+    Discard,
+    Import,
+    Cancel,
+}
+
+#[derive(Debug, Clone)]
+pub enum ImportOutput {
+    /// A report of what was imported
+    Imported(String),
+    /// The interrupted import was dropped
+    Discarded,
+    Cancelled,
+}
+
+#[derive(Debug)]
+pub enum ImportCommandOutput {
+    Loaded(String, Result<Vec<ImportItem>, String>),
+    Progress(usize, usize),
+    Finished(PendingImport, Result<(), String>),
+}
+
+#[relm4::component(pub)]
+impl Component for ImportDialog {
+    type Init = ImportInit;
+    type Input = ImportInput;
+    type Output = ImportOutput;
+    type CommandOutput = ImportCommandOutput;
+
+    view! {
+        adw::Dialog {
+            set_title: &gettext("Import Bookmarks"),
+            set_content_width: 500,
+            set_content_height: 560,
+
+            #[wrap(Some)]
+            set_child = &adw::ToolbarView {
+                add_top_bar = &adw::HeaderBar {
+                    #[wrap(Some)]
+                    set_title_widget = &adw::WindowTitle {
+                        set_title: &gettext("Import Bookmarks"),
+                    },
+                },
+
+                #[wrap(Some)]
+                set_content = &gtk::Box {
+                    set_orientation: gtk::Orientation::Vertical,
+                    set_margin_all: 24,
+                    set_spacing: 16,
+
+                    adw::PreferencesGroup {
+                        set_description: Some(&gettext(
+                            "Reads Pocket exports, bookmark files exported by browsers and CSV files with url, title, tags and time columns",
+                        )),
+
+                        adw::ActionRow {
+                            set_title: &gettext("File"),
+                            #[watch]
+                            set_subtitle: model.file_name.as_deref().unwrap_or(""),
+
+                            add_suffix = &gtk::Button {
+                                set_label: &gettext("Choose…"),
+                                set_valign: gtk::Align::Center,
+                                #[watch]
+                                set_sensitive: !model.loading && model.progress.is_none(),
+                                connect_clicked => ImportInput::ChooseFile,
+                            },
+                        },
+                    },
+
+                    gtk::Box {
+                        set_orientation: gtk::Orientation::Horizontal,
+                        set_spacing: 12,
+                        #[watch]
+                        set_visible: model.pending.is_some() && model.items.is_empty(),
+
+                        gtk::Label {
+                            #[watch]
+                            set_label: &model.pending_text(),
+                            set_wrap: true,
+                            set_xalign: 0.0,
+                            set_hexpand: true,
+                        },
+
+                        gtk::Button {
+                            set_label: &gettext("Discard"),
+                            set_valign: gtk::Align::Center,
+                            #[watch]
+                            set_sensitive: model.progress.is_none(),
+                            connect_clicked => ImportInput::Discard,
+                        },
+                    },
+
+                    gtk::Label {
+                        #[watch]
+                        set_visible: model.file_name.is_some() && !model.loading,
+                        #[watch]
+                        set_label: &model.preview_text(),
+                        set_wrap: true,
+                        set_xalign: 0.0,
+                    },
+
+                    gtk::ScrolledWindow {
+                        set_vexpand: true,
+                        set_hscrollbar_policy: gtk::PolicyType::Never,
+
+                        #[local_ref]
+                        preview_list -> gtk::ListBox {
+                            add_css_class: "boxed-list",
+                            set_selection_mode: gtk::SelectionMode::None,
+                            set_valign: gtk::Align::Start,
+                        },
+                    },
+
+                    gtk::ProgressBar {
+                        #[watch]
+                        set_visible: model.progress.is_some(),
+                        #[watch]
+                        set_fraction: model.progress_fraction(),
+                        set_show_text: true,
+                        #[watch]
+                        set_text: Some(&model.progress_text()),
+                    },
+
+                    gtk::Label {
+                        #[watch]
+                        set_visible: model.error_message.is_some(),
+                        #[watch]
+                        set_label: model.error_message.as_deref().unwrap_or(""),
+                        add_css_class: "error",
+                        set_wrap: true,
+                    },
+
+                    gtk::Box {
+                        set_orientation: gtk::Orientation::Horizontal,
+                        set_spacing: 12,
+                        set_halign: gtk::Align::End,
+
+                        gtk::Button {
+                            set_label: &gettext("Cancel"),
+                            connect_clicked => ImportInput::Cancel,
+                        },
+
+                        gtk::Button {
+                            #[watch]
+                            set_label: &model.import_label(),
+                            add_css_class: "suggested-action",
+                            #[watch]
+                            set_sensitive: !model.loading && model.progress.is_none(),
+                            connect_clicked => ImportInput::Import,
+                        },
+                    },
+                },
+            },
+
+            connect_closed[sender] => move |_| {
+                sender.input(ImportInput::Cancel);
+            },
+        }
+    }
+
+    fn init(
+        init: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let model = Self {
+            tokens: init.tokens,
+            existing: init.existing,
+            pending: init.pending,
+            file_name: None,
+            items: Vec::new(),
+            duplicates: 0,
+            loading: false,
+            progress: None,
+            error_message: None,
+            preview_list: gtk::ListBox::default(),
+        };
+
+        let preview_list = &model.preview_list;
+        let widgets = view_output!();
+
+        if let Some(pending) = &model.pending {
+            model.rebuild_preview(pending.bookmarks.iter().map(preview_row));
+        }
+
+        // Only present the dialog if we're not in a test environment
+        if !cfg!(test) {
+            root.present(Some(&relm4::main_application().windows()[0]));
+        }
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, message: Self::Input, sender: ComponentSender<Self>, root: &Self::Root) {
+        match message {
+            ImportInput::ChooseFile => {
+                let filter = gtk::FileFilter::new();
+                filter.set_name(Some(&gettext("Bookmark Files")));
+                for pattern in ["*.html", "*.htm", "*.csv"] {
+                    filter.add_pattern(pattern);
+                }
+                let filters = gio::ListStore::new::<gtk::FileFilter>();
+                filters.append(&filter);
+
+                let dialog = gtk::FileDialog::builder()
+                    .title(gettext("Import Bookmarks"))
+                    .filters(&filters)
+                    .modal(true)
+                    .build();
+
+                let window = root.root().and_downcast::<gtk::Window>();
+                let sender = sender.clone();
+                dialog.open(
+                    window.as_ref(),
+                    gio::Cancellable::NONE,
+                    move |result: Result<gio::File, glib::Error>| {
+                        if let Some(path) = result.ok().and_then(|file| file.path()) {
+                            sender.input(ImportInput::Load(path));
+                        }
+                    },
+                );
+            }
+            ImportInput::Load(path) => {
+                self.loading = true;
+                self.error_message = None;
+
+                let file_name = path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                sender.oneshot_command(async move {
+                    let items = std::fs::read(&path)
+                        .map(|bytes| import::parse(&String::from_utf8_lossy(&bytes)))
+                        .map_err(|e| format!("{}: {}", gettext("Failed to read the file"), e));
+                    ImportCommandOutput::Loaded(file_name, items)
+                });
+            }
+            ImportInput::Discard => {
+                self.pending = None;
+                self.rebuild_preview(std::iter::empty::<(String, String)>());
+                let _ = sender.output(ImportOutput::Discarded);
+            }
+            ImportInput::Import => {
+                if self.progress.is_some() {
+                    return;
+                }
+
+                // A new file replaces the import left unfinished
+                let mut queue = if !self.items.is_empty() {
+                    PendingImport::new(self.items.iter().cloned().map(Into::into).collect())
+                } else if let Some(pending) = &self.pending {
+                    pending.clone()
+                } else {
+                    self.error_message = Some(gettext("There are no new bookmarks to import"));
+                    return;
+                };
+
+                self.error_message = None;
+                self.progress = Some((queue.done(), queue.total));
+
+                let tokens = self.tokens.clone();
+                sender.command(move |out, shutdown| {
+                    shutdown
+                        .register(async move {
+                            let client = instapaper::client();
+                            let result = import::run(
+                                &mut queue,
+                                &import::THROTTLE,
+                                |bookmark| {
+                                    let client = client.clone();
+                                    let tokens = tokens.clone();
+                                    async move {
+                                        instapaper::add_bookmark(
+                                            &client,
+                                            &tokens,
+                                            &bookmark.url,
+                                            &bookmark.title,
+                                            &bookmark.tags,
+                                            instapaper::BASE_URL,
+                                        )
+                                        .await
+                                        .map(|_| ())
+                                    }
+                                },
+                                |queue| import_queue::write(queue),
+                                |done, total| {
+                                    let _ = out.send(ImportCommandOutput::Progress(done, total));
+                                },
+                            )
+                            .await;
+
+                            if result.is_ok() {
+                                if let Err(e) = import_queue::clear() {
+                                    eprintln!("Failed to clear the import queue: {}", e);
+                                }
+                            }
+                            let result = result.map_err(|e| match e {
+                                ImportError::Instapaper(e) => e.to_string(),
+                                ImportError::Save(e) => {
+                                    eprintln!("Failed to save the import queue: {}", e);
+                                    gettext("Failed to save the import progress")
+                                }
+                            });
+                            let _ = out.send(ImportCommandOutput::Finished(queue, result));
+                        })
+                        .drop_on_shutdown()
+                });
+            }
+            ImportInput::Cancel => {
+                root.close();
+                let _ = sender.output(ImportOutput::Cancelled);
+            }
+        }
+    }
+
+    fn update_cmd(
+        &mut self,
+        message: Self::CommandOutput,
+        sender: ComponentSender<Self>,
+        root: &Self::Root,
+    ) {
+        match message {
+            ImportCommandOutput::Loaded(file_name, Ok(items)) => {
+                self.loading = false;
+                self.file_name = Some(file_name);
+                self.show_items(items);
+            }
+            ImportCommandOutput::Loaded(_, Err(error)) => {
+                self.loading = false;
+                self.error_message = Some(error);
+            }
+            ImportCommandOutput::Progress(done, total) => {
+                self.progress = Some((done, total));
+            }
+            ImportCommandOutput::Finished(queue, result) => {
+                self.progress = None;
+                root.close();
+                let _ = sender.output(ImportOutput::Imported(summary(&queue, &result)));
+            }
+        }
+    }
+}
+
+impl ImportDialog {
+    /// Keeps the bookmarks of `items` that aren't saved yet and lists them.
+    fn show_items(&mut self, items: Vec<ImportItem>) {
+        let found = items.len();
+        let (items, duplicates) = import::dedupe(items, &self.existing);
+        self.items = import::oldest_first(items);
+        self.duplicates = duplicates;
+
+        if found == 0 {
+            self.error_message = Some(gettext("No bookmarks found in this file"));
+        }
+
+        let rows: Vec<_> = self
+            .items
+            .iter()
+            .map(|item| preview_row(&PendingBookmark::from(item.clone())))
+            .collect();
+        self.rebuild_preview(rows.into_iter());
+    }
+
+    fn rebuild_preview(&self, rows: impl Iterator<Item = (String, String)>) {
+        while let Some(child) = self.preview_list.first_child() {
+            self.preview_list.remove(&child);
+        }
+
+        let mut count = 0;
+        for (title, subtitle) in rows {
+            count += 1;
+            if count > PREVIEW_LIMIT {
+                continue;
+            }
+            let row = adw::ActionRow::builder()
+                .title(title)
+                .subtitle(subtitle)
+                .use_markup(false)
+                .build();
+            self.preview_list.append(&row);
+        }
+
+        if count > PREVIEW_LIMIT {
+            let more = count - PREVIEW_LIMIT;
+            let row = adw::ActionRow::builder()
+                .title(
+                    ngettext("And {} more", "And {} more", more as u32)
+                        .replace("{}", &more.to_string()),
+                )
+                .build();
+            self.preview_list.append(&row);
+        }
+        self.preview_list.set_visible(count > 0);
+    }
+
+    fn pending_text(&self) -> String {
+        let Some(pending) = &self.pending else {
+            return String::new();
+        };
+        let left = pending.bookmarks.len();
+        ngettext(
+            "An earlier import stopped with {} bookmark left",
+            "An earlier import stopped with {} bookmarks left",
+            left as u32,
+        )
+        .replace("{}", &left.to_string())
+    }
+
+    fn preview_text(&self) -> String {
+        let count = self.items.len();
+        let mut text = ngettext("{} new bookmark", "{} new bookmarks", count as u32)
+            .replace("{}", &count.to_string());
+        if self.duplicates > 0 {
+            text = format!(
+                "{}, {}",
+                text,
+                ngettext(
+                    "{} already saved or repeated",
+                    "{} already saved or repeated",
+                    self.duplicates as u32,
+                )
+                .replace("{}", &self.duplicates.to_string())
+            );
+        }
+        text
+    }
+
+    fn import_label(&self) -> String {
+        if self.items.is_empty() && self.pending.is_some() {
+            gettext("Resume")
+        } else {
+            gettext("Import")
+        }
+    }
+
+    fn progress_fraction(&self) -> f64 {
+        match self.progress {
+            Some((done, total)) if total > 0 => done as f64 / total as f64,
+            _ => 0.0,
+        }
+    }
+
+    fn progress_text(&self) -> String {
+        match self.progress {
+            Some((_, 0)) | None => String::new(),
+            Some((done, total)) => {
+                ngettext("{} of {} bookmark", "{} of {} bookmarks", total as u32)
+                    .replacen("{}", &done.to_string(), 1)
+                    .replacen("{}", &total.to_string(), 1)
+            }
+        }
+    }
+}
+
+/// Title and subtitle of the preview row of `bookmark`.
+fn preview_row(bookmark: &PendingBookmark) -> (String, String) {
+    let title = if bookmark.title.is_empty() {
+        bookmark.url.clone()
+    } else {
+        bookmark.title.clone()
+    };
+    let mut subtitle = bookmark.url.clone();
+    if !bookmark.tags.is_empty() {
+        subtitle = format!("{} · {}", subtitle, bookmark.tags.join(", "));
+    }
+    (title, subtitle)
+}
+
+/// What the import did, and how to go on when it stopped early.
+fn summary(queue: &PendingImport, result: &Result<(), String>) -> String {
+    let mut summary = ngettext(
+        "Imported {} of {} bookmark",
+        "Imported {} of {} bookmarks",
+        queue.total as u32,
+    )
+    .replacen("{}", &queue.added.to_string(), 1)
+    .replacen("{}", &queue.total.to_string(), 1);
+
+    if !queue.failed.is_empty() {
+        summary = format!(
+            "{}, {}",
+            summary,
+            ngettext("{} failed", "{} failed", queue.failed.len() as u32)
+                .replace("{}", &queue.failed.len().to_string())
+        );
+    }
+    if let Err(reason) = result {
+        summary = format!(
+            "{}. {} {}",
+            summary,
+            reason,
+            gettext("Import again later to resume.")
+        );
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::ComponentTester;
+
+    fn init(pending: Option<PendingImport>) -> ImportInit {
+        ImportInit {
+            tokens: TokenPair {
+                oauth_token: "test_token".to_string(),
+                oauth_token_secret: "test_secret".to_string(),
+            },
+            existing: vec!["https://example.com/saved".to_string()],
+            pending,
+        }
+    }
+
+    fn bookmark(url: &str) -> PendingBookmark {
+        PendingBookmark {
+            url: url.to_string(),
+            title: String::new(),
+            tags: Vec::new(),
+        }
+    }
+
+    #[gtk::test]
+    fn test_import_without_items() {
+        let tester = ComponentTester::<ImportDialog>::launch(init(None));
+        tester.send_input(ImportInput::Import);
+        tester.process_events();
+
+        assert_eq!(
+            tester.model().error_message,
+            Some(gettext("There are no new bookmarks to import"))
+        );
+        assert_eq!(tester.model().progress, None);
+    }
+
+    #[gtk::test]
+    fn test_pending_import() {
+        let mut pending = PendingImport::new(vec![
+            bookmark("https://example.com/a"),
+            bookmark("https://example.com/b"),
+        ]);
+        pending.bookmarks.pop_front();
+        pending.added = 1;
+
+        let tester = ComponentTester::<ImportDialog>::launch(init(Some(pending)));
+        tester.process_events();
+
+        assert_eq!(tester.model().import_label(), gettext("Resume"));
+        assert_eq!(
+            tester.model().pending_text(),
+            "An earlier import stopped with 1 bookmark left"
+        );
+    }
+
+    #[gtk::test]
+    fn test_discard_pending_import() {
+        let pending = PendingImport::new(vec![bookmark("https://example.com/a")]);
+        let tester = ComponentTester::<ImportDialog>::launch(init(Some(pending)));
+        tester.send_input(ImportInput::Discard);
+        tester.process_events();
+
+        assert!(tester.model().pending.is_none());
+        assert!(matches!(
+            tester.try_recv_output(),
+            Some(ImportOutput::Discarded)
+        ));
+    }
+
+    #[test]
+    fn test_preview_row() {
+        let mut bookmark = bookmark("https://example.com/a");
+        assert_eq!(
+            preview_row(&bookmark),
+            (
+                "https://example.com/a".to_string(),
+                "https://example.com/a".to_string()
+            )
+        );
+
+        bookmark.title = "A".to_string();
+        bookmark.tags = vec!["rust".to_string(), "gtk".to_string()];
+        assert_eq!(
+            preview_row(&bookmark),
+            (
+                "A".to_string(),
+                "https://example.com/a · rust, gtk".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_summary() {
+        let mut queue = PendingImport::new(vec![
+            bookmark("https://example.com/a"),
+            bookmark("https://example.com/b"),
+            bookmark("https://example.com/c"),
+        ]);
+        queue.bookmarks.pop_front();
+        queue.added = 1;
+
+        assert_eq!(summary(&queue, &Ok(())), "Imported 1 of 3 bookmarks");
+
+        queue
+            .failed
+            .push(("https://example.com/b".to_string(), "Gone".to_string()));
+        assert_eq!(
+            summary(&queue, &Err("Rate limited.".to_string())),
+            "Imported 1 of 3 bookmarks, 1 failed. Rate limited. Import again later to resume."
+        );
+    }
+}
@@ -179,10 +179,23 @@ impl Component for LoginDialog {
                 sender.oneshot_command(async move {
                     let client = instapaper::client();
 
-                    match instapaper::authenticate(&client, &username, &password).await {
+                    match instapaper::authenticate(
+                        &client,
+                        &username,
+                        &password,
+                        instapaper::BASE_URL,
+                    )
+                    .await
+                    {
                         Ok(tokens) => {
                             // Verify credentials and get username
-                            match instapaper::verify_credentials(&client, &tokens).await {
+                            match instapaper::verify_credentials(
+                                &client,
+                                &tokens,
+                                instapaper::BASE_URL,
+                            )
+                            .await
+                            {
                                 Ok(user) => LoginCommandOutput::LoginSuccess(tokens, user.username),
                                 Err(_) => LoginCommandOutput::LoginSuccess(tokens, username),
                             }
@@ -1,3 +1,7 @@
 pub mod about;
 pub mod add_bookmark;
+pub mod backup;
+pub mod export;
+pub mod import;
 pub mod login;
+pub mod preferences;
@@ -0,0 +1,315 @@
+use gtk::{gio, pango};
+use relm4::{
+    adw,
+    adw::prelude::{
+        ActionRowExt, AdwDialogExt, ComboRowExt, EditableExt, EntryRowExt, PreferencesDialogExt,
+        PreferencesGroupExt, PreferencesPageExt, PreferencesRowExt, SettingsExt, SettingsExtManual,
+        WidgetExt,
+    },
+    gtk, ComponentParts, ComponentSender, SimpleComponent,
+};
+
+use gettextrs::gettext;
+
+use crate::persistence::translation_key;
+
+const THEME_VALUES: &[&str] = &["system", "light", "sepia", "dark", "black"];
+const CONTENT_SOURCE_VALUES: &[&str] = &["original", "instapaper"];
+const IMAGE_LOADING_VALUES: &[&str] = &["always", "unmetered", "never"];
+const TRANSLATION_LAYOUT_VALUES: &[&str] = &["replace", "side-by-side"];
+
+/// Preferences window. Every row is bound straight to GSettings, the app
+/// listens for changes and applies them live. The translation API key is
+/// the exception: it lives in the keyring and is saved when applied.
+pub struct PreferencesDialog {
+    _settings: gio::Settings,
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for PreferencesDialog {
+    type Init = gio::Settings;
+    type Input = ();
+    type Output = ();
+
+    view! {
+        adw::PreferencesDialog {
+            set_search_enabled: true,
+
+            add = &adw::PreferencesPage {
+                set_title: &gettext("Reader"),
+                set_icon_name: Some("font-x-generic-symbolic"),
+
+                add = &adw::PreferencesGroup {
+                    set_title: &gettext("Typography"),
+
+                    adw::ActionRow {
+                        set_title: &gettext("Font"),
+
+                        #[name = "font_button"]
+                        add_suffix = &gtk::FontDialogButton {
+                            set_valign: gtk::Align::Center,
+                            set_level: gtk::FontLevel::Family,
+                            set_dialog: &gtk::FontDialog::new(),
+                        },
+                    },
+
+                    #[name = "font_size_row"]
+                    adw::SpinRow {
+                        set_title: &gettext("Font Size"),
+                        set_subtitle: &gettext("In points"),
+                        set_adjustment: Some(&gtk::Adjustment::new(12.0, 8.0, 32.0, 1.0, 4.0, 0.0)),
+                    },
+
+                    #[name = "line_height_row"]
+                    adw::SpinRow {
+                        set_title: &gettext("Line Height"),
+                        set_digits: 1,
+                        set_adjustment: Some(&gtk::Adjustment::new(1.5, 1.0, 2.5, 0.1, 0.5, 0.0)),
+                    },
+
+                    #[name = "line_width_row"]
+                    adw::SpinRow {
+                        set_title: &gettext("Line Width"),
+                        set_subtitle: &gettext("Maximum width of the text column, in pixels"),
+                        set_adjustment: Some(&gtk::Adjustment::new(800.0, 400.0, 2000.0, 20.0, 100.0, 0.0)),
+                    },
+                },
+
+                add = &adw::PreferencesGroup {
+                    set_title: &gettext("Appearance"),
+
+                    #[name = "theme_row"]
+                    adw::ComboRow {
+                        set_title: &gettext("Theme"),
+                        set_model: Some(&gtk::StringList::new(&[
+                            &gettext("Follow System"),
+                            &gettext("Light"),
+                            &gettext("Sepia"),
+                            &gettext("Dark"),
+                            &gettext("Black"),
+                        ])),
+                    },
+
+                    #[name = "image_loading_row"]
+                    adw::ComboRow {
+                        set_title: &gettext("Load Images Automatically"),
+                        set_subtitle: &gettext("Otherwise images are loaded on demand"),
+                        set_model: Some(&gtk::StringList::new(&[
+                            &gettext("Always"),
+                            &gettext("On Unmetered Networks"),
+                            &gettext("Never"),
+                        ])),
+                    },
+                },
+            },
+
+            add = &adw::PreferencesPage {
+                set_title: &gettext("Articles"),
+                set_icon_name: Some("view-list-symbolic"),
+
+                add = &adw::PreferencesGroup {
+                    set_title: &gettext("Content"),
+
+                    #[name = "content_source_row"]
+                    adw::ComboRow {
+                        set_title: &gettext("Default Content Source"),
+                        set_model: Some(&gtk::StringList::new(&[
+                            &gettext("Original Page"),
+                            &gettext("Instapaper Text"),
+                        ])),
+                    },
+
+                    #[name = "words_per_minute_row"]
+                    adw::SpinRow {
+                        set_title: &gettext("Reading Speed"),
+                        set_subtitle: &gettext("Words per minute, used to estimate reading time"),
+                        set_adjustment: Some(&gtk::Adjustment::new(200.0, 50.0, 1000.0, 10.0, 50.0, 0.0)),
+                    },
+                },
+
+                add = &adw::PreferencesGroup {
+                    set_title: &gettext("Sync"),
+
+                    #[name = "sync_interval_row"]
+                    adw::SpinRow {
+                        set_title: &gettext("Sync Interval"),
+                        set_subtitle: &gettext("Minutes between automatic refreshes, 0 to disable"),
+                        set_adjustment: Some(&gtk::Adjustment::new(0.0, 0.0, 1440.0, 5.0, 30.0, 0.0)),
+                    },
+
+                    #[name = "offline_cache_size_row"]
+                    adw::SpinRow {
+                        set_title: &gettext("Offline Cache Size"),
+                        set_subtitle: &gettext("In megabytes"),
+                        set_adjustment: Some(&gtk::Adjustment::new(200.0, 0.0, 10240.0, 50.0, 500.0, 0.0)),
+                    },
+                },
+
+                add = &adw::PreferencesGroup {
+                    set_title: &gettext("Translation"),
+                    set_description: Some(&gettext("Articles are translated by a LibreTranslate compatible server, such as one running on this computer")),
+
+                    #[name = "translation_endpoint_row"]
+                    adw::EntryRow {
+                        set_title: &gettext("Server Address"),
+                    },
+
+                    #[name = "translation_api_key_row"]
+                    adw::PasswordEntryRow {
+                        set_title: &gettext("API Key (Optional)"),
+                        set_show_apply_button: true,
+                    },
+
+                    #[name = "translation_language_row"]
+                    adw::EntryRow {
+                        set_title: &gettext("Target Language"),
+                        set_tooltip_text: Some(&gettext("A language code such as “en” or “it”, leave empty to use the system language")),
+                    },
+
+                    #[name = "translation_layout_row"]
+                    adw::ComboRow {
+                        set_title: &gettext("Show Translations"),
+                        set_model: Some(&gtk::StringList::new(&[
+                            &gettext("Instead of the Original"),
+                            &gettext("Side by Side"),
+                        ])),
+                    },
+                },
+            },
+        }
+    }
+
+    fn init(
+        settings: Self::Init,
+        root: Self::Root,
+        _sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let model = Self {
+            _settings: settings.clone(),
+        };
+
+        let widgets = view_output!();
+
+        settings
+            .bind("reader-font-size", &widgets.font_size_row, "value")
+            .build();
+        settings
+            .bind("reader-line-height", &widgets.line_height_row, "value")
+            .build();
+        settings
+            .bind("reader-line-width", &widgets.line_width_row, "value")
+            .build();
+        settings
+            .bind("words-per-minute", &widgets.words_per_minute_row, "value")
+            .build();
+        settings
+            .bind("sync-interval", &widgets.sync_interval_row, "value")
+            .build();
+        settings
+            .bind(
+                "offline-cache-size",
+                &widgets.offline_cache_size_row,
+                "value",
+            )
+            .build();
+
+        settings
+            .bind(
+                "translation-endpoint",
+                &widgets.translation_endpoint_row,
+                "text",
+            )
+            .build();
+        bind_api_key(&widgets.translation_api_key_row);
+        settings
+            .bind(
+                "translation-target-language",
+                &widgets.translation_language_row,
+                "text",
+            )
+            .build();
+
+        bind_choice(&settings, "reader-theme", &widgets.theme_row, THEME_VALUES);
+        bind_choice(
+            &settings,
+            "image-loading",
+            &widgets.image_loading_row,
+            IMAGE_LOADING_VALUES,
+        );
+        bind_choice(
+            &settings,
+            "content-source",
+            &widgets.content_source_row,
+            CONTENT_SOURCE_VALUES,
+        );
+        bind_choice(
+            &settings,
+            "translation-layout",
+            &widgets.translation_layout_row,
+            TRANSLATION_LAYOUT_VALUES,
+        );
+
+        let font_button = &widgets.font_button;
+        font_button.set_font_desc(&pango::FontDescription::from_string(
+            &settings.string("reader-font-family"),
+        ));
+        font_button.connect_font_desc_notify({
+            let settings = settings.clone();
+            move |button| {
+                if let Some(family) = button.font_desc().and_then(|desc| desc.family()) {
+                    let _ = settings.set_string("reader-font-family", &family);
+                }
+            }
+        });
+
+        // Only present the dialog if we're not in a test environment
+        if !cfg!(test) {
+            root.present(Some(&relm4::main_application().windows()[0]));
+        }
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, _message: Self::Input, _sender: ComponentSender<Self>) {}
+}
+
+/// Keeps a combo row in sync with a string key restricted to `values`,
+/// listed in the same order as the row's model.
+fn bind_choice(
+    settings: &gio::Settings,
+    key: &'static str,
+    row: &adw::ComboRow,
+    values: &'static [&'static str],
+) {
+    let current = settings.string(key);
+    let selected = values.iter().position(|v| *v == current).unwrap_or(0);
+    row.set_selected(selected as u32);
+
+    let settings = settings.clone();
+    row.connect_selected_notify(move |row| {
+        if let Some(value) = values.get(row.selected() as usize) {
+            let _ = settings.set_string(key, value);
+        }
+    });
+}
+
+/// Shows the API key kept in the keyring and stores it there when applied.
+fn bind_api_key(row: &adw::PasswordEntryRow) {
+    let loaded_row = row.clone();
+    relm4::spawn_local(async move {
+        match relm4::spawn(translation_key::read()).await {
+            Ok(Ok(api_key)) => loaded_row.set_text(&api_key),
+            Ok(Err(e)) => eprintln!("Failed to read the translation API key: {}", e),
+            Err(e) => eprintln!("Failed to read the translation API key: {}", e),
+        }
+    });
+
+    row.connect_apply(|row| {
+        let api_key = row.text().trim().to_string();
+        relm4::spawn(async move {
+            if let Err(e) = translation_key::save(&api_key).await {
+                eprintln!("Failed to save the translation API key: {}", e);
+            }
+        });
+    });
+}
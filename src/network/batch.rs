@@ -0,0 +1,247 @@
+use reqwest::Client;
+use std::future::Future;
+
+use crate::network::instapaper::{self, InstapaperError};
+use crate::persistence::token::TokenPair;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum BatchOperation {
+    Archive,
+    Delete,
+    Star,
+    MoveToFolder(i64),
+    AddTags(Vec<String>),
+}
+
+#[derive(Clone, Debug)]
+pub struct BatchTarget {
+    pub item_id: String,
+    pub uri: String,
+    pub title: String,
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct BatchReport {
+    /// Item ids of the bookmarks the operation was applied to
+    pub succeeded: Vec<String>,
+    /// Titles of the bookmarks that could not be updated, with the reason
+    pub failed: Vec<(String, String)>,
+}
+
+/// Applies a single batch operation to one bookmark through the Instapaper client.
+pub async fn apply(
+    client: &Client,
+    tokens: &TokenPair,
+    operation: &BatchOperation,
+    target: &BatchTarget,
+) -> Result<(), InstapaperError> {
+    match operation {
+        BatchOperation::Archive => {
+            let bookmark_id = instapaper::bookmark_id(&target.item_id)?;
+            instapaper::archive_bookmark(client, tokens, bookmark_id, instapaper::BASE_URL).await
+        }
+        BatchOperation::Delete => {
+            let bookmark_id = instapaper::bookmark_id(&target.item_id)?;
+            instapaper::delete_bookmark(client, tokens, bookmark_id, instapaper::BASE_URL).await
+        }
+        BatchOperation::Star => {
+            let bookmark_id = instapaper::bookmark_id(&target.item_id)?;
+            instapaper::star_bookmark(client, tokens, bookmark_id, instapaper::BASE_URL).await
+        }
+        BatchOperation::MoveToFolder(folder_id) => {
+            let bookmark_id = instapaper::bookmark_id(&target.item_id)?;
+            instapaper::move_bookmark(
+                client,
+                tokens,
+                bookmark_id,
+                *folder_id,
+                instapaper::BASE_URL,
+            )
+            .await
+        }
+        BatchOperation::AddTags(tags) => {
+            // Re-adding an existing URL updates the bookmark, so send the full tag set
+            let merged = merge_tags(&target.tags, tags);
+            instapaper::add_bookmark(
+                client,
+                tokens,
+                &target.uri,
+                "",
+                &merged,
+                instapaper::BASE_URL,
+            )
+            .await
+            .map(|_| ())
+        }
+    }
+}
+
+/// Runs `perform` on every target in order, reporting progress after each one.
+///
+/// Failures don't stop the batch, except for rate limiting: once Instapaper
+/// starts refusing requests every remaining target is reported as failed
+/// instead of hammering the API.
+pub async fn run<F, Fut>(
+    targets: Vec<BatchTarget>,
+    mut perform: F,
+    mut on_progress: impl FnMut(usize, usize),
+) -> BatchReport
+where
+    F: FnMut(BatchTarget) -> Fut,
+    Fut: Future<Output = Result<(), InstapaperError>>,
+{
+    let total = targets.len();
+    let mut report = BatchReport::default();
+    let mut targets = targets.into_iter();
+    let mut done = 0;
+
+    while let Some(target) = targets.next() {
+        let item_id = target.item_id.clone();
+        let title = target.title.clone();
+
+        match perform(target).await {
+            Ok(()) => report.succeeded.push(item_id),
+            Err(InstapaperError::RateLimited) => {
+                let reason = InstapaperError::RateLimited.to_string();
+                report.failed.push((title, reason.clone()));
+                report
+                    .failed
+                    .extend(targets.by_ref().map(|t| (t.title, reason.clone())));
+                on_progress(total, total);
+                break;
+            }
+            Err(e) => report.failed.push((title, e.to_string())),
+        }
+
+        done += 1;
+        on_progress(done, total);
+    }
+
+    report
+}
+
+pub fn merge_tags(existing: &[String], added: &[String]) -> Vec<String> {
+    let mut merged = existing.to_vec();
+    for tag in added {
+        if !merged.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            merged.push(tag.clone());
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(id: &str) -> BatchTarget {
+        BatchTarget {
+            item_id: id.to_string(),
+            uri: format!("https://example.com/{}", id),
+            title: format!("Article {}", id),
+            tags: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_reports_partial_failures() {
+        let targets = vec![target("1"), target("2"), target("3")];
+        let mut progress = Vec::new();
+
+        let report = run(
+            targets,
+            |t| async move {
+                if t.item_id == "2" {
+                    Err(InstapaperError::ServiceUnavailable)
+                } else {
+                    Ok(())
+                }
+            },
+            |done, total| progress.push((done, total)),
+        )
+        .await;
+
+        assert_eq!(report.succeeded, vec!["1", "3"]);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].0, "Article 2");
+        assert_eq!(progress, vec![(1, 3), (2, 3), (3, 3)]);
+    }
+
+    #[tokio::test]
+    async fn test_run_stops_when_rate_limited() {
+        let targets = vec![target("1"), target("2"), target("3"), target("4")];
+        let mut attempts = 0;
+        let mut progress = Vec::new();
+
+        let report = run(
+            targets,
+            |t| {
+                attempts += 1;
+                async move {
+                    if t.item_id == "2" {
+                        Err(InstapaperError::RateLimited)
+                    } else {
+                        Ok(())
+                    }
+                }
+            },
+            |done, total| progress.push((done, total)),
+        )
+        .await;
+
+        assert_eq!(attempts, 2);
+        assert_eq!(report.succeeded, vec!["1"]);
+        assert_eq!(
+            report
+                .failed
+                .iter()
+                .map(|(title, _)| title.as_str())
+                .collect::<Vec<_>>(),
+            vec!["Article 2", "Article 3", "Article 4"]
+        );
+        assert_eq!(progress.last(), Some(&(4, 4)));
+    }
+
+    #[tokio::test]
+    async fn test_apply_reports_invalid_ids() {
+        let client = instapaper::client();
+        let tokens = TokenPair {
+            oauth_token: "token".to_string(),
+            oauth_token_secret: "secret".to_string(),
+        };
+
+        let report = run(
+            vec![target("not-a-number")],
+            |t| {
+                let (client, tokens) = (&client, &tokens);
+                async move { apply(client, tokens, &BatchOperation::Archive, &t).await }
+            },
+            |_, _| {},
+        )
+        .await;
+
+        assert!(report.succeeded.is_empty());
+        assert_eq!(report.failed.len(), 1);
+        assert!(report.failed[0].1.contains("not-a-number"));
+    }
+
+    #[tokio::test]
+    async fn test_run_empty_batch() {
+        let report = run(Vec::new(), |_| async { Ok(()) }, |_, _| {}).await;
+
+        assert!(report.succeeded.is_empty());
+        assert!(report.failed.is_empty());
+    }
+
+    #[test]
+    fn test_merge_tags() {
+        let existing = vec!["Rust".to_string(), "Programming".to_string()];
+        let added = vec!["rust".to_string(), "GTK".to_string()];
+
+        assert_eq!(
+            merge_tags(&existing, &added),
+            vec!["Rust", "Programming", "GTK"]
+        );
+    }
+}
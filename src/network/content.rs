@@ -0,0 +1,140 @@
+use anyhow::{anyhow, bail, Result};
+use article_scraper::{FtrConfigEntry, FullTextParser, Readability};
+use reqwest::Client;
+use scraper::{Html, Selector};
+use url::Url;
+
+use crate::network::instapaper;
+use crate::persistence::article_cache::{self, CachedArticle};
+use crate::persistence::offline_cache;
+use crate::persistence::token::TokenPair;
+
+/// Meta tags pages name their author in, most reliable first.
+const AUTHOR_SELECTORS: &[&str] = &[
+    r#"meta[name="author"]"#,
+    r#"meta[property="article:author"]"#,
+    r#"meta[name="twitter:creator"]"#,
+    r#"meta[name="DC.creator"]"#,
+];
+
+/// Downloads the readable content of a bookmark and caches it as long as
+/// `cache_size`, in megabytes, isn't 0. With `instapaper_tokens`
+/// Instapaper's text view is used, falling back to extracting the original
+/// page.
+pub async fn fetch(
+    item_id: &str,
+    uri: &str,
+    instapaper_tokens: Option<&TokenPair>,
+    cache_size: u32,
+) -> Result<CachedArticle> {
+    let article = match instapaper_tokens {
+        Some(tokens) => {
+            let client = instapaper::client();
+            let bookmark_id = instapaper::bookmark_id(item_id).map_err(|e| anyhow!("{}", e))?;
+            match instapaper::get_text(&client, tokens, bookmark_id, instapaper::BASE_URL).await {
+                Ok(html) => CachedArticle { html, author: None },
+                Err(e) => {
+                    eprintln!(
+                        "Failed to get Instapaper text, extracting the original page: {}",
+                        e
+                    );
+                    extract(uri).await?
+                }
+            }
+        }
+        None => extract(uri).await?,
+    };
+
+    if cache_size == 0 {
+        return Ok(article);
+    }
+
+    let key = item_id.to_string();
+    let (article, cached) = relm4::spawn_blocking(move || {
+        let cached = article_cache::write(&key, &article);
+        (article, cached)
+    })
+    .await?;
+    match cached {
+        Ok(()) => offline_cache::schedule_trim(cache_size),
+        Err(e) => eprintln!("Failed to cache article {}: {}", item_id, e),
+    }
+
+    Ok(article)
+}
+
+/// The cached content of a bookmark, fetched when there is none yet.
+pub async fn load(
+    item_id: &str,
+    uri: &str,
+    instapaper_tokens: Option<&TokenPair>,
+    cache_size: u32,
+) -> Result<CachedArticle> {
+    let key = item_id.to_string();
+    match relm4::spawn_blocking(move || article_cache::read(&key)).await? {
+        Some(article) => Ok(article),
+        None => fetch(item_id, uri, instapaper_tokens, cache_size).await,
+    }
+}
+
+async fn extract(uri: &str) -> Result<CachedArticle> {
+    let page = get_html(uri).await?;
+    let author = page_author(&page);
+    let html = Readability::extract(&page, None)
+        .await
+        .map_err(|e| anyhow!("Failed to extract the article from {}: {}", uri, e))?;
+    if html.trim().is_empty() {
+        bail!("No article found at {}", uri);
+    }
+
+    Ok(CachedArticle { html, author })
+}
+
+async fn get_html(uri: &str) -> Result<String> {
+    let source_url = Url::parse(uri)?;
+
+    FullTextParser::download(
+        &source_url,
+        &Client::new(),
+        None,
+        &FtrConfigEntry::default(),
+    )
+    .await
+    .map_err(|e| anyhow!("Failed to download {}: {}", uri, e))
+}
+
+/// The author named in the metadata of a page, if any.
+fn page_author(html: &str) -> Option<String> {
+    let document = Html::parse_document(html);
+
+    AUTHOR_SELECTORS.iter().find_map(|selector| {
+        let selector = Selector::parse(selector).ok()?;
+        document
+            .select(&selector)
+            .filter_map(|element| element.value().attr("content"))
+            .map(|author| author.split_whitespace().collect::<Vec<_>>().join(" "))
+            .find(|author| !author.is_empty() && !author.starts_with("http"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_author() {
+        let html = r#"<html><head>
+            <meta property="article:author" content="https://example.com/ada">
+            <meta name="author" content="  Ada
+                Lovelace ">
+        </head></html>"#;
+        assert_eq!(page_author(html), Some("Ada Lovelace".to_string()));
+
+        // Profile links are no names
+        let html = r#"<meta property="article:author" content="https://example.com/ada">
+            <meta name="twitter:creator" content="@ada">"#;
+        assert_eq!(page_author(html), Some("@ada".to_string()));
+
+        assert_eq!(page_author("<p>No metadata</p>"), None);
+    }
+}
@@ -0,0 +1,97 @@
+use anyhow::Result;
+use reqwest::Client;
+use std::sync::LazyLock;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+
+use crate::persistence::{image_cache, offline_cache};
+
+/// Downloads running at once, the rest wait for a free slot.
+const MAX_CONCURRENT_DOWNLOADS: usize = 4;
+
+static CLIENT: LazyLock<Client> = LazyLock::new(|| {
+    Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .unwrap_or_default()
+});
+
+static DOWNLOAD_SLOTS: LazyLock<Semaphore> =
+    LazyLock::new(|| Semaphore::new(MAX_CONCURRENT_DOWNLOADS));
+
+/// Returns the image at `url`, from the disk cache when possible. New
+/// downloads are cached as long as `cache_size`, in megabytes, isn't 0.
+pub async fn fetch(url: &str, cache_size: u32) -> Result<Vec<u8>> {
+    let key = url.to_string();
+    if let Some(bytes) = relm4::spawn_blocking(move || image_cache::read(&key)).await? {
+        return Ok(bytes);
+    }
+
+    let bytes = download(&CLIENT, url).await?;
+    if cache_size == 0 {
+        return Ok(bytes);
+    }
+
+    let key = url.to_string();
+    let (bytes, cached) = relm4::spawn_blocking(move || {
+        let cached = image_cache::write(&key, &bytes);
+        (bytes, cached)
+    })
+    .await?;
+    match cached {
+        Ok(()) => offline_cache::schedule_trim(cache_size),
+        Err(e) => eprintln!("Failed to cache image {}: {}", url, e),
+    }
+
+    Ok(bytes)
+}
+
+async fn download(client: &Client, url: &str) -> Result<Vec<u8>> {
+    let _slot = DOWNLOAD_SLOTS.acquire().await?;
+
+    let response = client.get(url).send().await?.error_for_status()?;
+    let bytes = response.bytes().await?;
+
+    Ok(bytes.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Server;
+
+    #[tokio::test]
+    async fn test_download() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("GET", "/image.png")
+            .with_status(200)
+            .with_header("content-type", "image/png")
+            .with_body([0x89, b'P', b'N', b'G'])
+            .create_async()
+            .await;
+
+        let client = Client::new();
+        let bytes = download(&client, &format!("{}/image.png", server.url()))
+            .await
+            .unwrap();
+
+        assert_eq!(bytes, vec![0x89, b'P', b'N', b'G']);
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_download_error_status() {
+        let mut server = Server::new_async().await;
+        server
+            .mock("GET", "/missing.png")
+            .with_status(404)
+            .create_async()
+            .await;
+
+        let client = Client::new();
+        let result = download(&client, &format!("{}/missing.png", server.url())).await;
+
+        assert!(result.is_err());
+    }
+}
@@ -1 +1,5 @@
+pub mod batch;
+pub mod content;
+pub mod images;
 pub mod instapaper;
+pub mod translation;
@@ -0,0 +1,340 @@
+use anyhow::{anyhow, Result};
+use reqwest::{Client, Response};
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use crate::persistence::translations;
+use crate::preferences::TranslationSettings;
+
+/// Paragraphs sent in one request, so long articles don't hit request
+/// size limits or time out on slow machines.
+const PARAGRAPHS_PER_REQUEST: usize = 25;
+
+/// Characters of the article used to detect its language.
+const DETECTION_SAMPLE_CHARS: usize = 2000;
+
+static CLIENT: LazyLock<Client> = LazyLock::new(|| {
+    Client::builder()
+        .timeout(Duration::from_secs(120))
+        .build()
+        .unwrap_or_default()
+});
+
+#[derive(Debug, Deserialize)]
+struct Detection {
+    language: String,
+    confidence: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct Translation {
+    #[serde(rename = "translatedText")]
+    translated_text: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ServerError {
+    error: String,
+}
+
+/// Detects the language of `paragraphs` on the configured server and
+/// returns its code.
+pub async fn detect_language(
+    settings: &TranslationSettings,
+    paragraphs: &[String],
+) -> Result<String> {
+    detect_language_with(&CLIENT, settings, paragraphs).await
+}
+
+/// Translates `paragraphs` from `source` to the configured language,
+/// returning them in the same order.
+pub async fn translate(
+    settings: &TranslationSettings,
+    paragraphs: &[String],
+    source: &str,
+) -> Result<Vec<String>> {
+    translate_with(&CLIENT, settings, paragraphs, source).await
+}
+
+/// Translates the paragraphs of a bookmark, reusing the paragraphs
+/// translated before and caching the new ones.
+pub async fn translate_article(
+    bookmark_id: &str,
+    settings: &TranslationSettings,
+    paragraphs: &[String],
+) -> Result<Vec<String>> {
+    let target = settings.target_language.as_str();
+    let (id, language) = (bookmark_id.to_string(), target.to_string());
+    let mut cached = relm4::spawn_blocking(move || translations::read(&id, &language))
+        .await?
+        .unwrap_or_default();
+
+    let missing = missing_paragraphs(paragraphs, &cached.paragraphs);
+
+    if !missing.is_empty() {
+        if cached.source.is_empty() {
+            cached.source = detect_language(settings, paragraphs).await?;
+        }
+        if cached.source == target {
+            return Err(anyhow!(
+                "The article is already in the translation language"
+            ));
+        }
+
+        let translated = translate(settings, &missing, &cached.source).await?;
+        cached
+            .paragraphs
+            .extend(missing.into_iter().zip(translated));
+
+        let (id, language) = (bookmark_id.to_string(), target.to_string());
+        let (written, translation) = relm4::spawn_blocking(move || {
+            let written = translations::write(&id, &language, &cached);
+            (written, cached)
+        })
+        .await?;
+        cached = translation;
+        if let Err(e) = written {
+            eprintln!("Failed to cache the translation of {}: {}", bookmark_id, e);
+        }
+    }
+
+    Ok(paragraphs
+        .iter()
+        .map(|paragraph| {
+            cached
+                .paragraphs
+                .get(paragraph)
+                .cloned()
+                .unwrap_or_else(|| paragraph.clone())
+        })
+        .collect())
+}
+
+/// The paragraphs without a translation yet, each once, in article order.
+fn missing_paragraphs(paragraphs: &[String], translated: &HashMap<String, String>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    paragraphs
+        .iter()
+        .filter(|paragraph| !translated.contains_key(*paragraph))
+        .filter(|paragraph| seen.insert(paragraph.as_str()))
+        .cloned()
+        .collect()
+}
+
+async fn detect_language_with(
+    client: &Client,
+    settings: &TranslationSettings,
+    paragraphs: &[String],
+) -> Result<String> {
+    let sample: String = paragraphs
+        .join("\n")
+        .chars()
+        .take(DETECTION_SAMPLE_CHARS)
+        .collect();
+
+    let response = client
+        .post(endpoint_url(settings, "detect"))
+        .json(&with_api_key(settings, json!({ "q": sample })))
+        .send()
+        .await?;
+    let detections: Vec<Detection> = parse(response).await?;
+
+    detections
+        .into_iter()
+        .max_by(|a, b| a.confidence.total_cmp(&b.confidence))
+        .map(|detection| detection.language)
+        .ok_or_else(|| anyhow!("The translation server couldn't detect the language"))
+}
+
+async fn translate_with(
+    client: &Client,
+    settings: &TranslationSettings,
+    paragraphs: &[String],
+    source: &str,
+) -> Result<Vec<String>> {
+    let mut translated = Vec::with_capacity(paragraphs.len());
+
+    for chunk in paragraphs.chunks(PARAGRAPHS_PER_REQUEST) {
+        let body = json!({
+            "q": chunk,
+            "source": source,
+            "target": settings.target_language,
+            "format": "text",
+        });
+        let response = client
+            .post(endpoint_url(settings, "translate"))
+            .json(&with_api_key(settings, body))
+            .send()
+            .await?;
+        let translation: Translation = parse(response).await?;
+
+        if translation.translated_text.len() != chunk.len() {
+            return Err(anyhow!(
+                "Expected {} translated paragraphs, got {}",
+                chunk.len(),
+                translation.translated_text.len()
+            ));
+        }
+        translated.extend(translation.translated_text);
+    }
+
+    Ok(translated)
+}
+
+fn endpoint_url(settings: &TranslationSettings, path: &str) -> String {
+    format!("{}/{}", settings.endpoint.trim_end_matches('/'), path)
+}
+
+fn with_api_key(settings: &TranslationSettings, mut body: serde_json::Value) -> serde_json::Value {
+    if !settings.api_key.is_empty() {
+        body["api_key"] = json!(settings.api_key);
+    }
+    body
+}
+
+/// Decodes a successful response, or turns the server's error message
+/// into the returned error.
+async fn parse<T: for<'de> Deserialize<'de>>(response: Response) -> Result<T> {
+    let status = response.status();
+    let text = response.text().await?;
+
+    if !status.is_success() {
+        let message = serde_json::from_str::<ServerError>(&text)
+            .map(|e| e.error)
+            .unwrap_or_else(|_| status.to_string());
+        return Err(anyhow!("Translation server error: {}", message));
+    }
+
+    serde_json::from_str(&text)
+        .map_err(|e| anyhow!("Failed to parse the translation server response: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::{Matcher, Server};
+
+    fn settings(endpoint: String, api_key: &str) -> TranslationSettings {
+        TranslationSettings {
+            endpoint,
+            api_key: api_key.to_string(),
+            target_language: "en".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_detect_language_picks_most_confident() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("POST", "/detect")
+            .match_body(Matcher::PartialJson(json!({ "q": "Bonjour\nle monde" })))
+            .with_status(200)
+            .with_body(
+                r#"[{"language":"it","confidence":20.0},{"language":"fr","confidence":90.0}]"#,
+            )
+            .create_async()
+            .await;
+
+        let language = detect_language_with(
+            &Client::new(),
+            &settings(format!("{}/", server.url()), ""),
+            &["Bonjour".to_string(), "le monde".to_string()],
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(language, "fr");
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_translate_in_chunks() {
+        let mut server = Server::new_async().await;
+        let paragraphs: Vec<String> = (0..30).map(|i| format!("Paragrafo {}", i)).collect();
+
+        let first = server
+            .mock("POST", "/translate")
+            .match_body(Matcher::PartialJson(json!({
+                "source": "it",
+                "target": "en",
+                "api_key": "secret",
+                "q": paragraphs[..25],
+            })))
+            .with_status(200)
+            .with_body(
+                json!({
+                    "translatedText": (0..25).map(|i| format!("Paragraph {}", i)).collect::<Vec<_>>()
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+        let second = server
+            .mock("POST", "/translate")
+            .match_body(Matcher::PartialJson(json!({ "q": paragraphs[25..] })))
+            .with_status(200)
+            .with_body(
+                json!({
+                    "translatedText": (25..30).map(|i| format!("Paragraph {}", i)).collect::<Vec<_>>()
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+
+        let translated = translate_with(
+            &Client::new(),
+            &settings(server.url(), "secret"),
+            &paragraphs,
+            "it",
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(translated.len(), 30);
+        assert_eq!(translated[0], "Paragraph 0");
+        assert_eq!(translated[29], "Paragraph 29");
+        first.assert_async().await;
+        second.assert_async().await;
+    }
+
+    #[test]
+    fn test_missing_paragraphs() {
+        let paragraphs: Vec<String> = ["Hello", "World", "Hello", "Again", "World"]
+            .iter()
+            .map(|p| p.to_string())
+            .collect();
+        let translated = HashMap::from([("Again".to_string(), "Ancora".to_string())]);
+
+        assert_eq!(
+            missing_paragraphs(&paragraphs, &translated),
+            vec!["Hello".to_string(), "World".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_translate_server_error() {
+        let mut server = Server::new_async().await;
+        server
+            .mock("POST", "/translate")
+            .with_status(400)
+            .with_body(r#"{"error":"xx is not supported"}"#)
+            .create_async()
+            .await;
+
+        let result = translate_with(
+            &Client::new(),
+            &settings(server.url(), ""),
+            &["Ciao".to_string()],
+            "xx",
+        )
+        .await;
+
+        let error = result.unwrap_err().to_string();
+        assert!(error.contains("xx is not supported"), "{}", error);
+    }
+}
@@ -0,0 +1,104 @@
+use crate::config::APP_ID;
+use anyhow::Result;
+use relm4::gtk::glib;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// The readable content extracted for a bookmark.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CachedArticle {
+    pub html: String,
+    #[serde(default)]
+    pub author: Option<String>,
+}
+
+/// Returns the content cached for a bookmark, if any.
+pub fn read(bookmark_id: &str) -> Option<CachedArticle> {
+    read_from(&cache_dir(), bookmark_id)
+}
+
+pub fn write(bookmark_id: &str, article: &CachedArticle) -> Result<()> {
+    write_to(&cache_dir(), bookmark_id, article)
+}
+
+pub(super) fn cache_dir() -> PathBuf {
+    let mut path = glib::user_cache_dir();
+    path.push(APP_ID);
+    path.push("articles");
+    path
+}
+
+fn file_name(bookmark_id: &str) -> String {
+    let id: String = bookmark_id
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect();
+    format!("{}.json", id)
+}
+
+fn read_from(dir: &Path, bookmark_id: &str) -> Option<CachedArticle> {
+    let path = dir.join(file_name(bookmark_id));
+    let contents = fs::read_to_string(&path).ok()?;
+
+    // Bump the modification time so trimming evicts the least recently read
+    if let Ok(file) = File::options().write(true).open(&path) {
+        let _ = file.set_modified(SystemTime::now());
+    }
+
+    serde_json::from_str(&contents).ok()
+}
+
+fn write_to(dir: &Path, bookmark_id: &str, article: &CachedArticle) -> Result<()> {
+    fs::create_dir_all(dir)?;
+    let json = serde_json::to_string(article)?;
+    fs::write(dir.join(file_name(bookmark_id)), json)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::temp_dir::TestDir;
+    use std::time::Duration;
+
+    #[test]
+    fn test_write_and_read() {
+        let dir = TestDir::new("roundtrip");
+        assert_eq!(read_from(&dir, "42"), None);
+
+        let article = CachedArticle {
+            html: "<p>Body</p>".to_string(),
+            author: Some("Ada".to_string()),
+        };
+        write_to(&dir, "42", &article).unwrap();
+
+        assert_eq!(read_from(&dir, "42"), Some(article));
+        assert_eq!(read_from(&dir, "43"), None);
+    }
+
+    #[test]
+    fn test_read_marks_article_as_used() {
+        let dir = TestDir::new("used");
+        write_to(&dir, "42", &CachedArticle::default()).unwrap();
+
+        let path = dir.join(file_name("42"));
+        let past = SystemTime::now() - Duration::from_secs(60 * 60);
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(past)
+            .unwrap();
+
+        read_from(&dir, "42").unwrap();
+        let modified = fs::metadata(&path).unwrap().modified().unwrap();
+        assert!(modified > past + Duration::from_secs(30 * 60));
+    }
+
+    #[test]
+    fn test_file_name_stays_in_cache_dir() {
+        assert_eq!(file_name("../42"), "42.json");
+    }
+}
@@ -1,9 +1,7 @@
-use crate::config::APP_ID;
 use anyhow::Result;
-use relm4::gtk::glib;
 use serde::{Deserialize, Serialize};
-use std::fs::File;
-use std::io::{Read, Write};
+use std::fs;
+use std::path::Path;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PersistedArticle {
@@ -14,43 +12,20 @@ pub struct PersistedArticle {
     pub time: f64,
     #[serde(default)]
     pub tags: Vec<String>,
+    #[serde(default)]
+    pub word_count: Option<usize>,
 }
 
-pub fn save_articles(articles: &[PersistedArticle]) -> Result<()> {
-    let mut path = glib::user_data_dir();
-    path.push(APP_ID);
-    std::fs::create_dir_all(&path)?;
-    path.push("articles.json");
-
-    let json = serde_json::to_string(articles)?;
-    let mut file = File::create(path)?;
-    file.write_all(json.as_bytes())?;
-    Ok(())
-}
-
-pub fn read_articles() -> Result<Vec<PersistedArticle>> {
-    let mut path = glib::user_data_dir();
-    path.push(APP_ID);
-    path.push("articles.json");
+/// The file bookmarks were cached in before the SQLite store. It is read
+/// once, to move its bookmarks into the database.
+pub const LEGACY_FILE: &str = "articles.json";
 
-    let mut file = File::open(path)?;
-    let mut contents = String::new();
-    file.read_to_string(&mut contents)?;
+pub fn read_legacy(path: &Path) -> Result<Vec<PersistedArticle>> {
+    let contents = fs::read_to_string(path)?;
     let articles: Vec<PersistedArticle> = serde_json::from_str(&contents)?;
     Ok(articles)
 }
 
-pub fn clear_articles() -> Result<()> {
-    let mut path = glib::user_data_dir();
-    path.push(APP_ID);
-    path.push("articles.json");
-
-    if path.exists() {
-        std::fs::remove_file(path)?;
-    }
-    Ok(())
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -67,6 +42,7 @@ mod tests {
 
         let article: PersistedArticle = serde_json::from_str(json).unwrap();
         assert!(article.tags.is_empty());
+        assert_eq!(article.word_count, None);
     }
 
     #[test]
@@ -78,10 +54,12 @@ mod tests {
             description: "desc".to_string(),
             time: 0.0,
             tags: vec!["Rust".to_string(), "Programming".to_string()],
+            word_count: Some(1500),
         };
 
         let json = serde_json::to_string(&article).unwrap();
         let deserialized: PersistedArticle = serde_json::from_str(&json).unwrap();
         assert_eq!(deserialized.tags, vec!["Rust", "Programming"]);
+        assert_eq!(deserialized.word_count, Some(1500));
     }
 }
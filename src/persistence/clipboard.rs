@@ -1,5 +1,5 @@
 use relm4::gtk::gdk::prelude::DisplayExt;
-use relm4::gtk::gdk::Display;
+use relm4::gtk::gdk::{Display, Texture};
 
 // TODO: convert the return to a result
 pub fn copy(text: &str) {
@@ -7,3 +7,9 @@ pub fn copy(text: &str) {
     let clipboard = display.clipboard();
     clipboard.set_text(text);
 }
+
+pub fn copy_texture(texture: &Texture) {
+    let display = Display::default().unwrap();
+    let clipboard = display.clipboard();
+    clipboard.set_texture(texture);
+}
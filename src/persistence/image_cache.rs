@@ -0,0 +1,96 @@
+use crate::config::APP_ID;
+use anyhow::Result;
+use relm4::gtk::glib::{self, ChecksumType};
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Returns the cached copy of an image, if any.
+pub fn read(url: &str) -> Option<Vec<u8>> {
+    read_from(&cache_dir(), url)
+}
+
+pub fn write(url: &str, bytes: &[u8]) -> Result<()> {
+    write_to(&cache_dir(), url, bytes)
+}
+
+pub(super) fn cache_dir() -> PathBuf {
+    let mut path = glib::user_cache_dir();
+    path.push(APP_ID);
+    path.push("images");
+    path
+}
+
+/// File name of an image, stable across releases so the cache survives them.
+fn cache_key(url: &str) -> String {
+    glib::compute_checksum_for_string(ChecksumType::Sha256, url)
+        .map(|checksum| checksum.to_string())
+        .unwrap_or_default()
+}
+
+fn read_from(dir: &Path, url: &str) -> Option<Vec<u8>> {
+    let path = dir.join(cache_key(url));
+    let bytes = fs::read(&path).ok()?;
+
+    // Bump the modification time so trimming evicts the least recently read
+    if let Ok(file) = File::options().write(true).open(&path) {
+        let _ = file.set_modified(SystemTime::now());
+    }
+
+    Some(bytes)
+}
+
+fn write_to(dir: &Path, url: &str, bytes: &[u8]) -> Result<()> {
+    fs::create_dir_all(dir)?;
+    fs::write(dir.join(cache_key(url)), bytes)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::temp_dir::TestDir;
+    use std::time::Duration;
+
+    #[test]
+    fn test_write_and_read() {
+        let dir = TestDir::new("roundtrip");
+
+        assert_eq!(read_from(&dir, "https://example.com/a.png"), None);
+
+        write_to(&dir, "https://example.com/a.png", b"image").unwrap();
+        assert_eq!(
+            read_from(&dir, "https://example.com/a.png"),
+            Some(b"image".to_vec())
+        );
+        assert_eq!(read_from(&dir, "https://example.com/b.png"), None);
+    }
+
+    #[test]
+    fn test_read_marks_image_as_used() {
+        let dir = TestDir::new("used");
+        write_to(&dir, "image", &[0; 10]).unwrap();
+
+        let path = dir.join(cache_key("image"));
+        let past = SystemTime::now() - Duration::from_secs(60 * 60);
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(past)
+            .unwrap();
+
+        // Reading an image makes it the most recently used
+        read_from(&dir, "image").unwrap();
+        let modified = fs::metadata(&path).unwrap().modified().unwrap();
+        assert!(modified > past + Duration::from_secs(30 * 60));
+    }
+
+    #[test]
+    fn test_cache_key_is_stable() {
+        assert_eq!(
+            cache_key("https://example.com/a.png"),
+            "494a30704d4f32ac0b81739d18a66d3638d440cbc6f5669f6af66f840edee5ab"
+        );
+    }
+}
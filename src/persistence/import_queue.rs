@@ -0,0 +1,133 @@
+use crate::config::APP_ID;
+use anyhow::Result;
+use relm4::gtk::glib;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A bookmark waiting to be sent to Instapaper.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PendingBookmark {
+    pub url: String,
+    pub title: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// An import in progress, kept on disk so it can resume after being
+/// interrupted or rate limited.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PendingImport {
+    /// Bookmarks not sent yet, in order
+    pub bookmarks: VecDeque<PendingBookmark>,
+    /// Number of bookmarks when the import started
+    pub total: usize,
+    pub added: usize,
+    /// URLs of the bookmarks Instapaper refused, with the reason
+    #[serde(default)]
+    pub failed: Vec<(String, String)>,
+}
+
+impl PendingImport {
+    pub fn new(bookmarks: Vec<PendingBookmark>) -> Self {
+        Self {
+            total: bookmarks.len(),
+            bookmarks: bookmarks.into(),
+            added: 0,
+            failed: Vec::new(),
+        }
+    }
+
+    /// Number of bookmarks sent so far, added or not.
+    pub fn done(&self) -> usize {
+        self.total - self.bookmarks.len()
+    }
+}
+
+/// Returns the import left unfinished, if any.
+pub fn read() -> Option<PendingImport> {
+    read_from(&queue_path())
+}
+
+pub fn write(queue: &PendingImport) -> Result<()> {
+    write_to(&queue_path(), queue)
+}
+
+pub fn clear() -> Result<()> {
+    let path = queue_path();
+    if path.exists() {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+fn queue_path() -> PathBuf {
+    let mut path = glib::user_data_dir();
+    path.push(APP_ID);
+    path.push("import.json");
+    path
+}
+
+fn read_from(path: &Path) -> Option<PendingImport> {
+    let contents = fs::read_to_string(path).ok()?;
+    serde_json::from_str::<PendingImport>(&contents)
+        .ok()
+        .filter(|queue| !queue.bookmarks.is_empty())
+}
+
+fn write_to(path: &Path, queue: &PendingImport) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    // Written after every bookmark, so never leave a half written file behind
+    let temp = path.with_extension("json.tmp");
+    fs::write(&temp, serde_json::to_string(queue)?)?;
+    fs::rename(temp, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::temp_dir::TestDir;
+
+    fn bookmark(url: &str) -> PendingBookmark {
+        PendingBookmark {
+            url: url.to_string(),
+            title: "Title".to_string(),
+            tags: vec!["rust".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_write_and_read() {
+        let dir = TestDir::new("roundtrip");
+        let path = dir.join("import.json");
+        assert_eq!(read_from(&path), None);
+
+        let mut queue = PendingImport::new(vec![
+            bookmark("https://example.com/a"),
+            bookmark("https://example.com/b"),
+        ]);
+        queue.bookmarks.pop_front();
+        queue.added = 1;
+        write_to(&path, &queue).unwrap();
+
+        let read = read_from(&path).unwrap();
+        assert_eq!(read, queue);
+        assert_eq!(read.done(), 1);
+        assert_eq!(read.total, 2);
+    }
+
+    #[test]
+    fn test_finished_import_is_not_resumed() {
+        let dir = TestDir::new("finished");
+        let path = dir.join("import.json");
+        let mut queue = PendingImport::new(vec![bookmark("https://example.com/a")]);
+        queue.bookmarks.clear();
+        write_to(&path, &queue).unwrap();
+
+        assert_eq!(read_from(&path), None);
+    }
+}
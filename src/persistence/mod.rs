@@ -1,3 +1,10 @@
+pub mod article_cache;
 pub mod articles;
 pub mod clipboard;
+pub mod image_cache;
+pub mod import_queue;
+pub mod offline_cache;
+pub mod store;
 pub mod token;
+pub mod translation_key;
+pub mod translations;
@@ -0,0 +1,126 @@
+use crate::persistence::{article_cache, image_cache};
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime};
+
+/// How long after a write the cache is trimmed, so the images of a page
+/// are trimmed in one go.
+const TRIM_DELAY: Duration = Duration::from_secs(5);
+
+static TRIM_SCHEDULED: AtomicBool = AtomicBool::new(false);
+
+/// The directories of the articles and images kept for reading offline,
+/// which share the size limit.
+fn cache_dirs() -> [PathBuf; 2] {
+    [article_cache::cache_dir(), image_cache::cache_dir()]
+}
+
+/// Deletes the least recently used articles and images until the cache fits
+/// in `max_bytes`.
+pub fn trim(max_bytes: u64) -> Result<()> {
+    trim_dirs(&cache_dirs(), max_bytes)
+}
+
+/// Trims the cache to `cache_size` megabytes once writes settle.
+pub fn schedule_trim(cache_size: u32) {
+    if TRIM_SCHEDULED.swap(true, Ordering::AcqRel) {
+        return;
+    }
+
+    relm4::spawn(async move {
+        tokio::time::sleep(TRIM_DELAY).await;
+        TRIM_SCHEDULED.store(false, Ordering::Release);
+
+        let max_bytes = cache_size as u64 * 1024 * 1024;
+        if let Ok(Err(e)) = relm4::spawn_blocking(move || trim(max_bytes)).await {
+            eprintln!("Failed to trim the offline cache: {}", e);
+        }
+    });
+}
+
+/// Deletes every cached article and image.
+pub fn clear() -> Result<()> {
+    for dir in cache_dirs() {
+        if dir.exists() {
+            fs::remove_dir_all(dir)?;
+        }
+    }
+    Ok(())
+}
+
+fn trim_dirs(dirs: &[PathBuf], max_bytes: u64) -> Result<()> {
+    let mut entries = Vec::new();
+    for dir in dirs.iter().filter(|dir| dir.exists()) {
+        list_files(dir, &mut entries)?;
+    }
+
+    let mut total: u64 = entries.iter().map(|(_, size, _)| size).sum();
+    entries.sort_by_key(|(modified, _, _)| *modified);
+
+    for (_, size, path) in entries {
+        if total <= max_bytes {
+            break;
+        }
+        fs::remove_file(path)?;
+        total -= size;
+    }
+
+    Ok(())
+}
+
+fn list_files(dir: &Path, entries: &mut Vec<(SystemTime, u64, PathBuf)>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        if metadata.is_file() {
+            let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            entries.push((modified, metadata.len(), entry.path()));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::temp_dir::TestDir;
+    use std::fs::File;
+
+    fn write_aged(dir: &Path, name: &str, minutes_ago: u64) {
+        fs::create_dir_all(dir).unwrap();
+        let path = dir.join(name);
+        fs::write(&path, [0; 10]).unwrap();
+        let file = File::options().write(true).open(path).unwrap();
+        file.set_modified(SystemTime::now() - Duration::from_secs(minutes_ago * 60))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_trim_evicts_least_recently_used() {
+        let root = TestDir::new("trim");
+        let articles = root.join("articles");
+        let images = root.join("images");
+
+        write_aged(&images, "old", 3);
+        write_aged(&articles, "article", 2);
+        write_aged(&images, "new", 1);
+
+        // Articles and images count toward the same limit
+        trim_dirs(&[articles.clone(), images.clone()], 20).unwrap();
+
+        assert!(!images.join("old").exists());
+        assert!(articles.join("article").exists());
+        assert!(images.join("new").exists());
+
+        trim_dirs(&[articles.clone(), images.clone()], 0).unwrap();
+        assert_eq!(fs::read_dir(&articles).unwrap().count(), 0);
+        assert_eq!(fs::read_dir(&images).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_trim_missing_dir() {
+        assert!(trim_dirs(&[TestDir::new("missing").to_path_buf()], 0).is_ok());
+    }
+}
@@ -0,0 +1,352 @@
+use crate::config::APP_ID;
+use crate::persistence::articles::{self, PersistedArticle};
+use anyhow::{bail, Result};
+use relm4::gtk::glib;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+const DATABASE_FILE: &str = "library.db";
+
+/// Schema changes, applied in order. The database records how many ran in
+/// `user_version`, so never edit one that has shipped: append a new one.
+const MIGRATIONS: &[&str] = &[
+    // 1: bookmarks and their tags
+    "CREATE TABLE articles (
+        item_id TEXT PRIMARY KEY NOT NULL,
+        title TEXT NOT NULL,
+        uri TEXT NOT NULL,
+        description TEXT NOT NULL DEFAULT '',
+        time REAL NOT NULL DEFAULT 0,
+        word_count INTEGER
+    );
+    CREATE INDEX articles_time ON articles (time DESC);
+    CREATE TABLE article_tags (
+        item_id TEXT NOT NULL REFERENCES articles (item_id) ON DELETE CASCADE,
+        tag TEXT NOT NULL,
+        position INTEGER NOT NULL,
+        PRIMARY KEY (item_id, tag)
+    );
+    CREATE INDEX article_tags_tag ON article_tags (tag);",
+];
+
+/// Reads the bookmarks in list order, which the `articles_time` index serves.
+const ARTICLES_QUERY: &str = "SELECT item_id, title, uri, description, time, word_count
+     FROM articles ORDER BY time DESC, item_id";
+
+/// The bookmarks kept between runs, in an SQLite database in the user data
+/// directory. Every change is written in a single transaction.
+pub struct ArticleStore {
+    conn: Connection,
+}
+
+impl ArticleStore {
+    /// Opens the database, creating or upgrading it as needed, and moves the
+    /// bookmarks of the old `articles.json` cache into it the first time.
+    pub fn open() -> Result<Self> {
+        let mut dir = glib::user_data_dir();
+        dir.push(APP_ID);
+        Self::open_in(&dir)
+    }
+
+    fn open_in(dir: &Path) -> Result<Self> {
+        fs::create_dir_all(dir)?;
+        let conn = Connection::open(dir.join(DATABASE_FILE))?;
+        conn.pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get::<_, String>(0))?;
+        conn.pragma_update(None, "foreign_keys", true)?;
+
+        let mut store = Self { conn };
+        store.migrate()?;
+        store.import_legacy(&dir.join(articles::LEGACY_FILE))?;
+        Ok(store)
+    }
+
+    fn migrate(&mut self) -> Result<()> {
+        let version: i64 = self
+            .conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))?;
+        let version = version as usize;
+        if version > MIGRATIONS.len() {
+            bail!("The library was saved by a newer version of Cauldron");
+        }
+
+        for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+            let tx = self.conn.transaction()?;
+            tx.execute_batch(migration)?;
+            tx.pragma_update(None, "user_version", (index + 1) as i64)?;
+            tx.commit()?;
+        }
+        Ok(())
+    }
+
+    /// Moves the bookmarks of the JSON cache into an empty database, then
+    /// renames the file so it isn't imported again.
+    fn import_legacy(&mut self, path: &Path) -> Result<()> {
+        if !path.exists() {
+            return Ok(());
+        }
+
+        let has_articles = self
+            .conn
+            .query_row("SELECT 1 FROM articles LIMIT 1", [], |_| Ok(()))
+            .optional()?
+            .is_some();
+        if !has_articles {
+            // An unreadable cache is refilled by the next refresh anyway
+            if let Ok(legacy) = articles::read_legacy(path) {
+                self.replace_articles(&legacy)?;
+            }
+        }
+
+        fs::rename(path, path.with_extension("json.migrated"))?;
+        Ok(())
+    }
+
+    /// Every bookmark, most recently saved first.
+    pub fn articles(&self) -> Result<Vec<PersistedArticle>> {
+        let mut tags: HashMap<String, Vec<String>> = HashMap::new();
+        let mut statement = self
+            .conn
+            .prepare("SELECT item_id, tag FROM article_tags ORDER BY item_id, position")?;
+        let rows = statement.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        for row in rows {
+            let (item_id, tag): (String, String) = row?;
+            tags.entry(item_id).or_default().push(tag);
+        }
+
+        let mut statement = self.conn.prepare(ARTICLES_QUERY)?;
+        let articles = statement
+            .query_map([], |row| {
+                let item_id: String = row.get(0)?;
+                let word_count: Option<i64> = row.get(5)?;
+                Ok(PersistedArticle {
+                    tags: tags.remove(&item_id).unwrap_or_default(),
+                    item_id,
+                    title: row.get(1)?,
+                    uri: row.get(2)?,
+                    description: row.get(3)?,
+                    time: row.get(4)?,
+                    word_count: word_count.map(|count| count as usize),
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(articles)
+    }
+
+    /// Makes the stored bookmarks match `articles`, adding, updating and
+    /// removing rows as needed.
+    pub fn replace_articles(&mut self, articles: &[PersistedArticle]) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        {
+            let kept: HashSet<&str> = articles.iter().map(|a| a.item_id.as_str()).collect();
+            let stored: Vec<String> = tx
+                .prepare("SELECT item_id FROM articles")?
+                .query_map([], |row| row.get(0))?
+                .collect::<rusqlite::Result<_>>()?;
+
+            let mut delete = tx.prepare("DELETE FROM articles WHERE item_id = ?1")?;
+            for item_id in stored.iter().filter(|id| !kept.contains(id.as_str())) {
+                delete.execute(params![item_id])?;
+            }
+
+            let mut upsert = tx.prepare(
+                "INSERT INTO articles (item_id, title, uri, description, time, word_count)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                 ON CONFLICT (item_id) DO UPDATE SET
+                    title = excluded.title,
+                    uri = excluded.uri,
+                    description = excluded.description,
+                    time = excluded.time,
+                    word_count = excluded.word_count",
+            )?;
+            let mut clear_tags = tx.prepare("DELETE FROM article_tags WHERE item_id = ?1")?;
+            let mut add_tag = tx.prepare(
+                "INSERT OR IGNORE INTO article_tags (item_id, tag, position) VALUES (?1, ?2, ?3)",
+            )?;
+
+            for article in articles {
+                upsert.execute(params![
+                    article.item_id,
+                    article.title,
+                    article.uri,
+                    article.description,
+                    article.time,
+                    article.word_count.map(|count| count as i64),
+                ])?;
+                clear_tags.execute(params![article.item_id])?;
+                for (position, tag) in article.tags.iter().enumerate() {
+                    add_tag.execute(params![article.item_id, tag, position as i64])?;
+                }
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub fn set_word_count(&self, item_id: &str, word_count: usize) -> Result<()> {
+        self.conn.execute(
+            "UPDATE articles SET word_count = ?2 WHERE item_id = ?1",
+            params![item_id, word_count as i64],
+        )?;
+        Ok(())
+    }
+
+    pub fn clear(&mut self) -> Result<()> {
+        self.conn.execute("DELETE FROM articles", [])?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::temp_dir::TestDir;
+
+    fn article(item_id: &str, time: f64, tags: &[&str]) -> PersistedArticle {
+        PersistedArticle {
+            title: format!("Article {}", item_id),
+            uri: format!("https://example.com/{}", item_id),
+            item_id: item_id.to_string(),
+            description: String::new(),
+            time,
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            word_count: None,
+        }
+    }
+
+    fn ids(articles: &[PersistedArticle]) -> Vec<&str> {
+        articles.iter().map(|a| a.item_id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_replace_and_read() {
+        let dir = TestDir::new("replace");
+        let mut store = ArticleStore::open_in(&dir).unwrap();
+        assert!(store.articles().unwrap().is_empty());
+
+        store
+            .replace_articles(&[
+                article("1", 100.0, &["rust", "gtk"]),
+                article("2", 200.0, &[]),
+            ])
+            .unwrap();
+        let articles = store.articles().unwrap();
+        assert_eq!(ids(&articles), vec!["2", "1"]);
+        assert_eq!(articles[1].tags, vec!["rust", "gtk"]);
+
+        let mut updated = article("1", 100.0, &["gtk"]);
+        updated.title = "Renamed".to_string();
+        store
+            .replace_articles(&[updated, article("3", 300.0, &["news"])])
+            .unwrap();
+        let articles = store.articles().unwrap();
+        assert_eq!(ids(&articles), vec!["3", "1"]);
+        assert_eq!(articles[1].title, "Renamed");
+        assert_eq!(articles[1].tags, vec!["gtk"]);
+    }
+
+    #[test]
+    fn test_removed_articles_lose_their_tags() {
+        let dir = TestDir::new("cascade");
+        let mut store = ArticleStore::open_in(&dir).unwrap();
+        store
+            .replace_articles(&[article("1", 100.0, &["rust"])])
+            .unwrap();
+
+        store.replace_articles(&[]).unwrap();
+
+        let tags: i64 = store
+            .conn
+            .query_row("SELECT COUNT(*) FROM article_tags", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(tags, 0);
+    }
+
+    #[test]
+    fn test_set_word_count_and_clear() {
+        let dir = TestDir::new("word-count");
+        let mut store = ArticleStore::open_in(&dir).unwrap();
+        store.replace_articles(&[article("1", 100.0, &[])]).unwrap();
+
+        store.set_word_count("1", 1500).unwrap();
+        assert_eq!(store.articles().unwrap()[0].word_count, Some(1500));
+
+        store.clear().unwrap();
+        assert!(store.articles().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_imports_json_cache_once() {
+        let dir = TestDir::new("legacy");
+        fs::create_dir_all(&dir).unwrap();
+        let legacy = vec![article("1", 100.0, &["rust"]), article("2", 200.0, &[])];
+        fs::write(
+            dir.join(articles::LEGACY_FILE),
+            serde_json::to_string(&legacy).unwrap(),
+        )
+        .unwrap();
+
+        let store = ArticleStore::open_in(&dir).unwrap();
+        let articles = store.articles().unwrap();
+        assert_eq!(ids(&articles), vec!["2", "1"]);
+        assert_eq!(articles[1].tags, vec!["rust"]);
+        assert!(!dir.join(articles::LEGACY_FILE).exists());
+        assert!(dir.join("articles.json.migrated").exists());
+        drop(store);
+
+        let store = ArticleStore::open_in(&dir).unwrap();
+        assert_eq!(store.articles().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_articles_are_sorted_by_index() {
+        let dir = TestDir::new("index");
+        let store = ArticleStore::open_in(&dir).unwrap();
+
+        let plan: Vec<String> = store
+            .conn
+            .prepare(&format!("EXPLAIN QUERY PLAN {}", ARTICLES_QUERY))
+            .unwrap()
+            .query_map([], |row| row.get(3))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert!(plan.iter().any(|step| step.contains("articles_time")));
+    }
+
+    #[test]
+    fn test_tag_lookups_use_index() {
+        let dir = TestDir::new("tag-index");
+        let store = ArticleStore::open_in(&dir).unwrap();
+
+        let plan: Vec<String> = store
+            .conn
+            .prepare("EXPLAIN QUERY PLAN SELECT item_id FROM article_tags WHERE tag = 'rust'")
+            .unwrap()
+            .query_map([], |row| row.get(3))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert!(plan.iter().any(|step| step.contains("article_tags_tag")));
+    }
+
+    #[test]
+    fn test_migrations_are_recorded() {
+        let dir = TestDir::new("version");
+        drop(ArticleStore::open_in(&dir).unwrap());
+
+        let conn = Connection::open(dir.join(DATABASE_FILE)).unwrap();
+        let version: i64 = conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap();
+        assert_eq!(version as usize, MIGRATIONS.len());
+
+        // Reopening runs nothing twice
+        drop(ArticleStore::open_in(&dir).unwrap());
+
+        conn.pragma_update(None, "user_version", MIGRATIONS.len() as i64 + 1)
+            .unwrap();
+        assert!(ArticleStore::open_in(&dir).is_err());
+    }
+}
@@ -0,0 +1,63 @@
+use crate::config::APP_ID;
+use anyhow::Result;
+use relm4::gtk::gio;
+use relm4::gtk::prelude::SettingsExt;
+use std::collections::HashMap;
+
+const LABEL: &str = "Cauldron translation API key";
+
+/// Where older releases kept the key, in plain text.
+const LEGACY_SETTINGS_KEY: &str = "translation-api-key";
+
+/// Attributes the key is stored under in the keyring.
+fn attributes() -> HashMap<&'static str, &'static str> {
+    HashMap::from([("application", APP_ID), ("type", "translation-api-key")])
+}
+
+/// Returns the API key of the translation server, empty when none is set.
+pub async fn read() -> Result<String> {
+    let keyring = oo7::Keyring::new().await?;
+    let Some(item) = keyring
+        .search_items(&attributes())
+        .await?
+        .into_iter()
+        .next()
+    else {
+        return Ok(String::new());
+    };
+
+    let secret = item.secret().await?;
+    Ok(String::from_utf8_lossy(secret.as_bytes()).into_owned())
+}
+
+/// Stores the API key of the translation server in the keyring, or removes
+/// it when `api_key` is empty.
+pub async fn save(api_key: &str) -> Result<()> {
+    let keyring = oo7::Keyring::new().await?;
+    if api_key.is_empty() {
+        keyring.delete(&attributes()).await?;
+    } else {
+        keyring
+            .create_item(LABEL, &attributes(), api_key, true)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Moves a key saved by an older release from GSettings to the keyring,
+/// clearing the setting once the keyring has it.
+pub fn migrate_from_settings(settings: &gio::Settings) {
+    let api_key = settings.string(LEGACY_SETTINGS_KEY).trim().to_string();
+    if api_key.is_empty() {
+        return;
+    }
+
+    let settings = settings.clone();
+    relm4::spawn_local(async move {
+        match relm4::spawn(async move { save(&api_key).await }).await {
+            Ok(Ok(())) => settings.reset(LEGACY_SETTINGS_KEY),
+            Ok(Err(e)) => eprintln!("Failed to move the translation API key: {}", e),
+            Err(e) => eprintln!("Failed to move the translation API key: {}", e),
+        }
+    });
+}
@@ -0,0 +1,97 @@
+use crate::config::APP_ID;
+use anyhow::Result;
+use relm4::gtk::glib;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Translations of one bookmark into one language, keyed by the original
+/// paragraph text so they survive the article being extracted again.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CachedTranslation {
+    /// Detected language of the article, empty until known
+    pub source: String,
+    pub paragraphs: HashMap<String, String>,
+}
+
+/// Returns the cached translation of a bookmark, if any.
+pub fn read(bookmark_id: &str, target: &str) -> Option<CachedTranslation> {
+    read_from(&cache_dir(), bookmark_id, target)
+}
+
+pub fn write(bookmark_id: &str, target: &str, translation: &CachedTranslation) -> Result<()> {
+    write_to(&cache_dir(), bookmark_id, target, translation)
+}
+
+/// Deletes every cached translation.
+pub fn clear() -> Result<()> {
+    let dir = cache_dir();
+    if dir.exists() {
+        fs::remove_dir_all(dir)?;
+    }
+    Ok(())
+}
+
+fn cache_dir() -> PathBuf {
+    let mut path = glib::user_cache_dir();
+    path.push(APP_ID);
+    path.push("translations");
+    path
+}
+
+fn file_name(bookmark_id: &str, target: &str) -> String {
+    // Both come from outside, keep them from escaping the cache directory
+    let safe = |s: &str| -> String {
+        s.chars()
+            .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+            .collect()
+    };
+    format!("{}-{}.json", safe(bookmark_id), safe(target))
+}
+
+fn read_from(dir: &Path, bookmark_id: &str, target: &str) -> Option<CachedTranslation> {
+    let contents = fs::read_to_string(dir.join(file_name(bookmark_id, target))).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+fn write_to(
+    dir: &Path,
+    bookmark_id: &str,
+    target: &str,
+    translation: &CachedTranslation,
+) -> Result<()> {
+    fs::create_dir_all(dir)?;
+    let json = serde_json::to_string(translation)?;
+    fs::write(dir.join(file_name(bookmark_id, target)), json)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::temp_dir::TestDir;
+
+    #[test]
+    fn test_write_and_read() {
+        let dir = TestDir::new("roundtrip");
+        assert_eq!(read_from(&dir, "42", "en"), None);
+
+        let translation = CachedTranslation {
+            source: "it".to_string(),
+            paragraphs: HashMap::from([("Ciao".to_string(), "Hello".to_string())]),
+        };
+        write_to(&dir, "42", "en", &translation).unwrap();
+
+        assert_eq!(read_from(&dir, "42", "en"), Some(translation));
+        // Each language has its own cache
+        assert_eq!(read_from(&dir, "42", "de"), None);
+        assert_eq!(read_from(&dir, "43", "en"), None);
+    }
+
+    #[test]
+    fn test_file_name_stays_in_cache_dir() {
+        assert_eq!(file_name("42", "pt-BR"), "42-pt-BR.json");
+        assert_eq!(file_name("../42", "en/../x"), "42-enx.json");
+    }
+}
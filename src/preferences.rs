@@ -0,0 +1,204 @@
+use gtk::prelude::SettingsExt;
+use gtk::{gio, glib};
+use relm4::gtk;
+
+use crate::article::reader_style::{ReaderStyle, ReaderTheme};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ContentSource {
+    /// Download the original page and extract the article locally
+    #[default]
+    Original,
+    /// Use the text view processed by Instapaper
+    Instapaper,
+}
+
+impl ContentSource {
+    pub fn from_key(key: &str) -> Self {
+        match key {
+            "instapaper" => ContentSource::Instapaper,
+            _ => ContentSource::Original,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ImageLoading {
+    Always,
+    /// Only when the network connection isn't metered
+    #[default]
+    Unmetered,
+    Never,
+}
+
+impl ImageLoading {
+    pub fn from_key(key: &str) -> Self {
+        match key {
+            "always" => ImageLoading::Always,
+            "never" => ImageLoading::Never,
+            _ => ImageLoading::Unmetered,
+        }
+    }
+
+    pub fn loads_automatically(self, network_metered: bool) -> bool {
+        match self {
+            ImageLoading::Always => true,
+            ImageLoading::Unmetered => !network_metered,
+            ImageLoading::Never => false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TranslationLayout {
+    /// Translated paragraphs take the place of the original text
+    #[default]
+    Replace,
+    /// Each translated paragraph sits next to its original
+    SideBySide,
+}
+
+impl TranslationLayout {
+    pub fn from_key(key: &str) -> Self {
+        match key {
+            "side-by-side" => TranslationLayout::SideBySide,
+            _ => TranslationLayout::Replace,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TranslationSettings {
+    /// Base address of the LibreTranslate compatible server
+    pub endpoint: String,
+    /// Kept in the keyring, read when translating
+    pub api_key: String,
+    /// Language code articles are translated to
+    pub target_language: String,
+    pub layout: TranslationLayout,
+}
+
+/// Language code of a locale name such as `it_IT.UTF-8`, `None` for the
+/// untranslated C locale.
+pub fn language_code(locale: &str) -> Option<String> {
+    let code = locale.split(['_', '.', '@']).next().unwrap_or_default();
+    if code.is_empty() || code == "C" || code == "POSIX" {
+        return None;
+    }
+    Some(code.to_string())
+}
+
+/// Snapshot of the user preferences stored in GSettings.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Preferences {
+    pub reader_style: ReaderStyle,
+    pub image_loading: ImageLoading,
+    pub content_source: ContentSource,
+    /// Minutes between automatic refreshes, 0 when disabled
+    pub sync_interval: u32,
+    /// Offline cache budget in megabytes
+    pub offline_cache_size: u32,
+    pub words_per_minute: u32,
+    pub translation: TranslationSettings,
+}
+
+impl Preferences {
+    pub fn load(settings: &gio::Settings) -> Self {
+        Self {
+            reader_style: ReaderStyle {
+                font_family: settings.string("reader-font-family").to_string(),
+                font_size: settings.uint("reader-font-size"),
+                line_width: settings.uint("reader-line-width"),
+                line_height: settings.double("reader-line-height"),
+                theme: ReaderTheme::from_key(&settings.string("reader-theme")),
+            },
+            image_loading: ImageLoading::from_key(&settings.string("image-loading")),
+            content_source: ContentSource::from_key(&settings.string("content-source")),
+            sync_interval: settings.uint("sync-interval"),
+            offline_cache_size: settings.uint("offline-cache-size"),
+            words_per_minute: settings.uint("words-per-minute"),
+            translation: TranslationSettings {
+                endpoint: settings.string("translation-endpoint").trim().to_string(),
+                api_key: String::new(),
+                target_language: target_language(&settings.string("translation-target-language")),
+                layout: TranslationLayout::from_key(&settings.string("translation-layout")),
+            },
+        }
+    }
+}
+
+/// The configured translation language, falling back to the desktop one.
+/// Codes such as `pt-BR` or `zh-Hans` are case sensitive, so it is kept as
+/// entered.
+fn target_language(configured: &str) -> String {
+    let configured = configured.trim();
+    if !configured.is_empty() {
+        return configured.to_string();
+    }
+    desktop_language()
+}
+
+/// The language code of the desktop session, English when it has none.
+pub fn desktop_language() -> String {
+    glib::language_names()
+        .iter()
+        .find_map(|locale| language_code(locale))
+        .unwrap_or_else(|| "en".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_source_from_key() {
+        assert_eq!(
+            ContentSource::from_key("instapaper"),
+            ContentSource::Instapaper
+        );
+        assert_eq!(ContentSource::from_key("original"), ContentSource::Original);
+        assert_eq!(ContentSource::from_key(""), ContentSource::Original);
+    }
+
+    #[test]
+    fn test_image_loading() {
+        assert_eq!(ImageLoading::from_key("always"), ImageLoading::Always);
+        assert_eq!(ImageLoading::from_key("never"), ImageLoading::Never);
+        assert_eq!(ImageLoading::from_key("bogus"), ImageLoading::Unmetered);
+
+        assert!(ImageLoading::Always.loads_automatically(true));
+        assert!(ImageLoading::Unmetered.loads_automatically(false));
+        assert!(!ImageLoading::Unmetered.loads_automatically(true));
+        assert!(!ImageLoading::Never.loads_automatically(false));
+    }
+
+    #[test]
+    fn test_translation_layout_from_key() {
+        assert_eq!(
+            TranslationLayout::from_key("side-by-side"),
+            TranslationLayout::SideBySide
+        );
+        assert_eq!(
+            TranslationLayout::from_key("replace"),
+            TranslationLayout::Replace
+        );
+        assert_eq!(TranslationLayout::from_key(""), TranslationLayout::Replace);
+    }
+
+    #[test]
+    fn test_language_code() {
+        assert_eq!(language_code("it_IT.UTF-8"), Some("it".to_string()));
+        assert_eq!(language_code("de_DE@euro"), Some("de".to_string()));
+        assert_eq!(language_code("en"), Some("en".to_string()));
+        assert_eq!(language_code("C"), None);
+        assert_eq!(language_code("POSIX"), None);
+        assert_eq!(language_code(""), None);
+    }
+
+    #[test]
+    fn test_configured_target_language() {
+        assert_eq!(target_language(" pt "), "pt");
+        assert_eq!(target_language("pt-BR"), "pt-BR");
+        assert_eq!(target_language("zh-Hans"), "zh-Hans");
+    }
+}
@@ -60,6 +60,7 @@ use relm4::gtk;
 use relm4::{Component, ComponentController};
 use std::time::Duration;
 
+pub mod temp_dir;
 pub mod widget_inspection;
 
 /// A test helper for testing factory components in isolation.
@@ -0,0 +1,86 @@
+//! Temporary directories for tests that touch the filesystem
+
+use std::fs;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// A directory under the system temp dir that is deleted when dropped, even
+/// when the test panics.
+///
+/// The directory itself isn't created, so tests can check how code behaves
+/// when it is missing.
+///
+/// # Example
+///
+/// ```ignore
+/// let dir = TestDir::new("roundtrip");
+/// write_to(&dir, "42", &article).unwrap();
+/// assert_eq!(read_from(&dir, "42"), Some(article));
+/// ```
+pub struct TestDir {
+    path: PathBuf,
+}
+
+impl TestDir {
+    /// Reserves a fresh directory path, `name` only makes it recognizable.
+    pub fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!(
+            "cauldron-test-{}-{}-{}",
+            name,
+            std::process::id(),
+            NEXT_ID.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = fs::remove_dir_all(&path);
+        Self { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Deref for TestDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl AsRef<Path> for TestDir {
+    fn as_ref(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dir_is_removed_on_drop() {
+        let dir = TestDir::new("drop");
+        assert!(!dir.exists());
+
+        fs::create_dir_all(dir.join("nested")).unwrap();
+        fs::write(dir.join("nested").join("file"), "contents").unwrap();
+        let path = dir.to_path_buf();
+
+        drop(dir);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_dirs_are_unique() {
+        assert_ne!(TestDir::new("same").path(), TestDir::new("same").path());
+    }
+}